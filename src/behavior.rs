@@ -3,7 +3,11 @@ use std::ops::IndexMut;
 use std::ops::Index;
 use std::cmp::Ordering;
 use std::rc::Rc;
+use std::time::Duration;
 // pub mod parse_behavior;
+pub mod acs_profile;
+
+pub use acs_profile::*;
 pub use crate::parser::*;
 use crate::level::LevelLocals;
 
@@ -104,7 +108,9 @@ pub struct AcsProfileInfo {
     total_instr: u64,
     num_runs: u32,
     min_instr_per_run: u32,
-    max_instr_per_run: u32
+    max_instr_per_run: u32,
+    total_time: Duration,
+    max_time: Duration
 }
 
 
@@ -112,29 +118,70 @@ impl AcsProfileInfo {
     pub fn add_run(&mut self, num_instr: u32) {
         self.total_instr += u64::from(num_instr);
         self.num_runs += 1;
-        if self.num_runs < self.min_instr_per_run {
+        if num_instr < self.min_instr_per_run {
             self.min_instr_per_run = num_instr;
         }
-        if self.num_runs > self.max_instr_per_run {
+        if num_instr > self.max_instr_per_run {
             self.max_instr_per_run = num_instr;
         }
     }
 
+    //same as add_run but also keeps track of the wall time spent in the run
+    pub fn add_timed_run(&mut self, num_instr: u32, time: Duration) {
+        self.add_run(num_instr);
+        self.total_time += time;
+        if time > self.max_time {
+            self.max_time = time;
+        }
+    }
+
     pub fn reset(&mut self) {
         self.total_instr = 0;
         self.num_runs = 0;
-        self.min_instr_per_run = 0;
+        self.min_instr_per_run = u32::MAX;
         self.max_instr_per_run = 0;
+        self.total_time = Duration::ZERO;
+        self.max_time = Duration::ZERO;
     }
 
     pub fn new() -> Self {
         Self {
             total_instr: 0,
             num_runs: 0,
-            min_instr_per_run: 0,
-            max_instr_per_run: 0
+            min_instr_per_run: u32::MAX,
+            max_instr_per_run: 0,
+            total_time: Duration::ZERO,
+            max_time: Duration::ZERO
         }
     }
+
+    pub fn total_instr(&self) -> u64 {
+        self.total_instr
+    }
+
+    pub fn num_runs(&self) -> u32 {
+        self.num_runs
+    }
+
+    pub fn min_instr_per_run(&self) -> u32 {
+        if self.num_runs == 0 {0} else {self.min_instr_per_run}
+    }
+
+    pub fn max_instr_per_run(&self) -> u32 {
+        self.max_instr_per_run
+    }
+
+    pub fn avg_instr_per_run(&self) -> u32 {
+        if self.num_runs == 0 {0} else {(self.total_instr / u64::from(self.num_runs)) as u32}
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.total_time
+    }
+
+    pub fn max_time(&self) -> Duration {
+        self.max_time
+    }
 }
 
 #[derive(Clone, PartialEq, Eq)]
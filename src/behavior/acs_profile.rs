@@ -0,0 +1,306 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use super::AcsProfileInfo;
+
+/*
+ * Profiler for the acs scripts, works like the acsprofile command of zdoom.
+ * Every time a script or function finishes running the vm hands over the amount of
+ * instructions it executed (and the profiler measures the wall time itself), which
+ * then can be dumped as a sorted report so the mappers can find the slow scripts.
+ */
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AcsProfileKind {
+    Script,
+    Function
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AcsProfileSort {
+    Total,
+    Min,
+    Max,
+    Avg,
+    Runs,
+    Time,
+    Name
+}
+
+impl AcsProfileSort {
+    pub fn from_name(name: &str) -> Option<AcsProfileSort> {
+        match name.to_ascii_lowercase().as_str() {
+            "total" => Some(AcsProfileSort::Total),
+            "min" => Some(AcsProfileSort::Min),
+            "max" => Some(AcsProfileSort::Max),
+            "avg" => Some(AcsProfileSort::Avg),
+            "runs" => Some(AcsProfileSort::Runs),
+            "time" => Some(AcsProfileSort::Time),
+            "script" | "func" | "name" => Some(AcsProfileSort::Name),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct AcsProfileKey {
+    kind: AcsProfileKind,
+    module: String,
+    number: i32
+}
+
+pub struct AcsProfileEntry {
+    pub kind: AcsProfileKind,
+    pub module: String,
+    pub number: i32,
+    pub name: String,
+    pub info: AcsProfileInfo
+}
+
+//Handed out by begin_run and given back to end_run when the script/function stops running
+pub struct AcsProfileRun {
+    kind: AcsProfileKind,
+    module: String,
+    number: i32,
+    start: Instant
+}
+
+#[derive(Default)]
+pub struct AcsProfiler {
+    pub enabled: bool,
+    entries: HashMap<AcsProfileKey, AcsProfileEntry>
+}
+
+const DEFAULT_REPORT_COUNT: i32 = 10;
+
+impl AcsProfiler {
+    pub fn new() -> AcsProfiler {
+        AcsProfiler { enabled: false, entries: HashMap::new() }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn begin_run(&self, kind: AcsProfileKind, module: &str, number: i32) -> Option<AcsProfileRun> {
+        if !self.enabled {return None}
+        Some(AcsProfileRun { kind, module: module.to_string(), number, start: Instant::now() })
+    }
+
+    pub fn end_run(&mut self, run: Option<AcsProfileRun>, name: &str, num_instr: u32) {
+        if let Some(run) = run {
+            let time = run.start.elapsed();
+            self.record(run.kind, &run.module, run.number, name, num_instr, time);
+        }
+    }
+
+    pub fn record(&mut self, kind: AcsProfileKind, module: &str, number: i32, name: &str, num_instr: u32, time: Duration) {
+        let key = AcsProfileKey { kind, module: module.to_string(), number };
+        let entry = self.entries.entry(key).or_insert_with(|| AcsProfileEntry {
+            kind,
+            module: module.to_string(),
+            number,
+            name: name.to_string(),
+            info: AcsProfileInfo::new()
+        });
+        entry.info.add_timed_run(num_instr, time);
+    }
+
+    pub fn info(&self, kind: AcsProfileKind, module: &str, number: i32) -> Option<&AcsProfileInfo> {
+        let key = AcsProfileKey { kind, module: module.to_string(), number };
+        self.entries.get(&key).map(|entry| &entry.info)
+    }
+
+    //returns the entries of the kind sorted from the highest to lowest value (or lowest to highest if ascending)
+    pub fn sorted_entries(&self, kind: AcsProfileKind, sort: AcsProfileSort, ascending: bool) -> Vec<&AcsProfileEntry> {
+        let mut entries: Vec<&AcsProfileEntry> = self.entries.values().filter(|entry| entry.kind == kind && entry.info.num_runs() > 0).collect();
+        entries.sort_by(|a, b| {
+            let order = Self::compare(a, b, sort);
+            if ascending {order} else {order.reverse()}
+        });
+        entries
+    }
+
+    fn compare(a: &AcsProfileEntry, b: &AcsProfileEntry, sort: AcsProfileSort) -> Ordering {
+        let order = match sort {
+            AcsProfileSort::Total => a.info.total_instr().cmp(&b.info.total_instr()),
+            AcsProfileSort::Min => a.info.min_instr_per_run().cmp(&b.info.min_instr_per_run()),
+            AcsProfileSort::Max => a.info.max_instr_per_run().cmp(&b.info.max_instr_per_run()),
+            AcsProfileSort::Avg => a.info.avg_instr_per_run().cmp(&b.info.avg_instr_per_run()),
+            AcsProfileSort::Runs => a.info.num_runs().cmp(&b.info.num_runs()),
+            AcsProfileSort::Time => a.info.total_time().cmp(&b.info.total_time()),
+            //names are sorted a-z by default so reverse them here
+            AcsProfileSort::Name => Self::name_key(b).cmp(&Self::name_key(a))
+        };
+        //keep the output stable when the values are the same
+        order.then_with(|| b.module.cmp(&a.module)).then_with(|| b.number.cmp(&a.number))
+    }
+
+    //the numbered scripts go first by their number, the named ones after them by their name
+    fn name_key(entry: &AcsProfileEntry) -> (bool, i32, &str) {
+        match entry.name.parse::<i32>() {
+            Ok(number) => (false, number, ""),
+            Err(_) => (true, 0, entry.name.as_str())
+        }
+    }
+
+    /* Dumps the report of the scripts and the functions.
+     * count is the amount of entries to show, if negative the lowest entries are shown instead */
+    pub fn report(&self, sort: AcsProfileSort, count: i32) -> String {
+        let mut out = String::new();
+        Self::report_kind(self, &mut out, AcsProfileKind::Script, sort, count);
+        out.push('\n');
+        Self::report_kind(self, &mut out, AcsProfileKind::Function, sort, count);
+        out
+    }
+
+    fn report_kind(&self, out: &mut String, kind: AcsProfileKind, sort: AcsProfileSort, count: i32) {
+        let ascending = count < 0;
+        let entries = self.sorted_entries(kind, sort, ascending);
+        let limit = (count.unsigned_abs() as usize).min(entries.len());
+        let type_name = if kind == AcsProfileKind::Script {"Script"} else {"Function"};
+
+        let _ = writeln!(out, "{} {} {}s:", if ascending {"Bottom"} else {"Top"}, limit, type_name.to_ascii_lowercase());
+        if limit == 0 {return}
+
+        let _ = writeln!(out, "{:<24} {:<12} {:>12} {:>8} {:>10} {:>10} {:>10} {:>12}",
+            type_name, "Module", "Total", "Runs", "Avg", "Min", "Max", "Time (ms)");
+        for entry in &entries[..limit] {
+            let info = &entry.info;
+            let _ = writeln!(out, "{:<24} {:<12} {:>12} {:>8} {:>10} {:>10} {:>10} {:>12.3}",
+                entry.name, entry.module, info.total_instr(), info.num_runs(), info.avg_instr_per_run(),
+                info.min_instr_per_run(), info.max_instr_per_run(), info.total_time().as_secs_f64() * 1000.);
+        }
+    }
+
+    /* Handles the arguments of the acsprofile command:
+     * acsprofile clear
+     * acsprofile on/off
+     * acsprofile [total|min|max|avg|runs|time|script|func] [count] */
+    pub fn command(&mut self, args: &[&str]) -> String {
+        let mut sort = AcsProfileSort::Total;
+        let mut count = DEFAULT_REPORT_COUNT;
+
+        for arg in args {
+            match arg.to_ascii_lowercase().as_str() {
+                "clear" => {
+                    self.clear();
+                    return String::from("acs profile data cleared\n")
+                }
+                "on" => {
+                    self.enabled = true;
+                    return String::from("acs profiling enabled\n")
+                }
+                "off" => {
+                    self.enabled = false;
+                    return String::from("acs profiling disabled\n")
+                }
+                _ => {}
+            }
+            if let Some(s) = AcsProfileSort::from_name(arg) {sort = s}
+            else if let Ok(c) = arg.parse::<i32>() {
+                if c != 0 {count = c}
+            }
+            else {return format!("Unknown sort type: {}\nusage: acsprofile [clear|on|off|total|min|max|avg|runs|time|script|func] [count]\n", arg)}
+        }
+        self.report(sort, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profiler() -> AcsProfiler {
+        let mut profiler = AcsProfiler::new();
+        profiler.record(AcsProfileKind::Script, "MAP01", 1, "beta", 50, Duration::from_millis(2));
+        profiler.record(AcsProfileKind::Script, "MAP01", 1, "beta", 150, Duration::from_millis(2));
+        profiler.record(AcsProfileKind::Script, "MAP01", 2, "alpha", 300, Duration::from_millis(1));
+        profiler.record(AcsProfileKind::Script, "MAP01", 3, "gamma", 10, Duration::from_millis(9));
+        profiler.record(AcsProfileKind::Function, "MAP01", 1, "func", 7, Duration::from_millis(1));
+        profiler
+    }
+
+    fn numbers(entries: &[&AcsProfileEntry]) -> Vec<i32> {
+        entries.iter().map(|entry| entry.number).collect()
+    }
+
+    #[test]
+    fn sorts_by_the_values() {
+        let profiler = profiler();
+        assert_eq!(numbers(&profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Total, false)), [2, 1, 3]);
+        assert_eq!(numbers(&profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Total, true)), [3, 1, 2]);
+        assert_eq!(numbers(&profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Runs, false)), [1, 2, 3]);
+        assert_eq!(numbers(&profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Min, false)), [2, 1, 3]);
+        assert_eq!(numbers(&profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Max, false)), [2, 1, 3]);
+        assert_eq!(numbers(&profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Time, false)), [3, 1, 2]);
+        assert_eq!(numbers(&profiler.sorted_entries(AcsProfileKind::Function, AcsProfileSort::Total, false)), [1]);
+    }
+
+    #[test]
+    fn sorts_by_the_name() {
+        let profiler = profiler();
+        assert_eq!(numbers(&profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Name, false)), [2, 1, 3]);
+        assert_eq!(numbers(&profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Name, true)), [3, 1, 2]);
+        assert_eq!(AcsProfileSort::from_name("SCRIPT"), Some(AcsProfileSort::Name));
+    }
+
+    #[test]
+    fn numbered_scripts_sort_by_number() {
+        let mut profiler = AcsProfiler::new();
+        for (number, name) in [(10, "10"), (2, "2"), (0, "named"), (1, "1")] {
+            profiler.record(AcsProfileKind::Script, "MAP01", number, name, 1, Duration::ZERO);
+        }
+        let entries = profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Name, false);
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["1", "2", "10", "named"]);
+    }
+
+    #[test]
+    fn same_values_are_sorted_by_module_and_number() {
+        let mut profiler = AcsProfiler::new();
+        profiler.record(AcsProfileKind::Script, "B", 1, "same", 5, Duration::ZERO);
+        profiler.record(AcsProfileKind::Script, "A", 2, "same", 5, Duration::ZERO);
+        profiler.record(AcsProfileKind::Script, "A", 1, "same", 5, Duration::ZERO);
+        let entries = profiler.sorted_entries(AcsProfileKind::Script, AcsProfileSort::Name, false);
+        let keys: Vec<(&str, i32)> = entries.iter().map(|entry| (entry.module.as_str(), entry.number)).collect();
+        assert_eq!(keys, [("A", 1), ("A", 2), ("B", 1)]);
+    }
+
+    #[test]
+    fn report_shows_the_top_and_bottom() {
+        let profiler = profiler();
+        let report = profiler.report(AcsProfileSort::Total, 2);
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "Top 2 scripts:");
+        assert!(lines[1].starts_with("Script"));
+        assert!(lines[2].starts_with("alpha") && lines[2].contains("300"));
+        assert!(lines[3].starts_with("beta"));
+        assert_eq!(lines[4], "");
+        assert_eq!(lines[5], "Top 1 functions:");
+        assert!(lines[7].starts_with("func"));
+
+        let report = profiler.report(AcsProfileSort::Total, -1);
+        assert!(report.starts_with("Bottom 1 scripts:"));
+        assert!(report.lines().nth(2).unwrap().starts_with("gamma"));
+    }
+
+    #[test]
+    fn runs_are_only_recorded_when_enabled() {
+        let mut profiler = AcsProfiler::new();
+        let run = profiler.begin_run(AcsProfileKind::Script, "MAP01", 1);
+        profiler.end_run(run, "1", 10);
+        assert!(profiler.info(AcsProfileKind::Script, "MAP01", 1).is_none());
+
+        profiler.command(&["on"]);
+        let run = profiler.begin_run(AcsProfileKind::Script, "MAP01", 1);
+        profiler.end_run(run, "1", 10);
+        assert_eq!(profiler.info(AcsProfileKind::Script, "MAP01", 1).map(|info| info.total_instr()), Some(10));
+
+        profiler.command(&["clear"]);
+        assert_eq!(profiler.report(AcsProfileSort::Total, 10), "Top 0 scripts:\n\nTop 0 functions:\n");
+        assert!(profiler.command(&["bogus"]).starts_with("Unknown sort type"));
+    }
+}
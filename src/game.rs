@@ -1,24 +1,205 @@
+use crate::level::{LevelLocals, LevelExit};
+use crate::level::level_load::MapLoader;
+use crate::level::level_texture::TextureManager;
+use crate::parser::WADData;
+use crate::movement::{movement, follow_actor, reset_view};
+use crate::player::Player;
+
+pub const TICRATE: i32 = 35;
+
+//the state of the game, what G_Ticker runs (gamestate_t)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameState {
+    Startup,
+    Level,
+    Intermission,
+    Finale,
+    DemoScreen,
+    FullConsole
+}
+
+//the things the ticker does before the next tic (gameaction_t)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameAction {
+    Nothing,
+    LoadLevel,
+    NewGame,
+    LoadGame,
+    SaveGame,
+    Completed,
+    WorldDone
+}
+
+//the longest frame that is caught up with tics, so a hitch doesn't make the game run the lost time all at once
+const MAX_FRAME_TIME: f64 = 0.25;
+
 pub struct Game {
-    //TODO
     pub game_info: GameInfo,
+    pub game_state: GameState,
+    pub game_action: GameAction,
+    pub game_tic: i32, //the tics since the game started
+    pub wad: WADData,
+    pub tex_manager: TextureManager,
+    pub level: LevelLocals,
+    pub level_index: usize, //the level of the wad that is played
+    pub next_level_index: usize,
+    pub new_level: bool, //set when a level was loaded, the renderer needs to rebuild
+    pub player: Player,
+    tic_accumulator: f64
 }
 
 impl Game {
-    //TODO
-    pub fn new() -> Game {
-        Game { game_info: GameInfo::new() }
+    pub fn new(wad: WADData, player: Player) -> Game {
+        let mut tex_manager = TextureManager::new();
+        tex_manager.init(&wad);
+        Game { game_info: GameInfo::new(), game_state: GameState::Startup, game_action: GameAction::Nothing, game_tic: 0, wad, tex_manager,
+            level: LevelLocals::default(), level_index: 0, next_level_index: 0, new_level: false, player, tic_accumulator: 0. }
+    }
+
+    /*
+     * Adds the time of the frame and runs the tics that fit in it (TryRunTics).
+     * Returns how far the game is into the next tic (0..1), the renderer interpolates with it.
+     */
+    pub fn run_frame(&mut self, delta: f64) -> f64 {
+        let tic_time = 1. / TICRATE as f64;
+        self.tic_accumulator += delta.clamp(0., MAX_FRAME_TIME);
+        while self.tic_accumulator >= tic_time {
+            self.ticker();
+            self.tic_accumulator -= tic_time;
+        }
+        self.tic_accumulator / tic_time
+    }
+
+    //a command typed in the console (C_DoCommand), returns what it prints
+    pub fn console_command(&mut self, line: &str) -> String {
+        let mut args = line.split_whitespace();
+        match args.next() {
+            None => String::new(),
+            Some(command) if command.eq_ignore_ascii_case("acsprofile") => self.level.behaviors.profiler.command(&args.collect::<Vec<&str>>()),
+            Some(command) => format!("Unknown command \"{}\"\n", command)
+        }
+    }
+
+    //G_InitNew, starts the game on the level
+    pub fn init_new(&mut self, level_index: usize) {
+        self.next_level_index = level_index;
+        self.do_load_level();
+    }
+
+    //one game tic (G_Ticker)
+    pub fn ticker(&mut self) {
+        //the actions are done before the tic
+        while self.game_action != GameAction::Nothing {
+            match self.game_action {
+                GameAction::LoadLevel => self.do_load_level(),
+                GameAction::NewGame => {
+                    self.next_level_index = 0;
+                    self.do_load_level();
+                }
+                GameAction::LoadGame | GameAction::SaveGame => {
+                    //TODO G_DoLoadGame/G_DoSaveGame with the serializer
+                    self.game_action = GameAction::Nothing;
+                }
+                GameAction::Completed => self.do_completed(),
+                GameAction::WorldDone => self.do_world_done(),
+                GameAction::Nothing => break
+            }
+        }
+
+        match self.game_state {
+            GameState::Level => {
+                movement(&mut self.player, &mut self.level);
+                self.level.tick();
+                follow_actor(&mut self.player, &self.level);
+                if let Some(exit) = self.level.pending_exit.take() {self.exit_level(exit)}
+            }
+            GameState::Intermission => {
+                //TODO WI_Ticker, the intermission screen goes to the next level when it is done
+                self.game_action = GameAction::WorldDone;
+            }
+            GameState::Finale => {
+                //TODO F_Ticker
+            }
+            GameState::DemoScreen => {
+                //TODO D_PageTicker
+            }
+            GameState::Startup | GameState::FullConsole => ()
+        }
+        self.game_tic += 1;
+    }
+
+    //G_DoLoadLevel
+    fn do_load_level(&mut self) {
+        self.game_action = GameAction::Nothing;
+        if self.next_level_index >= self.wad.levels.len() {
+            println!("Game: there is no level {}", self.next_level_index);
+            self.game_state = GameState::FullConsole;
+            return
+        }
+        self.level_index = self.next_level_index;
+        self.level = LevelLocals::default();
+        let mut loader = MapLoader::new(&mut self.level, &self.tex_manager);
+        loader.load_level(&mut self.wad.levels[self.level_index], &self.game_info);
+        reset_view(&mut self.player, &self.level);
+        self.game_state = GameState::Level;
+        self.tic_accumulator = 0.;
+        self.new_level = true;
+    }
+
+    //the level asked to be left, where it goes is decided in G_DoCompleted
+    fn exit_level(&mut self, exit: LevelExit) {
+        match exit {
+            LevelExit::Normal { .. } => self.next_level_index = self.level_index + 1,
+            LevelExit::Secret { .. } => {
+                //TODO the secret level of the mapinfo
+                self.next_level_index = self.level_index + 1;
+            }
+            LevelExit::NewMap { map, .. } => {
+                //TODO find the level by the mapinfo number
+                self.next_level_index = (map - 1).max(0) as usize;
+            }
+            LevelExit::EndGame => {
+                self.game_state = GameState::Finale;
+                return
+            }
+        }
+        self.game_action = GameAction::Completed;
+    }
+
+    //G_DoCompleted
+    fn do_completed(&mut self) {
+        self.game_action = GameAction::Nothing;
+        self.game_state = GameState::Intermission;
+    }
+
+    //G_DoWorldDone
+    fn do_world_done(&mut self) {
+        self.game_action = GameAction::LoadLevel;
+        if self.next_level_index >= self.wad.levels.len() {
+            //the last level ends the game
+            self.game_action = GameAction::Nothing;
+            self.game_state = GameState::Finale;
+        }
     }
 }
 
 pub struct GameInfo {
     //TODO
-    pub game_type: GameType
+    pub game_type: GameType,
+    pub skill: i32, //0 is baby, 4 is nightmare
+    pub multiplayer: bool,
+    pub deathmatch: bool
 }
 
 impl GameInfo {
     //TODO
     pub fn new() -> GameInfo {
-        GameInfo { game_type: GameType::Doom }
+        GameInfo { game_type: GameType::Doom, skill: 2, multiplayer: false, deathmatch: false }
+    }
+
+    //the bit of the skill in the skill filter of the map things (SKILLP_SpawnFilter)
+    pub fn skill_filter(&self) -> u16 {
+        1 << self.skill.clamp(0, 4)
     }
 }
 
@@ -1,11 +1,10 @@
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
-use crate::parser::parse_level::WADLevelLinedef;
 use crate::vector::Vector2;
 use bitflags::bitflags;
 use num_derive::{FromPrimitive};
-use num_enum::IntoPrimitive;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 
 mod level_mesh;
 mod level_elements;
@@ -18,16 +17,60 @@ mod level_bsp;
 mod level_poly;
 pub mod level_load;
 mod level_behavior;
+mod level_specials;
+mod level_movers;
+mod level_lighting;
+mod level_sectors;
+mod level_translate;
+mod level_floor;
+mod level_ceiling;
+mod level_doors;
+mod level_plats;
+mod level_pillar;
+mod level_thinker;
+mod level_serializer;
+mod level_random;
+mod level_info;
+mod level_map;
+mod level_sight;
+mod level_maputl;
+mod level_enemy;
+mod level_inter;
+mod level_pspr;
+mod level_triangulate;
+mod level_teleport;
 
 use level_portal::*;
 use level_elements::*;
+pub use level_elements::{ChildNode, LineFlags, Sector, SectorE, SectorIndex, Sides, BOXBOTTOM, BOXLEFT, BOXRIGHT, BOXTOP};
 use level_lightmap::*;
-use level_mesh::LevelMesh;
+pub use level_mesh::{LevelMesh, MeshChunk};
 use level_texture::*;
 use level_light::*;
+pub use level_light::LightMode;
 use level_actor::*;
+pub use level_actor::{Actor, ActorIndex};
 use level_bsp::*;
 use level_poly::*;
+pub use level_specials::*;
+pub use level_movers::*;
+pub use level_translate::*;
+pub use level_floor::*;
+pub use level_ceiling::*;
+pub use level_doors::*;
+pub use level_plats::*;
+pub use level_pillar::*;
+pub use level_thinker::*;
+pub use level_serializer::*;
+pub use level_random::*;
+pub use level_info::*;
+pub use level_map::*;
+pub use level_sight::*;
+pub use level_maputl::*;
+pub use level_enemy::*;
+pub use level_inter::*;
+pub use level_pspr::*;
+pub use level_teleport::*;
 
 //TODO split this up in multiple structs (level stats, music, lights etc)
 //TODO give everything types
@@ -121,7 +164,11 @@ pub struct LevelLocals {
 	//TODO automap,
 	pub body_que_slot: i32,
 
-	pub players: [Rc<Player>;8], //8 max players
+	pub players: [Player;8], //8 max players
+	pub skill: i32, //0 is baby, 4 is nightmare
+	pub net_game: bool,
+	pub valid_count: i32, //the sectors with this count were already gone through
+	pub brain: BrainState,
 
 
     pub num_map_sections: i32,
@@ -182,6 +229,7 @@ pub struct LevelLocals {
     pub particles: Vec<Particle>,
     pub particles_in_subsec: Vec<u16>,
     pub thinkers: ThinkerCollection,
+    pub random: Random,
 
     pub scrolls: Vec<Vector2<f64>>,
 
@@ -213,11 +261,14 @@ pub struct LevelLocals {
 
     //TODO corpse_queue,
     //TODO fraggle_script_thinker,
-    //TODO acs_thinker,
+    pub acs_thinker: ThinkerIndex,
 
     //TODO spot_state,
 
-    pub sky_flat_num: TextureID
+    pub sky_flat_num: TextureID,
+
+    pub pending_exit: Option<LevelExit>,
+    pub teleports: Vec<(ActorIndex, TeleportMove)> //the teleports of the actors that are held while they move
 }
 
 impl DerefMut for LevelLocals {
@@ -235,46 +286,129 @@ impl Deref for LevelLocals {
 
 impl LevelLocals {
 
-    pub fn translate_sector_special(&self, _special: i16) -> i32 {
-        //TODO implement this
-        0
+    pub fn sector_has_tag(&self, index: usize, tag: i32) -> bool {
+        self.tag_manager.sector_has_tag(index as i32, tag)
     }
 
-    pub fn translate_linedef(&self, _line: &Line, _linedef: &WADLevelLinedef, _line_index: i32) {
-        //TODO
+    pub fn line_has_id(&self, line: i32, tag: i32) -> bool {
+        self.tag_manager.line_has_id(line, tag)
     }
 
-    pub fn sector_has_tag(&self, _index: usize, _tag: i16) -> bool {
-        //TODO
-        false
+    pub fn sectors_with_tag(&self, tag: i32) -> Vec<SectorIndex> {
+        self.tag_manager.sectors_with_tag(tag)
     }
 
-    pub fn line_has_id(&self, line: i32, tag: i32) -> bool {
-        //TODO
-        self.tag_manager.line_has_id(line, tag)
+    pub fn lines_with_id(&self, id: i32) -> Vec<LineIndex> {
+        self.tag_manager.lines_with_id(id)
+    }
+
+    //the second sky is drawn behind the first one
+    pub fn is_double_sky(&self) -> bool {
+        self.flags & LevelFlags::DoubleSky.bits() != 0
     }
 }
 
+const TAG_HASH_SIZE: usize = 256;
+
+/*
+ * The sector tags and line ids (FTagManager of zdoom).
+ * The items are sorted on their target so all the tags of a sector/line are next to each other,
+ * items with the same tag are chained together by the hash of the tag.
+ */
 #[derive(Default)]
 pub struct TagManager {
+    pub all_tags: Vec<TagItem>,
+    pub all_ids: Vec<TagItem>,
     pub start_for_sector: Vec<i32>,
     pub start_for_line: Vec<i32>,
-    pub all_ids: Vec<TagItem>
-} //TODO
+    tag_hash_first: Vec<i32>,
+    id_hash_first: Vec<i32>
+}
 
 impl TagManager {
-    pub fn add_sector_tag(&self, _i: usize, _tag: i16) {
-        //TODO
+    pub fn clear(&mut self) {
+        self.all_tags.clear();
+        self.all_ids.clear();
+        self.start_for_sector.clear();
+        self.start_for_line.clear();
+        self.tag_hash_first.clear();
+        self.id_hash_first.clear();
+    }
+
+    pub fn add_sector_tag(&mut self, sector: usize, tag: i32) {
+        if tag <= 0 {return}
+        self.all_tags.push(TagItem { target: sector as i32, tag, next_tag: -1 });
+    }
+
+    pub fn add_line_id(&mut self, line: usize, tag: i32) {
+        if tag == -1 {return}
+        self.all_ids.push(TagItem { target: line as i32, tag, next_tag: -1 });
+    }
+
+    //the udmf 'moreids' key, a space separated list of extra tags/ids
+    pub fn parse_more_ids(ids: &str) -> Vec<i32> {
+        ids.split_whitespace().filter_map(|id| id.parse::<i32>().ok()).collect()
     }
 
-    pub fn add_line_id(&self, _i: usize, _tag: u16) {
-        //TODO
+    //needs to be called after adding the tags/ids before they can be used
+    pub fn hash_tags(&mut self) {
+        Self::hash_items(&mut self.all_tags, &mut self.start_for_sector, &mut self.tag_hash_first);
+        Self::hash_items(&mut self.all_ids, &mut self.start_for_line, &mut self.id_hash_first);
+    }
+
+    fn hash_items(items: &mut [TagItem], start: &mut Vec<i32>, hash_first: &mut Vec<i32>) {
+        //stable so multiple tags of the same target stay in the order they were added
+        items.sort_by_key(|item| item.target);
+
+        start.clear();
+        if let Some(last) = items.last() {
+            start.resize(last.target as usize + 1, -1);
+        }
+        for (i, item) in items.iter().enumerate() {
+            if start[item.target as usize] == -1 {start[item.target as usize] = i as i32}
+        }
+
+        //built backwards so the chains iterate the targets in ascending order
+        hash_first.clear();
+        hash_first.resize(TAG_HASH_SIZE, -1);
+        for i in (0..items.len()).rev() {
+            let hash = (items[i].tag as usize) & (TAG_HASH_SIZE - 1);
+            items[i].next_tag = hash_first[hash];
+            hash_first[hash] = i as i32;
+        }
+    }
+
+    pub fn sector_has_tags(&self, sector: i32) -> bool {
+        sector >= 0 && sector < self.start_for_sector.len() as i32 && self.start_for_sector[sector as usize] >= 0
+    }
+
+    pub fn get_first_sector_tag(&self, sector: i32) -> i32 {
+        if self.sector_has_tags(sector) {self.all_tags[self.start_for_sector[sector as usize] as usize].tag} else {0}
+    }
+
+    pub fn sector_has_tag(&self, sector: i32, tag: i32) -> bool {
+        if self.sector_has_tags(sector) {
+            let mut ndx = self.start_for_sector[sector as usize] as usize;
+            while ndx < self.all_tags.len() && self.all_tags[ndx].target == sector {
+                if self.all_tags[ndx].tag == tag {return true}
+                ndx += 1;
+            }
+        }
+        false
+    }
+
+    pub fn line_has_ids(&self, line: i32) -> bool {
+        line >= 0 && line < self.start_for_line.len() as i32 && self.start_for_line[line as usize] >= 0
+    }
+
+    pub fn get_first_line_id(&self, line: i32) -> i32 {
+        if self.line_has_ids(line) {self.all_ids[self.start_for_line[line as usize] as usize].tag} else {0}
     }
 
     pub fn line_has_id(&self, line: i32, tag: i32) -> bool {
         if self.line_has_ids(line) {
             let mut ndx = self.start_for_line[line as usize] as usize;
-            while self.all_ids[ndx].tag == line {
+            while ndx < self.all_ids.len() && self.all_ids[ndx].target == line {
                 if self.all_ids[ndx].tag == tag {return true}
                 ndx += 1;
             }
@@ -282,8 +416,24 @@ impl TagManager {
         false
     }
 
-    fn line_has_ids(&self, sector: i32) -> bool {
-        sector >= 0 && sector < self.start_for_line.len() as i32 && self.start_for_line[sector as usize] >= 0
+    fn chain(items: &[TagItem], hash_first: &[i32], tag: i32) -> Vec<i32> {
+        let mut targets = vec![];
+        if hash_first.is_empty() {return targets}
+        let mut ndx = hash_first[(tag as usize) & (TAG_HASH_SIZE - 1)];
+        while ndx >= 0 {
+            let item = &items[ndx as usize];
+            if item.tag == tag {targets.push(item.target)}
+            ndx = item.next_tag;
+        }
+        targets
+    }
+
+    pub fn sectors_with_tag(&self, tag: i32) -> Vec<i32> {
+        Self::chain(&self.all_tags, &self.tag_hash_first, tag)
+    }
+
+    pub fn lines_with_id(&self, id: i32) -> Vec<i32> {
+        Self::chain(&self.all_ids, &self.id_hash_first, id)
     }
 }
 
@@ -297,13 +447,17 @@ pub struct TagItem {
 
 #[derive(Clone, Copy)]
 pub struct Part {
+    pub x_offset: f64,
+    pub y_offset: f64,
+    pub x_scale: f64,
+    pub y_scale: f64,
     pub texture: TextureID,
     //TODO
 }
 
 impl Part {
     pub fn new() -> Part {
-        Part { texture: TextureID { tex_num: 0 } }
+        Part { x_offset: 0., y_offset: 0., x_scale: 1., y_scale: 1., texture: TextureID { tex_num: 0 } }
     }
 }
 
@@ -326,8 +480,12 @@ pub struct LevelInfo {}
 bitflags! {
     pub struct LevelFlags: u32 {
    
+        const DoubleSky = 0x00000004;
         const SndSeqTotalCtrl = 0x00001000;
         const HasFadeTable = 0x00000008;
+        const Level2LaxMonsterActivation = 0x00000004;
+        const Level2LaxActivationMapInfo = 0x00000008;
+        const Level2MissilesActivateImpact = 0x00000010;
         const Level2ClipMidTex = 0x00000200;
         const Level2WrapMidTex = 0x00000400;
         const Level2CheckSwitchRange = 0x00000800;
@@ -372,15 +530,272 @@ bitflags! {
     }
 }
 
-#[derive(FromPrimitive)]
+#[derive(FromPrimitive, Clone, Copy, PartialEq, Debug)]
 pub enum ActionSpecials {
+    PolyobjStartLine = 1,
+    PolyobjRotateLeft = 2,
+    PolyobjRotateRight = 3,
+    PolyobjMove = 4,
+    PolyobjExplicitLine = 5,
+    PolyobjMoveTimes8 = 6,
+    PolyobjDoorSwing = 7,
+    PolyobjDoorSlide = 8,
+    LineHorizon = 9,
+    DoorClose = 10,
+    DoorOpen = 11,
+    DoorRaise = 12,
+    DoorLockedRaise = 13,
+    DoorAnimated = 14,
+    Autosave = 15,
+    TransferWallLight = 16,
+    ThingRaise = 17,
+    StartConversation = 18,
+    ThingStop = 19,
+    FloorLowerByValue = 20,
+    FloorLowerToLowest = 21,
+    FloorLowerToNearest = 22,
+    FloorRaiseByValue = 23,
+    FloorRaiseToHighest = 24,
+    FloorRaiseToNearest = 25,
+    StairsBuildDown = 26,
+    StairsBuildUp = 27,
+    FloorRaiseAndCrush = 28,
+    PillarBuild = 29,
+    PillarOpen = 30,
+    StairsBuildDownSync = 31,
+    StairsBuildUpSync = 32,
+    ForceField = 33,
+    ClearForceField = 34,
+    FloorRaiseByValueTimes8 = 35,
+    FloorLowerByValueTimes8 = 36,
+    FloorMoveToValue = 37,
+    CeilingWaggle = 38,
+    TeleportZombieChanger = 39,
+    CeilingLowerByValue = 40,
+    CeilingRaiseByValue = 41,
+    CeilingCrushAndRaise = 42,
+    CeilingLowerAndCrush = 43,
+    CeilingCrushStop = 44,
+    CeilingCrushRaiseAndStay = 45,
+    FloorCrushStop = 46,
+    CeilingMoveToValue = 47,
+    SectorAttach3dMidtex = 48,
+    GlassBreak = 49,
+    ExtraFloorLightOnly = 50,
+    SectorSetLink = 51,
+    ScrollWall = 52,
+    LineSetTextureOffset = 53,
+    SectorChangeFlags = 54,
+    LineSetBlocking = 55,
+    LineSetTextureScale = 56,
+    SectorSetPortal = 57,
+    SectorCopyScroller = 58,
+    PolyobjOrMoveToSpot = 59,
+    PlatPerpetualRaise = 60,
+    PlatStop = 61,
+    PlatDownWaitUpStay = 62,
+    PlatDownByValue = 63,
+    PlatUpWaitDownStay = 64,
+    PlatUpByValue = 65,
+    FloorLowerInstant = 66,
+    FloorRaiseInstant = 67,
+    FloorMoveToValueTimes8 = 68,
+    CeilingMoveToValueTimes8 = 69,
+    Teleport = 70,
+    TeleportNoFog = 71,
+    ThrustThing = 72,
+    DamageThing = 73,
+    TeleportNewMap = 74,
+    TeleportEndGame = 75,
+    TeleportOther = 76,
+    TeleportGroup = 77,
+    TeleportInSector = 78,
+    ThingSetConversation = 79,
+    AcsExecute = 80,
+    AcsSuspend = 81,
+    AcsTerminate = 82,
+    AcsLockedExecute = 83,
+    AcsExecuteWithResult = 84,
+    AcsLockedExecuteDoor = 85,
+    PolyobjMoveToSpot = 86,
+    PolyobjStop = 87,
+    PolyobjMoveTo = 88,
+    PolyobjOrMoveTo = 89,
+    PolyobjOrRotateLeft = 90,
+    PolyobjOrRotateRight = 91,
+    PolyobjOrMove = 92,
+    PolyobjOrMoveTimes8 = 93,
+    PillarBuildAndCrush = 94,
+    FloorAndCeilingLowerByValue = 95,
+    FloorAndCeilingRaiseByValue = 96,
+    CeilingLowerAndCrushDist = 97,
+    SectorSetTranslucent = 98,
+    FloorRaiseAndCrushDoom = 99,
+    ScrollTextureLeft = 100,
+    ScrollTextureRight = 101,
+    ScrollTextureUp = 102,
+    ScrollTextureDown = 103,
+    CeilingCrushAndRaiseSilentDist = 104,
+    DoorWaitRaise = 105,
+    DoorWaitClose = 106,
+    LineSetPortalTarget = 107,
+    LightForceLightning = 109,
+    LightRaiseByValue = 110,
+    LightLowerByValue = 111,
+    LightChangeToValue = 112,
+    LightFade = 113,
+    LightGlow = 114,
+    LightFlicker = 115,
+    LightStrobe = 116,
+    LightStop = 117,
+    PlaneCopy = 118,
+    ThingDamage = 119,
+    RadiusQuake = 120,
+    LineSetIdentification = 121,
+    ThingMove = 125,
+    ThingSetSpecial = 127,
+    ThrustThingZ = 128,
+    UsePuzzleItem = 129,
+    ThingActivate = 130,
+    ThingDeactivate = 131,
+    ThingRemove = 132,
+    ThingDestroy = 133,
+    ThingProjectile = 134,
+    ThingSpawn = 135,
+    ThingProjectileGravity = 136,
+    ThingSpawnNoFog = 137,
+    FloorWaggle = 138,
+    ThingSpawnFacing = 139,
+    SectorChangeSound = 140,
+    PlayerSetTeam = 145,
+    TeamScore = 152,
+    TeamGivePoints = 153,
+    TeleportNoStop = 154,
+    LineSetPortal = 156,
+    SetGlobalFogParameter = 157,
+    FsExecute = 158,
+    SectorSetPlaneReflection = 159,
+    SectorSet3dFloor = 160,
+    SectorSetContents = 161,
+    CeilingCrushAndRaiseDist = 168,
+    GenericCrusher2 = 169,
+    SectorSetCeilingScale2 = 170,
+    SectorSetFloorScale2 = 171,
+    PlatUpNearestWaitDownStay = 172,
+    NoiseAlert = 173,
+    SendToCommunicator = 174,
+    ThingProjectileIntercept = 175,
+    ThingChangeTID = 176,
+    ThingHate = 177,
+    ThingProjectileAimed = 178,
+    ChangeSkill = 179,
+    ThingSetTranslation = 180,
+    PlaneAlign = 181,
+    LineMirror = 182,
+    LineAlignCeiling = 183,
+    LineAlignFloor = 184,
+    SectorSetRotation = 185,
+    SectorSetCeilingPanning = 186,
+    SectorSetFloorPanning = 187,
+    SectorSetCeilingScale = 188,
+    SectorSetFloorScale = 189,
+    StaticInit = 190,
+    SetPlayerProperty = 191,
+    CeilingLowerToHighestFloor = 192,
+    CeilingLowerInstant = 193,
+    CeilingRaiseInstant = 194,
+    CeilingCrushRaiseAndStayA = 195,
+    CeilingCrushAndRaiseA = 196,
+    CeilingCrushAndRaiseSilentA = 197,
+    CeilingRaiseByValueTimes8 = 198,
+    CeilingLowerByValueTimes8 = 199,
+    GenericFloor = 200,
+    GenericCeiling = 201,
+    GenericDoor = 202,
+    GenericLift = 203,
+    GenericStairs = 204,
+    GenericCrusher = 205,
+    PlatDownWaitUpStayLip = 206,
+    PlatPerpetualRaiseLip = 207,
     TranslucentLine = 208,
     TransferHeights = 209,
-    StaticInit = 190,
-    SectorSet3dFloor = 160,
+    TransferFloorLight = 210,
+    TransferCeilingLight = 211,
+    SectorSetColor = 212,
+    SectorSetFade = 213,
+    SectorSetDamage = 214,
+    TeleportLine = 215,
+    SectorSetGravity = 216,
+    StairsBuildUpDoom = 217,
+    SectorSetWind = 218,
+    SectorSetFriction = 219,
+    SectorSetCurrent = 220,
+    ScrollTextureBoth = 221,
+    ScrollTextureModel = 222,
+    ScrollFloor = 223,
+    ScrollCeiling = 224,
+    ScrollTextureOffsets = 225,
+    AcsExecuteAlways = 226,
+    PointPushSetForce = 227,
+    PlatRaiseAndStayTx0 = 228,
+    ThingSetGoal = 229,
+    PlatUpByValueStayTx = 230,
+    PlatToggleCeiling = 231,
+    LightStrobeDoom = 232,
+    LightMinNeighbor = 233,
+    LightMaxNeighbor = 234,
+    FloorTransferTrigger = 235,
+    FloorTransferNumeric = 236,
+    ChangeCamera = 237,
+    FloorRaiseToLowestCeiling = 238,
+    FloorRaiseByValueTxTy = 239,
+    FloorRaiseByTexture = 240,
+    FloorLowerToLowestTxTy = 241,
+    FloorLowerToHighest = 242,
+    ExitNormal = 243,
+    ExitSecret = 244,
+    ElevatorRaiseToNearest = 245,
+    ElevatorMoveToFloor = 246,
+    ElevatorLowerToNearest = 247,
+    HealThing = 248,
+    DoorCloseWaitOpen = 249,
+    FloorDonut = 250,
+    FloorAndCeilingLowerRaise = 251,
+    CeilingRaiseToNearest = 252,
+    CeilingLowerToLowest = 253,
+    CeilingLowerToFloor = 254,
+    CeilingCrushRaiseAndStaySilA = 255,
+    FloorLowerToHighestEE = 256,
+    FloorRaiseToLowest = 257,
+    FloorLowerToLowestCeiling = 258,
+    FloorRaiseToCeiling = 259,
+    FloorToCeilingInstant = 260,
+    FloorLowerByTexture = 261,
+    CeilingRaiseToHighest = 262,
+    CeilingToHighestInstant = 263,
+    CeilingLowerToNearest = 264,
+    CeilingRaiseToLowest = 265,
+    CeilingRaiseToHighestFloor = 266,
+    CeilingToFloorInstant = 267,
+    CeilingRaiseByTexture = 268,
+    CeilingLowerByTexture = 269,
+    StairsBuildDownDoom = 270,
+    StairsBuildUpDoomSync = 271,
+    StairsBuildDownDoomSync = 272,
+    StairsBuildUpDoomCrush = 273,
+    DoorAnimatedClose = 274,
+    FloorStop = 275,
+    CeilingStop = 276,
+    SectorSetFloorGlow = 277,
+    SectorSetCeilingGlow = 278,
+    FloorMoveToValueAndCrush = 279,
+    CeilingMoveToValueAndCrush = 280,
+    LineSetAutomapFlags = 281,
+    LineSetAutomapStyle = 282,
+    PolyobjStopSound = 283,
 }
 
-#[derive(IntoPrimitive)]
+#[derive(IntoPrimitive, TryFromPrimitive, Clone, Copy, PartialEq, Debug)]
 #[repr(i16)]
 pub enum SpecialMapThings {
     Player1Start = 1,
@@ -410,9 +825,122 @@ pub enum SpecialMapThings {
 	EDThing,
 }
 
+//the sector specials of zdoom, the doom types are translated to these
+#[derive(FromPrimitive, Clone, Copy, PartialEq, Debug)]
+pub enum SectorSpecials {
+    LightPhased = 1,
+    LightSequenceStart = 2,
+    LightSequenceSpecial1 = 3,
+    LightSequenceSpecial2 = 4,
+    StairsSpecial1 = 26,
+    StairsSpecial2 = 27,
+    DLightFlicker = 65,
+    DLightStrobeFast = 66,
+    DLightStrobeSlow = 67,
+    DLightStrobeHurt = 68,
+    DDamageHellslime = 69,
+    DDamageNukage = 71,
+    DLightGlow = 72,
+    DSectorDoorCloseIn30 = 74,
+    DDamageEnd = 75,
+    DLightStrobeSlowSync = 76,
+    DLightStrobeFastSync = 77,
+    DSectorDoorRaiseIn5Mins = 78,
+    DFrictionLow = 79,
+    DDamageSuperHellslime = 80,
+    DLightFireFlicker = 81,
+    DDamageLavaWimpy = 82,
+    DDamageLavaHefty = 83,
+    DScrollEastLavaDamage = 84,
+    HDamageSludge = 85,
+    SectorOutside = 87,
+    SLightStrobeHurt = 104,
+    SDamageHellslime = 105,
+    DamageInstantDeath = 115,
+    SDamageSuperHellslime = 116,
+    ScrollStrifeCurrent = 118,
+    SectorHidden = 195,
+    SectorHealNoTarget = 196,
+    SkyOutside = 197,
+}
+
 //(special_number, min_script_args, max_script_args, args_on_line)
 // type Special = (u32, i16, i16, i16);
 
 
 
 //TODO ExtSector, Sector, Seg
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //the tags go in out of order, like the sectors of a udmf map with extra ids
+    fn tags() -> TagManager {
+        let mut tags = TagManager::default();
+        tags.add_sector_tag(3, 1);
+        tags.add_sector_tag(0, 1);
+        tags.add_sector_tag(3, 257);
+        tags.add_sector_tag(2, 257);
+        tags.add_sector_tag(3, 5);
+        tags.add_sector_tag(1, 0);
+        tags.add_line_id(4, 0);
+        tags.add_line_id(2, -1);
+        tags.add_line_id(1, 7);
+        tags.hash_tags();
+        tags
+    }
+
+    #[test]
+    fn sector_with_several_tags() {
+        let tags = tags();
+        assert!(tags.sector_has_tag(3, 1));
+        assert!(tags.sector_has_tag(3, 257));
+        assert!(tags.sector_has_tag(3, 5));
+        assert!(!tags.sector_has_tag(3, 2));
+        assert_eq!(tags.get_first_sector_tag(3), 1);
+        //tag 0 isn't a tag
+        assert!(!tags.sector_has_tags(1));
+        assert_eq!(tags.get_first_sector_tag(1), 0);
+    }
+
+    #[test]
+    fn sectors_with_tag_in_order() {
+        let tags = tags();
+        assert_eq!(tags.sectors_with_tag(1), [0, 3]);
+        //257 is in the same hash bucket as 1
+        assert_eq!(tags.sectors_with_tag(257), [2, 3]);
+        assert_eq!(tags.sectors_with_tag(5), [3]);
+        assert!(tags.sectors_with_tag(513).is_empty());
+    }
+
+    #[test]
+    fn line_ids() {
+        let tags = tags();
+        assert!(tags.line_has_id(4, 0));
+        assert_eq!(tags.lines_with_id(0), [4]);
+        //-1 is no id
+        assert!(!tags.line_has_ids(2));
+        assert!(!tags.line_has_id(2, -1));
+        assert!(tags.lines_with_id(-1).is_empty());
+        assert_eq!(tags.get_first_line_id(1), 7);
+        assert!(!tags.line_has_id(10, 7));
+    }
+
+    #[test]
+    fn hash_tags_twice() {
+        let mut tags = tags();
+        tags.hash_tags();
+        assert_eq!(tags.sectors_with_tag(1), [0, 3]);
+        assert_eq!(tags.sectors_with_tag(257), [2, 3]);
+        assert_eq!(tags.get_first_sector_tag(3), 1);
+        assert_eq!(tags.lines_with_id(7), [1]);
+    }
+
+    #[test]
+    fn more_ids() {
+        assert_eq!(TagManager::parse_more_ids("3 12  -4"), [3, 12, -4]);
+        assert_eq!(TagManager::parse_more_ids("5 x 6"), [5, 6]);
+        assert!(TagManager::parse_more_ids("").is_empty());
+    }
+}
@@ -1,20 +1,625 @@
-use std::rc::Rc;
+use crate::behavior::AcsProfiler;
+use crate::vector::{Angle, Vector2, Vector3};
 
-#[derive(Default, Clone)]
+use crate::game::GameInfo;
+
+use super::{LevelLocals, MapThingFlags, SpecialMapThings};
+use super::level_behavior::{ScriptAction, ScriptRequest};
+use super::level_elements::{LineIndex, SectorIndex, SubSectorIndex};
+use super::level_info::*;
+use super::level_map::MELEERANGE;
+use super::level_pspr::{PspDef, WeaponType, AM_CLIP, MAXAMMO, NUMAMMO, NUMPSPRITES, NUMWEAPONS, WP_FIST, WP_PISTOL};
+use super::level_maputl::approx_distance;
+use super::level_load::MapThing;
+use super::level_specials::Activator;
+use super::level_serializer::{Serializable, Serializer};
+use super::level_thinker::{ThinkerData, STAT_DEFAULT};
+
+pub type ActorIndex = i32; //the index of the thinker of the actor, if -1 -> does not exist (NULL)
+
+pub const MAXPLAYERS: i32 = 8;
+pub const MAPBLOCKUNITS: f64 = 128.;
+
+//the z of the spawn that puts the actor on the floor or at the ceiling (ONFLOORZ and ONCEILINGZ)
+pub const ON_FLOOR_Z: f64 = f64::MIN;
+pub const ON_CEILING_Z: f64 = f64::MAX;
+
+//mobj_t of doom, AActor of zdoom
+#[derive(Clone)]
 pub struct Actor {
+    pub type_: MobjType,
+    pub pos: Vector3<f64>,
+    pub prev: Vector3<f64>, //the position at the previous tic, for the interpolation
+    pub vel: Vector3<f64>,
+    pub angle: Angle<f64>,
+    pub radius: f64,
+    pub height: f64,
+    pub health: i32,
+    pub flags: MobjFlags,
+
+    pub sector: SectorIndex,
+    pub subsector: SubSectorIndex,
+    pub floor_z: f64,
+    pub ceiling_z: f64,
+    pub dropoff_z: f64,
+    pub block_node: BlockNodeIndex, //the first node of the blocks the actor is in
+    pub blocking_line: LineIndex, //the line that stopped the last move
+    pub blocking_actor: ActorIndex,
+
+    //the state machine
+    pub state: StateNum,
+    pub tics: i32,
+    pub sprite: SpriteNum,
+    pub frame: i32,
+
+    pub target: ActorIndex,
+    pub tracer: ActorIndex,
+    pub reaction_time: i32,
+    pub threshold: i32, //the tics the monster keeps chasing the target
+    pub move_dir: i32,
+    pub move_count: i32,
+    pub player: i32, //the number of the player, -1 for the others
+    pub last_look: i32,
+
+    //for the respawning
+    pub spawn_point: Vector3<f64>,
+    pub spawn_angle: i32,
+    pub spawn_flags: i32,
+
+    pub tid: i32,
+    pub special: i32,
+    pub args: [i32; 5]
+}
+
+impl Default for Actor {
+    fn default() -> Self {
+        Self::new(MT_PLAYER)
+    }
+}
+
+impl Actor {
+    pub fn new(type_: MobjType) -> Actor {
+        let info = &MOBJINFO[type_];
+        Actor { type_, pos: Vector3::default(), prev: Vector3::default(), vel: Vector3::default(), angle: Angle::default(), radius: info.radius, height: info.height,
+            health: info.spawn_health, flags: info.flags, sector: -1, subsector: -1, floor_z: 0., ceiling_z: 0., dropoff_z: 0.,
+            block_node: -1, blocking_line: -1, blocking_actor: -1, state: S_NULL, tics: -1, sprite: SPR_TROO, frame: 0,
+            target: -1, tracer: -1, reaction_time: info.reaction_time, threshold: 0, move_dir: 0, move_count: 0, player: -1, last_look: 0,
+            spawn_point: Vector3::default(), spawn_angle: 0, spawn_flags: 0, tid: 0, special: 0, args: [0; 5] }
+    }
+
+    pub fn info(&self) -> &'static MobjInfo {
+        &MOBJINFO[self.type_]
+    }
+
+    pub fn class_name(&self) -> &'static str {
+        self.info().name
+    }
+
+    pub fn top(&self) -> f64 {
+        self.pos.z + self.height
+    }
 
+    //R_PointToAngle2, the angle from the actor to the other one
+    pub fn angle_to(&self, other: &Actor) -> Angle<f64> {
+        Vector2 { x: other.pos.x - self.pos.x, y: other.pos.y - self.pos.y }.angle()
+    }
 }
 
+impl Serializable for Actor {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        let mut type_ = self.type_ as i32;
+        let mut state = self.state as i32;
+        let mut sprite = self.sprite as i32;
+        let mut flags = self.flags.bits() as i32;
+        let mut angle = self.angle.degrees();
+        arc.int("type", &mut type_)
+            .float("x", &mut self.pos.x)
+            .float("y", &mut self.pos.y)
+            .float("z", &mut self.pos.z)
+            .float("velx", &mut self.vel.x)
+            .float("vely", &mut self.vel.y)
+            .float("velz", &mut self.vel.z)
+            .float("angle", &mut angle)
+            .float("radius", &mut self.radius)
+            .float("height", &mut self.height)
+            .int("health", &mut self.health)
+            .int("flags", &mut flags)
+            .int("sector", &mut self.sector)
+            .int("subsector", &mut self.subsector)
+            .float("floorz", &mut self.floor_z)
+            .float("ceilingz", &mut self.ceiling_z)
+            .float("dropoffz", &mut self.dropoff_z)
+            .int("state", &mut state)
+            .int("tics", &mut self.tics)
+            .int("sprite", &mut sprite)
+            .int("frame", &mut self.frame)
+            .int("target", &mut self.target)
+            .int("tracer", &mut self.tracer)
+            .int("reactiontime", &mut self.reaction_time)
+            .int("threshold", &mut self.threshold)
+            .int("movedir", &mut self.move_dir)
+            .int("movecount", &mut self.move_count)
+            .int("player", &mut self.player)
+            .int("lastlook", &mut self.last_look)
+            .float("spawnpointx", &mut self.spawn_point.x)
+            .float("spawnpointy", &mut self.spawn_point.y)
+            .float("spawnpointz", &mut self.spawn_point.z)
+            .int("spawnangle", &mut self.spawn_angle)
+            .int("spawnflags", &mut self.spawn_flags)
+            .int("tid", &mut self.tid)
+            .int("special", &mut self.special);
+        for (i, arg) in self.args.iter_mut().enumerate() {
+            arc.int(&format!("arg{}", i), arg);
+        }
+        if arc.is_reading() {
+            self.type_ = (type_ as usize).min(NUMMOBJTYPES - 1);
+            self.state = (state as usize).min(NUMSTATES - 1);
+            self.sprite = (sprite as usize).min(NUMSPRITES - 1);
+            self.flags = MobjFlags::from_bits_truncate(flags as u32);
+            self.angle = Angle::<f64>::from_degrees(angle);
+            self.prev = self.pos;
+        }
+    }
+}
+
+//PClassActor of zdoom, the class is the doom thing type until the actor definitions are parsed
 #[derive(Default)]
 pub struct ClassActor {
+    pub type_: MobjType
+}
 
+impl ClassActor {
+    pub fn info(&self) -> &'static MobjInfo {
+        &MOBJINFO[self.type_]
+    }
+}
+
+/*
+ * Spawning the actors and running their state machine (p_mobj.cpp)
+ */
+impl LevelLocals {
+    pub fn actor(&self, index: ActorIndex) -> Option<&Actor> {
+        match self.thinkers.get(index) {
+            Some(ThinkerData::Actor(actor)) => Some(actor),
+            _ => None
+        }
+    }
+
+    pub fn actor_mut(&mut self, index: ActorIndex) -> Option<&mut Actor> {
+        match self.thinkers.get_mut(index) {
+            Some(ThinkerData::Actor(actor)) => Some(actor),
+            _ => None
+        }
+    }
+
+    //P_SpawnMobj, the z can be ON_FLOOR_Z or ON_CEILING_Z
+    pub fn spawn_actor(&mut self, type_: MobjType, x: f64, y: f64, z: f64) -> ActorIndex {
+        let mut actor = Actor::new(type_);
+        actor.pos = Vector3 { x, y, z };
+        if self.fast_monsters() {actor.reaction_time = 0}
+        actor.last_look = self.random.random() % MAXPLAYERS;
+
+        //the spawn state doesn't call its action
+        let state = &STATES[actor.info().spawn_state];
+        actor.state = actor.info().spawn_state;
+        actor.tics = state.tics;
+        actor.sprite = state.sprite;
+        actor.frame = state.frame;
+
+        actor.subsector = self.point_in_subsector(x, y);
+        actor.sector = if actor.subsector >= 0 {self.subsectors[actor.subsector as usize].borrow().sector} else {-1};
+        if actor.sector >= 0 {
+            let sector = self.sectors[actor.sector as usize].borrow();
+            let xy = actor.pos.xy();
+            actor.floor_z = sector.floorplane.z_at(&xy);
+            actor.ceiling_z = sector.ceilingplane.z_at(&xy);
+            actor.dropoff_z = actor.floor_z;
+        }
+
+        if z == ON_FLOOR_Z {actor.pos.z = actor.floor_z}
+        else if z == ON_CEILING_Z {actor.pos.z = actor.ceiling_z - actor.height}
+        actor.prev = actor.pos;
+        actor.spawn_point = actor.pos;
+
+        if actor.flags.contains(MobjFlags::CountKill) {self.total_monster += 1}
+        if actor.flags.contains(MobjFlags::CountItem) {self.total_items += 1}
+
+        //the sector is already known, the rest of LinkToWorld
+        let (sector, flags, pos, radius) = (actor.sector, actor.flags, actor.pos, actor.radius);
+        let index = self.create_thinker(ThinkerData::Actor(Box::new(actor)), STAT_DEFAULT);
+        Self::link_to_sector(self, index, sector, flags);
+        if !flags.contains(MobjFlags::NoBlockmap) {
+            let block_node = self.block_map.link(index, pos.x, pos.y, radius);
+            if let Some(actor) = self.actor_mut(index) {actor.block_node = block_node}
+        }
+        index
+    }
+
+    //P_SpawnPuff, the puff of a hitscan that hit a wall, the melee attacks don't make sparks
+    pub fn spawn_puff(&mut self, x: f64, y: f64, z: f64, range: f64) -> ActorIndex {
+        let z = z + self.random.random2() as f64 / 64.;
+        let index = Self::spawn_actor(self, MT_PUFF, x, y, z);
+        let tics = self.random.random() & 3;
+        Self::with_actor(self, index, |level, actor| {
+            actor.vel.z = 1.;
+            actor.tics = (actor.tics - tics).max(1);
+            if range == MELEERANGE {Self::set_actor_state(level, index, actor, S_PUFF3);}
+        });
+        index
+    }
+
+    //P_SpawnBlood, there is less blood for less damage
+    pub fn spawn_blood(&mut self, x: f64, y: f64, z: f64, damage: i32) -> ActorIndex {
+        let z = z + self.random.random2() as f64 / 64.;
+        let index = Self::spawn_actor(self, MT_BLOOD, x, y, z);
+        let tics = self.random.random() & 3;
+        Self::with_actor(self, index, |level, actor| {
+            actor.vel.z = 2.;
+            actor.tics = (actor.tics - tics).max(1);
+            if (9..=12).contains(&damage) {Self::set_actor_state(level, index, actor, S_BLOOD2);}
+            else if damage < 9 {Self::set_actor_state(level, index, actor, S_BLOOD3);}
+        });
+        index
+    }
+
+    /*
+     * P_SpawnMissile, the missile of the source flies at the dest.
+     * It can already be exploded when it was spawned in a wall.
+     */
+    pub fn spawn_missile(&mut self, index: ActorIndex, source: &Actor, dest: &Actor, type_: MobjType) -> ActorIndex {
+        let missile = Self::spawn_actor(self, type_, source.pos.x, source.pos.y, source.pos.z + 32.);
+        //TODO the see sound of the missile
+        let mut angle = source.angle_to(dest);
+        //the fuzzy targets are harder to hit
+        if dest.flags.contains(MobjFlags::Shadow) {angle.add(&Angle::<f64>::from_bam_i(self.random.random2() << 20))}
+
+        Self::with_actor(self, missile, |level, actor| {
+            //TODO the faster missiles on nightmare
+            let speed = actor.info().speed;
+            actor.target = index;
+            actor.angle = angle;
+            let vel = angle.to_vector(speed);
+            actor.vel.x = vel.x;
+            actor.vel.y = vel.y;
+            let dist = (approx_distance(dest.pos.x - source.pos.x, dest.pos.y - source.pos.y) / speed).floor().max(1.);
+            actor.vel.z = (dest.pos.z - source.pos.z) / dist;
+            Self::check_missile_spawn(level, missile, actor);
+        });
+        missile
+    }
+
+    //P_SpawnPlayerMissile, the missile of the player is aimed up or down at the thing in front, a bit to the sides when there is none
+    pub fn spawn_player_missile(&mut self, index: ActorIndex, source: &Actor, type_: MobjType) -> ActorIndex {
+        let mut angle = source.angle;
+        let (mut slope, mut target) = Self::aim_line_attack(self, index, source, angle, 16. * 64.);
+        if target < 0 {
+            angle.add(&Angle::<f64>::from_bam_i(1 << 26));
+            (slope, target) = Self::aim_line_attack(self, index, source, angle, 16. * 64.);
+            if target < 0 {
+                angle.subtract(&Angle::<f64>::from_bam_i(2 << 26));
+                (slope, target) = Self::aim_line_attack(self, index, source, angle, 16. * 64.);
+            }
+            if target < 0 {
+                angle = source.angle;
+                slope = 0.;
+            }
+        }
+
+        let missile = Self::spawn_actor(self, type_, source.pos.x, source.pos.y, source.pos.z + 32.);
+        //TODO the see sound of the missile
+        Self::with_actor(self, missile, |level, actor| {
+            let speed = actor.info().speed;
+            actor.target = index;
+            actor.angle = angle;
+            let vel = angle.to_vector(speed);
+            actor.vel.x = vel.x;
+            actor.vel.y = vel.y;
+            actor.vel.z = speed * slope;
+            Self::check_missile_spawn(level, missile, actor);
+        });
+        missile
+    }
+
+    //P_CheckMissileSpawn, the missile starts a bit ahead so a shot against a wall explodes in front of the shooter
+    pub fn check_missile_spawn(&mut self, index: ActorIndex, actor: &mut Actor) -> bool {
+        actor.tics = (actor.tics - (self.random.random() & 3)).max(1);
+        actor.pos.x += actor.vel.x / 2.;
+        actor.pos.y += actor.vel.y / 2.;
+        actor.pos.z += actor.vel.z / 2.;
+        if !Self::try_move(self, index, actor, actor.pos.x, actor.pos.y) {return Self::explode_missile(self, index, actor)}
+        true
+    }
+
+    /*
+     * P_SpawnMapThing, the thing is filtered by the game mode and the skill.
+     * Returns -1 when no actor was spawned, the player starts and the special things are not actors.
+     */
+    pub fn spawn_map_thing(&mut self, thing: &MapThing, game_info: &GameInfo) -> ActorIndex {
+        let entry = match &thing.info {
+            Some(entry) => entry.clone(),
+            None => {
+                if thing.ed_num != 0 && thing.ed_num != -1 {
+                    println!("Unknown type {} at ({}, {})", thing.ed_num, thing.pos.x, thing.pos.y);
+                }
+                return -1
+            }
+        };
+
+        if let Ok(special) = SpecialMapThings::try_from(entry.special) {
+            match special {
+                SpecialMapThings::DeathmatchStart => {
+                    self.death_match_starts.push(PlayerStart::new(thing));
+                    return -1
+                }
+                SpecialMapThings::Player1Start | SpecialMapThings::Player2Start | SpecialMapThings::Player3Start | SpecialMapThings::Player4Start |
+                SpecialMapThings::Player5Start | SpecialMapThings::Player6Start | SpecialMapThings::Player7Start | SpecialMapThings::Player8Start => {
+                    let player = (entry.special - i16::from(SpecialMapThings::Player1Start)) as usize;
+                    let start = PlayerStart::new(thing);
+                    self.all_player_starts.push(start.clone());
+                    //the last start of the player is the real one, the others are voodoo dolls
+                    if game_info.deathmatch {return -1}
+                    self.player_starts[player] = start.clone();
+                    //TODO the other players of a netgame
+                    if player != 0 {return -1}
+                    return Self::spawn_player(self, player, &start)
+                }
+                SpecialMapThings::PolyAnchor | SpecialMapThings::PolySpawn | SpecialMapThings::PolySpawnCrush | SpecialMapThings::PolySpawnHurt => {
+                    //the polyobjects find their things themselves (PO_Init)
+                    return -1
+                }
+                SpecialMapThings::SlopeFloorPointLine | SpecialMapThings::SlopeCeilingPointLine | SpecialMapThings::SetFloorSlope | SpecialMapThings::SetCeilingSlope |
+                SpecialMapThings::VavoomFloor | SpecialMapThings::VavoomCeiling | SpecialMapThings::CopyFloorPlane | SpecialMapThings::CopyCeilingPlane |
+                SpecialMapThings::VertexFloorZ | SpecialMapThings::VertexCeilingZ => {
+                    //TODO SpawnSlopeMakers and SetSlopesFromVertexHeights use them before the things are spawned
+                    return -1
+                }
+                SpecialMapThings::SSeqOverride => {
+                    //TODO the sound sequences of the sector
+                    return -1
+                }
+                SpecialMapThings::EDThing => return -1
+            }
+        }
+
+        //the game mode
+        let mode = if game_info.deathmatch {MapThingFlags::DeathMatch} else if game_info.multiplayer {MapThingFlags::Cooperative} else {MapThingFlags::Single};
+        if thing.flags & mode.bits() == 0 {return -1}
+        if thing.skill_filter & game_info.skill_filter() == 0 {return -1}
+        //TODO the class filter of hexen with the class of the player
+
+        let type_ = entry.type_.type_;
+        let info = entry.type_.info();
+        //the keys are not in deathmatch
+        if game_info.deathmatch && info.flags.contains(MobjFlags::NotDMatch) {return -1}
+        //TODO the nomonsters dmflag
+
+        let spawn_ceiling = info.flags.contains(MobjFlags::SpawnCeiling);
+        let index = Self::spawn_actor(self, type_, thing.pos.x, thing.pos.y, if spawn_ceiling {ON_CEILING_Z} else {ON_FLOOR_Z});
+
+        //the animations of the things of the same type don't go together
+        let tics = self.actor(index).map(|actor| actor.tics).unwrap_or(-1);
+        let tics = if tics > 0 {1 + self.random.random() % tics} else {tics};
+
+        let no_count = thing.flags & MapThingFlags::NoCount.bits() != 0;
+        let mut cleared = (false, false);
+        //only the pickups can be found, the other secret things don't count
+        let count_secret = thing.flags & MapThingFlags::Secret.bits() != 0 && info.flags.contains(MobjFlags::Special);
+        if let Some(actor) = self.actor_mut(index) {
+            actor.tics = tics;
+            if spawn_ceiling {actor.pos.z -= thing.pos.z} else {actor.pos.z += thing.pos.z}
+            actor.prev = actor.pos;
+            actor.spawn_point = actor.pos;
+            actor.angle = Angle::<f64>::from_degrees(thing.angle as f64);
+            actor.spawn_angle = thing.angle as i32;
+            actor.spawn_flags = thing.flags as i32;
+            actor.tid = thing.thing_id;
+            actor.special = thing.special;
+            actor.args = thing.args;
+
+            if thing.flags & MapThingFlags::Ambush.bits() != 0 {actor.flags.insert(MobjFlags::Ambush)}
+            if thing.flags & MapThingFlags::Shadow.bits() != 0 {actor.flags.insert(MobjFlags::Shadow)}
+            if thing.flags & MapThingFlags::Friendly.bits() != 0 {actor.flags.insert(MobjFlags::Friendly)}
+            if thing.flags & MapThingFlags::NoInFighting.bits() != 0 {actor.flags.insert(MobjFlags::NoInFighting)}
+            if count_secret {actor.flags.insert(MobjFlags::CountSecret)}
+            //TODO the dormant, standstill and altshadow flags
+
+            //ClearCounters
+            if no_count {
+                cleared = (actor.flags.contains(MobjFlags::CountKill), actor.flags.contains(MobjFlags::CountItem));
+                actor.flags.remove(MobjFlags::CountKill | MobjFlags::CountItem);
+            }
+        }
+        if cleared.0 {self.total_monster -= 1}
+        if cleared.1 {self.total_items -= 1}
+        if count_secret {self.total_secrets += 1}
+        index
+    }
+
+    /*
+     * P_SpawnPlayer, the actor of the player is spawned at the start.
+     * A player with more starts gets the last one, the actors of the earlier starts stay as voodoo dolls.
+     */
+    pub fn spawn_player(&mut self, player: usize, start: &PlayerStart) -> ActorIndex {
+        let index = Self::spawn_actor(self, MT_PLAYER, start.pos.x, start.pos.y, ON_FLOOR_Z);
+        if let Some(actor) = self.actor_mut(index) {
+            actor.angle = Angle::<f64>::from_degrees(start.angle as f64);
+            actor.player = player as i32;
+            //TODO the player colors, the inventory and the spawn telefrag
+        }
+        let p = &mut self.players[player];
+        p.mo = index;
+        p.player_state = PlayerState::Live;
+        p.health = MOBJINFO[MT_PLAYER].spawn_health;
+        Self::with_actor(self, index, |level, actor| level.setup_psprites(player, index, actor));
+        index
+    }
+
+    //the sector part of LinkToWorld, the things of the sector are in its thing list
+    pub fn link_to_sector(&mut self, index: ActorIndex, sector: SectorIndex, flags: MobjFlags) {
+        if sector < 0 || flags.contains(MobjFlags::NoSector) {return}
+        let mut sector = self.sectors[sector as usize].borrow_mut();
+        if !sector.thing_list.contains(&index) {sector.thing_list.push(index)}
+    }
+
+    pub fn unlink_from_sector(&mut self, index: ActorIndex, sector: SectorIndex) {
+        if sector < 0 {return}
+        self.sectors[sector as usize].borrow_mut().thing_list.retain(|thing| *thing != index);
+    }
+
+    //AActor::LinkToWorld, the actor goes in the sector at its position and in the blocks it touches
+    pub fn link_to_world(&mut self, index: ActorIndex, actor: &mut Actor) {
+        actor.subsector = self.point_in_subsector(actor.pos.x, actor.pos.y);
+        actor.sector = if actor.subsector >= 0 {self.subsectors[actor.subsector as usize].borrow().sector} else {-1};
+        Self::link_to_sector(self, index, actor.sector, actor.flags);
+        if !actor.flags.contains(MobjFlags::NoBlockmap) {
+            actor.block_node = self.block_map.link(index, actor.pos.x, actor.pos.y, actor.radius);
+        }
+    }
+
+    //AActor::UnlinkFromWorld
+    pub fn unlink_from_world(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::unlink_from_sector(self, index, actor.sector);
+        self.block_map.unlink(actor.block_node);
+        actor.block_node = -1;
+    }
+
+    /*
+     * P_SetMobjState, the states with no tics are gone through at once.
+     * Returns false when the actor was removed, S_NULL removes it.
+     */
+    pub fn set_actor_state(&mut self, index: ActorIndex, actor: &mut Actor, mut state: StateNum) -> bool {
+        loop {
+            if state == S_NULL {
+                actor.state = S_NULL;
+                Self::destroy_thinker(self, index);
+                return false
+            }
+
+            let st = &STATES[state];
+            actor.state = state;
+            actor.tics = st.tics;
+            actor.sprite = st.sprite;
+            actor.frame = st.frame;
+
+            if let Some(action) = st.action {
+                Self::call_action(self, index, actor, action);
+                if !self.thinkers.exists(index) {return false}
+            }
+
+            state = st.next_state;
+            if actor.tics != 0 {return true}
+        }
+    }
+
+    //the tic of the actor (AActor::Tick), false when it is removed
+    pub fn tick_actor(&mut self, index: ActorIndex, actor: &mut Actor) -> bool {
+        actor.prev = actor.pos;
+
+        if (actor.vel.x != 0. || actor.vel.y != 0. || actor.flags.contains(MobjFlags::SkullFly)) && !Self::xy_movement(self, index, actor) {
+            return false
+        }
+        if (actor.pos.z != actor.floor_z || actor.vel.z != 0.) && !Self::z_movement(self, index, actor) {
+            return false
+        }
+
+        //the state with -1 tics stays
+        if actor.tics == -1 {
+            //TODO the respawning of the monsters
+            return true
+        }
+
+        actor.tics -= 1;
+        if actor.tics <= 0 {
+            let next = STATES[actor.state].next_state;
+            return Self::set_actor_state(self, index, actor, next)
+        }
+        true
+    }
+
+    //the code pointers of the states, most of them belong to the monsters and the weapons
+    pub fn call_action(&mut self, index: ActorIndex, actor: &mut Actor, action: ActionFunction) {
+        match action {
+            ActionFunction::Explode => Self::a_explode(self, index, actor),
+            ActionFunction::Pain => Self::a_pain(self, index, actor),
+            ActionFunction::PlayerScream => Self::a_player_scream(self, index, actor),
+            ActionFunction::Fall => actor.flags.remove(MobjFlags::Solid),
+            ActionFunction::XScream => Self::a_xscream(self, index, actor),
+            ActionFunction::Look => Self::a_look(self, index, actor),
+            ActionFunction::Chase => Self::a_chase(self, index, actor),
+            ActionFunction::FaceTarget => Self::face_target(self, actor),
+            ActionFunction::PosAttack => Self::a_pos_attack(self, index, actor),
+            ActionFunction::Scream => Self::a_scream(self, index, actor),
+            ActionFunction::SPosAttack => Self::a_spos_attack(self, index, actor),
+            ActionFunction::VileChase => Self::a_vile_chase(self, index, actor),
+            ActionFunction::VileStart => Self::a_vile_start(self, index, actor),
+            ActionFunction::VileTarget => Self::a_vile_target(self, index, actor),
+            ActionFunction::VileAttack => Self::a_vile_attack(self, index, actor),
+            ActionFunction::StartFire => Self::a_start_fire(self, index, actor),
+            ActionFunction::Fire => Self::a_fire(self, index, actor),
+            ActionFunction::FireCrackle => Self::a_fire_crackle(self, index, actor),
+            ActionFunction::Tracer => Self::a_tracer(self, index, actor),
+            ActionFunction::SkelWhoosh => Self::a_skel_whoosh(self, index, actor),
+            ActionFunction::SkelFist => Self::a_skel_fist(self, index, actor),
+            ActionFunction::SkelMissile => Self::a_skel_missile(self, index, actor),
+            ActionFunction::FatRaise => Self::a_fat_raise(self, index, actor),
+            ActionFunction::FatAttack1 => Self::a_fat_attack1(self, index, actor),
+            ActionFunction::FatAttack2 => Self::a_fat_attack2(self, index, actor),
+            ActionFunction::FatAttack3 => Self::a_fat_attack3(self, index, actor),
+            ActionFunction::BossDeath => Self::a_boss_death(self, index, actor),
+            ActionFunction::CPosAttack => Self::a_cpos_attack(self, index, actor),
+            ActionFunction::CPosRefire => Self::a_cpos_refire(self, index, actor),
+            ActionFunction::TroopAttack => Self::a_troop_attack(self, index, actor),
+            ActionFunction::SargAttack => Self::a_sarg_attack(self, index, actor),
+            ActionFunction::HeadAttack => Self::a_head_attack(self, index, actor),
+            ActionFunction::BruisAttack => Self::a_bruis_attack(self, index, actor),
+            ActionFunction::SkullAttack => Self::a_skull_attack(self, index, actor),
+            ActionFunction::Metal => Self::a_walk_sound(self, index, actor, "metal"),
+            ActionFunction::SpidRefire => Self::a_spid_refire(self, index, actor),
+            ActionFunction::BabyMetal => Self::a_walk_sound(self, index, actor, "bspwlk"),
+            ActionFunction::BspiAttack => Self::a_bspi_attack(self, index, actor),
+            ActionFunction::Hoof => Self::a_walk_sound(self, index, actor, "hoof"),
+            ActionFunction::CyberAttack => Self::a_cyber_attack(self, index, actor),
+            ActionFunction::PainAttack => Self::a_pain_attack(self, index, actor),
+            ActionFunction::PainDie => Self::a_pain_die(self, index, actor),
+            ActionFunction::KeenDie => Self::a_keen_die(self, index, actor),
+            ActionFunction::BrainPain => Self::a_brain_pain(self, index, actor),
+            ActionFunction::BrainScream => Self::a_brain_scream(self, index, actor),
+            ActionFunction::BrainDie => Self::a_brain_die(self, index, actor),
+            ActionFunction::BrainAwake => Self::a_brain_awake(self, index, actor),
+            ActionFunction::BrainSpit => Self::a_brain_spit(self, index, actor),
+            ActionFunction::SpawnSound => Self::a_spawn_sound(self, index, actor),
+            ActionFunction::SpawnFly => Self::a_spawn_fly(self, index, actor),
+            ActionFunction::BrainExplode => Self::a_brain_explode(self, index, actor),
+            ActionFunction::BFGSpray => Self::a_bfg_spray(self, index, actor),
+            //the weapon actions are called by the psprites
+            _ => println!("Actor: {:?} is an action of the weapons", action)
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct EventManager {}
 
 #[derive(Default)]
-pub struct BehaviorContainer {}
+pub struct BehaviorContainer {
+    pub profiler: AcsProfiler,
+    pub script_requests: Vec<ScriptRequest>,
+}
+
+impl BehaviorContainer {
+    #[allow(clippy::too_many_arguments)]
+    pub fn request_script(&mut self, action: ScriptAction, script: i32, map: i32, args: &[i32], activator: Activator, line: LineIndex, backside: bool) {
+        let mut script_args = [0;4];
+        let count = args.len().min(4);
+        script_args[..count].copy_from_slice(&args[..count]);
+        self.script_requests.push(ScriptRequest { action, script, map, args: script_args, activator, line, backside });
+    }
+
+    //hands the requests over to the vm
+    pub fn take_script_requests(&mut self) -> Vec<ScriptRequest> {
+        std::mem::take(&mut self.script_requests)
+    }
+}
 
 pub struct StrifeDialogueNode {}
 
@@ -22,14 +627,81 @@ pub struct StrifeDialogueNode {}
 pub struct CajunMaster {}
 
 
-#[derive(Default)]
-pub struct Player {}
+//playerstate_t
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum PlayerState {
+    #[default]
+    Live, //playing or camping
+    Dead, //dead on the ground, the view follows the killer
+    Reborn //ready to restart or respawn
+}
 
-#[derive(Default)]
-pub struct ThinkerCollection {}
+//player_t, the part of the player that belongs to the level
+#[derive(Clone)]
+pub struct Player {
+    pub mo: ActorIndex, //the actor of the player, -1 when it isn't in the level
+    pub player_state: PlayerState,
+    pub cmd: TicCmd,
+    pub health: i32, //the health of the status bar, the actor has the real one
+    pub armor_points: i32,
+    pub armor_type: i32, //0 is none, 1 is the green armor, 2 the blue one
+    pub damage_count: i32, //the red of the screen
+    pub bonus_count: i32, //the yellow of the screen when something is picked up
+    pub attacker: ActorIndex, //who did the damage, -1 for the world
+    pub kill_count: i32,
+    pub item_count: i32,
+    pub secret_count: i32,
 
-#[derive(Default)]
-pub struct PlayerStart {}
+    //the weapons
+    pub ready_weapon: WeaponType,
+    pub pending_weapon: WeaponType, //WP_NOCHANGE when the weapon isn't changed
+    pub weapon_owned: [bool; NUMWEAPONS],
+    pub ammo: [i32; NUMAMMO],
+    pub max_ammo: [i32; NUMAMMO],
+    pub backpack: bool,
+    pub psprites: [PspDef; NUMPSPRITES],
+    pub attack_down: bool, //the attack was down at the last tic
+    pub refire: i32, //the shots in a row, the first one is accurate
+    pub extra_light: i32, //the light of the gun flashes
+    pub bob: f64, //the bob of the weapon and the view with the movement
+    pub fix_angle: bool //a teleport turned the actor, the view takes its angle
+}
+
+//a new player has the fist and the pistol with some bullets (G_PlayerReborn)
+impl Default for Player {
+    fn default() -> Self {
+        let mut weapon_owned = [false; NUMWEAPONS];
+        weapon_owned[WP_FIST] = true;
+        weapon_owned[WP_PISTOL] = true;
+        let mut ammo = [0; NUMAMMO];
+        ammo[AM_CLIP] = 50;
+        Player { mo: -1, player_state: PlayerState::Live, cmd: TicCmd::default(), health: MOBJINFO[MT_PLAYER].spawn_health, armor_points: 0, armor_type: 0,
+            damage_count: 0, bonus_count: 0, attacker: -1, kill_count: 0, item_count: 0, secret_count: 0, ready_weapon: WP_PISTOL, pending_weapon: WP_PISTOL, weapon_owned, ammo,
+            max_ammo: MAXAMMO, backpack: false, psprites: [PspDef::default(); NUMPSPRITES], attack_down: false, refire: 0, extra_light: 0, bob: 0., fix_angle: false }
+    }
+}
+
+//ticcmd_t, what the player wants to do in the tic
+#[derive(Default, Clone, Copy)]
+pub struct TicCmd {
+    pub attack: bool,
+    pub weapon_slot: Option<usize> //the weapon slot that was selected, 0 is the first
+}
+
+//FPlayerStart of zdoom
+#[derive(Default, Clone)]
+pub struct PlayerStart {
+    pub pos: Vector3<f64>,
+    pub angle: i16,
+    pub type_: i16, //the editor number
+    pub flags: u32
+}
+
+impl PlayerStart {
+    pub fn new(thing: &MapThing) -> PlayerStart {
+        PlayerStart { pos: thing.pos, angle: thing.angle, type_: thing.ed_num, flags: thing.flags }
+    }
+}
 
 #[derive(Default)]
 pub struct SequenceNode {}
@@ -44,7 +716,9 @@ pub struct BlockMap {
     pub blockmap_height: i32,
     pub blockmap_origin_x: f64,
     pub blockmap_origin_y: f64,
-    pub block_links: Vec<BlockNode>
+    pub block_links: Vec<BlockNodeIndex>, //the first node of the things in each block
+    pub block_nodes: Vec<BlockNode>,
+    pub free_nodes: Vec<BlockNodeIndex>
 }
 
 impl BlockMap {
@@ -101,9 +775,10 @@ impl BlockMap {
 
 pub type BlockNodeIndex = i32;
 
+//the things of a block are a list of the nodes, the nodes of one thing are linked with next_block
 #[derive(Clone)]
 pub struct BlockNode {
-    me: Option<Rc<Actor>>,
+    me: ActorIndex,
     block_index: i32,
     group: i32,
     prev_actor: BlockNodeIndex,
@@ -114,6 +789,104 @@ pub struct BlockNode {
 
 impl BlockNode {
     pub fn new() -> BlockNode {
-        BlockNode { me: None, block_index: 0, group: 0, prev_actor: -1, next_actor: -1, prev_block: -1, next_block: -1 }
+        BlockNode { me: -1, block_index: 0, group: 0, prev_actor: -1, next_actor: -1, prev_block: -1, next_block: -1 }
+    }
+}
+
+/*
+ * Finding the lines and things of the blocks, the blocks are MAPBLOCKUNITS big
+ */
+impl BlockMap {
+    pub fn block_x(&self, x: f64) -> i32 {
+        ((x - self.blockmap_origin_x) / MAPBLOCKUNITS).floor() as i32
+    }
+
+    pub fn block_y(&self, y: f64) -> i32 {
+        ((y - self.blockmap_origin_y) / MAPBLOCKUNITS).floor() as i32
     }
-}
\ No newline at end of file
+
+    pub fn is_valid_block(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.blockmap_width && y < self.blockmap_height
+    }
+
+    //the lines of the block, the offsets of the lump count the 4 shorts of the header
+    pub fn block_lines(&self, x: i32, y: i32) -> Vec<LineIndex> {
+        let mut lines = vec![];
+        if !self.is_valid_block(x, y) {return lines}
+        let offset = match self.blockmap.get((y * self.blockmap_width + x) as usize) {
+            Some(offset) => *offset - 4,
+            None => return lines
+        };
+        if offset < 0 {return lines}
+        //the list starts with a 0 that isn't a line
+        for line in self.blockmap.iter().skip(offset as usize + 1) {
+            if *line == -1 {break}
+            lines.push(*line);
+        }
+        lines
+    }
+
+    pub fn block_things(&self, x: i32, y: i32) -> Vec<ActorIndex> {
+        let mut things = vec![];
+        if !self.is_valid_block(x, y) {return things}
+        let mut node = self.block_links[(y * self.blockmap_width + x) as usize];
+        while node >= 0 {
+            let block_node = &self.block_nodes[node as usize];
+            things.push(block_node.me);
+            node = block_node.next_actor;
+        }
+        things
+    }
+
+    //puts the thing in all the blocks it touches, returns the first node of the thing
+    pub fn link(&mut self, actor: ActorIndex, x: f64, y: f64, radius: f64) -> BlockNodeIndex {
+        let (x1, x2) = (self.block_x(x - radius), self.block_x(x + radius));
+        let (y1, y2) = (self.block_y(y - radius), self.block_y(y + radius));
+        let mut first = -1;
+        let mut last: BlockNodeIndex = -1;
+        for by in y1..=y2 {
+            for bx in x1..=x2 {
+                if !self.is_valid_block(bx, by) {continue}
+                let block_index = by * self.blockmap_width + bx;
+                let head = self.block_links[block_index as usize];
+
+                let mut node = BlockNode::new();
+                node.me = actor;
+                node.block_index = block_index;
+                node.next_actor = head;
+                node.prev_block = last;
+                let index = match self.free_nodes.pop() {
+                    Some(index) => {
+                        self.block_nodes[index as usize] = node;
+                        index
+                    }
+                    None => {
+                        self.block_nodes.push(node);
+                        self.block_nodes.len() as BlockNodeIndex - 1
+                    }
+                };
+
+                if head >= 0 {self.block_nodes[head as usize].prev_actor = index}
+                self.block_links[block_index as usize] = index;
+                if last >= 0 {self.block_nodes[last as usize].next_block = index}
+                else {first = index}
+                last = index;
+            }
+        }
+        first
+    }
+
+    //takes the nodes of the thing out of their blocks
+    pub fn unlink(&mut self, first: BlockNodeIndex) {
+        let mut index = first;
+        while index >= 0 {
+            let node = self.block_nodes[index as usize].clone();
+            if node.prev_actor >= 0 {self.block_nodes[node.prev_actor as usize].next_actor = node.next_actor}
+            else {self.block_links[node.block_index as usize] = node.next_actor}
+            if node.next_actor >= 0 {self.block_nodes[node.next_actor as usize].prev_actor = node.prev_actor}
+            self.block_nodes[index as usize] = BlockNode::new();
+            self.free_nodes.push(index);
+            index = node.next_block;
+        }
+    }
+}
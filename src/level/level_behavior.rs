@@ -1,3 +1,144 @@
+use num_derive::{FromPrimitive, ToPrimitive};
+
+use super::LevelLocals;
+use super::level_elements::LineIndex;
+use super::level_specials::{Activator, ActivatorType};
+use super::level_serializer::{Serializable, Serializer};
+use crate::behavior::acs_profile::AcsProfileKind;
+
 // pub fn load_behavior() {
     
-// }
\ No newline at end of file
+// }
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum ScriptAction {
+    #[default]
+    Execute,
+    ExecuteAlways,
+    ExecuteWithResult,
+    Suspend,
+    Terminate
+}
+
+//a script start/stop asked for by a special, these get handled by the acs vm
+#[derive(Clone, Debug)]
+pub struct ScriptRequest {
+    pub action: ScriptAction,
+    pub script: i32,
+    pub map: i32,
+    pub args: [i32;4],
+    pub activator: Activator,
+    pub line: LineIndex,
+    pub backside: bool
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum ScriptState {
+    #[default]
+    Running,
+    Suspended,
+    Finished //removed after the tic (SCRIPT_PleaseRemove)
+}
+
+//a script that was started on the level (DLevelScript of zdoom)
+#[derive(Clone, Debug)]
+pub struct RunningScript {
+    pub script: i32,
+    pub args: [i32;4],
+    pub activator: Activator,
+    pub line: LineIndex,
+    pub backside: bool,
+    pub state: ScriptState
+}
+
+impl Default for RunningScript {
+    fn default() -> Self {
+        RunningScript { script: 0, args: [0;4], activator: Activator::world(), line: -1, backside: false, state: ScriptState::Running }
+    }
+}
+
+impl Serializable for RunningScript {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        arc.int("script", &mut self.script)
+            .int("arg0", &mut self.args[0])
+            .int("arg1", &mut self.args[1])
+            .int("arg2", &mut self.args[2])
+            .int("arg3", &mut self.args[3])
+            .int("activator", &mut self.activator.actor)
+            .enumeration::<ActivatorType>("activatortype", &mut self.activator.type_)
+            .int("line", &mut self.line)
+            .boolean("backside", &mut self.backside)
+            .enumeration("state", &mut self.state);
+    }
+}
+
+//the thinker that runs all the scripts of the level (DACSThinker of zdoom)
+#[derive(Default)]
+pub struct AcsThinker {
+    pub scripts: Vec<RunningScript>
+}
+
+impl AcsThinker {
+    pub fn is_running(&self, script: i32) -> bool {
+        self.scripts.iter().any(|running| running.script == script)
+    }
+}
+
+impl Serializable for AcsThinker {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        let mut count = self.scripts.len() as i32;
+        arc.int("numscripts", &mut count);
+        if arc.is_reading() {self.scripts = vec![RunningScript::default(); count.max(0) as usize]}
+        for script in self.scripts.iter_mut() {
+            script.serialize(arc);
+        }
+    }
+}
+
+impl LevelLocals {
+    //starts and stops the scripts the specials asked for, then runs them
+    pub fn tick_scripts(&mut self, scripts: &mut AcsThinker) -> bool {
+        for request in self.behaviors.take_script_requests() {
+            if request.map != 0 && request.map != self.level_num {
+                //TODO the scripts of other maps wait until that map is entered (P_AddDeferredScript)
+                continue
+            }
+            match request.action {
+                ScriptAction::Execute | ScriptAction::ExecuteWithResult => {
+                    //a suspended script continues
+                    if let Some(running) = scripts.scripts.iter_mut().find(|running| running.script == request.script) {
+                        running.state = ScriptState::Running;
+                        continue
+                    }
+                    scripts.scripts.push(RunningScript { script: request.script, args: request.args, activator: request.activator, line: request.line, backside: request.backside, state: ScriptState::Running });
+                }
+                ScriptAction::ExecuteAlways => {
+                    scripts.scripts.push(RunningScript { script: request.script, args: request.args, activator: request.activator, line: request.line, backside: request.backside, state: ScriptState::Running });
+                }
+                ScriptAction::Suspend => {
+                    for running in scripts.scripts.iter_mut().filter(|running| running.script == request.script) {
+                        running.state = ScriptState::Suspended;
+                    }
+                }
+                ScriptAction::Terminate => scripts.scripts.retain(|running| running.script != request.script)
+            }
+        }
+        //the profiler gets each run of a script that did something, it only records while acsprofile is on
+        for running in scripts.scripts.iter_mut().filter(|running| running.state == ScriptState::Running) {
+            let run = self.behaviors.profiler.begin_run(AcsProfileKind::Script, &self.map_name, running.script);
+            let num_instr = Self::run_script(self, running);
+            if num_instr > 0 {self.behaviors.profiler.end_run(run, &running.script.to_string(), num_instr)}
+        }
+        scripts.scripts.retain(|running| running.state != ScriptState::Finished);
+        true
+    }
+
+    /*
+     * Runs the script until it suspends or ends, returns the instructions it executed.
+     * TODO the acs vm, until then there is no code to run so the script ends right away
+     */
+    fn run_script(&mut self, running: &mut RunningScript) -> u32 {
+        running.state = ScriptState::Finished;
+        0
+    }
+}
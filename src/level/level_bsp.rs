@@ -1,7 +1,103 @@
-
+use super::level_elements::{LevelElements, ChildNode, LineIndex, NodeIndex, SectorIndex, SubSectorIndex, BOXTOP, BOXBOTTOM, BOXLEFT, BOXRIGHT};
 
 #[derive(Default)]
 pub struct AABBTree {}
 
 #[derive(Clone)]
-pub struct MiniBSP {}
\ No newline at end of file
+pub struct MiniBSP {}
+
+/*
+ * Walking the nodes of the bsp (r_bsp.cpp and p_maputl.cpp)
+ */
+impl LevelElements {
+    //the child the map starts in, a map with one subsector has no nodes
+    pub fn root_child(&self) -> Option<ChildNode> {
+        if !self.nodes.is_empty() {Some(ChildNode::new(-1, self.nodes.len() as NodeIndex - 1))}
+        else if !self.subsectors.is_empty() {Some(ChildNode::new(0, -1))}
+        else {None}
+    }
+
+    //R_PointOnSide, 0 for the front (right) child of the node and 1 for the back
+    pub fn point_on_node_side(&self, x: f64, y: f64, node: NodeIndex) -> usize {
+        let node = self.nodes[node as usize].borrow();
+        ((y - node.y as f64) * node.dx as f64 + (node.x as f64 - x) * node.dy as f64 > 0.) as usize
+    }
+
+    //R_PointInSubsector, walks the nodes down from the root
+    pub fn point_in_subsector(&self, x: f64, y: f64) -> SubSectorIndex {
+        let mut child = match self.root_child() {
+            Some(child) => child,
+            None => return -1
+        };
+        while child.node >= 0 {
+            let side = self.point_on_node_side(x, y, child.node);
+            child = self.nodes[child.node as usize].borrow().children[side];
+        }
+        child.subsector
+    }
+
+    //P_PointInSector
+    pub fn point_in_sector(&self, x: f64, y: f64) -> SectorIndex {
+        let subsector = self.point_in_subsector(x, y);
+        if subsector < 0 {return -1}
+        self.subsectors[subsector as usize].borrow().sector
+    }
+
+    //P_PointOnLineSide, 0 for the front and 1 for the back
+    pub fn point_on_line_side(&self, x: f64, y: f64, line: LineIndex) -> i32 {
+        self.lines[line as usize].borrow().point_on_side(x, y)
+    }
+
+    //P_BoxOnLineSide, the side the box is on or -1 when the line goes through it
+    pub fn box_on_line_side(&self, bbox: &[f64;4], line: LineIndex) -> i32 {
+        self.lines[line as usize].borrow().box_on_side(bbox)
+    }
+
+    //the bounding box of one of the children of the node
+    pub fn node_bbox(&self, node: NodeIndex, side: usize) -> [f64;4] {
+        self.nodes[node as usize].borrow().bbox[side].map(f64::from)
+    }
+
+    /*
+     * Goes through the subsectors front to back as seen from the point (R_RenderBSPNode).
+     * A child of a node is only visited when check_box accepts its bounding box.
+     * Returns false when visit stopped the traversal by returning false.
+     */
+    pub fn traverse_bsp<B, V>(&self, x: f64, y: f64, mut check_box: B, mut visit: V) -> bool
+            where B: FnMut(&[f64;4]) -> bool, V: FnMut(SubSectorIndex) -> bool {
+        match self.root_child() {
+            Some(child) => self.traverse_child(child, x, y, &mut check_box, &mut visit),
+            None => true
+        }
+    }
+
+    fn traverse_child<B, V>(&self, child: ChildNode, x: f64, y: f64, check_box: &mut B, visit: &mut V) -> bool
+            where B: FnMut(&[f64;4]) -> bool, V: FnMut(SubSectorIndex) -> bool {
+        if child.node < 0 {return visit(child.subsector)}
+
+        let side = self.point_on_node_side(x, y, child.node);
+        let children = self.nodes[child.node as usize].borrow().children;
+        if check_box(&self.node_bbox(child.node, side)) && !self.traverse_child(children[side], x, y, check_box, visit) {
+            return false
+        }
+        if check_box(&self.node_bbox(child.node, side ^ 1)) {
+            return self.traverse_child(children[side ^ 1], x, y, check_box, visit)
+        }
+        true
+    }
+
+    //the subsectors that touch the box, the ones closest to the center come first
+    pub fn subsectors_in_box(&self, bbox: &[f64;4]) -> Vec<SubSectorIndex> {
+        let mut subsectors = vec![];
+        let (x, y) = ((bbox[BOXLEFT] + bbox[BOXRIGHT]) / 2., (bbox[BOXTOP] + bbox[BOXBOTTOM]) / 2.);
+        self.traverse_bsp(x, y, |node_box| boxes_touch(bbox, node_box), |subsector| {
+            subsectors.push(subsector);
+            true
+        });
+        subsectors
+    }
+}
+
+pub fn boxes_touch(a: &[f64;4], b: &[f64;4]) -> bool {
+    a[BOXLEFT] <= b[BOXRIGHT] && a[BOXRIGHT] >= b[BOXLEFT] && a[BOXBOTTOM] <= b[BOXTOP] && a[BOXTOP] >= b[BOXBOTTOM]
+}
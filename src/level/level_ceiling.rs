@@ -0,0 +1,235 @@
+use super::LevelLocals;
+use super::level_elements::{LineIndex, SectorIndex, SectorE, SecSpecial};
+use super::level_texture::TextureID;
+use super::level_movers::*;
+use super::level_serializer::{Serializable, Serializer};
+use super::level_thinker::STAT_SECTOREFFECT;
+
+//DCeiling of zdoom, also the crushers
+#[derive(Default)]
+pub struct Ceiling {
+    pub sector: SectorIndex,
+    pub type_: CeilingType,
+    pub tag: i32,
+    pub bottom_height: f64,
+    pub top_height: f64,
+    pub speed: f64,
+    pub speed1: f64, //down speed of the crushers
+    pub speed2: f64, //up speed of the crushers
+    pub crush: i32,
+    pub crush_mode: CrushMode,
+    pub silent: i32,
+    pub direction: i32, //0 -> in stasis
+    pub old_direction: i32,
+    //the texture and special the sector gets when the ceiling is done (None -> no change)
+    pub texture: Option<TextureID>,
+    pub new_special: Option<SecSpecial>
+}
+
+impl Serializable for Ceiling {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        arc.int("sector", &mut self.sector)
+            .enumeration("type", &mut self.type_)
+            .int("tag", &mut self.tag)
+            .float("bottomheight", &mut self.bottom_height)
+            .float("topheight", &mut self.top_height)
+            .float("speed", &mut self.speed)
+            .float("speed1", &mut self.speed1)
+            .float("speed2", &mut self.speed2)
+            .int("crush", &mut self.crush)
+            .enumeration("crushmode", &mut self.crush_mode)
+            .int("silent", &mut self.silent)
+            .int("direction", &mut self.direction)
+            .int("olddirection", &mut self.old_direction)
+            .option("texture", &mut self.texture, |arc, key, texture| {arc.texture(key, texture);})
+            .option("newspecial", &mut self.new_special, |arc, key, special| {arc.special(key, special);});
+    }
+}
+
+/*
+ * The ceiling movers (p_ceiling.cpp of zdoom)
+ */
+#[allow(clippy::too_many_arguments)]
+impl LevelLocals {
+    pub fn ev_do_ceiling(&mut self, type_: CeilingType, line: LineIndex, tag: i32, speed: f64, speed2: f64, height: f64, crush: i32, silent: i32, change: i32, crush_mode: CrushMode) -> bool {
+        //manual trigger, only the sector behind the line
+        if tag == 0 {
+            if line < 0 {return false}
+            let sector = self.lines[line as usize].borrow().back_sector;
+            if sector < 0 {return false}
+            //the manual crushers can be restarted too
+            let tag = tag ^ (sector | 0x1000000);
+            Self::activate_in_stasis_ceiling(self, tag);
+            return Self::create_ceiling(self, sector, type_, line, tag, speed, speed2, height, crush, silent, change, crush_mode)
+        }
+
+        //restart the stopped crushers
+        if type_ == CeilingType::CrushAndRaise {Self::activate_in_stasis_ceiling(self, tag)}
+
+        let mut rtn = false;
+        for sector in Self::sectors_with_tag(self, tag) {
+            rtn |= Self::create_ceiling(self, sector, type_, line, tag, speed, speed2, height, crush, silent, change, crush_mode);
+        }
+        rtn
+    }
+
+    pub fn create_ceiling(&mut self, sector: SectorIndex, type_: CeilingType, line: LineIndex, tag: i32, speed: f64, speed2: f64, height: f64, crush: i32, silent: i32, change: i32, crush_mode: CrushMode) -> bool {
+        let sector_rc = self.elements.sectors[sector as usize].clone();
+        //already moving, don't start a second one
+        if sector_rc.borrow().plane_moving(SectorE::Ceiling as usize) {return false}
+
+        let (spot, ceiling_d, center_ceiling) = {
+            let sec = sector_rc.borrow();
+            (sec.center_spot, sec.ceilingplane.fd(), sec.center_ceiling())
+        };
+        let dist = |height: f64| sector_rc.borrow().ceilingplane.point_to_dist(&spot, height);
+
+        let crush_mode = if crush_mode == CrushMode::Default {CrushMode::Doom} else {crush_mode};
+        let mut ceiling = Ceiling { sector, type_, tag, bottom_height: ceiling_d, top_height: ceiling_d, speed, speed1: speed, speed2, crush, crush_mode, silent: silent & !4,
+            direction: 0, old_direction: 0, texture: None, new_special: None };
+
+        let (direction, target_height) = match type_ {
+            CeilingType::CrushAndRaise | CeilingType::CrushRaiseAndStay | CeilingType::LowerAndCrush =>
+                (-1, Self::find_highest_floor_point(self, sector) + height),
+            CeilingType::RaiseToHighest => (1, Self::find_highest_ceiling_surrounding(self, sector)),
+            CeilingType::LowerByValue => (-1, center_ceiling - height),
+            CeilingType::RaiseByValue => (1, center_ceiling + height),
+            CeilingType::MoveToValue => (if height < center_ceiling {-1} else {1}, height),
+            CeilingType::LowerToHighestFloor => (-1, Self::find_highest_floor_surrounding(self, sector) + height),
+            CeilingType::RaiseToHighestFloor => (1, Self::find_highest_floor_surrounding(self, sector)),
+            CeilingType::LowerInstant => {
+                ceiling.speed = height;
+                (-1, center_ceiling - height)
+            }
+            CeilingType::RaiseInstant => {
+                ceiling.speed = height;
+                (1, center_ceiling + height)
+            }
+            CeilingType::LowerToNearest => (-1, Self::find_next_lowest_ceiling(self, sector)),
+            CeilingType::RaiseToNearest => (1, Self::find_next_highest_ceiling(self, sector)),
+            CeilingType::LowerToLowest => (-1, Self::find_lowest_ceiling_surrounding(self, sector)),
+            CeilingType::RaiseToLowest => (1, Self::find_lowest_ceiling_surrounding(self, sector)),
+            CeilingType::LowerToFloor => (-1, Self::find_highest_floor_point(self, sector) + height),
+            //moving the wrong way gets there in one step (the instant types)
+            CeilingType::RaiseToFloor | CeilingType::ToFloorInstant => (1, Self::find_highest_floor_point(self, sector) + height),
+            CeilingType::LowerToHighest | CeilingType::ToHighestInstant => (-1, Self::find_highest_ceiling_surrounding(self, sector)),
+            CeilingType::LowerByTexture => (-1, center_ceiling - Self::find_shortest_upper_around(self, sector)),
+            CeilingType::RaiseByTexture => (1, center_ceiling + Self::find_shortest_upper_around(self, sector))
+        };
+        ceiling.direction = direction;
+        if direction < 0 {ceiling.bottom_height = dist(target_height)}
+        else {ceiling.top_height = dist(target_height)}
+
+        if change & 3 != 0 {
+            let model = if change & 4 != 0 {
+                //numeric model change
+                match type_ {
+                    CeilingType::LowerToHighestFloor | CeilingType::RaiseToHighestFloor | CeilingType::LowerToFloor | CeilingType::RaiseToFloor | CeilingType::ToFloorInstant =>
+                        Self::find_model_floor_sector(self, sector, target_height),
+                    _ => Self::find_model_ceiling_sector(self, sector, target_height)
+                }
+            }
+            else if line >= 0 {
+                //trigger model change
+                self.lines[line as usize].borrow().front_sector
+            }
+            else {-1};
+            if model >= 0 {Self::set_ceiling_change_type(self, &mut ceiling, model, change & 3)}
+        }
+
+        //TODO the ceiling sound
+        Self::add_mover(self, SectorMover::Ceiling(ceiling));
+        true
+    }
+
+    fn set_ceiling_change_type(&self, ceiling: &mut Ceiling, model: SectorIndex, change: i32) {
+        let model = self.sectors[model as usize].borrow();
+        ceiling.texture = Some(model.get_texture(SectorE::Ceiling as usize));
+        match change {
+            1 => ceiling.new_special = Some(SecSpecial::default()),
+            3 => ceiling.new_special = Some(model.get_special()),
+            _ => ()
+        }
+    }
+
+    pub fn tick_ceiling(&mut self, ceiling: &mut Ceiling) -> bool {
+        match ceiling.direction {
+            1 => {
+                let res = Self::move_ceiling(self, ceiling.sector, ceiling.speed, ceiling.top_height, -1, ceiling.direction, false);
+                if res != MoveResult::PastDest {return true}
+                if ceiling.type_ == CeilingType::CrushAndRaise {
+                    ceiling.direction = -1;
+                    ceiling.speed = ceiling.speed1;
+                    return true
+                }
+                Self::finish_ceiling(self, ceiling);
+                false
+            }
+            -1 => {
+                let res = Self::move_ceiling(self, ceiling.sector, ceiling.speed, ceiling.bottom_height, ceiling.crush, ceiling.direction, ceiling.crush_mode == CrushMode::Hexen);
+                match res {
+                    MoveResult::PastDest => {
+                        if ceiling.type_ == CeilingType::CrushAndRaise || ceiling.type_ == CeilingType::CrushRaiseAndStay {
+                            ceiling.speed = ceiling.speed2;
+                            ceiling.direction = 1;
+                            return true
+                        }
+                        Self::finish_ceiling(self, ceiling);
+                        false
+                    }
+                    MoveResult::Crushed => {
+                        let crusher = matches!(ceiling.type_, CeilingType::CrushAndRaise | CeilingType::LowerAndCrush | CeilingType::CrushRaiseAndStay);
+                        if crusher && ceiling.crush_mode == CrushMode::Slowdown {ceiling.speed = 1. / 8.}
+                        true
+                    }
+                    MoveResult::Ok => true
+                }
+            }
+            //in stasis
+            _ => true
+        }
+    }
+
+    fn finish_ceiling(&mut self, ceiling: &Ceiling) {
+        let mut sec = self.sectors[ceiling.sector as usize].borrow_mut();
+        if let Some(special) = &ceiling.new_special {sec.set_special(special)}
+        if let Some(texture) = ceiling.texture {sec.set_texture(SectorE::Ceiling as usize, texture)}
+    }
+
+    //restarts the crushers that were stopped
+    pub fn activate_in_stasis_ceiling(&mut self, tag: i32) {
+        for index in self.thinkers.stat_list(STAT_SECTOREFFECT) {
+            if let Some(SectorMover::Ceiling(ceiling)) = Self::mover_mut(self, index) {
+                if ceiling.tag == tag && ceiling.direction == 0 {
+                    ceiling.direction = ceiling.old_direction;
+                }
+            }
+        }
+    }
+
+    pub fn ev_ceiling_crush_stop(&mut self, tag: i32, remove: bool) -> bool {
+        let mut rtn = false;
+        for index in self.thinkers.stat_list(STAT_SECTOREFFECT) {
+            let ceiling = match Self::mover_mut(self, index) {
+                Some(SectorMover::Ceiling(ceiling)) => ceiling,
+                _ => continue
+            };
+            if ceiling.tag != tag || ceiling.direction == 0 {continue}
+            if remove {Self::destroy_thinker(self, index)}
+            else {
+                ceiling.old_direction = ceiling.direction;
+                ceiling.direction = 0;
+            }
+            rtn = true;
+        }
+        rtn
+    }
+
+    pub fn ev_stop_ceiling(&mut self, tag: i32, line: LineIndex) -> bool {
+        for sector in Self::tagged_sectors(self, tag, line) {
+            let ceiling_data = self.sectors[sector as usize].borrow().ceiling_data;
+            Self::destroy_thinker(self, ceiling_data);
+        }
+        true
+    }
+}
@@ -0,0 +1,223 @@
+use super::LevelLocals;
+use super::level_elements::{LineIndex, SectorIndex, SectorE};
+use super::level_specials::{Activator, SpecialActivation};
+use super::level_movers::*;
+use super::level_serializer::{Serializable, Serializer};
+
+//DDoor of zdoom, moves the ceiling
+#[derive(Default)]
+pub struct Door {
+    pub sector: SectorIndex,
+    pub type_: DoorType,
+    pub top_dist: f64,
+    pub bot_dist: f64,
+    pub old_floor_dist: f64,
+    pub speed: f64,
+    pub direction: i32, //1 up, 0 waiting, -1 down, 2 initial wait
+    pub top_wait: i32, //tics to wait at the top
+    pub top_countdown: i32,
+    pub light_tag: i32
+}
+
+impl Serializable for Door {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        arc.int("sector", &mut self.sector)
+            .enumeration("type", &mut self.type_)
+            .float("topdist", &mut self.top_dist)
+            .float("botdist", &mut self.bot_dist)
+            .float("oldfloordist", &mut self.old_floor_dist)
+            .float("speed", &mut self.speed)
+            .int("direction", &mut self.direction)
+            .int("topwait", &mut self.top_wait)
+            .int("topcountdown", &mut self.top_countdown)
+            .int("lighttag", &mut self.light_tag);
+    }
+}
+
+/*
+ * The doors (p_doors.cpp of zdoom)
+ */
+#[allow(clippy::too_many_arguments)]
+impl LevelLocals {
+    pub fn ev_do_door(&mut self, type_: DoorType, line: LineIndex, activator: &Activator, tag: i32, speed: f64, delay: i32, lock: i32, light_tag: i32, boom_gen: bool, top_count_down: i32) -> bool {
+        if lock != 0 && !Self::check_lock(self, activator, lock) {return false}
+
+        if tag != 0 {
+            //remote door
+            let mut rtn = false;
+            for sector in Self::sectors_with_tag(self, tag) {
+                //the ceiling is already moving
+                if self.sectors[sector as usize].borrow().plane_moving(SectorE::Ceiling as usize) {continue}
+                Self::create_door(self, sector, type_, speed, delay, light_tag, top_count_down);
+                rtn = true;
+            }
+            return rtn
+        }
+
+        //manual door, the sector on the other side of the line
+        if line < 0 {return false}
+        let (sector, activation) = {
+            let line = self.lines[line as usize].borrow();
+            //TODO the *usefail sound when the wrong side is used
+            if line.sidedef[1] < 0 {return false}
+            (line.back_sector, line.activation)
+        };
+        if sector < 0 {return false}
+
+        let ceiling_data = self.sectors[sector as usize].borrow().ceiling_data;
+        if ceiling_data < 0 {
+            Self::create_door(self, sector, type_, speed, delay, light_tag, top_count_down);
+            return true
+        }
+
+        //boom used the remote door logic for the generalized doors
+        if boom_gen {return false}
+        let door = match Self::mover_mut(self, ceiling_data) {
+            Some(SectorMover::Door(door)) => door,
+            _ => return false
+        };
+        //only the raise doors can be retriggered
+        if door.type_ != DoorType::Raise || type_ != DoorType::Raise {return false}
+        if door.direction == -1 {
+            //go back up
+            door.direction = 1;
+            return true
+        }
+        //push doors don't close when you run into them
+        if activation & (SpecialActivation::Push.bits() | SpecialActivation::MPush.bits()) != 0 {return false}
+        //monsters never close doors
+        if !activator.is_player() {return false}
+        door.direction = -1;
+        true
+    }
+
+    pub fn create_door(&mut self, sector: SectorIndex, type_: DoorType, speed: f64, delay: i32, light_tag: i32, top_count_down: i32) {
+        let lowest_ceiling = Self::find_lowest_ceiling_surrounding(self, sector);
+        let floor_data = self.sectors[sector as usize].borrow().floor_data;
+        let on_lift = matches!(Self::mover(self, floor_data), Some(SectorMover::Plat(plat)) if plat.is_lift());
+        //the door closes on the lift instead of the floor
+        let bottom = if on_lift {Self::find_lowest_ceiling_point(self, sector)} else {Self::find_highest_floor_point(self, sector)};
+
+        let door = {
+            let sec = self.sectors[sector as usize].borrow();
+            let spot = sec.center_spot;
+            let mut door = Door { sector, type_, top_dist: sec.ceilingplane.fd(), bot_dist: sec.ceilingplane.point_to_dist(&spot, bottom), old_floor_dist: sec.floorplane.fd(),
+                speed, direction: 0, top_wait: delay, top_countdown: top_count_down, light_tag };
+
+            match type_ {
+                DoorType::Close => {
+                    door.direction = -1;
+                    door.top_dist = sec.ceilingplane.point_to_dist(&spot, lowest_ceiling - 4.);
+                }
+                DoorType::Open | DoorType::Raise => {
+                    door.direction = 1;
+                    door.top_dist = sec.ceilingplane.point_to_dist(&spot, lowest_ceiling - 4.);
+                }
+                DoorType::CloseWaitOpen => door.direction = -1,
+                DoorType::WaitRaise => {
+                    door.direction = 2;
+                    door.top_dist = sec.ceilingplane.point_to_dist(&spot, lowest_ceiling - 4.);
+                }
+                DoorType::WaitClose => {
+                    //waits with the door open and then closes like a raise door
+                    door.direction = 0;
+                    door.type_ = DoorType::Raise;
+                }
+            }
+            door
+        };
+        //TODO the door sounds
+        Self::add_mover(self, SectorMover::Door(door));
+    }
+
+    pub fn tick_door(&mut self, door: &mut Door) -> bool {
+        //follow the floor when it moves, but not when it is a lift
+        let (floor_d, on_lift) = {
+            let sec = self.sectors[door.sector as usize].borrow();
+            (sec.floorplane.fd(), matches!(Self::mover(self, sec.floor_data), Some(SectorMover::Plat(plat)) if plat.is_lift()))
+        };
+        if floor_d != door.old_floor_dist && !on_lift {
+            let sec = self.sectors[door.sector as usize].borrow();
+            door.old_floor_dist = floor_d;
+            door.bot_dist = sec.ceilingplane.point_to_dist(&sec.center_spot, sec.center_floor());
+        }
+
+        match door.direction {
+            //waiting
+            0 => {
+                door.top_countdown -= 1;
+                if door.top_countdown == 0 {
+                    match door.type_ {
+                        DoorType::Raise => door.direction = -1,
+                        DoorType::CloseWaitOpen => door.direction = 1,
+                        _ => ()
+                    }
+                }
+                true
+            }
+            //initial wait
+            2 => {
+                door.top_countdown -= 1;
+                if door.top_countdown == 0 && door.type_ == DoorType::WaitRaise {
+                    door.direction = 1;
+                    door.type_ = DoorType::Raise;
+                }
+                true
+            }
+            -1 => {
+                let res = Self::move_ceiling(self, door.sector, door.speed, door.bot_dist, -1, door.direction, false);
+                Self::door_light(self, door);
+                match res {
+                    MoveResult::PastDest => match door.type_ {
+                        DoorType::Raise | DoorType::Close => false,
+                        DoorType::CloseWaitOpen => {
+                            door.direction = 0;
+                            door.top_countdown = door.top_wait;
+                            true
+                        }
+                        _ => true
+                    },
+                    //close doors don't go back up
+                    MoveResult::Crushed => {
+                        if door.type_ != DoorType::Close {door.direction = 1}
+                        true
+                    }
+                    MoveResult::Ok => true
+                }
+            }
+            1 => {
+                let res = Self::move_ceiling(self, door.sector, door.speed, door.top_dist, -1, door.direction, false);
+                Self::door_light(self, door);
+                match res {
+                    MoveResult::PastDest => match door.type_ {
+                        DoorType::Raise => {
+                            //wait at the top
+                            door.direction = 0;
+                            door.top_countdown = door.top_wait;
+                            true
+                        }
+                        DoorType::CloseWaitOpen | DoorType::Open => false,
+                        _ => true
+                    },
+                    MoveResult::Crushed => {
+                        if door.type_ == DoorType::Raise || door.type_ == DoorType::WaitRaise {door.direction = -1}
+                        true
+                    }
+                    MoveResult::Ok => true
+                }
+            }
+            _ => true
+        }
+    }
+
+    //the tagged lights follow how far the door is open
+    fn door_light(&mut self, door: &Door) {
+        if door.light_tag == 0 {return}
+        let (floor_d, ceiling_d) = {
+            let sec = self.sectors[door.sector as usize].borrow();
+            (sec.floorplane.fd(), sec.ceilingplane.fd())
+        };
+        if door.top_dist == -floor_d {return}
+        Self::ev_light_turn_on_partway(self, door.light_tag, (ceiling_d + floor_d) / (door.top_dist + floor_d));
+    }
+}
@@ -4,12 +4,12 @@ use std::cell::RefCell;
 use bitflags::bitflags;
 
 
-use crate::vector::{Vector2, Transform};
+use crate::vector::{Vector2, Transform, Angle};
 use super::level_lightmap::*;
 use super::level_portal::*;
 use super::level_mesh::SectorPlane;
 use super::{PolyNode, MiniBSP, AutoMapLineStyle, BaseDecal, Part, PolyObj, LevelLocals};
-use super::level_actor::Actor;
+use super::level_actor::ActorIndex;
 use super::level_texture::{TextureID, TextureManipulation};
 
 pub type SectorIndex = i32; //if -1 -> does not exist (NULL)
@@ -22,6 +22,14 @@ pub type SideDefIndex = i32; //if -1 -> does not exist (NULL)
 pub type VertexIndex = i32;
 pub type NodeIndex = i32;
 
+//the sides of the bounding boxes
+pub const BOXTOP: usize = 0;
+pub const BOXBOTTOM: usize = 1;
+pub const BOXLEFT: usize = 2;
+pub const BOXRIGHT: usize = 3;
+
+pub const EQUAL_EPSILON: f64 = 1. / 65536.;
+
 #[derive(Clone)]
 pub struct SubSector {
     pub sector: SectorIndex,
@@ -88,9 +96,36 @@ impl Line {
         Line { v1: Vertex::new(0, 0), v2: Vertex::new(0, 0), delta: Vector2::<f64>::new(), flags: 0, flags2: 0, activation: 0, special: 0, args: [0;5], alpha: 0., sidedef: [-1;2], bbox: [0.;4], front_sector: -1, back_sector: -1, valid_count: 0, lock_number: 0, portal_index: 0, portal_transfered: 0, auto_map_style: AutoMapLineStyle::new(), health: 0, health_group: 0, line_num: 0 }
     }
 
-    pub fn adjust_line(&self) {
-        // println!("adjusting line TODO");
-        //TODO
+    //sets the delta and the bounding box after the vertexes changed
+    pub fn adjust_line(&mut self) {
+        self.delta = Vector2 { x: self.v2.fx() - self.v1.fx(), y: self.v2.fy() - self.v1.fy() };
+        self.bbox[BOXTOP] = self.v1.fy().max(self.v2.fy());
+        self.bbox[BOXBOTTOM] = self.v1.fy().min(self.v2.fy());
+        self.bbox[BOXLEFT] = self.v1.fx().min(self.v2.fx());
+        self.bbox[BOXRIGHT] = self.v1.fx().max(self.v2.fx());
+    }
+
+    //P_PointOnLineSide, 0 for the front and 1 for the back
+    pub fn point_on_side(&self, x: f64, y: f64) -> i32 {
+        ((y - self.v1.fy()) * self.delta.x + (self.v1.fx() - x) * self.delta.y > EQUAL_EPSILON) as i32
+    }
+
+    //P_BoxOnLineSide, the side the box is on or -1 when the line goes through it
+    pub fn box_on_side(&self, bbox: &[f64;4]) -> i32 {
+        let (p, q) = if self.delta.x == 0. {
+            //vertical
+            let back = self.delta.y < 0.;
+            (((bbox[BOXRIGHT] < self.v1.fx()) != back) as i32, ((bbox[BOXLEFT] < self.v1.fx()) != back) as i32)
+        } else if self.delta.y == 0. {
+            //horizontal
+            let back = self.delta.x < 0.;
+            (((bbox[BOXTOP] > self.v1.fy()) != back) as i32, ((bbox[BOXBOTTOM] > self.v1.fy()) != back) as i32)
+        } else if self.delta.x * self.delta.y >= 0. {
+            (self.point_on_side(bbox[BOXLEFT], bbox[BOXTOP]), self.point_on_side(bbox[BOXRIGHT], bbox[BOXBOTTOM]))
+        } else {
+            (self.point_on_side(bbox[BOXRIGHT], bbox[BOXTOP]), self.point_on_side(bbox[BOXLEFT], bbox[BOXBOTTOM]))
+        };
+        if p == q {p} else {-1}
     }
 
     pub fn index(&self) -> i32 {
@@ -134,20 +169,37 @@ impl Side {
         self.side_num
     }
 
-    pub fn set_texture_x_offset(&mut self, _offset: f64) {
-        //TODO
+    //the offsets and scales of the sidedef are the same for all the parts
+    pub fn set_texture_x_offset(&mut self, offset: f64) {
+        for part in &mut self.textures {part.x_offset = offset}
+    }
+
+    pub fn set_texture_y_offset(&mut self, offset: f64) {
+        for part in &mut self.textures {part.y_offset = offset}
     }
 
-    pub fn set_texture_y_offset(&mut self, _offset: f64) {
-        //TODO
+    pub fn set_texture_x_scale(&mut self, scale: f64) {
+        for part in &mut self.textures {part.x_scale = scale}
     }
 
-    pub fn set_texture_x_scale(&mut self, _scale: f64) {
-        //TODO
+    pub fn set_texture_y_scale(&mut self, scale: f64) {
+        for part in &mut self.textures {part.y_scale = scale}
     }
 
-    pub fn set_texture_y_scale(&mut self, _scale: f64) {
-        //TODO
+    pub fn get_texture_x_offset(&self, which: usize) -> f64 {
+        self.textures[which].x_offset
+    }
+
+    pub fn get_texture_y_offset(&self, which: usize) -> f64 {
+        self.textures[which].y_offset
+    }
+
+    pub fn get_texture_x_scale(&self, which: usize) -> f64 {
+        self.textures[which].x_scale
+    }
+
+    pub fn get_texture_y_scale(&self, which: usize) -> f64 {
+        self.textures[which].y_scale
     }
 
     pub fn set_texture(&mut self, which: usize, tex: TextureID) {
@@ -178,8 +230,9 @@ impl Vertex {
         self.p
     }
 
+    //the position in map units, set_i32 and set_f64 take fixed point
     pub fn new(x: i16, y: i16) -> Vertex {
-        let p: Vector2<f64> = Vector2 { x: x as f64 / 65536., y: y as f64 / 65536. };
+        let p: Vector2<f64> = Vector2 { x: x as f64, y: y as f64 };
         Vertex { p, vertex_num: 0, view_angle: 0, angle_time: 0, dirty: true, num_heights: 0, num_sectors: 0, sectors: vec![], height_list: vec![] }
     }
 
@@ -204,6 +257,15 @@ impl Vertex {
     pub fn set_from_vector(&mut self, pos: &Vector2<f64>) {
         self.p = pos.to_owned();
     }
+
+    //a plane of a sector with the vertex moved, the level mesh has to be updated
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
 }
 
 #[derive(Clone)]
@@ -259,13 +321,13 @@ pub struct Sector {
     pub has_light_map: bool,
 
     //Stuff not to do with renderer
-    //TODO sound_target, 
-    pub thing_list: Vec<Actor>,
+    pub sound_target: ActorIndex, //the thing that made the last noise the monsters here heard, -1 if none
+    pub thing_list: Vec<ActorIndex>,
     pub gravity: f64, //1.0 is normal?
 
-    //TODO floor_data,
-    //TODO ceiling_data,
-    //TODO lighting_data,
+    pub floor_data: i32, //the mover of the floor, -1 if none
+    pub ceiling_data: i32, //the mover of the ceiling, -1 if none
+    pub lighting_data: i32, //the lighting thinker, -1 if none
 
     //TODO interpolations: [;4],
 
@@ -344,11 +406,47 @@ bitflags! {
     }
 
     pub struct LineFlags: u32 {
+        const Blocking = 0x00000001;
+        const BlockMonsters = 0x00000002;
+        const TwoSided = 0x00000004;
+        const DontPegTop = 0x00000008;
+        const DontPegBottom = 0x00000010;
+        const Secret = 0x00000020;
+        const SoundBlock = 0x00000040;
+        const DontDraw = 0x00000080;
+        const Mapped = 0x00000100;
+        const RepeatSpecial = 0x00000200;
+        const AddTrans = 0x00000400;	// additive translucency (can only be set internally)
+        const MonstersCanActivate = 0x00002000;
+        const BlockPlayers = 0x00004000;
+        const BlockEverything = 0x00008000;
+        const ZoneBoundary = 0x00010000;
+        const Railing = 0x00020000;
+        const BlockFloaters = 0x00040000;
         const ClipMidTex = 0x00080000;
         const WrapMidTex = 0x00100000;
+        const MidTex3D = 0x00200000;
         const CheckSwitchRange = 0x00400000;
-        const AddTrans = 0x00000400;	// additive translucency (can only be set internally)
-        const TwoSided = 0x00000004;
+        const FirstSideOnly = 0x00800000;
+        const BlockProjectile = 0x01000000;
+        const BlockUse = 0x02000000;
+        const BlockSight = 0x04000000;
+        const BlockHitscan = 0x08000000;
+
+        // the activation bits of the hexen format (removed from the flags after loading)
+        const SpacMask = 0x00001c00;
+    }
+
+    pub struct PlaneFlags: i32 {
+        const AbsLighting = 1;
+        const Blocked = 2; //can't be moved anymore
+        const Additive = 4;
+        const NoRender = 8;
+        const NoPass = 16;
+        const BlockSound = 32;
+        const Disabled = 64;
+        const Obstructed = 128;
+        const Linked = 256;
     }
 
     pub struct Sides: u32 {
@@ -358,9 +456,12 @@ bitflags! {
     }
 }
 
+//the shift of the hexen activation bits in LineFlags::SpacMask
+pub const SPAC_SHIFT: u32 = 10;
+
 impl Sector {
     pub fn new(e: ExtSectorIndex) -> Sector {
-        Sector {e, floorplane: SectorPlane::new(), ceilingplane: SectorPlane::new(), splane: [Splane::new();2], level: None, center_spot: Vector2::<f64>::new(), lines: vec![], height_sec: -1, special_colors: [PalEntry::new(); 5], additive_colors: [PalEntry::new(); 5], color_map: ColorMap::new(), special: 0, sky: 0, valid_count: 0, bottom_map: 0, mid_map: 0, top_map: 0, trans_door: false, light_level: 0, more_flags: 0, flags: 0, portals: [0;2], portal_group: 0, sector_num: 0, subsector_count: 0, reflect: [0.;2], trans_door_height: 0., subsectors: vec![], portals_fc: [SectorPortalGroup::default(); 2], vbo_index: [0;4], ibo_index: [0;4], vbo_height: [[0.;2];2], vbo_count: [0;2], ibo_count: 0, has_light_map: false, thing_list: vec![], gravity: 0., touching_thing_list: vec![], friction: 0., move_factor: 0., terrain_num: [0;2], sec_name: String::new(), sec_type: 0, sound_traversed: 0, stair_lock: 0, prev_sec: 0, next_sec: 0, damage_type: String::new(), damage_amount: 0, damage_interval: 0, leaky_damage: 0, zone_number: 0, health_floor: 0, health_ceiling: 0, health_3d: 0, health_floor_group: 0, health_ceiling_group: 0, health_3d_group: 0, sector_portal_thinglist: SecNode::default(), touching_render_things: SecNode::default() }
+        Sector {e, floorplane: SectorPlane::new(), ceilingplane: SectorPlane::new(), splane: [Splane::new();2], level: None, center_spot: Vector2::<f64>::new(), lines: vec![], height_sec: -1, special_colors: [PalEntry::new(); 5], additive_colors: [PalEntry::new(); 5], color_map: ColorMap::new(), special: 0, sky: 0, valid_count: 0, bottom_map: 0, mid_map: 0, top_map: 0, trans_door: false, light_level: 0, more_flags: 0, flags: 0, portals: [0;2], portal_group: 0, sector_num: 0, subsector_count: 0, reflect: [0.;2], trans_door_height: 0., subsectors: vec![], portals_fc: [SectorPortalGroup::default(); 2], vbo_index: [0;4], ibo_index: [0;4], vbo_height: [[0.;2];2], vbo_count: [0;2], ibo_count: 0, has_light_map: false, thing_list: vec![], gravity: 0., floor_data: -1, ceiling_data: -1, lighting_data: -1, touching_thing_list: vec![], friction: 0., move_factor: 0., terrain_num: [0;2], sec_name: String::new(), sec_type: 0, sound_traversed: 0, sound_target: -1, stair_lock: 0, prev_sec: 0, next_sec: 0, damage_type: String::new(), damage_amount: 0, damage_interval: 0, leaky_damage: 0, zone_number: 0, health_floor: 0, health_ceiling: 0, health_3d: 0, health_floor_group: 0, health_ceiling_group: 0, health_3d_group: 0, sector_portal_thinglist: SecNode::default(), touching_render_things: SecNode::default() }
         // Sector {e, floorplane: SectorPlane::new(), ceilingplane: SectorPlane::new(), splane: [Splane::new();2], level: None, height_sec: -1, sector_portal_thinglist: SecNode::default(), touching_render_things: SecNode::default(), color_map: ColorMap::new(), special: 0, bottom_map: 0, mid_map: 0, top_map: 0, light_level: 0, more_flags: 0, flags: 0, sector_num: 0, ibo_count: 0, thing_list: vec![], gravity: 0., friction: 0., move_factor: 0., terrain_num: [0;2], sec_name: String::new(), sec_type: 0, prev_sec: 0, next_sec: 0, zone_number: 0, health_floor: 0, health_ceiling: 0, health_3d: 0, health_floor_group: 0, health_ceiling_group: 0, health_3d_group: 0 }
     }
     
@@ -368,12 +469,18 @@ impl Sector {
         self.splane[pos].texture
     }
 
-    pub fn set_plane_tex_z(&mut self, pos: usize, val: f64, dirtify: Option<bool>) {
+    //elements is needed to mark the vertices of the sector dirty (None while loading)
+    pub fn set_plane_tex_z(&mut self, pos: usize, val: f64, dirtify: Option<&LevelElements>) {
         self.splane[pos].tex_z = val;
-        if dirtify.unwrap_or(false) { Self::set_all_vertices_dirty(self);}
+        if let Some(elements) = dirtify { Self::set_all_vertices_dirty(self, elements);}
         Self::check_overlap(self)
     }
 
+    pub fn change_plane_tex_z(&mut self, pos: usize, val: f64, dirtify: Option<&LevelElements>) {
+        let tex_z = self.splane[pos].tex_z + val;
+        Self::set_plane_tex_z(self, pos, tex_z, dirtify);
+    }
+
     fn check_overlap(&mut self) {
         if self.splane[SectorE::Floor as usize].tex_z > self.splane[SectorE::Ceiling as usize].tex_z && 
                 !self.floorplane.is_slope() && !self.ceilingplane.is_slope() {
@@ -382,31 +489,52 @@ impl Sector {
         else { self.more_flags &= !SectorMoreFlags::Overlapping.bits(); }
     }
 
-    fn set_all_vertices_dirty(&mut self) {
-        Self::set_vertices_dirty(self);
-        let binding = &mut self.level.as_mut().unwrap().borrow_mut().upgrade().unwrap();
-        let ext_sector = &binding.borrow_mut().elements.extsectors[self.e as usize];
-        let mut bind = binding.borrow_mut();
-        let sectors = &mut bind.sectors;
-        for i in 0..ext_sector.fake_floor.len() {
-            let index = ext_sector.fake_floor[i] as usize;
-            sectors[index].borrow_mut().set_all_vertices_dirty();
+    fn set_all_vertices_dirty(&self, elements: &LevelElements) {
+        Self::set_vertices_dirty(self, elements);
+        let ext_sector = &elements.extsectors[self.e as usize];
+        for index in ext_sector.fake_floor.iter().chain(ext_sector.x_floor.attached.iter()) {
+            //the sector itself is already borrowed by the caller
+            if let Ok(sector) = elements.sectors[*index as usize].try_borrow() {
+                sector.set_all_vertices_dirty(elements);
+            }
         }
+    }
 
-        for i in 0..ext_sector.x_floor.attached.len() {
-            let index = ext_sector.x_floor.attached[i] as usize;
-            sectors[index].borrow_mut().set_all_vertices_dirty();
+    fn set_vertices_dirty(&self, elements: &LevelElements) {
+        let ext_sector = &elements.extsectors[self.e as usize];
+        for index in &ext_sector.vertices {
+            elements.vertexes[*index as usize].borrow_mut().dirty = true;
         }
     }
 
-    fn set_vertices_dirty(&mut self) {
-        let binding = &mut self.level.as_mut().unwrap().borrow_mut().upgrade().unwrap();
-        let ext_sector = &binding.borrow_mut().elements.extsectors[self.e as usize];
-        let vertices = &mut binding.borrow_mut().vertexes;
-        for i in 0..ext_sector.vertices.len() {
-            let index = ext_sector.vertices[i] as usize;
-            vertices[index].borrow_mut().dirty = true;
-        }
+    pub fn plane_moving(&self, pos: usize) -> bool {
+        if pos == SectorE::Floor as usize {self.floor_data >= 0} else {self.ceiling_data >= 0}
+    }
+
+    pub fn center_floor(&self) -> f64 {
+        self.floorplane.z_at(&self.center_spot)
+    }
+
+    pub fn center_ceiling(&self) -> f64 {
+        self.ceilingplane.z_at(&self.center_spot)
+    }
+
+    pub fn get_special(&self) -> SecSpecial {
+        SecSpecial { special: self.special, damage_amount: self.damage_amount, damage_interval: self.damage_interval, leaky_damage: self.leaky_damage, damage_type: self.damage_type.clone(), flags: self.flags & SectorFlags::SpecialFlags.bits() }
+    }
+
+    pub fn set_special(&mut self, spec: &SecSpecial) {
+        self.special = spec.special;
+        self.damage_amount = spec.damage_amount;
+        self.damage_interval = spec.damage_interval;
+        self.leaky_damage = spec.leaky_damage;
+        self.damage_type = spec.damage_type.clone();
+        self.flags = (self.flags & !SectorFlags::SpecialFlags.bits()) | (spec.flags & SectorFlags::SpecialFlags.bits());
+    }
+
+    //the secret flags stay
+    pub fn clear_special(&mut self) {
+        self.set_special(&SecSpecial::default());
     }
 
     pub fn get_plane_tex_z(&self, pos: usize) -> f64 {
@@ -426,9 +554,52 @@ impl Sector {
         self.splane[pos].x_form.y_scale = val;
     }
 
-    pub fn set_texture(&mut self, _pos: usize, _texure: TextureID) {
-        //TODO
+    pub fn get_x_offset(&self, pos: usize) -> f64 {
+        self.splane[pos].x_form.x_offset
+    }
+
+    pub fn get_y_offset(&self, pos: usize) -> f64 {
+        self.splane[pos].x_form.y_offset
+    }
+
+    pub fn get_x_scale(&self, pos: usize) -> f64 {
+        self.splane[pos].x_form.x_scale
+    }
+
+    pub fn get_y_scale(&self, pos: usize) -> f64 {
+        self.splane[pos].x_form.y_scale
+    }
+
+    pub fn get_angle(&self, pos: usize) -> Angle<f64> {
+        self.splane[pos].x_form.angle
     }
+
+    pub fn set_light_level(&mut self, level: i32) {
+        self.light_level = level.clamp(0, 255) as i16;
+    }
+
+    pub fn set_texture(&mut self, pos: usize, texture: TextureID) {
+        self.splane[pos].texture = texture;
+    }
+
+    pub fn get_plane_flags(&self, pos: usize) -> i32 {
+        self.splane[pos].flags
+    }
+
+    pub fn change_plane_flags(&mut self, pos: usize, and: i32, or: i32) {
+        self.splane[pos].flags = (self.splane[pos].flags & !and) | or;
+    }
+}
+
+//the special of a sector with its damage and flags (secspecial_t)
+#[derive(Clone, Default, Debug)]
+pub struct SecSpecial {
+    pub special: i32,
+    pub damage_amount: i32,
+    pub damage_interval: i16,
+    pub leaky_damage: i16,
+    pub damage_type: String,
+    pub flags: u32
 }
 
 #[derive(PartialEq)]
@@ -638,7 +809,7 @@ impl Splane {
 #[derive(Default, Clone)]
 pub struct SecNode{
     pub sector: Option<Rc<Sector>>,
-    pub thing: Actor,
+    pub thing: ActorIndex,
     pub thing_prev: Option<Rc<SecNode>>,
     pub thing_next: Option<Rc<SecNode>>,
     pub sec_prev: Option<Rc<SecNode>>,
@@ -0,0 +1,962 @@
+use std::collections::HashSet;
+
+use crate::vector::Angle;
+
+use super::{ActionSpecials, LevelLocals};
+use super::level_actor::{Actor, ActorIndex, MAXPLAYERS};
+use super::level_elements::{LineFlags, SectorIndex};
+use super::level_info::*;
+use super::level_map::{MAXRADIUS, MELEERANGE, MISSILERANGE, FLOATSPEED};
+use super::level_maputl::approx_distance;
+use super::level_sight::SightFlags;
+use super::level_specials::{Activator, ActivatorType, LevelExit, SpecialActivation};
+
+//dirtype_t, the directions the monsters walk in
+pub const DI_EAST: i32 = 0;
+pub const DI_NORTHEAST: i32 = 1;
+pub const DI_NORTH: i32 = 2;
+pub const DI_NORTHWEST: i32 = 3;
+pub const DI_WEST: i32 = 4;
+pub const DI_SOUTHWEST: i32 = 5;
+pub const DI_SOUTH: i32 = 6;
+pub const DI_SOUTHEAST: i32 = 7;
+pub const DI_NODIR: i32 = 8;
+
+const OPPOSITE: [i32; 9] = [DI_WEST, DI_SOUTHWEST, DI_SOUTH, DI_SOUTHEAST, DI_EAST, DI_NORTHEAST, DI_NORTH, DI_NORTHWEST, DI_NODIR];
+const DIAGS: [i32; 4] = [DI_NORTHWEST, DI_NORTHEAST, DI_SOUTHWEST, DI_SOUTHEAST];
+
+//the step of each direction, doom uses 47000 for the diagonals
+const DIAGONAL: f64 = 47000. / 65536.;
+const X_SPEED: [f64; 8] = [1., DIAGONAL, 0., -DIAGONAL, -1., -DIAGONAL, 0., DIAGONAL];
+const Y_SPEED: [f64; 8] = [0., DIAGONAL, 1., DIAGONAL, 0., -DIAGONAL, -1., -DIAGONAL];
+
+//the binary angles of doom
+const ANG90: u32 = 0x40000000;
+const ANG270: u32 = 0xc0000000;
+const FATSPREAD: i32 = ANG90 as i32 / 8;
+const TRACEANGLE: u32 = 0xc000000;
+
+const SKULLSPEED: f64 = 20.;
+
+//the targets of the cubes of the boss brain
+#[derive(Default)]
+pub struct BrainState {
+    pub targets: Vec<ActorIndex>,
+    pub target_on: usize,
+    pub easy: bool //on the easy skills only every other cube is spit
+}
+
+/*
+ * The monsters waking up, chasing and attacking (p_enemy.cpp)
+ */
+impl LevelLocals {
+    //SKILLP_FastMonsters, the monsters of nightmare don't wait between the attacks
+    pub fn fast_monsters(&self) -> bool {
+        //TODO the -fast parameter
+        self.skill >= 4
+    }
+
+    fn start_sound(&self, _actor: &Actor, _sound: &str) {
+        //TODO S_StartSound, the bosses are heard everywhere
+    }
+
+    /*
+     * P_NoiseAlert, the monsters that hear the emitter go after the target.
+     * The sound goes through the open lines and through one line that blocks the sound.
+     */
+    pub fn noise_alert(&mut self, target: ActorIndex, emitter: &Actor) {
+        self.valid_count += 1;
+        Self::recursive_sound(self, emitter.sector, 0, target);
+    }
+
+    //P_RecursiveSound
+    fn recursive_sound(&mut self, sector: SectorIndex, sound_blocks: i32, target: ActorIndex) {
+        if sector < 0 {return}
+        let lines = {
+            let mut sec = self.sectors[sector as usize].borrow_mut();
+            //the sound was already here as loud
+            if sec.valid_count == self.valid_count && sec.sound_traversed as i32 <= sound_blocks + 1 {return}
+            sec.valid_count = self.valid_count;
+            sec.sound_traversed = (sound_blocks + 1) as u8;
+            sec.sound_target = target;
+            sec.lines.clone()
+        };
+
+        for line in lines {
+            let (flags, front, back, middle) = {
+                let line = self.lines[line as usize].borrow();
+                let delta = line.delta();
+                (line.flags, line.front_sector, line.back_sector, crate::vector::Vector2 { x: line.v1.fx() + delta.x / 2., y: line.v1.fy() + delta.y / 2. })
+            };
+            if back < 0 {continue}
+            //a closed door
+            if !self.line_opening(line, &middle).is_some_and(|opening| opening.range > 0.) {continue}
+
+            let other = if front == sector {back} else {front};
+            if flags & LineFlags::SoundBlock.bits() != 0 {
+                if sound_blocks == 0 {Self::recursive_sound(self, other, 1, target)}
+            }
+            else {Self::recursive_sound(self, other, sound_blocks, target)}
+        }
+    }
+
+    /*
+     * P_LookForPlayers, the monster looks for a player it can see, two of them each time.
+     * The players behind it are only seen up close, unless it looks all around.
+     */
+    pub fn look_for_players(&self, actor: &mut Actor, all_around: bool) -> bool {
+        if self.players.iter().all(|player| player.mo < 0) {return false}
+        let mask = MAXPLAYERS - 1;
+        let stop = (actor.last_look - 1) & mask;
+        let mut count = 0;
+        loop {
+            let mo = self.players[actor.last_look as usize].mo;
+            if mo >= 0 {
+                if count == 2 || actor.last_look == stop {return false}
+                count += 1;
+                let seen = match self.actor(mo) {
+                    Some(player) if player.health > 0 && self.check_sight(actor, player, SightFlags::empty()) => {
+                        let angle = actor.angle_to(player).bams().wrapping_sub(actor.angle.bams());
+                        let behind = angle > ANG90 && angle < ANG270;
+                        all_around || !behind || approx_distance(player.pos.x - actor.pos.x, player.pos.y - actor.pos.y) <= MELEERANGE
+                    }
+                    _ => false
+                };
+                if seen {
+                    actor.target = mo;
+                    return true
+                }
+            }
+            actor.last_look = (actor.last_look + 1) & mask;
+        }
+    }
+
+    //P_CheckMeleeRange
+    fn check_melee_range(&self, actor: &Actor) -> bool {
+        match self.actor(actor.target) {
+            Some(target) => {
+                let dist = approx_distance(target.pos.x - actor.pos.x, target.pos.y - actor.pos.y);
+                dist < MELEERANGE - 20. + target.info().radius && self.check_sight(actor, target, SightFlags::empty())
+            }
+            None => false
+        }
+    }
+
+    //P_CheckMissileRange, the further away the target is the less likely the monster fires
+    fn check_missile_range(&mut self, actor: &mut Actor) -> bool {
+        let dist = match self.actor(actor.target) {
+            Some(target) if self.check_sight(actor, target, SightFlags::empty()) => approx_distance(target.pos.x - actor.pos.x, target.pos.y - actor.pos.y),
+            _ => return false
+        };
+        //the target just hit the monster, so fight back
+        if actor.flags.contains(MobjFlags::JustHit) {
+            actor.flags.remove(MobjFlags::JustHit);
+            return true
+        }
+        //do not attack yet
+        if actor.reaction_time > 0 {return false}
+
+        let mut dist = dist - 64.;
+        //no melee attack, so fire more
+        if actor.info().melee_state == S_NULL {dist -= 128.}
+        let mut dist = dist.floor() as i32;
+        match actor.type_ {
+            //too far away for the flames
+            MT_VILE if dist > 14 * 64 => return false,
+            //close for the fist attack
+            MT_UNDEAD => {
+                if dist < 196 {return false}
+                dist >>= 1;
+            }
+            MT_CYBORG | MT_SPIDER | MT_SKULL => dist >>= 1,
+            _ => {}
+        }
+        dist = dist.min(200);
+        if actor.type_ == MT_CYBORG {dist = dist.min(160)}
+        self.random.random() >= dist
+    }
+
+    /*
+     * P_Move, a step in the direction of the monster, false when it is blocked.
+     * The floating monsters go up or down to get past, the others open the doors in the way.
+     */
+    fn move_actor(&mut self, index: ActorIndex, actor: &mut Actor) -> bool {
+        if !(0..DI_NODIR).contains(&actor.move_dir) {return false}
+        let dir = actor.move_dir as usize;
+        let speed = actor.info().speed;
+        let (moved, tm) = Self::try_move_position(self, index, actor, actor.pos.x + speed * X_SPEED[dir], actor.pos.y + speed * Y_SPEED[dir]);
+        if !moved {
+            if actor.flags.contains(MobjFlags::Float) && tm.float_ok {
+                if actor.pos.z < tm.floor_z {actor.pos.z += FLOATSPEED}
+                else {actor.pos.z -= FLOATSPEED}
+                actor.flags.insert(MobjFlags::InFloat);
+                return true
+            }
+            if tm.spec_hit.is_empty() {return false}
+
+            actor.move_dir = DI_NODIR;
+            let activator = Activator::new(index, ActivatorType::Monster);
+            let mut good = false;
+            for line in tm.spec_hit.iter().rev() {
+                if Self::activate_line(self, *line, &activator, 0, SpecialActivation::Use.bits()) {good = true}
+            }
+            return good
+        }
+
+        actor.flags.remove(MobjFlags::InFloat);
+        if !actor.flags.contains(MobjFlags::Float) {actor.pos.z = actor.floor_z}
+        true
+    }
+
+    //P_TryWalk, the monster keeps walking in the direction for a while
+    fn try_walk(&mut self, index: ActorIndex, actor: &mut Actor) -> bool {
+        if !Self::move_actor(self, index, actor) {return false}
+        actor.move_count = self.random.random() & 15;
+        true
+    }
+
+    //P_NewChaseDir, the monster picks the direction to the target, or another one when it is blocked
+    fn new_chase_dir(&mut self, index: ActorIndex, actor: &mut Actor) {
+        let target = match self.actor(actor.target) {
+            Some(target) => target.pos,
+            None => return
+        };
+        let old_dir = actor.move_dir.clamp(0, DI_NODIR);
+        let turnaround = OPPOSITE[old_dir as usize];
+
+        let (dx, dy) = (target.x - actor.pos.x, target.y - actor.pos.y);
+        let mut d1 = if dx > 10. {DI_EAST} else if dx < -10. {DI_WEST} else {DI_NODIR};
+        let mut d2 = if dy < -10. {DI_SOUTH} else if dy > 10. {DI_NORTH} else {DI_NODIR};
+
+        //try the direct route
+        if d1 != DI_NODIR && d2 != DI_NODIR {
+            actor.move_dir = DIAGS[(((dy < 0.) as usize) << 1) + (dx > 0.) as usize];
+            if actor.move_dir != turnaround && Self::try_walk(self, index, actor) {return}
+        }
+
+        //try the other directions
+        if self.random.random() > 200 || dy.abs() > dx.abs() {std::mem::swap(&mut d1, &mut d2)}
+        if d1 == turnaround {d1 = DI_NODIR}
+        if d2 == turnaround {d2 = DI_NODIR}
+        for dir in [d1, d2] {
+            if dir == DI_NODIR {continue}
+            actor.move_dir = dir;
+            if Self::try_walk(self, index, actor) {return}
+        }
+
+        //there is no direct path to the target, so pick another direction
+        if old_dir != DI_NODIR {
+            actor.move_dir = old_dir;
+            if Self::try_walk(self, index, actor) {return}
+        }
+
+        //randomly determine the direction of the search
+        let dirs: Vec<i32> = if self.random.random() & 1 != 0 {(DI_EAST..=DI_SOUTHEAST).collect()} else {(DI_EAST..=DI_SOUTHEAST).rev().collect()};
+        for dir in dirs {
+            if dir == turnaround {continue}
+            actor.move_dir = dir;
+            if Self::try_walk(self, index, actor) {return}
+        }
+
+        if turnaround != DI_NODIR {
+            actor.move_dir = turnaround;
+            if Self::try_walk(self, index, actor) {return}
+        }
+        //can not move
+        actor.move_dir = DI_NODIR;
+    }
+
+    //A_FaceTarget, the fuzzy targets are harder to face
+    pub fn face_target(&mut self, actor: &mut Actor) {
+        let (angle, shadow) = match self.actor(actor.target) {
+            Some(target) => (actor.angle_to(target), target.flags.contains(MobjFlags::Shadow)),
+            None => return
+        };
+        actor.flags.remove(MobjFlags::Ambush);
+        actor.angle = angle;
+        if shadow {actor.angle.add(&Angle::<f64>::from_bam_i(self.random.random2() << 21))}
+    }
+
+    //A_Look, the monster waits until it hears or sees a player
+    pub fn a_look(&mut self, index: ActorIndex, actor: &mut Actor) {
+        //any shot will wake it up
+        actor.threshold = 0;
+        let sound_target = if actor.sector >= 0 {self.sectors[actor.sector as usize].borrow().sound_target} else {-1};
+        let heard = match self.actor(sound_target) {
+            Some(target) if target.flags.contains(MobjFlags::Shootable) => {
+                actor.target = sound_target;
+                //the ambushing monsters need to see it too
+                !actor.flags.contains(MobjFlags::Ambush) || self.check_sight(actor, target, SightFlags::empty())
+            }
+            _ => false
+        };
+        if !heard && !Self::look_for_players(self, actor, false) {return}
+
+        let see_sound = match actor.info().see_sound {
+            "" => "",
+            "posit1" | "posit2" | "posit3" => ["posit1", "posit2", "posit3"][(self.random.random() % 3) as usize],
+            "bgsit1" | "bgsit2" => ["bgsit1", "bgsit2"][(self.random.random() % 2) as usize],
+            sound => sound
+        };
+        if !see_sound.is_empty() {Self::start_sound(self, actor, see_sound)}
+        Self::set_actor_state(self, index, actor, actor.info().see_state);
+    }
+
+    //A_Chase, the monster walks to the target and attacks it when it can
+    pub fn a_chase(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if actor.reaction_time > 0 {actor.reaction_time -= 1}
+
+        //the monster stops going after the one that hurt it after a while
+        if actor.threshold > 0 {
+            if self.actor(actor.target).is_some_and(|target| target.health > 0) {actor.threshold -= 1}
+            else {actor.threshold = 0}
+        }
+
+        //turn towards the movement direction
+        if (0..DI_NODIR).contains(&actor.move_dir) {
+            let angle = actor.angle.bams() & (7 << 29);
+            let delta = angle.wrapping_sub((actor.move_dir as u32) << 29) as i32;
+            let angle = if delta > 0 {angle.wrapping_sub(ANG90 / 2)} else if delta < 0 {angle.wrapping_add(ANG90 / 2)} else {angle};
+            actor.angle = Angle::<f64>::from_bam_u(angle);
+        }
+
+        if !self.actor(actor.target).is_some_and(|target| target.flags.contains(MobjFlags::Shootable)) {
+            //look for a new target
+            if Self::look_for_players(self, actor, true) {return}
+            Self::set_actor_state(self, index, actor, actor.info().spawn_state);
+            return
+        }
+
+        //do not attack twice in a row
+        if actor.flags.contains(MobjFlags::JustAttacked) {
+            actor.flags.remove(MobjFlags::JustAttacked);
+            if !self.fast_monsters() {Self::new_chase_dir(self, index, actor)}
+            return
+        }
+
+        let info = actor.info();
+        if info.melee_state != S_NULL && Self::check_melee_range(self, actor) {
+            if !info.attack_sound.is_empty() {Self::start_sound(self, actor, info.attack_sound)}
+            Self::set_actor_state(self, index, actor, info.melee_state);
+            return
+        }
+
+        //the monster waits a few steps between the missiles, except on nightmare
+        if info.missile_state != S_NULL && (self.fast_monsters() || actor.move_count == 0) && Self::check_missile_range(self, actor) {
+            Self::set_actor_state(self, index, actor, info.missile_state);
+            actor.flags.insert(MobjFlags::JustAttacked);
+            return
+        }
+
+        //possibly choose another target
+        if self.net_game && actor.threshold == 0 && !self.actor(actor.target).is_some_and(|target| self.check_sight(actor, target, SightFlags::empty())) &&
+                Self::look_for_players(self, actor, true) {
+            return
+        }
+
+        //chase towards the target
+        actor.move_count -= 1;
+        if actor.move_count < 0 || !Self::move_actor(self, index, actor) {Self::new_chase_dir(self, index, actor)}
+
+        if !info.active_sound.is_empty() && self.random.random() < 3 {Self::start_sound(self, actor, info.active_sound)}
+    }
+
+    //the melee damage to the target of the monster
+    fn melee_damage(&mut self, index: ActorIndex, actor: &Actor, damage: i32) {
+        Self::damage_actor(self, actor.target, Some(actor), index, Some(actor), damage);
+    }
+
+    //the missile of the monster at its target
+    fn missile_at_target(&mut self, index: ActorIndex, actor: &Actor, type_: MobjType) -> ActorIndex {
+        let target = match self.actor(actor.target) {
+            Some(target) => target.clone(),
+            None => return -1
+        };
+        Self::spawn_missile(self, index, actor, &target, type_)
+    }
+
+    //turns the missile with its velocity, for the spread of the mancubus
+    fn turn_missile(&mut self, missile: ActorIndex, bams: i32) {
+        if let Some(actor) = self.actor_mut(missile) {
+            actor.angle.add(&Angle::<f64>::from_bam_i(bams));
+            let vel = actor.angle.to_vector(actor.info().speed);
+            actor.vel.x = vel.x;
+            actor.vel.y = vel.y;
+        }
+    }
+
+    //the hitscan attacks of the zombies, each bullet spreads around the aim
+    fn monster_bullets(&mut self, index: ActorIndex, actor: &mut Actor, sound: &str, bullets: i32) {
+        if self.actor(actor.target).is_none() {return}
+        Self::start_sound(self, actor, sound);
+        Self::face_target(self, actor);
+        let (slope, _) = Self::aim_line_attack(self, index, actor, actor.angle, MISSILERANGE);
+        for _ in 0..bullets {
+            let mut angle = actor.angle;
+            angle.add(&Angle::<f64>::from_bam_i(self.random.random2() << 20));
+            let damage = (self.random.random() % 5 + 1) * 3;
+            Self::line_attack(self, index, actor, angle, MISSILERANGE, slope, damage);
+        }
+    }
+
+    //A_CPosRefire and A_SpidRefire, the monster keeps firing until the target is out of sight
+    fn monster_refire(&mut self, index: ActorIndex, actor: &mut Actor, chance: i32) {
+        Self::face_target(self, actor);
+        if self.random.random() < chance {return}
+        let visible = self.actor(actor.target).is_some_and(|target| target.health > 0 && self.check_sight(actor, target, SightFlags::empty()));
+        if !visible {Self::set_actor_state(self, index, actor, actor.info().see_state);}
+    }
+
+    pub fn a_pos_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::monster_bullets(self, index, actor, "pistol", 1);
+    }
+
+    pub fn a_spos_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::monster_bullets(self, index, actor, "shotgn", 3);
+    }
+
+    pub fn a_cpos_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::monster_bullets(self, index, actor, "shotgn", 1);
+    }
+
+    pub fn a_cpos_refire(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::monster_refire(self, index, actor, 40);
+    }
+
+    pub fn a_spid_refire(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::monster_refire(self, index, actor, 10);
+    }
+
+    pub fn a_bspi_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        Self::missile_at_target(self, index, actor, MT_ARACHPLAZ);
+    }
+
+    pub fn a_troop_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        if Self::check_melee_range(self, actor) {
+            Self::start_sound(self, actor, "claw");
+            let damage = (self.random.random() % 8 + 1) * 3;
+            Self::melee_damage(self, index, actor, damage);
+            return
+        }
+        Self::missile_at_target(self, index, actor, MT_TROOPSHOT);
+    }
+
+    pub fn a_sarg_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        if Self::check_melee_range(self, actor) {
+            let damage = (self.random.random() % 10 + 1) * 4;
+            Self::melee_damage(self, index, actor, damage);
+        }
+    }
+
+    pub fn a_head_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        if Self::check_melee_range(self, actor) {
+            let damage = (self.random.random() % 6 + 1) * 10;
+            Self::melee_damage(self, index, actor, damage);
+            return
+        }
+        Self::missile_at_target(self, index, actor, MT_HEADSHOT);
+    }
+
+    pub fn a_cyber_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        Self::missile_at_target(self, index, actor, MT_ROCKET);
+    }
+
+    //the baron doesn't turn to the target before it attacks
+    pub fn a_bruis_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        if Self::check_melee_range(self, actor) {
+            Self::start_sound(self, actor, "claw");
+            let damage = (self.random.random() % 8 + 1) * 10;
+            Self::melee_damage(self, index, actor, damage);
+            return
+        }
+        Self::missile_at_target(self, index, actor, MT_BRUISERSHOT);
+    }
+
+    //A_SkelMissile, the homing missile of the revenant starts a step ahead
+    pub fn a_skel_missile(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        actor.pos.z += 16.;
+        let missile = Self::missile_at_target(self, index, actor, MT_TRACER);
+        actor.pos.z -= 16.;
+        let target = actor.target;
+        Self::with_actor(self, missile, |level, actor| {
+            Self::unlink_from_world(level, missile, actor);
+            actor.pos.x += actor.vel.x;
+            actor.pos.y += actor.vel.y;
+            Self::link_to_world(level, missile, actor);
+            actor.tracer = target;
+        });
+    }
+
+    //A_Tracer, the missile of the revenant leaves smoke and turns to its target
+    pub fn a_tracer(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        if self.maptime & 3 != 0 {return}
+
+        Self::spawn_puff(self, actor.pos.x, actor.pos.y, actor.pos.z, MISSILERANGE);
+        let smoke = Self::spawn_actor(self, MT_SMOKE, actor.pos.x - actor.vel.x, actor.pos.y - actor.vel.y, actor.pos.z);
+        let tics = self.random.random() & 3;
+        if let Some(smoke) = self.actor_mut(smoke) {
+            smoke.vel.z = 1.;
+            smoke.tics = (smoke.tics - tics).max(1);
+        }
+
+        let (exact, dest) = match self.actor(actor.tracer) {
+            Some(dest) if dest.health > 0 => (actor.angle_to(dest).bams(), dest.pos),
+            _ => return
+        };
+        //change the angle a bit towards the target
+        let mut angle = actor.angle.bams();
+        if exact != angle {
+            if exact.wrapping_sub(angle) > 0x80000000 {
+                angle = angle.wrapping_sub(TRACEANGLE);
+                if exact.wrapping_sub(angle) < 0x80000000 {angle = exact}
+            }
+            else {
+                angle = angle.wrapping_add(TRACEANGLE);
+                if exact.wrapping_sub(angle) > 0x80000000 {angle = exact}
+            }
+        }
+        actor.angle = Angle::<f64>::from_bam_u(angle);
+        let speed = actor.info().speed;
+        let vel = actor.angle.to_vector(speed);
+        actor.vel.x = vel.x;
+        actor.vel.y = vel.y;
+
+        //change the slope
+        let dist = (approx_distance(dest.x - actor.pos.x, dest.y - actor.pos.y) / speed).floor().max(1.);
+        let slope = (dest.z + 40. - actor.pos.z) / dist;
+        if slope < actor.vel.z {actor.vel.z -= 1. / 8.}
+        else {actor.vel.z += 1. / 8.}
+    }
+
+    pub fn a_skel_whoosh(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        Self::start_sound(self, actor, "skeswg");
+    }
+
+    pub fn a_skel_fist(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        if Self::check_melee_range(self, actor) {
+            let damage = (self.random.random() % 10 + 1) * 6;
+            Self::start_sound(self, actor, "skepch");
+            Self::melee_damage(self, index, actor, damage);
+        }
+    }
+
+    /*
+     * PIT_VileCheck, the corpse can be raised when it lies still and there is room for it.
+     * The archvile isn't in the thinkers while it looks, it is checked as in the way by hand.
+     */
+    fn vile_check(&mut self, thing: ActorIndex, vile: &Actor, try_x: f64, try_y: f64) -> bool {
+        let corpse = match self.actor_mut(thing) {
+            Some(corpse) if corpse.flags.contains(MobjFlags::Corpse) && corpse.tics == -1 && corpse.info().raise_state != S_NULL => corpse,
+            _ => return false
+        };
+        let max_dist = corpse.info().radius + MOBJINFO[MT_VILE].radius;
+        if (corpse.pos.x - try_x).abs() > max_dist || (corpse.pos.y - try_y).abs() > max_dist {return false}
+        corpse.vel.x = 0.;
+        corpse.vel.y = 0.;
+
+        let mut corpse = corpse.clone();
+        corpse.height *= 4.;
+        let block_dist = corpse.radius + vile.radius;
+        if (corpse.pos.x - vile.pos.x).abs() < block_dist && (corpse.pos.y - vile.pos.y).abs() < block_dist {return false}
+        Self::check_position(self, thing, &corpse, corpse.pos.x, corpse.pos.y).0
+    }
+
+    //A_VileChase, the archvile raises the corpses it walks over
+    pub fn a_vile_chase(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if (0..DI_NODIR).contains(&actor.move_dir) {
+            let speed = actor.info().speed;
+            let try_x = actor.pos.x + speed * X_SPEED[actor.move_dir as usize];
+            let try_y = actor.pos.y + speed * Y_SPEED[actor.move_dir as usize];
+
+            let block_map = &self.block_map;
+            let mut things = vec![];
+            let mut checked = HashSet::new();
+            for bx in block_map.block_x(try_x - MAXRADIUS * 2.)..=block_map.block_x(try_x + MAXRADIUS * 2.) {
+                for by in block_map.block_y(try_y - MAXRADIUS * 2.)..=block_map.block_y(try_y + MAXRADIUS * 2.) {
+                    for thing in block_map.block_things(bx, by) {
+                        if checked.insert(thing) {things.push(thing)}
+                    }
+                }
+            }
+
+            for thing in things {
+                if !Self::vile_check(self, thing, actor, try_x, try_y) {continue}
+                //got one, face the corpse
+                let target = actor.target;
+                actor.target = thing;
+                Self::face_target(self, actor);
+                actor.target = target;
+                if !Self::set_actor_state(self, index, actor, S_VILE_HEAL1) {return}
+
+                Self::with_actor(self, thing, |level, corpse| {
+                    Self::start_sound(level, corpse, "slop");
+                    let info = corpse.info();
+                    Self::set_actor_state(level, thing, corpse, info.raise_state);
+                    corpse.height *= 4.;
+                    corpse.flags = info.flags;
+                    corpse.health = info.spawn_health;
+                    corpse.target = -1;
+                });
+                return
+            }
+        }
+        //return to the normal attack mode
+        Self::a_chase(self, index, actor);
+    }
+
+    pub fn a_vile_start(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        Self::start_sound(self, actor, "vilatk");
+    }
+
+    //A_VileTarget, the fire starts on the target
+    pub fn a_vile_target(&mut self, index: ActorIndex, actor: &mut Actor) {
+        let target = match self.actor(actor.target) {
+            Some(target) => target.pos,
+            None => return
+        };
+        Self::face_target(self, actor);
+        let fire = Self::spawn_actor(self, MT_FIRE, target.x, target.y, target.z);
+        actor.tracer = fire;
+        let dest = actor.target;
+        Self::with_actor(self, fire, |level, fire_actor| {
+            fire_actor.target = index;
+            fire_actor.tracer = dest;
+            Self::fire_follow(level, fire, fire_actor, actor);
+        });
+    }
+
+    //A_Fire, the fire stays in front of the target as long as the archvile sees it
+    fn fire_follow(&mut self, index: ActorIndex, actor: &mut Actor, vile: &Actor) {
+        let (pos, angle) = match self.actor(actor.tracer) {
+            Some(dest) if self.check_sight(vile, dest, SightFlags::empty()) => (dest.pos, dest.angle),
+            _ => return
+        };
+        let offset = angle.to_vector(24.);
+        Self::unlink_from_world(self, index, actor);
+        actor.pos.x = pos.x + offset.x;
+        actor.pos.y = pos.y + offset.y;
+        actor.pos.z = pos.z;
+        Self::link_to_world(self, index, actor);
+    }
+
+    pub fn a_fire(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if let Some(vile) = self.actor(actor.target).cloned() {
+            Self::fire_follow(self, index, actor, &vile);
+        }
+    }
+
+    pub fn a_start_fire(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::start_sound(self, actor, "flamst");
+        Self::a_fire(self, index, actor);
+    }
+
+    pub fn a_fire_crackle(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::start_sound(self, actor, "flame");
+        Self::a_fire(self, index, actor);
+    }
+
+    //A_VileAttack, the target is thrown up and the fire explodes between the archvile and the target
+    pub fn a_vile_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        if !self.actor(actor.target).is_some_and(|target| self.check_sight(actor, target, SightFlags::empty())) {return}
+
+        Self::start_sound(self, actor, "barexp");
+        let target = actor.target;
+        Self::melee_damage(self, index, actor, 20);
+        let target_pos = match self.actor_mut(target) {
+            Some(target) => {
+                target.vel.z = 1000. / target.info().mass as f64;
+                target.pos
+            }
+            None => return
+        };
+
+        let offset = actor.angle.to_vector(24.);
+        let spot = match self.actor_mut(actor.tracer) {
+            Some(fire) => {
+                fire.pos.x = target_pos.x - offset.x;
+                fire.pos.y = target_pos.y - offset.y;
+                fire.clone()
+            }
+            None => return
+        };
+        Self::radius_attack(self, &spot, index, Some(actor), 70);
+    }
+
+    pub fn a_fat_raise(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        Self::face_target(self, actor);
+        Self::start_sound(self, actor, "manatk");
+    }
+
+    //the mancubus fires two missiles at a time, the attacks spread them to both sides
+    pub fn a_fat_attack1(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::face_target(self, actor);
+        actor.angle.add(&Angle::<f64>::from_bam_i(FATSPREAD));
+        Self::missile_at_target(self, index, actor, MT_FATSHOT);
+        let missile = Self::missile_at_target(self, index, actor, MT_FATSHOT);
+        Self::turn_missile(self, missile, FATSPREAD);
+    }
+
+    pub fn a_fat_attack2(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::face_target(self, actor);
+        actor.angle.subtract(&Angle::<f64>::from_bam_i(FATSPREAD));
+        Self::missile_at_target(self, index, actor, MT_FATSHOT);
+        let missile = Self::missile_at_target(self, index, actor, MT_FATSHOT);
+        Self::turn_missile(self, missile, -FATSPREAD * 2);
+    }
+
+    pub fn a_fat_attack3(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::face_target(self, actor);
+        let missile = Self::missile_at_target(self, index, actor, MT_FATSHOT);
+        Self::turn_missile(self, missile, -FATSPREAD / 2);
+        let missile = Self::missile_at_target(self, index, actor, MT_FATSHOT);
+        Self::turn_missile(self, missile, FATSPREAD / 2);
+    }
+
+    //A_SkullAttack, the lost soul flies at the target
+    pub fn a_skull_attack(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        let (dest, height) = match self.actor(actor.target) {
+            Some(target) => (target.pos, target.height),
+            None => return
+        };
+        actor.flags.insert(MobjFlags::SkullFly);
+        Self::start_sound(self, actor, actor.info().attack_sound);
+        Self::face_target(self, actor);
+        let vel = actor.angle.to_vector(SKULLSPEED);
+        actor.vel.x = vel.x;
+        actor.vel.y = vel.y;
+        let dist = (approx_distance(dest.x - actor.pos.x, dest.y - actor.pos.y) / SKULLSPEED).floor().max(1.);
+        actor.vel.z = (dest.z + height / 2. - actor.pos.z) / dist;
+    }
+
+    //A_PainShootSkull, the pain elemental spits a lost soul, it dies at once when it is spawned in a wall
+    fn pain_shoot_skull(&mut self, index: ActorIndex, actor: &Actor, angle: Angle<f64>) {
+        //too many lost souls already
+        let count = self.thinkers.all().into_iter().filter(|thing| self.actor(*thing).is_some_and(|other| other.type_ == MT_SKULL)).count();
+        if count > 20 {return}
+
+        let prestep = 4. + 3. * (actor.info().radius + MOBJINFO[MT_SKULL].radius) / 2.;
+        let offset = angle.to_vector(prestep);
+        let skull = Self::spawn_actor(self, MT_SKULL, actor.pos.x + offset.x, actor.pos.y + offset.y, actor.pos.z + 8.);
+        let target = actor.target;
+        let stuck = Self::with_actor(self, skull, |level, mo| {
+            if !Self::try_move(level, skull, mo, mo.pos.x, mo.pos.y) {return true}
+            mo.target = target;
+            Self::a_skull_attack(level, skull, mo);
+            false
+        });
+        if stuck == Some(true) {Self::damage_actor(self, skull, Some(actor), index, Some(actor), 10000)}
+    }
+
+    pub fn a_pain_attack(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if self.actor(actor.target).is_none() {return}
+        Self::face_target(self, actor);
+        Self::pain_shoot_skull(self, index, actor, actor.angle);
+    }
+
+    pub fn a_pain_die(&mut self, index: ActorIndex, actor: &mut Actor) {
+        actor.flags.remove(MobjFlags::Solid);
+        for turn in [90., 180., 270.] {
+            let mut angle = actor.angle;
+            angle.add(&Angle::<f64>::from_degrees(turn));
+            Self::pain_shoot_skull(self, index, actor, angle);
+        }
+    }
+
+    //A_Scream, the zombies and the imps have more death sounds
+    pub fn a_scream(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        let sound = match actor.info().death_sound {
+            "" => return,
+            "podth1" | "podth2" | "podth3" => ["podth1", "podth2", "podth3"][(self.random.random() % 3) as usize],
+            "bgdth1" | "bgdth2" => ["bgdth1", "bgdth2"][(self.random.random() % 2) as usize],
+            sound => sound
+        };
+        Self::start_sound(self, actor, sound);
+    }
+
+    pub fn a_player_scream(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        //TODO the pdiehi sound of doom 2 when the player is gibbed
+        Self::start_sound(self, actor, "pldeth");
+    }
+
+    pub fn a_xscream(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        Self::start_sound(self, actor, "slop");
+    }
+
+    pub fn a_pain(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        let sound = actor.info().pain_sound;
+        if !sound.is_empty() {Self::start_sound(self, actor, sound)}
+    }
+
+    pub fn a_brain_pain(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        Self::start_sound(self, actor, "bospn");
+    }
+
+    //the sounds of the monsters that walk heavily
+    pub fn a_walk_sound(&mut self, index: ActorIndex, actor: &mut Actor, sound: &str) {
+        Self::start_sound(self, actor, sound);
+        Self::a_chase(self, index, actor);
+    }
+
+    //A_Explode, the missile or the barrel hurts the things around it
+    pub fn a_explode(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        let source = self.actor(actor.target).cloned();
+        Self::radius_attack(self, actor, actor.target, source.as_ref(), 128);
+    }
+
+    //if another actor of the type is still alive
+    fn others_alive(&self, index: ActorIndex, type_: MobjType) -> bool {
+        self.thinkers.all().into_iter().any(|thing| thing != index && self.actor(thing).is_some_and(|other| other.type_ == type_ && other.health > 0))
+    }
+
+    /*
+     * A_BossDeath, the special of the map happens when all its bosses are dead.
+     * The maps of the episodes are ExMy, the ones of doom 2 are MAPxx.
+     */
+    pub fn a_boss_death(&mut self, index: ActorIndex, actor: &mut Actor) {
+        let map = self.map_name.to_uppercase();
+        let type_ = actor.type_;
+        let boss = match map.as_str() {
+            "MAP07" => type_ == MT_FATSO || type_ == MT_BABY,
+            "E1M8" => type_ == MT_BRUISER,
+            "E2M8" | "E4M6" => type_ == MT_CYBORG,
+            "E3M8" | "E4M8" => type_ == MT_SPIDER,
+            _ => !map.starts_with("MAP") && map.ends_with("M8")
+        };
+        if !boss {return}
+        //somebody needs to be alive for the victory
+        if !self.players.iter().any(|player| player.mo >= 0 && player.health > 0) {return}
+        if Self::others_alive(self, index, type_) {return}
+
+        let (special, args) = match map.as_str() {
+            "MAP07" if type_ == MT_FATSO => (ActionSpecials::FloorLowerToLowest, [666, 8, 0]),
+            "MAP07" => (ActionSpecials::FloorRaiseByTexture, [667, 8, 0]),
+            "E1M8" | "E4M8" => (ActionSpecials::FloorLowerToLowest, [666, 8, 0]),
+            "E4M6" => (ActionSpecials::DoorOpen, [666, 64, 0]),
+            _ => {
+                Self::exit_level(self, LevelExit::Normal { position: 0 });
+                return
+            }
+        };
+        Self::execute_line_special(self, special as i32, &Activator::world(), &args);
+    }
+
+    //A_KeenDie, the door opens when all the keens are dead
+    pub fn a_keen_die(&mut self, index: ActorIndex, actor: &mut Actor) {
+        actor.flags.remove(MobjFlags::Solid);
+        if Self::others_alive(self, index, actor.type_) {return}
+        Self::execute_line_special(self, ActionSpecials::DoorOpen as i32, &Activator::world(), &[666, 16, 0]);
+    }
+
+    //A_BrainAwake, the boss brain finds the targets of its cubes
+    pub fn a_brain_awake(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        let targets = self.thinkers.all().into_iter().filter(|thing| self.actor(*thing).is_some_and(|other| other.type_ == MT_BOSSTARGET)).collect();
+        self.brain.targets = targets;
+        self.brain.target_on = 0;
+        Self::start_sound(self, actor, "bossit");
+    }
+
+    //A_BrainSpit, the cube flies to the next target, it knows when it gets there
+    pub fn a_brain_spit(&mut self, index: ActorIndex, actor: &mut Actor) {
+        self.brain.easy = !self.brain.easy;
+        if self.skill <= 1 && !self.brain.easy {return}
+        if self.brain.targets.is_empty() {return}
+
+        let target = self.brain.targets[self.brain.target_on % self.brain.targets.len()];
+        self.brain.target_on = (self.brain.target_on + 1) % self.brain.targets.len();
+        let dest = match self.actor(target) {
+            Some(dest) => dest.clone(),
+            None => return
+        };
+        let cube = Self::spawn_missile(self, index, actor, &dest, MT_SPAWNSHOT);
+        if let Some(cube) = self.actor_mut(cube) {
+            cube.target = target;
+            let tics = STATES[cube.state].tics.max(1);
+            cube.reaction_time = ((dest.pos.y - actor.pos.y) / cube.vel.y) as i32 / tics;
+        }
+        Self::start_sound(self, actor, "bospit");
+    }
+
+    pub fn a_spawn_sound(&mut self, index: ActorIndex, actor: &mut Actor) {
+        Self::start_sound(self, actor, "boscub");
+        Self::a_spawn_fly(self, index, actor);
+    }
+
+    //A_SpawnFly, the cube turns into a random monster at its target
+    pub fn a_spawn_fly(&mut self, index: ActorIndex, actor: &mut Actor) {
+        actor.reaction_time -= 1;
+        //still flying
+        if actor.reaction_time != 0 {return}
+        let target = match self.actor(actor.target) {
+            Some(target) => target.pos,
+            None => return
+        };
+
+        let fog = Self::spawn_actor(self, MT_SPAWNFIRE, target.x, target.y, target.z);
+        if let Some(fog) = self.actor(fog) {Self::start_sound(self, fog, "telept")}
+
+        //the odds of the monsters are those of doom
+        let r = self.random.random();
+        let type_ = if r < 50 {MT_TROOP} else if r < 90 {MT_SERGEANT} else if r < 120 {MT_SHADOWS} else if r < 130 {MT_PAIN}
+            else if r < 160 {MT_HEAD} else if r < 162 {MT_VILE} else if r < 172 {MT_UNDEAD} else if r < 192 {MT_BABY}
+            else if r < 222 {MT_FATSO} else if r < 246 {MT_KNIGHT} else {MT_BRUISER};
+        let monster = Self::spawn_actor(self, type_, target.x, target.y, target.z);
+        Self::with_actor(self, monster, |level, mo| {
+            if Self::look_for_players(level, mo, true) {
+                Self::set_actor_state(level, monster, mo, mo.info().see_state);
+            }
+            //TODO P_TeleportMove, the monster telefrags what is in the way
+        });
+        //the cube is gone
+        Self::destroy_thinker(self, index);
+    }
+
+    pub fn a_brain_scream(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        let mut x = actor.pos.x - 196.;
+        while x < actor.pos.x + 320. {
+            let z = 128. + self.random.random() as f64 * 2.;
+            Self::brain_explosion(self, x, actor.pos.y - 320., z);
+            x += 8.;
+        }
+        Self::start_sound(self, actor, "bosdth");
+    }
+
+    pub fn a_brain_explode(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        let x = actor.pos.x + self.random.random2() as f64 / 32.;
+        let z = 128. + self.random.random() as f64 * 2.;
+        Self::brain_explosion(self, x, actor.pos.y, z);
+    }
+
+    //the explosions of the dying boss brain are rockets that don't fly
+    fn brain_explosion(&mut self, x: f64, y: f64, z: f64) {
+        let rocket = Self::spawn_actor(self, MT_ROCKET, x, y, z);
+        let vel_z = self.random.random() as f64 / 128.;
+        Self::with_actor(self, rocket, |level, actor| {
+            actor.vel.z = vel_z;
+            if !Self::set_actor_state(level, rocket, actor, S_BRAINEXPLODE1) {return}
+            actor.tics = (actor.tics - (level.random.random() & 7)).max(1);
+        });
+    }
+
+    pub fn a_brain_die(&mut self, _index: ActorIndex, _actor: &mut Actor) {
+        Self::exit_level(self, LevelExit::Normal { position: 0 });
+    }
+}
@@ -0,0 +1,595 @@
+use super::{LevelLocals, SectorSpecials};
+use super::level_elements::{LineIndex, SectorIndex, SectorE, SecSpecial, LineFlags};
+use super::level_texture::TextureID;
+use super::level_movers::*;
+use super::level_serializer::{Serializable, Serializer};
+
+//DFloor of zdoom, also used for the stairs and donuts
+#[derive(Default)]
+pub struct Floor {
+    pub sector: SectorIndex,
+    pub type_: FloorType,
+    pub crush: i32,
+    pub hexen_crush: bool,
+    pub direction: i32,
+    pub speed: f64,
+    pub floor_dest_dist: f64,
+    //the texture and special the sector gets when the floor is done (None -> no change)
+    pub texture: Option<TextureID>,
+    pub new_special: Option<SecSpecial>,
+
+    //stairs
+    pub reset_count: i32,
+    pub org_dist: f64,
+    pub delay: i32,
+    pub pause_time: i32,
+    pub step_time: i32,
+    pub per_step_time: i32
+}
+
+impl Floor {
+    pub fn new(sector: SectorIndex, type_: FloorType, speed: f64, org_dist: f64) -> Floor {
+        Floor { sector, type_, crush: -1, hexen_crush: false, direction: 0, speed, floor_dest_dist: org_dist, texture: None, new_special: None, reset_count: 0, org_dist, delay: 0, pause_time: 0, step_time: 0, per_step_time: 0 }
+    }
+}
+
+//DElevator of zdoom, moves the floor and the ceiling together
+#[derive(Default)]
+pub struct Elevator {
+    pub sector: SectorIndex,
+    pub type_: ElevatorType,
+    pub direction: i32,
+    pub floor_dest_dist: f64,
+    pub ceiling_dest_dist: f64,
+    pub speed: f64
+}
+
+impl Serializable for Floor {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        arc.int("sector", &mut self.sector)
+            .enumeration("type", &mut self.type_)
+            .int("crush", &mut self.crush)
+            .boolean("hexencrush", &mut self.hexen_crush)
+            .int("direction", &mut self.direction)
+            .float("speed", &mut self.speed)
+            .float("floordestdist", &mut self.floor_dest_dist)
+            .option("texture", &mut self.texture, |arc, key, texture| {arc.texture(key, texture);})
+            .option("newspecial", &mut self.new_special, |arc, key, special| {arc.special(key, special);})
+            .int("resetcount", &mut self.reset_count)
+            .float("orgdist", &mut self.org_dist)
+            .int("delay", &mut self.delay)
+            .int("pausetime", &mut self.pause_time)
+            .int("steptime", &mut self.step_time)
+            .int("persteptime", &mut self.per_step_time);
+    }
+}
+
+impl Serializable for Elevator {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        arc.int("sector", &mut self.sector)
+            .enumeration("type", &mut self.type_)
+            .int("direction", &mut self.direction)
+            .float("floordestdist", &mut self.floor_dest_dist)
+            .float("ceilingdestdist", &mut self.ceiling_dest_dist)
+            .float("speed", &mut self.speed);
+    }
+}
+
+/*
+ * The floor movers (p_floor.cpp of zdoom)
+ */
+#[allow(clippy::too_many_arguments)]
+impl LevelLocals {
+    pub fn ev_do_floor(&mut self, type_: FloorType, line: LineIndex, tag: i32, speed: f64, height: f64, crush: i32, change: i32, hexen_crush: bool, hereticlower: bool) -> bool {
+        let mut rtn = false;
+        for sector in Self::tagged_sectors(self, tag, line) {
+            rtn |= Self::create_floor(self, sector, type_, line, speed, height, crush, change, hexen_crush, hereticlower);
+        }
+        rtn
+    }
+
+    pub fn create_floor(&mut self, sector: SectorIndex, type_: FloorType, line: LineIndex, speed: f64, height: f64, crush: i32, change: i32, hexen_crush: bool, hereticlower: bool) -> bool {
+        let sector_rc = self.elements.sectors[sector as usize].clone();
+        //already moving, keep going
+        if sector_rc.borrow().plane_moving(SectorE::Floor as usize) {return false}
+
+        let (center, floor_d) = {
+            let sec = sector_rc.borrow();
+            (sec.center_spot, sec.floorplane.fd())
+        };
+        let center_floor = sector_rc.borrow().center_floor();
+        let dist = |height: f64| sector_rc.borrow().floorplane.point_to_dist(&center, height);
+
+        let mut floor = Floor::new(sector, type_, speed, floor_d);
+        floor.crush = crush;
+        floor.hexen_crush = hexen_crush;
+
+        match type_ {
+            FloorType::LowerToHighest => {
+                floor.direction = -1;
+                let new_height = Self::find_highest_floor_surrounding(self, sector);
+                floor.floor_dest_dist = dist(new_height);
+                //doom's turbo lower does this, extended to all the lower to highest types
+                if hereticlower || floor.floor_dest_dist != floor_d {
+                    floor.floor_dest_dist = dist(new_height + height);
+                }
+            }
+            FloorType::LowerToLowest => {
+                floor.direction = -1;
+                floor.floor_dest_dist = dist(Self::find_lowest_floor_surrounding(self, sector));
+            }
+            FloorType::LowerToNearest => {
+                floor.direction = -1;
+                floor.floor_dest_dist = dist(Self::find_next_lowest_floor(self, sector));
+            }
+            FloorType::LowerInstant | FloorType::LowerByValue => {
+                if type_ == FloorType::LowerInstant {floor.speed = height}
+                floor.direction = -1;
+                floor.floor_dest_dist = dist(center_floor - height);
+            }
+            FloorType::RaiseInstant | FloorType::RaiseByValue => {
+                if type_ == FloorType::RaiseInstant {floor.speed = height}
+                floor.direction = 1;
+                floor.floor_dest_dist = dist(center_floor + height);
+            }
+            FloorType::MoveToValue => {
+                floor.floor_dest_dist = dist(height);
+                floor.direction = if floor.floor_dest_dist > floor_d {-1} else {1};
+            }
+            FloorType::RaiseAndCrushDoom | FloorType::RaiseToLowestCeiling => {
+                let height = if type_ == FloorType::RaiseAndCrushDoom {8.} else {height};
+                floor.direction = 1;
+                let new_height = Self::find_lowest_ceiling_surrounding(self, sector) - height;
+                let ceiling_height = Self::find_lowest_ceiling_point(self, sector);
+                floor.floor_dest_dist = dist(new_height);
+                if new_height > ceiling_height {
+                    floor.floor_dest_dist = dist(if type_ == FloorType::RaiseAndCrushDoom {ceiling_height - 8.} else {ceiling_height});
+                }
+            }
+            FloorType::RaiseToHighest => {
+                floor.direction = 1;
+                floor.floor_dest_dist = dist(Self::find_highest_floor_surrounding(self, sector));
+            }
+            FloorType::RaiseToNearest => {
+                floor.direction = 1;
+                floor.floor_dest_dist = dist(Self::find_next_highest_floor(self, sector));
+            }
+            FloorType::RaiseToLowest => {
+                floor.direction = 1;
+                floor.floor_dest_dist = dist(Self::find_lowest_floor_surrounding(self, sector));
+            }
+            FloorType::RaiseAndCrush => {
+                floor.direction = 1;
+                floor.floor_dest_dist = dist(Self::find_lowest_ceiling_point(self, sector) - 8.);
+            }
+            FloorType::RaiseToCeiling => {
+                floor.direction = 1;
+                floor.floor_dest_dist = dist(Self::find_lowest_ceiling_point(self, sector) - height);
+            }
+            FloorType::LowerToLowestCeiling => {
+                floor.direction = -1;
+                floor.floor_dest_dist = dist(Self::find_lowest_ceiling_surrounding(self, sector));
+            }
+            FloorType::LowerByTexture => {
+                floor.direction = -1;
+                floor.floor_dest_dist = dist(center_floor - Self::find_shortest_texture_around(self, sector));
+            }
+            FloorType::LowerToCeiling => {
+                floor.direction = -1;
+                floor.floor_dest_dist = dist(Self::find_lowest_ceiling_point(self, sector) - height);
+            }
+            FloorType::ToCeilingInstant => {
+                floor.floor_dest_dist = dist(Self::find_lowest_ceiling_point(self, sector) - height);
+                floor.direction = if floor.floor_dest_dist > floor_d {-1} else {1};
+                floor.speed = (floor.floor_dest_dist - floor_d).abs();
+            }
+            FloorType::RaiseByTexture => {
+                floor.direction = 1;
+                floor.floor_dest_dist = dist(center_floor + Self::find_shortest_texture_around(self, sector));
+            }
+            FloorType::Raise24 | FloorType::Raise24AndChange => {
+                let height = if height != 0. {height} else {24.};
+                floor.direction = 1;
+                floor.floor_dest_dist = dist(center_floor + height);
+                if type_ == FloorType::Raise24AndChange {
+                    //the change happens right away
+                    if line >= 0 {
+                        let front = self.lines[line as usize].borrow().front_sector;
+                        let (texture, special) = {
+                            let front = self.sectors[front as usize].borrow();
+                            (front.get_texture(SectorE::Floor as usize), front.special)
+                        };
+                        let mut sec = sector_rc.borrow_mut();
+                        sec.set_texture(SectorE::Floor as usize, texture);
+                        sec.special = special;
+                    }
+                    else {sector_rc.borrow_mut().special = 0}
+                }
+            }
+            FloorType::LowerAndChange => {
+                floor.direction = -1;
+                let new_height = Self::find_lowest_floor_surrounding(self, sector);
+                floor.floor_dest_dist = dist(new_height);
+                //the sector keeps its own texture and special when there is no model
+                let model = Self::find_model_floor_sector(self, sector, new_height);
+                let model_rc = if model >= 0 {self.sectors[model as usize].clone()} else {sector_rc.clone()};
+                let model = model_rc.borrow();
+                floor.texture = Some(model.get_texture(SectorE::Floor as usize));
+                floor.new_special = Some(model.get_special());
+            }
+            _ => ()
+        }
+
+        if change & 3 != 0 {
+            let model = if change & 4 != 0 {
+                //numeric model change
+                let dest_height = -floor.floor_dest_dist;
+                match type_ {
+                    FloorType::RaiseToLowestCeiling | FloorType::LowerToLowestCeiling | FloorType::RaiseToCeiling | FloorType::LowerToCeiling =>
+                        Self::find_model_ceiling_sector(self, sector, dest_height),
+                    _ => Self::find_model_floor_sector(self, sector, dest_height)
+                }
+            }
+            else if line >= 0 {
+                //trigger model change
+                self.lines[line as usize].borrow().front_sector
+            }
+            else {-1};
+            if model >= 0 {Self::set_floor_change_type(self, &mut floor, model, change & 3)}
+        }
+
+        //TODO the floor sound, not for instant movement
+        Self::add_mover(self, SectorMover::Floor(floor));
+        true
+    }
+
+    fn set_floor_change_type(&self, floor: &mut Floor, model: SectorIndex, change: i32) {
+        let model = self.sectors[model as usize].borrow();
+        floor.texture = Some(model.get_texture(SectorE::Floor as usize));
+        match change {
+            1 => floor.new_special = Some(SecSpecial::default()),
+            3 => floor.new_special = Some(model.get_special()),
+            _ => ()
+        }
+    }
+
+    pub fn tick_floor(&mut self, floor: &mut Floor) -> bool {
+        //resetting stairs
+        if floor.type_ == FloorType::BuildStair || floor.type_ == FloorType::WaitStair {
+            if floor.reset_count != 0 {
+                floor.reset_count -= 1;
+                if floor.reset_count == 0 {
+                    floor.type_ = FloorType::ResetStair;
+                    floor.direction = if floor.direction > 0 {-1} else {1};
+                    floor.floor_dest_dist = floor.org_dist;
+                }
+            }
+            if floor.pause_time != 0 {
+                floor.pause_time -= 1;
+                return true
+            }
+            else if floor.step_time != 0 {
+                floor.step_time -= 1;
+                if floor.step_time == 0 {
+                    floor.pause_time = floor.delay;
+                    floor.step_time = floor.per_step_time;
+                }
+            }
+        }
+        if floor.type_ == FloorType::WaitStair {return true}
+
+        let res = Self::move_floor(self, floor.sector, floor.speed, floor.floor_dest_dist, floor.crush, floor.direction, floor.hexen_crush);
+        if res != MoveResult::PastDest {return true}
+
+        if floor.type_ == FloorType::BuildStair {floor.type_ = FloorType::WaitStair}
+        if floor.type_ == FloorType::WaitStair && floor.reset_count != 0 {return true}
+
+        {
+            let mut sec = self.sectors[floor.sector as usize].borrow_mut();
+            if let Some(special) = &floor.new_special {sec.set_special(special)}
+            if let Some(texture) = floor.texture {sec.set_texture(SectorE::Floor as usize, texture)}
+        }
+        Self::unlock_stairs(self, floor.sector);
+        false
+    }
+
+    //the retriggerable stairs stay locked until all the steps are done
+    fn unlock_stairs(&mut self, sector: SectorIndex) {
+        let sectors = &self.elements.sectors;
+        if sectors[sector as usize].borrow().stair_lock != -2 {return}
+        sectors[sector as usize].borrow_mut().stair_lock = -1;
+
+        let mut sec = sector;
+        loop {
+            let prev = sectors[sec as usize].borrow().prev_sec;
+            if prev == -1 || sectors[prev as usize].borrow().stair_lock == -2 {break}
+            sec = prev;
+        }
+        if sectors[sec as usize].borrow().prev_sec != -1 {return}
+
+        sec = sector;
+        loop {
+            let next = sectors[sec as usize].borrow().next_sec;
+            if next == -1 || sectors[next as usize].borrow().stair_lock == -2 {break}
+            sec = next;
+        }
+        if sectors[sec as usize].borrow().next_sec != -1 {return}
+
+        //all the steps are done
+        loop {
+            let mut s = sectors[sec as usize].borrow_mut();
+            s.stair_lock = 0;
+            if s.prev_sec == -1 {break}
+            sec = s.prev_sec;
+        }
+    }
+
+    fn stair_step(&self, sector: SectorIndex, direction: i32, speed: f64, height: f64, delay: i32, reset: i32, per_step_time: i32, crush: i32) -> Floor {
+        let sec = self.sectors[sector as usize].borrow();
+        let mut floor = Floor::new(sector, FloorType::BuildStair, speed, sec.floorplane.fd());
+        floor.direction = direction;
+        floor.floor_dest_dist = sec.floorplane.point_to_dist(&sec.center_spot, height);
+        floor.crush = crush;
+        floor.reset_count = reset;
+        floor.delay = delay;
+        floor.step_time = per_step_time;
+        floor.per_step_time = per_step_time;
+        floor
+    }
+
+    fn stair_blocked(&self, sector: SectorIndex) -> bool {
+        let sec = self.sectors[sector as usize].borrow();
+        sec.plane_moving(SectorE::Floor as usize) || sec.stair_lock != 0
+    }
+
+    pub fn ev_build_stairs(&mut self, tag: i32, type_: StairType, line: LineIndex, stair_size: f64, speed: f64, delay: i32, reset: i32, ign_txt: bool, use_specials: i32) -> bool {
+        if speed == 0. {return false}
+        let per_step_time = (stair_size / speed) as i32;
+        let direction = if type_ == StairType::BuildUp {1} else {-1};
+        let stair_step = stair_size * direction as f64;
+        let mut rtn = false;
+
+        for first in Self::tagged_sectors(self, tag, line) {
+            if Self::stair_blocked(self, first) {continue}
+            rtn = true;
+
+            let mut height = self.sectors[first as usize].borrow().center_floor() + stair_step;
+            let crush = if use_specials & STAIR_CRUSH != 0 {10} else {-1};
+            let floor = Self::stair_step(self, first, direction, speed, height, delay, reset, per_step_time, crush);
+            Self::add_mover(self, SectorMover::Floor(floor));
+
+            let texture = self.sectors[first as usize].borrow().get_texture(SectorE::Floor as usize);
+            let mut sec = first;
+            let mut prev = -1;
+            loop {
+                //find the next step
+                let mut next = -1;
+                if use_specials & STAIR_USE_SPECIALS != 0 {
+                    let special = if self.sectors[sec as usize].borrow().special & 0xff == SectorSpecials::StairsSpecial1 as i32 {
+                        SectorSpecials::StairsSpecial2
+                    } else {SectorSpecials::StairsSpecial1};
+                    let other = Self::next_special_sector(self, sec, special as i32, prev);
+                    if other < 0 {break}
+                    height += stair_step;
+                    //already moving, look further
+                    if Self::stair_blocked(self, other) {
+                        prev = sec;
+                        sec = other;
+                        continue;
+                    }
+                    next = other;
+                }
+                else {
+                    let lines = self.sectors[sec as usize].borrow().lines.clone();
+                    for line in lines {
+                        let (flags, front, back) = {
+                            let line = self.lines[line as usize].borrow();
+                            (line.flags, line.front_sector, line.back_sector)
+                        };
+                        if flags & LineFlags::TwoSided.bits() == 0 || front != sec || back < 0 {continue}
+                        if !ign_txt && self.sectors[back as usize].borrow().get_texture(SectorE::Floor as usize) != texture {continue}
+                        if Self::stair_blocked(self, back) {continue}
+                        height += stair_step;
+                        next = back;
+                        break;
+                    }
+                    if next < 0 {break}
+                }
+
+                //link the steps in both directions and lock them until the stairs are done
+                self.sectors[sec as usize].borrow_mut().next_sec = next;
+                {
+                    let mut s = self.sectors[next as usize].borrow_mut();
+                    s.prev_sec = sec;
+                    s.next_sec = -1;
+                    s.stair_lock = -2;
+                }
+                prev = sec;
+                sec = next;
+
+                let step_speed = if use_specials & STAIR_SYNC != 0 {
+                    let rise = height - self.sectors[sec as usize].borrow().center_floor();
+                    speed * rise / stair_step
+                } else {speed};
+                let crush = if use_specials & STAIR_CRUSH != 0 || (use_specials & STAIR_USE_SPECIALS == 0 && speed == 4.) {10} else {-1};
+                let floor = Self::stair_step(self, sec, direction, step_speed, height, delay, reset, per_step_time, crush);
+                Self::add_mover(self, SectorMover::Floor(floor));
+            }
+            //the first step can't point back, the unlocking would loop
+            self.sectors[first as usize].borrow_mut().prev_sec = -1;
+        }
+        rtn
+    }
+
+    pub fn ev_do_donut(&mut self, tag: i32, line: LineIndex, pillar_speed: f64, slime_speed: f64) -> bool {
+        let mut rtn = false;
+        for pillar in Self::tagged_sectors(self, tag, line) {
+            if self.sectors[pillar as usize].borrow().plane_moving(SectorE::Floor as usize) {continue}
+            rtn = true;
+
+            //the pool is behind the lowest numbered line of the pillar
+            let first_line = match self.sectors[pillar as usize].borrow().lines.first() {
+                Some(line) => *line,
+                None => continue
+            };
+            let pool = Self::get_next_sector(self, first_line, pillar);
+            if pool < 0 || self.sectors[pool as usize].borrow().plane_moving(SectorE::Floor as usize) {continue}
+
+            let lines = self.sectors[pool as usize].borrow().lines.clone();
+            for line in lines {
+                let (flags, back) = {
+                    let line = self.lines[line as usize].borrow();
+                    (line.flags, line.back_sector)
+                };
+                if flags & LineFlags::TwoSided.bits() == 0 || back == pillar || back < 0 {continue}
+                let height = Self::find_highest_floor_point(self, back);
+
+                //the rising slime
+                let mut floor = {
+                    let pool_sec = self.sectors[pool as usize].borrow();
+                    let mut floor = Floor::new(pool, FloorType::DonutRaise, slime_speed, pool_sec.floorplane.fd());
+                    floor.floor_dest_dist = pool_sec.floorplane.point_to_dist(&pool_sec.center_spot, height);
+                    floor
+                };
+                floor.direction = 1;
+                floor.texture = Some(self.sectors[back as usize].borrow().get_texture(SectorE::Floor as usize));
+                floor.new_special = Some(SecSpecial::default());
+                Self::add_mover(self, SectorMover::Floor(floor));
+
+                //the lowering donut hole
+                let mut floor = {
+                    let pillar_sec = self.sectors[pillar as usize].borrow();
+                    let mut floor = Floor::new(pillar, FloorType::LowerToNearest, pillar_speed, pillar_sec.floorplane.fd());
+                    floor.floor_dest_dist = pillar_sec.floorplane.point_to_dist(&pillar_sec.center_spot, height);
+                    floor
+                };
+                floor.direction = -1;
+                Self::add_mover(self, SectorMover::Floor(floor));
+                break;
+            }
+        }
+        rtn
+    }
+
+    pub fn ev_do_elevator(&mut self, line: LineIndex, type_: ElevatorType, speed: f64, height: f64, tag: i32) -> bool {
+        if line < 0 && type_ == ElevatorType::Current {return false}
+        let mut rtn = false;
+        for sector in Self::tagged_sectors(self, tag, line) {
+            {
+                let sec = self.sectors[sector as usize].borrow();
+                if sec.plane_moving(SectorE::Floor as usize) || sec.plane_moving(SectorE::Ceiling as usize) {continue}
+            }
+            rtn = true;
+
+            let sector_rc = self.sectors[sector as usize].clone();
+            let (spot, floor_height, ceiling_height) = {
+                let sec = sector_rc.borrow();
+                (sec.center_spot, sec.center_floor(), sec.center_ceiling())
+            };
+            let (direction, floor_dest, ceiling_dest) = match type_ {
+                ElevatorType::Down => {
+                    let new_height = Self::find_next_lowest_floor(self, sector);
+                    (-1, new_height, new_height + ceiling_height - floor_height)
+                }
+                ElevatorType::Up => {
+                    let new_height = Self::find_next_highest_floor(self, sector);
+                    (1, new_height, new_height + ceiling_height - floor_height)
+                }
+                ElevatorType::Current => {
+                    //to the floor of the front sector of the switch
+                    let (front, v1) = {
+                        let line = self.lines[line as usize].borrow();
+                        (line.front_sector, line.v1.f_pos())
+                    };
+                    let new_height = self.sectors[front as usize].borrow().floorplane.z_at(&v1);
+                    let sec = sector_rc.borrow();
+                    let direction = if new_height < sec.floorplane.z_at(&v1) {-1} else {1};
+                    (direction, new_height, new_height + sec.ceilingplane.z_at(&v1) - sec.floorplane.z_at(&v1))
+                }
+                ElevatorType::Raise => (1, floor_height + height, ceiling_height + height),
+                ElevatorType::Lower => (-1, floor_height - height, ceiling_height - height)
+            };
+
+            let elevator = {
+                let sec = sector_rc.borrow();
+                Elevator { sector, type_, direction, speed,
+                    floor_dest_dist: sec.floorplane.point_to_dist(&spot, floor_dest),
+                    ceiling_dest_dist: sec.ceilingplane.point_to_dist(&spot, ceiling_dest) }
+            };
+            Self::add_mover(self, SectorMover::Elevator(elevator));
+        }
+        rtn
+    }
+
+    pub fn tick_elevator(&mut self, elevator: &mut Elevator) -> bool {
+        let (old_floor, old_ceiling) = {
+            let sec = self.sectors[elevator.sector as usize].borrow();
+            (sec.floorplane.fd(), sec.ceilingplane.fd())
+        };
+
+        //the plane in front moves first, the other one can't follow when something is in the way
+        let res = if elevator.direction < 0 {
+            let mut res = Self::move_floor(self, elevator.sector, elevator.speed, elevator.floor_dest_dist, -1, elevator.direction, false);
+            if res != MoveResult::Crushed {
+                res = Self::move_ceiling(self, elevator.sector, elevator.speed, elevator.ceiling_dest_dist, -1, elevator.direction, false);
+                if res == MoveResult::Crushed {
+                    Self::move_floor(self, elevator.sector, elevator.speed, old_floor, -1, -elevator.direction, false);
+                }
+            }
+            res
+        }
+        else {
+            let mut res = Self::move_ceiling(self, elevator.sector, elevator.speed, elevator.ceiling_dest_dist, -1, elevator.direction, false);
+            if res != MoveResult::Crushed {
+                res = Self::move_floor(self, elevator.sector, elevator.speed, elevator.floor_dest_dist, -1, elevator.direction, false);
+                if res == MoveResult::Crushed {
+                    Self::move_ceiling(self, elevator.sector, elevator.speed, old_ceiling, -1, -elevator.direction, false);
+                }
+            }
+            res
+        };
+        res != MoveResult::PastDest
+    }
+
+    pub fn ev_do_change(&mut self, line: LineIndex, type_: ChangeType, tag: i32) -> bool {
+        let mut rtn = false;
+        for sector in Self::sectors_with_tag(self, tag) {
+            rtn = true;
+            let model = match type_ {
+                ChangeType::TriggerChangeOnly => if line >= 0 {self.lines[line as usize].borrow().front_sector} else {-1},
+                ChangeType::NumericChangeOnly => {
+                    let height = self.sectors[sector as usize].borrow().center_floor();
+                    Self::find_model_floor_sector(self, sector, height)
+                }
+            };
+            if model < 0 {continue}
+            let (texture, special) = {
+                let model = self.sectors[model as usize].borrow();
+                (model.get_texture(SectorE::Floor as usize), model.get_special())
+            };
+            let mut sec = self.sectors[sector as usize].borrow_mut();
+            sec.set_texture(SectorE::Floor as usize, texture);
+            sec.set_special(&special);
+        }
+        rtn
+    }
+
+    pub fn ev_stop_floor(&mut self, tag: i32, line: LineIndex) -> bool {
+        for sector in Self::tagged_sectors(self, tag, line) {
+            let floor_data = self.sectors[sector as usize].borrow().floor_data;
+            Self::destroy_thinker(self, floor_data);
+        }
+        true
+    }
+
+    //only the crushing floors stop
+    pub fn ev_floor_crush_stop(&mut self, tag: i32, line: LineIndex) -> bool {
+        for sector in Self::tagged_sectors(self, tag, line) {
+            let floor_data = self.sectors[sector as usize].borrow().floor_data;
+            if let Some(SectorMover::Floor(floor)) = Self::mover(self, floor_data) {
+                if floor.type_ == FloorType::RaiseAndCrush {Self::destroy_thinker(self, floor_data)}
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,242 @@
+use crate::vector::{Angle, Vector3};
+
+use super::LevelLocals;
+use super::level_actor::{Actor, ActorIndex, PlayerState, ON_FLOOR_Z};
+use super::level_info::*;
+use super::level_pspr::*;
+
+pub const BASETHRESHOLD: i32 = 100; //the tics a monster keeps chasing the one that hurt it
+const BONUSADD: i32 = 6;
+
+/*
+ * Damaging and killing the actors (p_interaction.cpp)
+ */
+impl LevelLocals {
+    /*
+     * P_DamageMobj, the inflictor did the damage (the missile, the monster or the player), the source gets the blame.
+     * Both are None for the damage of the world. The actors passed in can't be in the thinkers, clone the ones that are.
+     */
+    pub fn damage_actor(&mut self, target: ActorIndex, inflictor: Option<&Actor>, source: ActorIndex, source_actor: Option<&Actor>, damage: i32) {
+        Self::with_actor(self, target, |level, actor| {
+            if !actor.flags.contains(MobjFlags::Shootable) || actor.health <= 0 {return}
+            if actor.flags.contains(MobjFlags::SkullFly) {actor.vel = Vector3::default()}
+
+            let mut damage = damage;
+            //the baby skill takes half the damage
+            if actor.player >= 0 && level.skill == 0 {damage >>= 1}
+
+            //the hit pushes the target away from the inflictor
+            //TODO the chainsaw of the source doesn't push
+            if let Some(inflictor) = inflictor {
+                if !actor.flags.contains(MobjFlags::NoClip) {
+                    let mut angle = inflictor.angle_to(actor);
+                    let mut thrust = damage as f64 * 12.5 / actor.info().mass as f64;
+                    //a deadly hit from below makes it fall forwards sometimes
+                    if damage < 40 && damage > actor.health && actor.pos.z - inflictor.pos.z > 64. && level.random.random() & 1 != 0 {
+                        angle.add(&Angle::<f64>::from_degrees(180.));
+                        thrust *= 4.;
+                    }
+                    let push = angle.to_vector(thrust);
+                    actor.vel.x += push.x;
+                    actor.vel.y += push.y;
+                }
+            }
+
+            if actor.player >= 0 {
+                //TODO the end of the game sector doesn't kill, god mode and the invulnerability
+                let player = &mut level.players[actor.player as usize];
+                //the armor takes a part of the damage
+                if player.armor_type != 0 {
+                    let mut saved = if player.armor_type == 1 {damage / 3} else {damage / 2};
+                    if player.armor_points <= saved {
+                        saved = player.armor_points;
+                        player.armor_type = 0;
+                    }
+                    player.armor_points -= saved;
+                    damage -= saved;
+                }
+                player.health = (player.health - damage).max(0);
+                player.attacker = source;
+                player.damage_count = (player.damage_count + damage).min(100);
+            }
+
+            actor.health -= damage;
+            if actor.health <= 0 {
+                Self::kill_actor(level, target, actor, source_actor);
+                return
+            }
+
+            if level.random.random() < actor.info().pain_chance && !actor.flags.contains(MobjFlags::SkullFly) {
+                //fight back
+                actor.flags.insert(MobjFlags::JustHit);
+                if !Self::set_actor_state(level, target, actor, actor.info().pain_state) {return}
+            }
+            //the monster doesn't wait to fight back
+            actor.reaction_time = 0;
+
+            //it goes after the source when it isn't intent on another one, the archvile always is and never gets the blame
+            let source_actor = match source_actor {
+                Some(source_actor) if source >= 0 && source != target => source_actor,
+                _ => return
+            };
+            if actor.threshold != 0 && actor.type_ != MT_VILE {return}
+            if source_actor.type_ == MT_VILE {return}
+            //the monsters that don't infight only go after the players
+            if actor.flags.contains(MobjFlags::NoInFighting) && source_actor.player < 0 {return}
+            actor.target = source;
+            actor.threshold = BASETHRESHOLD;
+            let info = actor.info();
+            if actor.state == info.spawn_state && info.see_state != S_NULL {
+                Self::set_actor_state(level, target, actor, info.see_state);
+            }
+        });
+    }
+
+    //P_KillMobj, the source gets the kill
+    pub fn kill_actor(&mut self, index: ActorIndex, actor: &mut Actor, source_actor: Option<&Actor>) {
+        actor.flags.remove(MobjFlags::Shootable | MobjFlags::Float | MobjFlags::SkullFly);
+        if actor.type_ != MT_SKULL {actor.flags.remove(MobjFlags::NoGravity)}
+        actor.flags.insert(MobjFlags::Corpse | MobjFlags::Dropoff);
+        actor.height /= 4.;
+
+        if actor.flags.contains(MobjFlags::CountKill) {
+            self.killed_monsters += 1;
+            match source_actor {
+                Some(source) if source.player >= 0 => self.players[source.player as usize].kill_count += 1,
+                //in single player all the deaths count, also the ones of the infighting
+                _ => if !self.net_game {self.players[0].kill_count += 1}
+            }
+        }
+        //TODO the frags of the players
+
+        if actor.player >= 0 {
+            actor.flags.remove(MobjFlags::Solid);
+            self.players[actor.player as usize].player_state = PlayerState::Dead;
+            Self::drop_weapon(self, actor.player as usize, index, actor);
+        }
+
+        let info = actor.info();
+        let state = if actor.health < -info.spawn_health && info.xdeath_state != S_NULL {info.xdeath_state} else {info.death_state};
+        if !Self::set_actor_state(self, index, actor, state) {return}
+        actor.tics = (actor.tics - (self.random.random() & 3)).max(1);
+
+        //the zombies drop their guns
+        let item = match actor.type_ {
+            MT_WOLFSS | MT_POSSESSED => MT_CLIP,
+            MT_SHOTGUY => MT_SHOTGUN,
+            MT_CHAINGUY => MT_CHAINGUN,
+            _ => return
+        };
+        let dropped = Self::spawn_actor(self, item, actor.pos.x, actor.pos.y, ON_FLOOR_Z);
+        if let Some(dropped) = self.actor_mut(dropped) {dropped.flags.insert(MobjFlags::Dropped)}
+    }
+
+    /*
+     * P_GiveAmmo, the clips are the clips of the ammo type, 0 is half of one for the dropped clips.
+     * A player that had none of the ammo changes to a better weapon for it.
+     */
+    pub fn give_ammo(&mut self, player: usize, ammo: AmmoType, clips: i32) -> bool {
+        if ammo == AM_NOAMMO {return false}
+        let baby_or_nightmare = self.skill == 0 || self.skill == 4;
+        let p = &mut self.players[player];
+        if p.ammo[ammo] == p.max_ammo[ammo] {return false}
+
+        let mut count = if clips > 0 {clips * CLIPAMMO[ammo]} else {CLIPAMMO[ammo] / 2};
+        //give double the ammo on the baby and the nightmare skills
+        if baby_or_nightmare {count <<= 1}
+        let old_ammo = p.ammo[ammo];
+        p.ammo[ammo] = (p.ammo[ammo] + count).min(p.max_ammo[ammo]);
+        //the weapon doesn't change when the player already had the ammo
+        if old_ammo != 0 {return true}
+
+        match ammo {
+            AM_CLIP if p.ready_weapon == WP_FIST => p.pending_weapon = if p.weapon_owned[WP_CHAINGUN] {WP_CHAINGUN} else {WP_PISTOL},
+            AM_SHELL if (p.ready_weapon == WP_FIST || p.ready_weapon == WP_PISTOL) && p.weapon_owned[WP_SHOTGUN] => p.pending_weapon = WP_SHOTGUN,
+            AM_CELL if (p.ready_weapon == WP_FIST || p.ready_weapon == WP_PISTOL) && p.weapon_owned[WP_PLASMA] => p.pending_weapon = WP_PLASMA,
+            AM_MISL if p.ready_weapon == WP_FIST && p.weapon_owned[WP_MISSILE] => p.pending_weapon = WP_MISSILE,
+            _ => {}
+        }
+        true
+    }
+
+    //P_GiveWeapon, the dropped weapons have half the ammo
+    pub fn give_weapon(&mut self, player: usize, weapon: WeaponType, dropped: bool) -> bool {
+        //TODO the weapons stay in the net games
+        let ammo = WEAPONINFO[weapon].ammo;
+        let gave_ammo = ammo != AM_NOAMMO && Self::give_ammo(self, player, ammo, if dropped {1} else {2});
+
+        let p = &mut self.players[player];
+        if p.weapon_owned[weapon] {return gave_ammo}
+        p.weapon_owned[weapon] = true;
+        p.pending_weapon = weapon;
+        true
+    }
+
+    /*
+     * P_TouchSpecialThing, the player picks up the special thing it touched.
+     * The thing is out of reach when it is above the player or too far below.
+     */
+    pub fn touch_special_thing(&mut self, special: ActorIndex, toucher: &Actor) {
+        let (delta, type_, dropped, count_item, count_secret) = match self.actor(special) {
+            Some(thing) => (thing.pos.z - toucher.pos.z, thing.type_, thing.flags.contains(MobjFlags::Dropped), thing.flags.contains(MobjFlags::CountItem), thing.flags.contains(MobjFlags::CountSecret)),
+            None => return
+        };
+        if delta > toucher.height || delta < -8. {return}
+        //the dead can't pick up anything
+        if toucher.player < 0 || toucher.health <= 0 {return}
+        let player = toucher.player as usize;
+
+        let picked = match type_ {
+            //the ammo
+            MT_CLIP => Self::give_ammo(self, player, AM_CLIP, if dropped {0} else {1}),
+            MT_MISC17 => Self::give_ammo(self, player, AM_CLIP, 5),
+            MT_MISC18 => Self::give_ammo(self, player, AM_MISL, 1),
+            MT_MISC19 => Self::give_ammo(self, player, AM_MISL, 5),
+            MT_MISC20 => Self::give_ammo(self, player, AM_CELL, 1),
+            MT_MISC21 => Self::give_ammo(self, player, AM_CELL, 5),
+            MT_MISC22 => Self::give_ammo(self, player, AM_SHELL, 1),
+            MT_MISC23 => Self::give_ammo(self, player, AM_SHELL, 5),
+            //the backpack doubles the ammo the player can carry
+            MT_MISC24 => {
+                let p = &mut self.players[player];
+                if !p.backpack {
+                    for max_ammo in p.max_ammo.iter_mut() {
+                        *max_ammo *= 2;
+                    }
+                    p.backpack = true;
+                }
+                for ammo in 0..NUMAMMO {
+                    Self::give_ammo(self, player, ammo, 1);
+                }
+                true
+            }
+
+            //the weapons
+            MT_MISC25 => Self::give_weapon(self, player, WP_BFG, false),
+            MT_CHAINGUN => Self::give_weapon(self, player, WP_CHAINGUN, dropped),
+            MT_MISC26 => Self::give_weapon(self, player, WP_CHAINSAW, false),
+            MT_MISC27 => Self::give_weapon(self, player, WP_MISSILE, false),
+            MT_MISC28 => Self::give_weapon(self, player, WP_PLASMA, false),
+            MT_SHOTGUN => Self::give_weapon(self, player, WP_SHOTGUN, dropped),
+            MT_SUPERSHOTGUN => Self::give_weapon(self, player, WP_SUPERSHOTGUN, dropped),
+            _ => {
+                //TODO the health, the armor, the keys and the powerups
+                return
+            }
+        };
+        if !picked {return}
+
+        //TODO the messages and the sounds of the pickups
+        if count_item {
+            self.found_items += 1;
+            self.players[player].item_count += 1;
+        }
+        //P_GiveSecret, TODO the message and the sound
+        if count_secret {
+            self.found_secrets += 1;
+            self.players[player].secret_count += 1;
+        }
+        Self::destroy_thinker(self, special);
+        self.players[player].bonus_count += BONUSADD;
+    }
+}
@@ -4,8 +4,27 @@ pub struct DynamicLights {
 
 }
 
-#[derive(Default)]
+//how the light gets darker with the distance (ELightMode of gzdoom)
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
 pub enum LightMode {
     #[default]
-    NotSet = -1
+    NotSet = -1,
+    LinearStandard = 0, //the light of the sector without the distance
+    Doom = 2, //the diminishing of doom but smooth
+    DoomSoftware = 16 //the bands of the colormap like the software renderer
+}
+
+impl LightMode {
+    //the mode of a level that didn't set one
+    pub fn resolve(self) -> LightMode {
+        if self == LightMode::NotSet {LightMode::DoomSoftware} else {self}
+    }
+
+    pub fn next(self) -> LightMode {
+        match self.resolve() {
+            LightMode::LinearStandard => LightMode::Doom,
+            LightMode::Doom => LightMode::DoomSoftware,
+            _ => LightMode::LinearStandard
+        }
+    }
 }
\ No newline at end of file
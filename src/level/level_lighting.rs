@@ -0,0 +1,423 @@
+use super::LevelLocals;
+use super::level_elements::SectorIndex;
+use super::level_serializer::{Serializable, Serializer};
+use super::level_thinker::{ThinkerData, ThinkerIndex, STAT_LIGHT};
+
+//the tics of the doom strobes
+pub const STROBEBRIGHT: i32 = 5;
+pub const FASTDARK: i32 = 15;
+pub const SLOWDARK: i32 = 35;
+//the light change per tic of the doom glow
+const GLOWSPEED: i32 = 8;
+
+//DFireFlicker of zdoom
+#[derive(Default)]
+pub struct FireFlicker {
+    pub sector: SectorIndex,
+    pub count: i32,
+    pub max_light: i32,
+    pub min_light: i32
+}
+
+//DFlicker of zdoom, Light_Flicker
+#[derive(Default)]
+pub struct Flicker {
+    pub sector: SectorIndex,
+    pub count: i32,
+    pub max_light: i32,
+    pub min_light: i32
+}
+
+//DLightFlash of zdoom, the doom flickering lights
+#[derive(Default)]
+pub struct LightFlash {
+    pub sector: SectorIndex,
+    pub count: i32,
+    pub max_light: i32,
+    pub min_light: i32,
+    pub max_time: i32,
+    pub min_time: i32
+}
+
+//DStrobe of zdoom
+#[derive(Default)]
+pub struct Strobe {
+    pub sector: SectorIndex,
+    pub count: i32,
+    pub min_light: i32,
+    pub max_light: i32,
+    pub dark_time: i32,
+    pub bright_time: i32
+}
+
+//DGlow of zdoom, the doom glowing lights
+#[derive(Default)]
+pub struct Glow {
+    pub sector: SectorIndex,
+    pub min_light: i32,
+    pub max_light: i32,
+    pub direction: i32
+}
+
+//DGlow2 of zdoom, Light_Glow and Light_Fade (one_shot)
+#[derive(Default)]
+pub struct Glow2 {
+    pub sector: SectorIndex,
+    pub start: i32,
+    pub end: i32,
+    pub max_tics: i32,
+    pub tics: i32,
+    pub one_shot: bool
+}
+
+//the lighting thinkers of a sector (DLighting of zdoom)
+pub enum LightEffect {
+    FireFlicker(FireFlicker),
+    Flicker(Flicker),
+    LightFlash(LightFlash),
+    Strobe(Strobe),
+    Glow(Glow),
+    Glow2(Glow2)
+}
+
+impl LightEffect {
+    pub fn sector(&self) -> SectorIndex {
+        match self {
+            LightEffect::FireFlicker(light) => light.sector,
+            LightEffect::Flicker(light) => light.sector,
+            LightEffect::LightFlash(light) => light.sector,
+            LightEffect::Strobe(light) => light.sector,
+            LightEffect::Glow(light) => light.sector,
+            LightEffect::Glow2(light) => light.sector
+        }
+    }
+
+    pub fn class_name(&self) -> &'static str {
+        match self {
+            LightEffect::FireFlicker(_) => "FireFlicker",
+            LightEffect::Flicker(_) => "Flicker",
+            LightEffect::LightFlash(_) => "LightFlash",
+            LightEffect::Strobe(_) => "Strobe",
+            LightEffect::Glow(_) => "Glow",
+            LightEffect::Glow2(_) => "Glow2"
+        }
+    }
+
+    pub fn from_class(name: &str) -> Option<LightEffect> {
+        match name {
+            "FireFlicker" => Some(LightEffect::FireFlicker(FireFlicker::default())),
+            "Flicker" => Some(LightEffect::Flicker(Flicker::default())),
+            "LightFlash" => Some(LightEffect::LightFlash(LightFlash::default())),
+            "Strobe" => Some(LightEffect::Strobe(Strobe::default())),
+            "Glow" => Some(LightEffect::Glow(Glow::default())),
+            "Glow2" => Some(LightEffect::Glow2(Glow2::default())),
+            _ => None
+        }
+    }
+}
+
+impl Serializable for LightEffect {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        match self {
+            LightEffect::FireFlicker(light) => {
+                arc.int("sector", &mut light.sector)
+                    .int("count", &mut light.count)
+                    .int("maxlight", &mut light.max_light)
+                    .int("minlight", &mut light.min_light);
+            }
+            LightEffect::Flicker(light) => {
+                arc.int("sector", &mut light.sector)
+                    .int("count", &mut light.count)
+                    .int("maxlight", &mut light.max_light)
+                    .int("minlight", &mut light.min_light);
+            }
+            LightEffect::LightFlash(light) => {
+                arc.int("sector", &mut light.sector)
+                    .int("count", &mut light.count)
+                    .int("maxlight", &mut light.max_light)
+                    .int("minlight", &mut light.min_light)
+                    .int("maxtime", &mut light.max_time)
+                    .int("mintime", &mut light.min_time);
+            }
+            LightEffect::Strobe(light) => {
+                arc.int("sector", &mut light.sector)
+                    .int("count", &mut light.count)
+                    .int("minlight", &mut light.min_light)
+                    .int("maxlight", &mut light.max_light)
+                    .int("darktime", &mut light.dark_time)
+                    .int("brighttime", &mut light.bright_time);
+            }
+            LightEffect::Glow(light) => {
+                arc.int("sector", &mut light.sector)
+                    .int("minlight", &mut light.min_light)
+                    .int("maxlight", &mut light.max_light)
+                    .int("direction", &mut light.direction);
+            }
+            LightEffect::Glow2(light) => {
+                arc.int("sector", &mut light.sector)
+                    .int("start", &mut light.start)
+                    .int("end", &mut light.end)
+                    .int("maxtics", &mut light.max_tics)
+                    .int("tics", &mut light.tics)
+                    .boolean("oneshot", &mut light.one_shot);
+            }
+        }
+    }
+}
+
+/*
+ * The light specials (p_lights.cpp of zdoom).
+ * The instant changes are done here, the ones that change the light over time are lighting thinkers.
+ */
+impl LevelLocals {
+    //Light_RaiseByValue/Light_LowerByValue
+    pub fn ev_light_change(&mut self, tag: i32, value: i32) {
+        for sector in Self::sectors_with_tag(self, tag) {
+            let mut sec = self.sectors[sector as usize].borrow_mut();
+            let light = sec.light_level as i32 + value;
+            sec.set_light_level(light);
+        }
+    }
+
+    //Light_ChangeToValue, a bright of -1 takes the highest light of the surrounding sectors
+    pub fn ev_light_turn_on(&mut self, tag: i32, bright: i32) {
+        for sector in Self::sectors_with_tag(self, tag) {
+            let light = if bright < 0 {Self::find_max_surrounding_light(self, sector, bright)} else {bright};
+            self.sectors[sector as usize].borrow_mut().set_light_level(light);
+        }
+    }
+
+    //Light_MinNeighbor
+    pub fn ev_turn_tag_lights_off(&mut self, tag: i32) {
+        for sector in Self::sectors_with_tag(self, tag) {
+            let own = self.sectors[sector as usize].borrow().light_level as i32;
+            let light = Self::find_min_surrounding_light(self, sector, own);
+            self.sectors[sector as usize].borrow_mut().set_light_level(light);
+        }
+    }
+
+    //the boom door light effect, frac goes from 0 (closed) to 1 (open)
+    pub fn ev_light_turn_on_partway(&mut self, tag: i32, frac: f64) {
+        let frac = frac.clamp(0., 1.);
+        for sector in Self::sectors_with_tag(self, tag) {
+            let own = self.sectors[sector as usize].borrow().light_level as i32;
+            let bright = Self::find_max_surrounding_light(self, sector, 0);
+            let min = Self::find_min_surrounding_light(self, sector, own);
+            let light = (frac * bright as f64 + (1. - frac) * min as f64) as i32;
+            self.sectors[sector as usize].borrow_mut().set_light_level(light);
+        }
+    }
+
+    //Light_Fade, fades to the value in tics
+    pub fn ev_start_light_fading(&mut self, tag: i32, value: i32, tics: i32) {
+        for sector in Self::sectors_with_tag(self, tag) {
+            let (lighting_data, light) = {
+                let sec = self.sectors[sector as usize].borrow();
+                (sec.lighting_data, sec.light_level as i32)
+            };
+            if lighting_data >= 0 {continue}
+            if tics <= 0 {
+                self.sectors[sector as usize].borrow_mut().set_light_level(value);
+                continue
+            }
+            //already at the value
+            if light == value {continue}
+            let glow = Self::create_glow2(self, sector, light, value, tics, true);
+            Self::add_light_effect(self, LightEffect::Glow2(glow));
+        }
+    }
+
+    //Light_Glow
+    pub fn ev_start_light_glowing(&mut self, tag: i32, upper: i32, lower: i32, tics: i32) {
+        if tics <= 0 {return}
+        let (upper, lower) = if upper < lower {(lower, upper)} else {(upper, lower)};
+        for sector in Self::sectors_with_tag(self, tag) {
+            if self.sectors[sector as usize].borrow().lighting_data >= 0 {continue}
+            let glow = Self::create_glow2(self, sector, upper, lower, tics, false);
+            Self::add_light_effect(self, LightEffect::Glow2(glow));
+        }
+    }
+
+    //Light_Flicker
+    pub fn ev_start_light_flickering(&mut self, tag: i32, upper: i32, lower: i32) {
+        for sector in Self::sectors_with_tag(self, tag) {
+            self.sectors[sector as usize].borrow_mut().set_light_level(upper);
+            let count = (self.random.random() & 64) + 1;
+            Self::add_light_effect(self, LightEffect::Flicker(Flicker { sector, count, max_light: upper, min_light: lower }));
+        }
+    }
+
+    //Light_Strobe
+    pub fn ev_start_light_strobing(&mut self, tag: i32, upper: i32, lower: i32, utics: i32, ltics: i32) {
+        for sector in Self::sectors_with_tag(self, tag) {
+            if self.sectors[sector as usize].borrow().lighting_data >= 0 {continue}
+            //the hexen strobes are always in sync
+            let strobe = Strobe { sector, count: 1, min_light: lower.clamp(0, 255), max_light: upper.clamp(0, 255), dark_time: ltics, bright_time: utics };
+            Self::add_light_effect(self, LightEffect::Strobe(strobe));
+        }
+    }
+
+    //Light_StrobeDoom
+    pub fn ev_start_light_strobing_doom(&mut self, tag: i32, utics: i32, ltics: i32) {
+        for sector in Self::sectors_with_tag(self, tag) {
+            if self.sectors[sector as usize].borrow().lighting_data >= 0 {continue}
+            let strobe = Self::create_strobe(self, sector, utics, ltics, false);
+            Self::add_light_effect(self, LightEffect::Strobe(strobe));
+        }
+    }
+
+    //Light_Stop
+    pub fn ev_stop_light_effect(&mut self, tag: i32) {
+        for index in self.thinkers.stat_list(STAT_LIGHT) {
+            let sector = match self.thinkers.get(index) {
+                Some(ThinkerData::Light(light)) => light.sector(),
+                _ => continue
+            };
+            if self.tag_manager.sector_has_tag(sector, tag) {Self::destroy_thinker(self, index)}
+        }
+    }
+
+    pub fn ev_force_lightning(&mut self, _mode: i32) {
+        //TODO lightning
+    }
+}
+
+/*
+ * The lighting thinkers (DLighting and its subclasses of zdoom)
+ */
+impl LevelLocals {
+    //the effect becomes a thinker and the lighting_data of its sector
+    pub fn add_light_effect(&mut self, light: LightEffect) -> ThinkerIndex {
+        let sector = light.sector();
+        let index = self.thinkers.create(ThinkerData::Light(light), STAT_LIGHT);
+        self.sectors[sector as usize].borrow_mut().lighting_data = index;
+        index
+    }
+
+    pub fn release_light_sector(&mut self, light: &LightEffect, index: ThinkerIndex) {
+        let mut sector = self.sectors[light.sector() as usize].borrow_mut();
+        if sector.lighting_data == index {sector.lighting_data = -1}
+    }
+
+    fn light_level(&self, sector: SectorIndex) -> i32 {
+        self.sectors[sector as usize].borrow().light_level as i32
+    }
+
+    fn set_light(&self, sector: SectorIndex, light: i32) {
+        self.sectors[sector as usize].borrow_mut().set_light_level(light);
+    }
+
+    pub fn create_fire_flicker(&self, sector: SectorIndex) -> FireFlicker {
+        let light = Self::light_level(self, sector);
+        FireFlicker { sector, count: 4, max_light: light, min_light: Self::find_min_surrounding_light(self, sector, light) + 16 }
+    }
+
+    pub fn create_light_flash(&mut self, sector: SectorIndex) -> LightFlash {
+        let light = Self::light_level(self, sector);
+        let min_light = Self::find_min_surrounding_light(self, sector, light);
+        LightFlash { sector, count: (self.random.random() & 64) + 1, max_light: light, min_light, max_time: 64, min_time: 7 }
+    }
+
+    //the doom strobes go between the light of the sector and the darkest neighbour
+    pub fn create_strobe(&mut self, sector: SectorIndex, utics: i32, ltics: i32, in_sync: bool) -> Strobe {
+        let light = Self::light_level(self, sector);
+        let mut min_light = Self::find_min_surrounding_light(self, sector, light);
+        if min_light == light {min_light = 0}
+        let count = if in_sync {1} else {(self.random.random() & 7) + 1};
+        Strobe { sector, count, min_light, max_light: light, dark_time: ltics, bright_time: utics }
+    }
+
+    pub fn create_glow(&self, sector: SectorIndex) -> Glow {
+        let light = Self::light_level(self, sector);
+        Glow { sector, min_light: Self::find_min_surrounding_light(self, sector, light), max_light: light, direction: -1 }
+    }
+
+    pub fn create_glow2(&self, sector: SectorIndex, start: i32, end: i32, tics: i32, one_shot: bool) -> Glow2 {
+        Glow2 { sector, start: start.clamp(0, 255), end: end.clamp(0, 255), max_tics: tics, tics: -1, one_shot }
+    }
+
+    //one tic of the effect, false when it is finished
+    pub fn tick_light_effect(&mut self, light: &mut LightEffect) -> bool {
+        match light {
+            LightEffect::FireFlicker(flicker) => {
+                flicker.count -= 1;
+                if flicker.count == 0 {
+                    let amount = (self.random.random() & 3) << 4;
+                    let light = if Self::light_level(self, flicker.sector) - amount < flicker.min_light {flicker.min_light}
+                        else {flicker.max_light - amount};
+                    Self::set_light(self, flicker.sector, light);
+                    flicker.count = 4;
+                }
+            }
+            LightEffect::Flicker(flicker) => {
+                if flicker.count > 0 {flicker.count -= 1}
+                else if Self::light_level(self, flicker.sector) == flicker.max_light {
+                    Self::set_light(self, flicker.sector, flicker.min_light);
+                    flicker.count = (self.random.random() & 7) + 1;
+                }
+                else {
+                    Self::set_light(self, flicker.sector, flicker.max_light);
+                    flicker.count = (self.random.random() & 31) + 1;
+                }
+            }
+            LightEffect::LightFlash(flash) => {
+                flash.count -= 1;
+                if flash.count == 0 {
+                    if Self::light_level(self, flash.sector) == flash.max_light {
+                        Self::set_light(self, flash.sector, flash.min_light);
+                        flash.count = (self.random.random() & flash.min_time) + 1;
+                    }
+                    else {
+                        Self::set_light(self, flash.sector, flash.max_light);
+                        flash.count = (self.random.random() & flash.max_time) + 1;
+                    }
+                }
+            }
+            LightEffect::Strobe(strobe) => {
+                strobe.count -= 1;
+                if strobe.count == 0 {
+                    if Self::light_level(self, strobe.sector) == strobe.min_light {
+                        Self::set_light(self, strobe.sector, strobe.max_light);
+                        strobe.count = strobe.bright_time;
+                    }
+                    else {
+                        Self::set_light(self, strobe.sector, strobe.min_light);
+                        strobe.count = strobe.dark_time;
+                    }
+                }
+            }
+            LightEffect::Glow(glow) => {
+                let mut light = Self::light_level(self, glow.sector);
+                if glow.direction == -1 {
+                    light -= GLOWSPEED;
+                    if light <= glow.min_light {
+                        light += GLOWSPEED;
+                        glow.direction = 1;
+                    }
+                }
+                else {
+                    light += GLOWSPEED;
+                    if light >= glow.max_light {
+                        light -= GLOWSPEED;
+                        glow.direction = -1;
+                    }
+                }
+                Self::set_light(self, glow.sector, light);
+            }
+            LightEffect::Glow2(glow) => {
+                let tics = glow.tics;
+                glow.tics += 1;
+                if tics >= glow.max_tics {
+                    if glow.one_shot {
+                        Self::set_light(self, glow.sector, glow.end);
+                        return false
+                    }
+                    std::mem::swap(&mut glow.start, &mut glow.end);
+                    glow.tics -= glow.max_tics - 1;
+                }
+                Self::set_light(self, glow.sector, (glow.end - glow.start) * glow.tics / glow.max_tics + glow.start);
+            }
+        }
+        true
+    }
+}
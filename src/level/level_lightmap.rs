@@ -98,35 +98,44 @@ impl PalEntry {
         PalEntry::Argb(Argb{ a: 0, r, g, b })
     }
 
+    //the color as 0xAARRGGBB
     pub fn d(&self) -> u32 {
         match self {
-            PalEntry::D(t) => {return t.to_owned()}
-            PalEntry::Argb(t) => {return u32::from_le_bytes([t.a, t.r, t.g, t.b])}
+            PalEntry::D(t) => *t,
+            PalEntry::Argb(t) => u32::from_be_bytes([t.a, t.r, t.g, t.b])
         }
     }
 
     pub fn argb(&self) -> Argb {
         match self {
             PalEntry::D(t) => {
-                let argb = t.to_le_bytes();
-                let a = argb[0];
-                let r = argb[1];
-                let g = argb[2];
-                let b = argb[3];
+                let [a, r, g, b] = t.to_be_bytes();
                 return Argb{a, r, g, b}
             }
             PalEntry::Argb(t) => {return t.to_owned()}
         }
     }
 
+    pub fn r(&self) -> u8 {
+        self.argb().r
+    }
+
+    pub fn g(&self) -> u8 {
+        self.argb().g
+    }
+
+    pub fn b(&self) -> u8 {
+        self.argb().b
+    }
+
     pub fn set_rgb(&mut self, other: u32) {
         match self {
             PalEntry::D(t) => {
                 *t = other & 0xffffff;
             }
             PalEntry::Argb(t) => {
-                let argb = other.to_le_bytes();
-                *t = Argb{a: argb[0] & 0xff, r: argb[1] & 0xff, g: argb[2] & 0xff, b: argb[3] & 0xff};
+                let [_, r, g, b] = other.to_be_bytes();
+                *t = Argb{a: 0, r, g, b};
             }
         }
     }
@@ -3,13 +3,16 @@ use std::cell::RefCell;
 use std::time::Instant;
 
 use crate::parser::parse_level::WADLevelLinedef;
-use crate::game::{Game, GameType};
+use crate::game::{GameInfo, GameType};
 use crate::parser::parse_level::WADLevel;
 use crate::vector::{Vector3, Vector2, Angle};
 
-use super::level_actor::{ClassActor, BlockNode};
+use super::level_actor::ClassActor;
+use super::level_info::find_doomednum;
+use super::level_behavior::AcsThinker;
+use super::level_thinker::{ThinkerData, STAT_SCRIPTS};
 use super::level_mesh::LevelMesh;
-use super::{LevelLocals, ActionSpecials, SpecialMapThings, MapThingFlags};
+use super::{LevelLocals, ActionSpecials, SpecialMapThings, MapThingFlags, hexen_activation};
 use super::level_elements::{Vertex, Sector, ExtSector, SectorFlags, SectorE, Line, SideDefIndex, LineFlags, Side, SectorIndex, Sides, SubSector, Node, ChildNode, Seg};
 use super::level_lightmap::{PalEntry, SurfaceType};
 use super::level_texture::{MissingTextureTracker, TextureID, TextureManager, MapSideDef, TextureType, TexManFlags, FakeColorMap};
@@ -35,6 +38,35 @@ impl MapLoader<'_, '_> {
         MapLoader { level, tex_manager, force_node_build: false, side_count: 0, line_map: vec![], side_temp: vec![], file_system: None, fake_color_maps: vec![], map_things_converted: vec![] }
     }
 
+    /*
+     * The sky flat and the sky textures of the level, these are the defaults of the MAPINFO of doom and heretic from the name of the map.
+     * TODO the sky, its speed and the double sky of the MAPINFO of the level
+     */
+    fn init_sky(&mut self) {
+        let flags = TexManFlags::Overridable.bits() | TexManFlags::TryAny.bits();
+        self.level.sky_flat_num = self.tex_manager.check_for_texture(&"F_SKY1".to_string(), TextureType::Flat, flags);
+
+        let name = self.level.map_name.to_uppercase();
+        let sky = match (name.strip_prefix("MAP").and_then(|num| num.parse::<i32>().ok()), name.as_bytes()) {
+            (Some(num), _) if num < 12 => "SKY1",
+            (Some(num), _) if num < 21 => "SKY2",
+            (Some(_), _) => "SKY3",
+            (None, [b'E', episode, b'M', ..]) => match episode {
+                b'2' => "SKY2",
+                b'3' | b'5' => "SKY3",
+                b'4' if self.tex_manager.check_for_texture(&"SKY4".to_string(), TextureType::Wall, flags).exists() => "SKY4",
+                _ => "SKY1"
+            },
+            _ => "SKY1"
+        };
+        self.level.sky_texture1 = self.tex_manager.check_for_texture(&sky.to_string(), TextureType::Wall, flags);
+        self.level.sky_texture2 = self.level.sky_texture1;
+        self.level.sky_speed1 = 0.;
+        self.level.sky_speed2 = 0.;
+        self.level.sky_pos1 = 0.;
+        self.level.sky_pos2 = 0.;
+    }
+
     /* 
      * for behavior: first loadbehavior, then load default modules , then load the ACS lump
      * if strife also load the dialogues
@@ -44,7 +76,7 @@ impl MapLoader<'_, '_> {
     /* This function will load a single level (i.e. e1m1) and for normal doom map may 
      * need a translator to make it a udmf like map?
      * also needs to load the scripts */
-    pub fn load_level(&mut self, map: &mut WADLevel, game: &Game) {
+    pub fn load_level(&mut self, map: &mut WADLevel, game_info: &GameInfo) {
         /*TODO 
          * loadbehavior()
          * T_LoadScripts();
@@ -54,11 +86,24 @@ impl MapLoader<'_, '_> {
         */
 
         let mut missing_textures: MissingTextureTracker = MissingTextureTracker::new();
+        self.level.tag_manager.clear();
+        self.level.thinkers.destroy_all();
+        self.level.random.clear();
+        self.level.map_name = map.name.clone();
+        self.level.skill = game_info.skill;
+        self.level.net_game = game_info.multiplayer;
+        //TODO these come from the MAPINFO of the level, these are its defaults doubled like gzdoom does
+        self.level.wall_vert_light = -16;
+        self.level.wall_hori_light = 16;
+        self.init_sky();
+        self.level.acs_thinker = self.level.create_thinker(ThinkerData::Scripts(AcsThinker::default()), STAT_SCRIPTS);
 
         if !map.is_text {
             self.load_vertexes(map);
             println!("going to load sectors");
             self.load_sectors(map, &mut missing_textures);
+            //the sidedefs already need the sector tags
+            self.level.tag_manager.hash_tags();
             println!("finished loading sectors");
             println!("going to load lines");
             if !map.has_behavior {self.load_linedefs(map)}
@@ -70,13 +115,17 @@ impl MapLoader<'_, '_> {
             self.finish_loading_linedefs();
             println!("finished doing finish_loading_linedefs");
             println!("going to load things");
-            if !map.has_behavior {self.load_things(map, game)}
-            else {self.load_things2(map, game)}
+            if !map.has_behavior {
+                self.level.flags2 |= LevelFlags::Level2LaxMonsterActivation.bits();
+                self.load_things(map, game_info)
+            }
+            else {self.load_things2(map, game_info)}
             println!("finished loading things");
         }
         else {
             self.parse_textmap(map, &missing_textures);
         }
+        self.level.tag_manager.hash_tags();
         self.calc_indices();
         println!("finished calculating indices");
         //TODO PostProcessLevel();
@@ -161,8 +210,7 @@ impl MapLoader<'_, '_> {
 
         self.load_blockmap(map);
         self.load_reject(map);
-        //TODO loadreject();
-        //TODO grouplines();
+        self.group_lines();
         //TODO floodzones();
         //TODO setrendersector();
         //TODO fixMinisegReferences();
@@ -178,7 +226,7 @@ impl MapLoader<'_, '_> {
         //TODO SpawnSlopeMakers();
         //TODO CopySlopes();
         //TODO Spawn3DFloors();
-        //TODO SpawnThings();
+        self.spawn_things(game_info);
 
         if !self.force_node_build {
             self.load_lightmap(map);
@@ -192,7 +240,7 @@ impl MapLoader<'_, '_> {
 
         //TODO maybe delete oldvertextable?
 
-        //TODO SpawnSpecials();
+        self.level.spawn_specials();
 
         //TODO loop through sectors to disable reflective planes on slopes
         //TODO loop through nodes and set the node.len
@@ -234,8 +282,10 @@ impl MapLoader<'_, '_> {
             eprintln!("Map has no vertexes");
         }
         println!("going to load: {} vertexes", map.vertexes.len());
-        for v in &map.vertexes {
-            self.level.vertexes.push(Rc::new(RefCell::new(Vertex::new(v.x, v.y))));
+        for (i, v) in map.vertexes.iter().enumerate() {
+            let mut vertex = Vertex::new(v.x, v.y);
+            vertex.vertex_num = i as i32;
+            self.level.vertexes.push(Rc::new(RefCell::new(vertex)));
         }
         println!("loaded vertexes: {}", self.level.vertexes.len());
     }
@@ -266,8 +316,9 @@ impl MapLoader<'_, '_> {
     
             if map.has_behavior { sector.special = i32::from(ms.special); }
             else { sector.special = self.level.translate_sector_special(ms.special);}
+            self.level.init_sector_special_bits(&mut sector);
     
-            self.level.tag_manager.add_sector_tag(i, ms.tag);
+            self.level.tag_manager.add_sector_tag(i, ms.tag as i32);
             sector.sec_type = def_sec_type;
             sector.next_sec = -1;
             sector.prev_sec = -1;
@@ -313,7 +364,7 @@ impl MapLoader<'_, '_> {
             return false
         }
 
-        self.level.subsectors.resize(subsector_amount, Rc::new(RefCell::new(SubSector::new())));
+        self.level.subsectors.resize_with(subsector_amount, || Rc::new(RefCell::new(SubSector::new())));
         for i in 0..subsector_amount {
             if map.ssectors[i].num_segs == 0 {
                 println!("Subsector {} is empty.", i);
@@ -354,7 +405,7 @@ impl MapLoader<'_, '_> {
             return false
         }
 
-        self.level.nodes.resize(node_amount, Rc::new(RefCell::new(Node::new())));
+        self.level.nodes.resize_with(node_amount, || Rc::new(RefCell::new(Node::new())));
         let mut used = vec![0;node_amount];
         let mut ret = true;
 
@@ -419,7 +470,7 @@ impl MapLoader<'_, '_> {
             return false
         }
 
-        self.level.segs.resize(seg_amount, Rc::new(RefCell::new(Seg::new())));
+        self.level.segs.resize_with(seg_amount, || Rc::new(RefCell::new(Seg::new())));
 
         // for i in 0..self.level.subsectors.len() {
         //     self.level.subsectors[i].borrow_mut().first_line = 
@@ -570,10 +621,10 @@ impl MapLoader<'_, '_> {
             line.portal_transfered = u32::max_value();
             //TODO check if the translate of map linedef special,tag and flags is needed?
 
-            self.level.translate_linedef(&line, linedef, -1);
+            self.level.translate_linedef(&mut line, linedef, -1);
             if line.special != 190 /*Static_INIT ? */ && line.args[1] != 254 /*InitEdLine */ && line.args[1] != 253 /*InitEdSector */{
                 let temp = linedef.clone();
-                self.level.tag_manager.add_line_id(i, temp.doom.unwrap().tag);
+                self.level.tag_manager.add_line_id(i, temp.doom.unwrap().tag as i32);
                 // println!("add line id");
                 //TODO
             }
@@ -619,9 +670,125 @@ impl MapLoader<'_, '_> {
     }
 
     //This is for hexen map formats
-    fn load_linedefs2(&mut self, _map: &WADLevel) {
-    
-        //TODO make this
+    fn load_linedefs2(&mut self, map: &mut WADLevel) {
+
+        let mut line_amount = map.linedefs.len();
+        let side_amount = map.sidedefs.len();
+        self.line_map.resize(line_amount, 0);
+        let mut skipped = 0;
+        let mut i = 0;
+        self.side_count = 0;
+
+        while i < line_amount {
+            let linedef = &map.linedefs[i];
+            let v1 = linedef.from;
+            let v2 = linedef.to;
+
+            if v1 as usize >= self.level.elements.vertexes.len() || v2  as usize >= self.level.elements.vertexes.len() {
+                eprintln!("Line {} has invalid vertices: {} and/or {}.\nThe map only contains {} vertices.",
+                i + skipped, v1, v2, self.level.elements.vertexes.len());
+                map.linedefs.remove(i);
+                self.force_node_build = true;
+                skipped += 1;
+                line_amount -= 1;
+                continue
+            }
+            else if v1 == v2 ||
+            (self.level.elements.vertexes[v1 as usize].borrow().fx() == self.level.elements.vertexes[v2 as usize].borrow().fx()
+            && self.level.elements.vertexes[v1 as usize].borrow().fy() == self.level.elements.vertexes[v2 as usize].borrow().fy()
+            ) {
+                println!("removing 0-length line {}", i + skipped);
+                map.linedefs.remove(i);
+                self.force_node_build = true;
+                skipped += 1;
+                line_amount -= 1;
+                continue
+            }
+            self.side_count += 1;
+            if linedef.back_sidedef != 0xffff /*NO_INDEX */ {self.side_count += 1;}
+            self.line_map[i] = (i + skipped) as i32;
+            i +=1;
+        }
+
+        self.level.lines.reserve(line_amount);
+        Self::allocate_sidedefs(self, map, self.side_count as usize);
+        for i in 0..line_amount {
+            let linedef = &mut map.linedefs[i];
+            let mut line = Line::new();
+
+            line.alpha = 1.;
+            line.portal_index = u32::MAX;
+            line.portal_transfered = u32::MAX;
+
+            if let Some(hex) = &linedef.hex {
+                line.special = hex.type_ as i32;
+                line.args = [hex.arg1 as i32, hex.arg2 as i32, hex.arg3 as i32, hex.arg4 as i32, hex.arg5 as i32];
+            }
+            line.flags = linedef.flags as u32;
+            line.activation = hexen_activation(line.flags);
+            line.flags &= !LineFlags::SpacMask.bits();
+            Self::set_line_id(self, i, &mut line);
+
+            if linedef.front_sidedef != 0xffff /*NO_INDEX */ && (linedef.front_sidedef as usize) >= side_amount {
+                linedef.front_sidedef = 0; //dummy sidedef
+                println!("Linedef {} has a bad sidedef", i);
+            }
+            if linedef.back_sidedef != 0xffff /*NO_INDEX */ && (linedef.back_sidedef as usize) >= side_amount {
+                linedef.back_sidedef = 0; //dummy sidedef
+                println!("Linedef {} has a bad sidedef", i);
+            }
+            if linedef.front_sidedef == 0xffff /*NO_INDEX */ {
+                linedef.front_sidedef = 0;
+                println!("Linedef {} has no front sidedef", i);
+            }
+
+            let vertexes = &self.level.vertexes;
+            line.v1 = vertexes[linedef.from as usize].borrow().clone();
+            line.v2 = vertexes[linedef.to as usize].borrow().clone();
+
+            Self::set_side_num(self, &mut line.sidedef[0], linedef.front_sidedef);
+            Self::set_side_num(self, &mut line.sidedef[1], linedef.back_sidedef);
+
+            line.adjust_line();
+            Self::save_line_special(self, &line);
+
+            if self.level.flags2 & LevelFlags::Level2ClipMidTex.bits() != 0 {
+                line.flags |= LineFlags::ClipMidTex.bits();
+            }
+            if self.level.flags2 & LevelFlags::Level2WrapMidTex.bits() != 0 {
+                line.flags |= LineFlags::WrapMidTex.bits();
+            }
+            if self.level.flags2 & LevelFlags::Level2CheckSwitchRange.bits() != 0 {
+                line.flags |= LineFlags::CheckSwitchRange.bits();
+            }
+            self.level.lines.push(Rc::new(RefCell::new(line)));
+        }
+    }
+
+    //hexen maps have no tag on the lines so the id is taken from the args of some specials
+    fn set_line_id(&mut self, index: usize, line: &mut Line) {
+        let special = num::FromPrimitive::from_i32(line.special);
+        let id = match special {
+            Some(ActionSpecials::LineSetIdentification) => {
+                line.flags |= (line.args[1] as u32) << 16; //the extended line flags
+                line.special = 0;
+                line.args[0] + 256 * line.args[4]
+            }
+            Some(ActionSpecials::TranslucentLine) => {
+                line.flags |= (line.args[3] as u32) << 16;
+                line.args[0]
+            }
+            Some(ActionSpecials::TeleportLine) | Some(ActionSpecials::ScrollTextureModel) => line.args[0],
+            Some(ActionSpecials::PolyobjStartLine) => line.args[3],
+            Some(ActionSpecials::PolyobjExplicitLine) => line.args[4],
+            Some(ActionSpecials::PlaneAlign) => line.args[2],
+            Some(ActionSpecials::StaticInit) if line.args[1] == 254 /*Init_SectorLink */ => line.args[0],
+            Some(ActionSpecials::LineSetPortal) => line.args[1],
+            _ => -1
+        };
+        if id != -1 {
+            self.level.tag_manager.add_line_id(index, id);
+        }
     }
     
     fn load_sidedefs(&mut self, map: &WADLevel, missing_textures: &MissingTextureTracker) {
@@ -871,7 +1038,7 @@ impl MapLoader<'_, '_> {
         res
     }
     
-    fn load_things(&mut self, map: &WADLevel, game: &Game) {
+    fn load_things(&mut self, map: &WADLevel, game_info: &GameInfo) {
         let thing_count = map.things.len();
 
         self.map_things_converted.resize_with(thing_count, || MapThing::default());
@@ -892,7 +1059,7 @@ impl MapLoader<'_, '_> {
             mapthing.pos.y = map.things[i].y as f64;
             mapthing.angle = map.things[i].angle;
             mapthing.ed_num = map.things[i].type_;
-            //TODO mapthing.info
+            mapthing.info = Self::doom_ed_entry(mapthing.ed_num, game_info);
 
             
             //TODO is in an ifnded NO_EDATA
@@ -902,7 +1069,7 @@ impl MapLoader<'_, '_> {
             else {
                 flags &= !MapThingFlags::SkillMask.bits() as i16;
                 mapthing.flags = (((flags & 0xf) | 0x7e0) as i16) as u32;
-                match game.game_info.game_type {
+                match game_info.game_type {
                     GameType::Strife => {
                         mapthing.flags &= !MapThingFlags::Ambush.bits();
                         if flags as u32 & MapThingFlags::SShadow.bits() != 0 {mapthing.flags |= MapThingFlags::Shadow.bits()}
@@ -913,12 +1080,12 @@ impl MapLoader<'_, '_> {
                     }
                     _ => {
                         if flags as u32 & MapThingFlags::BBadEditorCheck.bits() != 0 {flags &= 0x1f}
-                        if flags as u32 & MapThingFlags::BNotDeathMatch.bits() != 0 {mapthing.flags |= MapThingFlags::DeathMatch.bits()}
-                        if flags as u32 & MapThingFlags::BNotCooperative.bits() != 0 {mapthing.flags |= MapThingFlags::Cooperative.bits()}
+                        if flags as u32 & MapThingFlags::BNotDeathMatch.bits() != 0 {mapthing.flags &= !MapThingFlags::DeathMatch.bits()}
+                        if flags as u32 & MapThingFlags::BNotCooperative.bits() != 0 {mapthing.flags &= !MapThingFlags::Cooperative.bits()}
                         if flags as u32 & MapThingFlags::BFriendly.bits() != 0 {mapthing.flags |= MapThingFlags::Friendly.bits()}
                     }
                 }
-                if flags as u32 & MapThingFlags::BNotSingle.bits() != 0 {mapthing.flags |= MapThingFlags::Single.bits()}
+                if flags as u32 & MapThingFlags::BNotSingle.bits() != 0 {mapthing.flags &= !MapThingFlags::Single.bits()}
             }
         }
     }
@@ -949,13 +1116,96 @@ impl MapLoader<'_, '_> {
         }
     }
 
+    //the class or the special of the editor number (DoomEdMap of zdoom), None for the unknown numbers
+    fn doom_ed_entry(ed_num: i16, game_info: &GameInfo) -> Option<Rc<DoomEternityEntry>> {
+        let hexen = matches!(game_info.game_type, GameType::Hexen);
+        let special = match ed_num {
+            1 => Some(SpecialMapThings::Player1Start),
+            2 => Some(SpecialMapThings::Player2Start),
+            3 => Some(SpecialMapThings::Player3Start),
+            4 => Some(SpecialMapThings::Player4Start),
+            4001 => Some(SpecialMapThings::Player5Start),
+            4002 => Some(SpecialMapThings::Player6Start),
+            4003 => Some(SpecialMapThings::Player7Start),
+            4004 => Some(SpecialMapThings::Player8Start),
+            11 => Some(SpecialMapThings::DeathmatchStart),
+            1400..=1411 => Some(SpecialMapThings::SSeqOverride),
+            3000 if hexen => Some(SpecialMapThings::PolyAnchor),
+            3001 if hexen => Some(SpecialMapThings::PolySpawn),
+            3002 if hexen => Some(SpecialMapThings::PolySpawnCrush),
+            9300 => Some(SpecialMapThings::PolyAnchor),
+            9301 => Some(SpecialMapThings::PolySpawn),
+            9302 => Some(SpecialMapThings::PolySpawnCrush),
+            9303 => Some(SpecialMapThings::PolySpawnHurt),
+            9500 => Some(SpecialMapThings::SlopeFloorPointLine),
+            9501 => Some(SpecialMapThings::SlopeCeilingPointLine),
+            9502 => Some(SpecialMapThings::SetFloorSlope),
+            9503 => Some(SpecialMapThings::SetCeilingSlope),
+            1500 => Some(SpecialMapThings::VavoomFloor),
+            1501 => Some(SpecialMapThings::VavoomCeiling),
+            9510 => Some(SpecialMapThings::CopyFloorPlane),
+            9511 => Some(SpecialMapThings::CopyCeilingPlane),
+            1504 => Some(SpecialMapThings::VertexFloorZ),
+            1505 => Some(SpecialMapThings::VertexCeilingZ),
+            _ => None
+        };
+        if let Some(special) = special {
+            return Some(Rc::new(DoomEternityEntry { special: special.into(), ..Default::default() }))
+        }
+
+        let type_ = match game_info.game_type {
+            GameType::Doom | GameType::Chex | GameType::DoomChex | GameType::DoomStrifeChex | GameType::Any => find_doomednum(ed_num as i32)?,
+            _ => {
+                //TODO the actor definitions of heretic, hexen and strife
+                return None
+            }
+        };
+        Some(Rc::new(DoomEternityEntry { type_: Rc::new(ClassActor { type_ }), ..Default::default() }))
+    }
+
+    //SpawnThings, the map things become actors and player starts
+    fn spawn_things(&mut self, game_info: &GameInfo) {
+        for thing in &self.map_things_converted {
+            self.level.spawn_map_thing(thing, game_info);
+        }
+    }
+
     fn process_eternity_map_thing(&self) {
         //TODO
     }
     
     //This is for hexen map formats
-    fn load_things2(&mut self, _map: &WADLevel, _game: &Game) {
-        //TODO
+    fn load_things2(&mut self, map: &WADLevel, game_info: &GameInfo) {
+        let thing_count = map.things.len();
+
+        self.map_things_converted.resize_with(thing_count, MapThing::default);
+        for i in 0..thing_count {
+            let mapthing = &mut self.map_things_converted[i];
+            let thing = &map.things[i];
+            let flags = thing.options as u16 as u32;
+
+            if let Some(hex) = &thing.hex {
+                mapthing.thing_id = hex.thing_id as i32;
+                mapthing.pos.z = hex.z as f64;
+                mapthing.special = hex.action_special as i32;
+                mapthing.args = [hex.arg1 as i32, hex.arg2 as i32, hex.arg3 as i32, hex.arg4 as i32, hex.arg5 as i32];
+            }
+            mapthing.pos.x = thing.x as f64;
+            mapthing.pos.y = thing.y as f64;
+            mapthing.angle = thing.angle;
+            mapthing.ed_num = thing.type_;
+            mapthing.info = Self::doom_ed_entry(mapthing.ed_num, game_info);
+
+            mapthing.skill_filter = Self::make_skill(flags as i32);
+            mapthing.class_filter = ((flags & MapThingFlags::ClassMask.bits()) >> MapThingFlags::ClassShift.bits()) as u16;
+            mapthing.flags = flags & !(MapThingFlags::SkillMask.bits() | MapThingFlags::ClassMask.bits());
+
+            mapthing.gravity = 1.;
+            mapthing.render_style = 19; //TODO StyleCount
+            mapthing.alpha = -1.;
+            mapthing.health = 1.;
+            mapthing.float_bob_phase = -1;
+        }
     }
 
     fn set_side_num(&mut self, sidedef: &mut SideDefIndex, side_num: u16) {
@@ -994,7 +1244,7 @@ impl MapLoader<'_, '_> {
     }
 
     fn allocate_sidedefs(&mut self, map: &WADLevel, count: usize) {
-        self.level.sides.resize(count, Rc::new(RefCell::new(Side::new())));
+        self.level.sides.resize_with(count, || Rc::new(RefCell::new(Side::new())));
         self.side_temp.resize_with(count.max(self.level.vertexes.len()), || SideInit::new());
 
         for i in 0..count {
@@ -1051,7 +1301,7 @@ impl MapLoader<'_, '_> {
             if !texture.exists() {
                 let mut short = name.clone();
                 short.truncate(8);
-                *blend = u32::from_str_radix(&short.to_string(), 16).unwrap();
+                *blend = u32::from_str_radix(&short.to_string(), 16).unwrap_or(0);
 
                 texture = TextureID::new();
             }
@@ -1092,7 +1342,7 @@ impl MapLoader<'_, '_> {
 
 
             if !name.starts_with("#") {
-                *color = u32::from_str_radix(&name.to_string(), 16).unwrap();
+                *color = u32::from_str_radix(&name.to_string(), 16).unwrap_or(0);
                 // texture = TextureID::new(); //TODO seems unnecesary?
                 // *valid_color = *stop == 0 && 
                 //TODO weird stuff here?
@@ -1171,7 +1421,7 @@ impl MapLoader<'_, '_> {
 
                         if color_good | fog_good {
                             for i in 0..self.level.sectors.len() {
-                                if self.level.sector_has_tag(i, t.tag) {
+                                if self.level.sector_has_tag(i, t.tag as i32) {
                                     if color_good {
                                         self.level.sectors[i].borrow_mut().color_map.light_color.set_rgb(color);
                                         self.level.sectors[i].borrow_mut().color_map.blend_factor = (color >> 24 & 0xff) as u8;
@@ -1223,7 +1473,7 @@ impl MapLoader<'_, '_> {
                         Self::set_texture_side(self,side, Sides::Top.bits() as usize, &imsd.top_texture, missing_textures);
                         Self::set_texture_side(self,side, Sides::Bottom.bits() as usize, &imsd.bottom_texture, missing_textures);
                     }
-                    None => {
+                    _ => {
                         Self::set_texture_side(self,side, Sides::Mid.bits() as usize, &imsd.middle_texture, missing_textures);
                         Self::set_texture_side(self,side, Sides::Top.bits() as usize, &imsd.top_texture, missing_textures);
                         Self::set_texture_side(self,side, Sides::Bottom.bits() as usize, &imsd.bottom_texture, missing_textures);
@@ -1264,7 +1514,7 @@ impl MapLoader<'_, '_> {
     }
 
     fn parse_textmap(&mut self, _map: &WADLevel, _missing_textures: &MissingTextureTracker) {
-        //TODO
+        //TODO the 'id' and 'moreids' (TagManager::parse_more_ids) keys of the sectors and lines go to the tag manager
     }
 
     fn load_blockmap(&mut self, map: &WADLevel) {
@@ -1286,16 +1536,77 @@ impl MapLoader<'_, '_> {
         self.level.block_map.blockmap_height = map.blockmap.height as i32;
 
         count = (self.level.block_map.blockmap_height * self.level.block_map.blockmap_width) as usize;
-        self.level.block_map.block_links = vec![BlockNode::new(); count];
+        self.level.block_map.block_links = vec![-1; count];
         self.level.block_map.blockmap = map.blockmap.blockmap_lump.clone();
     }
 
+    /* Sets the sector of the subsectors and builds the line and vertex lists of the sectors */
+    fn group_lines(&mut self) {
+        for i in 0..self.level.subsectors.len() {
+            let mut subsector = self.level.subsectors[i].borrow_mut();
+            if subsector.first_line < 0 || subsector.first_line as usize >= self.level.segs.len() {continue}
+            let sidedef = self.level.segs[subsector.first_line as usize].borrow().sidedef;
+            if sidedef < 0 {
+                println!("Subsector {} is degenerate", i);
+                continue
+            }
+            subsector.sector = self.level.sides[sidedef as usize].borrow().sector;
+            subsector.render_sector = subsector.sector;
+            if subsector.sector >= 0 {
+                self.level.sectors[subsector.sector as usize].borrow_mut().subsectors.push(i as i32);
+            }
+        }
+
+        let elements = &mut self.level.elements;
+        for i in 0..elements.lines.len() {
+            let line = elements.lines[i].borrow();
+            for sector in [line.front_sector, line.back_sector] {
+                if sector < 0 {continue}
+                let mut sec = elements.sectors[sector as usize].borrow_mut();
+                if !sec.lines.contains(&(i as i32)) {sec.lines.push(i as i32)}
+                let vertices = &mut elements.extsectors[sec.e as usize].vertices;
+                for vertex in [line.v1.vertex_num, line.v2.vertex_num] {
+                    if !vertices.contains(&vertex) {vertices.push(vertex)}
+                }
+            }
+        }
+
+        for i in 0..self.level.sectors.len() {
+            let mut sector = self.level.sectors[i].borrow_mut();
+            sector.subsector_count = sector.subsectors.len() as i32;
+            let e = sector.e as usize;
+            let vertices = &self.level.elements.extsectors[e].vertices;
+            if vertices.is_empty() {continue}
+            let mut bbox = [f64::MIN, f64::MAX, f64::MAX, f64::MIN]; //top, bottom, left, right
+            for v in vertices {
+                let pos = self.level.vertexes[*v as usize].borrow().f_pos();
+                bbox[0] = bbox[0].max(pos.y);
+                bbox[1] = bbox[1].min(pos.y);
+                bbox[2] = bbox[2].min(pos.x);
+                bbox[3] = bbox[3].max(pos.x);
+            }
+            sector.center_spot = Vector2::<f64> { x: (bbox[2] + bbox[3]) / 2., y: (bbox[0] + bbox[1]) / 2. };
+        }
+    }
+
     fn create_block_map(&mut self) {
         //TODO
     }
 
-    fn load_reject(&mut self, _map: &WADLevel) {
-        //TODO
+    //the reject table packed in bits again, it is left empty when it rejects nothing
+    fn load_reject(&mut self, map: &WADLevel) {
+        let sectors = self.level.sectors.len();
+        self.level.reject_matrix.clear();
+        if map.reject.len() != sectors || !map.reject.iter().flatten().any(|reject| *reject) {return}
+
+        let mut matrix = vec![0u8; (sectors * sectors).div_ceil(8)];
+        for (s1, row) in map.reject.iter().enumerate() {
+            for (s2, reject) in row.iter().enumerate() {
+                let bit = s1 * sectors + s2;
+                if *reject {matrix[bit / 8] |= 1 << (bit % 8)}
+            }
+        }
+        self.level.reject_matrix = matrix;
     }
 
 }
@@ -1378,15 +1689,15 @@ pub struct SideInitB {
 
 #[derive(Default)]
 pub struct MapThing {
-    _thing_id: i32,
-    pos: Vector3<f64>,
-    angle: i16,
-    skill_filter: u16,
-    class_filter: u16,
-    ed_num: i16,
-    flags: u32,
-    _special: i32,
-    _args: [i32;5],
+    pub thing_id: i32,
+    pub pos: Vector3<f64>,
+    pub angle: i16,
+    pub skill_filter: u16,
+    pub class_filter: u16,
+    pub ed_num: i16,
+    pub flags: u32,
+    pub special: i32,
+    pub args: [i32;5],
     conversation: i32,
     gravity: f64,
     alpha: f64,
@@ -1405,8 +1716,8 @@ pub struct MapThing {
 
 #[derive(Default)]
 pub struct DoomEternityEntry {
-    _type_: Rc<ClassActor>,
-    special: i16,
+    pub type_: Rc<ClassActor>,
+    pub special: i16,
     _args_defined: i8,
     _no_skill_flags: bool,
     _args: [i32;5]
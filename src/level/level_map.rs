@@ -0,0 +1,750 @@
+use std::collections::HashSet;
+
+use crate::vector::{Angle, Vector2, Vector3};
+
+use super::LevelLocals;
+use super::level_actor::{Actor, ActorIndex};
+use super::level_elements::{LineFlags, LineIndex, SectorIndex, BOXTOP, BOXBOTTOM, BOXLEFT, BOXRIGHT};
+use super::level_info::*;
+use super::level_maputl::{approx_distance, InterceptTarget, PathFlags};
+use super::level_sight::SightFlags;
+use super::level_specials::{Activator, ActivatorType, SpecialActivation};
+
+pub const MAXMOVE: f64 = 30.;
+pub const MAXRADIUS: f64 = 32.; //the things are in the blocks of their center, the biggest radius is checked around them
+pub const STOPSPEED: f64 = 0x1000 as f64 / 65536.;
+pub const FRICTION: f64 = 0xe800 as f64 / 65536.;
+pub const GRAVITY: f64 = 1.;
+pub const MAXSTEPHEIGHT: f64 = 24.;
+pub const VIEWHEIGHT: f64 = 41.;
+pub const MELEERANGE: f64 = 64.;
+pub const MISSILERANGE: f64 = 32. * 64.;
+pub const USERANGE: f64 = 64.;
+//the slopes the autoaim looks between, the height of the screen over its width
+pub const AIM_TOP_SLOPE: f64 = 100. / 160.;
+//the spread of the guns, (P_Random() - P_Random()) << 18 of doom in degrees
+pub const SPREAD_DEGREES: f64 = 360. / 16384.;
+pub const FLOATSPEED: f64 = 4.;
+
+//what check_position found at the position, the tm* variables of doom
+#[derive(Default)]
+pub struct CheckPosition {
+    pub sector: SectorIndex,
+    pub floor_z: f64,
+    pub ceiling_z: f64,
+    pub dropoff_z: f64,
+    pub ceiling_line: LineIndex,
+    pub blocking_line: LineIndex,
+    pub blocking_actor: ActorIndex,
+    pub hit_actor: ActorIndex, //the thing the missile or the flying lost soul ran into, it gets the damage
+    pub float_ok: bool, //the actor fits in the height, a floating monster can go up or down to get there
+    pub spec_hit: Vec<LineIndex>, //the special lines the actor touches, they can be crossed
+    pub touched: Vec<ActorIndex> //the special things the actor can pick up
+}
+
+//the space between the sectors of a two sided line (P_LineOpening)
+pub struct LineOpening {
+    pub top: f64,
+    pub bottom: f64,
+    pub range: f64,
+    pub low_floor: f64
+}
+
+/*
+ * Moving the actors and checking if they fit (p_map.cpp)
+ */
+impl LevelLocals {
+    pub fn line_opening(&self, line: LineIndex, pos: &Vector2<f64>) -> Option<LineOpening> {
+        let line = self.lines[line as usize].borrow();
+        if line.front_sector < 0 || line.back_sector < 0 {return None}
+        let front = self.sectors[line.front_sector as usize].borrow();
+        let back = self.sectors[line.back_sector as usize].borrow();
+
+        let (front_floor, back_floor) = (front.floorplane.z_at(pos), back.floorplane.z_at(pos));
+        let top = front.ceilingplane.z_at(pos).min(back.ceilingplane.z_at(pos));
+        let (bottom, low_floor) = if front_floor > back_floor {(front_floor, back_floor)} else {(back_floor, front_floor)};
+        Some(LineOpening { top, bottom, range: top - bottom, low_floor })
+    }
+
+    /*
+     * P_CheckPosition, checks if the actor can be at the position without moving it.
+     * The heights of the position are in the result, also when something is in the way.
+     */
+    pub fn check_position(&self, index: ActorIndex, actor: &Actor, x: f64, y: f64) -> (bool, CheckPosition) {
+        let bbox = [y + actor.radius, y - actor.radius, x - actor.radius, x + actor.radius];
+        let mut tm = CheckPosition { sector: self.point_in_sector(x, y), ceiling_line: -1, blocking_line: -1, blocking_actor: -1, hit_actor: -1, ..Default::default() };
+        if tm.sector >= 0 {
+            let sector = self.sectors[tm.sector as usize].borrow();
+            let xy = Vector2 { x, y };
+            tm.floor_z = sector.floorplane.z_at(&xy);
+            tm.dropoff_z = tm.floor_z;
+            tm.ceiling_z = sector.ceilingplane.z_at(&xy);
+        }
+
+        if actor.flags.contains(MobjFlags::NoClip) {return (true, tm)}
+
+        //the things
+        let block_map = &self.block_map;
+        let mut checked = HashSet::new();
+        for by in block_map.block_y(bbox[BOXBOTTOM] - MAXRADIUS)..=block_map.block_y(bbox[BOXTOP] + MAXRADIUS) {
+            for bx in block_map.block_x(bbox[BOXLEFT] - MAXRADIUS)..=block_map.block_x(bbox[BOXRIGHT] + MAXRADIUS) {
+                for thing in block_map.block_things(bx, by) {
+                    if thing == index || !checked.insert(thing) {continue}
+                    if !Self::check_thing(self, actor, thing, x, y, &mut tm) {return (false, tm)}
+                }
+            }
+        }
+
+        //the lines
+        let mut checked = HashSet::new();
+        for by in block_map.block_y(bbox[BOXBOTTOM])..=block_map.block_y(bbox[BOXTOP]) {
+            for bx in block_map.block_x(bbox[BOXLEFT])..=block_map.block_x(bbox[BOXRIGHT]) {
+                for line in block_map.block_lines(bx, by) {
+                    if !checked.insert(line) {continue}
+                    if !Self::check_line(self, actor, line, &bbox, &mut tm) {return (false, tm)}
+                }
+            }
+        }
+        (true, tm)
+    }
+
+    //PIT_CheckThing, false when the thing is in the way
+    fn check_thing(&self, actor: &Actor, thing: ActorIndex, x: f64, y: f64, tm: &mut CheckPosition) -> bool {
+        let other = match self.actor(thing) {
+            Some(other) => other,
+            None => return true
+        };
+        if !other.flags.intersects(MobjFlags::Solid | MobjFlags::Special | MobjFlags::Shootable) {return true}
+
+        let block_dist = other.radius + actor.radius;
+        if (other.pos.x - x).abs() >= block_dist || (other.pos.y - y).abs() >= block_dist {return true}
+
+        if actor.flags.contains(MobjFlags::SkullFly) {
+            tm.blocking_actor = thing;
+            tm.hit_actor = thing;
+            return false
+        }
+
+        if actor.flags.contains(MobjFlags::Missile) {
+            //over or under the thing
+            if actor.pos.z > other.top() || actor.top() < other.pos.z {return true}
+            //the shooter isn't hit by its own missile
+            if actor.target == thing {return true}
+            //the monsters don't hurt their own kind with their missiles, the missiles of the players do
+            if self.actor(actor.target).is_some_and(|shooter| same_species(shooter.type_, other.type_)) && other.type_ != MT_PLAYER {
+                tm.blocking_actor = thing;
+                return false
+            }
+            if !other.flags.contains(MobjFlags::Shootable) {return !other.flags.contains(MobjFlags::Solid)}
+            tm.blocking_actor = thing;
+            tm.hit_actor = thing;
+            return false
+        }
+
+        if other.flags.contains(MobjFlags::Special) {
+            let solid = other.flags.contains(MobjFlags::Solid);
+            //it is picked up after the check
+            if actor.flags.contains(MobjFlags::Pickup) {tm.touched.push(thing)}
+            if solid {tm.blocking_actor = thing}
+            return !solid
+        }
+
+        if other.flags.contains(MobjFlags::Solid) {
+            tm.blocking_actor = thing;
+            return false
+        }
+        true
+    }
+
+    //PIT_CheckLine, false when the line is in the way, the opening of the others lowers the space of the position
+    fn check_line(&self, actor: &Actor, line_index: LineIndex, bbox: &[f64;4], tm: &mut CheckPosition) -> bool {
+        let (back_sector, flags, special) = {
+            let line = self.lines[line_index as usize].borrow();
+            if bbox[BOXRIGHT] <= line.bbox[BOXLEFT] || bbox[BOXLEFT] >= line.bbox[BOXRIGHT] ||
+                    bbox[BOXTOP] <= line.bbox[BOXBOTTOM] || bbox[BOXBOTTOM] >= line.bbox[BOXTOP] {
+                return true
+            }
+            if line.box_on_side(bbox) != -1 {return true}
+            (line.back_sector, line.flags, line.special)
+        };
+
+        //one sided lines block everything
+        if back_sector < 0 {
+            tm.blocking_line = line_index;
+            return false
+        }
+
+        if !actor.flags.contains(MobjFlags::Missile) {
+            let player = actor.player >= 0;
+            let blocking = flags & (LineFlags::Blocking.bits() | LineFlags::BlockEverything.bits()) != 0 ||
+                (player && flags & LineFlags::BlockPlayers.bits() != 0) ||
+                (!player && flags & LineFlags::BlockMonsters.bits() != 0 && !actor.flags.contains(MobjFlags::Friendly));
+            if blocking {
+                tm.blocking_line = line_index;
+                return false
+            }
+        }
+        else if flags & (LineFlags::BlockEverything.bits() | LineFlags::BlockProjectile.bits()) != 0 {
+            tm.blocking_line = line_index;
+            return false
+        }
+
+        let center = Vector2 { x: (bbox[BOXLEFT] + bbox[BOXRIGHT]) / 2., y: (bbox[BOXTOP] + bbox[BOXBOTTOM]) / 2. };
+        if let Some(opening) = Self::line_opening(self, line_index, &center) {
+            if opening.top < tm.ceiling_z {
+                tm.ceiling_z = opening.top;
+                tm.ceiling_line = line_index;
+            }
+            if opening.bottom > tm.floor_z {tm.floor_z = opening.bottom}
+            if opening.low_floor < tm.dropoff_z {tm.dropoff_z = opening.low_floor}
+        }
+
+        if special != 0 {tm.spec_hit.push(line_index)}
+        true
+    }
+
+    /*
+     * P_TryMove, moves the actor to the position when it fits there.
+     * The special lines that were crossed are activated.
+     */
+    pub fn try_move(&mut self, index: ActorIndex, actor: &mut Actor, x: f64, y: f64) -> bool {
+        Self::try_move_position(self, index, actor, x, y).0
+    }
+
+    //P_TryMove that also returns what check_position found, the monsters use the special lines they bumped into
+    pub fn try_move_position(&mut self, index: ActorIndex, actor: &mut Actor, x: f64, y: f64) -> (bool, CheckPosition) {
+        let (fits, mut tm) = Self::check_position(self, index, actor, x, y);
+        for thing in std::mem::take(&mut tm.touched) {
+            Self::touch_special_thing(self, thing, actor);
+        }
+        actor.blocking_line = tm.blocking_line;
+        actor.blocking_actor = tm.blocking_actor;
+        if !fits {
+            if tm.hit_actor >= 0 {Self::impact_damage(self, index, actor, tm.hit_actor)}
+            return (false, tm)
+        }
+
+        if !actor.flags.contains(MobjFlags::NoClip) {
+            //doesn't fit
+            if tm.ceiling_z - tm.floor_z < actor.height {return (false, tm)}
+            tm.float_ok = true;
+            let teleport = actor.flags.contains(MobjFlags::Teleport);
+            //would have to lower itself to fit
+            if !teleport && tm.ceiling_z - actor.pos.z < actor.height {
+                actor.blocking_line = tm.ceiling_line;
+                return (false, tm)
+            }
+            //too big a step up
+            if !teleport && tm.floor_z - actor.pos.z > MAXSTEPHEIGHT {return (false, tm)}
+            //don't stand over a dropoff
+            if !actor.flags.intersects(MobjFlags::Dropoff | MobjFlags::Float) && tm.floor_z - tm.dropoff_z > MAXSTEPHEIGHT {return (false, tm)}
+        }
+
+        let old = actor.pos;
+        Self::unlink_from_world(self, index, actor);
+        actor.floor_z = tm.floor_z;
+        actor.ceiling_z = tm.ceiling_z;
+        actor.dropoff_z = tm.dropoff_z;
+        actor.pos.x = x;
+        actor.pos.y = y;
+        Self::link_to_world(self, index, actor);
+
+        //the crossed special lines
+        if !actor.flags.intersects(MobjFlags::Teleport | MobjFlags::NoClip) {
+            let (activation, activator_type) = if actor.player >= 0 {
+                (SpecialActivation::Cross, ActivatorType::Player)
+            } else if actor.flags.contains(MobjFlags::Missile) {
+                (SpecialActivation::PCross, ActivatorType::Projectile)
+            } else if actor.flags.contains(MobjFlags::CountKill) {
+                (SpecialActivation::MCross, ActivatorType::Monster)
+            } else {
+                (SpecialActivation::AnyCross, ActivatorType::World)
+            };
+            let activator = Activator::new(index, activator_type);
+            for line in tm.spec_hit.iter().rev() {
+                let side = self.point_on_line_side(actor.pos.x, actor.pos.y, *line);
+                let old_side = self.point_on_line_side(old.x, old.y, *line);
+                if side != old_side {
+                    Self::activate_line(self, *line, &activator, old_side, activation.bits());
+                }
+            }
+            Self::run_teleports(self, index, actor);
+        }
+        (true, tm)
+    }
+
+    //the damage of the missile or the flying lost soul that ran into the thing (the end of PIT_CheckThing)
+    fn impact_damage(&mut self, index: ActorIndex, actor: &mut Actor, thing: ActorIndex) {
+        let damage = (self.random.random() % 8 + 1) * actor.info().damage;
+        if actor.flags.contains(MobjFlags::SkullFly) {
+            Self::damage_actor(self, thing, Some(actor), index, Some(actor), damage);
+            actor.flags.remove(MobjFlags::SkullFly);
+            actor.vel = Vector3::default();
+            Self::set_actor_state(self, index, actor, actor.info().spawn_state);
+            return
+        }
+        let source = self.actor(actor.target).cloned();
+        Self::damage_actor(self, thing, Some(actor), actor.target, source.as_ref(), damage);
+    }
+
+    /*
+     * P_SlideMove, the move along the line that blocked it.
+     * When that doesn't fit either it tries to move on one axis (the stairstep).
+     */
+    pub fn slide_move(&mut self, index: ActorIndex, actor: &mut Actor, move_x: f64, move_y: f64) {
+        if actor.blocking_line >= 0 {
+            let delta = self.lines[actor.blocking_line as usize].borrow().delta();
+            let length = delta.x * delta.x + delta.y * delta.y;
+            if length > 0. {
+                let dot = (move_x * delta.x + move_y * delta.y) / length;
+                let (slide_x, slide_y) = (delta.x * dot, delta.y * dot);
+                if Self::try_move(self, index, actor, actor.pos.x + slide_x, actor.pos.y + slide_y) {
+                    let dot = (actor.vel.x * delta.x + actor.vel.y * delta.y) / length;
+                    actor.vel.x = delta.x * dot;
+                    actor.vel.y = delta.y * dot;
+                    return
+                }
+            }
+        }
+
+        if Self::try_move(self, index, actor, actor.pos.x, actor.pos.y + move_y) {actor.vel.x = 0.}
+        else if Self::try_move(self, index, actor, actor.pos.x + move_x, actor.pos.y) {actor.vel.y = 0.}
+        else {
+            actor.vel.x = 0.;
+            actor.vel.y = 0.;
+        }
+    }
+
+    //P_ExplodeMissile, false when the missile was removed
+    pub fn explode_missile(&mut self, index: ActorIndex, actor: &mut Actor) -> bool {
+        actor.vel.x = 0.;
+        actor.vel.y = 0.;
+        actor.vel.z = 0.;
+        if !Self::set_actor_state(self, index, actor, actor.info().death_state) {return false}
+        actor.tics = (actor.tics - (self.random.random() & 3)).max(1);
+        actor.flags.remove(MobjFlags::Missile);
+        //TODO the death sound
+        true
+    }
+
+    //P_XYMovement, false when the actor was removed
+    pub fn xy_movement(&mut self, index: ActorIndex, actor: &mut Actor) -> bool {
+        if actor.vel.x == 0. && actor.vel.y == 0. {
+            if actor.flags.contains(MobjFlags::SkullFly) {
+                //the lost soul slammed into something
+                actor.flags.remove(MobjFlags::SkullFly);
+                actor.vel.z = 0.;
+                return Self::set_actor_state(self, index, actor, actor.info().spawn_state)
+            }
+            return true
+        }
+
+        actor.vel.x = actor.vel.x.clamp(-MAXMOVE, MAXMOVE);
+        actor.vel.y = actor.vel.y.clamp(-MAXMOVE, MAXMOVE);
+        let (mut move_x, mut move_y) = (actor.vel.x, actor.vel.y);
+
+        //the fast moves are done in steps so they don't go through the lines
+        while move_x != 0. || move_y != 0. {
+            let (step_x, step_y) = if move_x.abs() > MAXMOVE / 2. || move_y.abs() > MAXMOVE / 2. {
+                (move_x / 2., move_y / 2.)
+            } else {
+                (move_x, move_y)
+            };
+            move_x -= step_x;
+            move_y -= step_y;
+
+            if !Self::try_move(self, index, actor, actor.pos.x + step_x, actor.pos.y + step_y) {
+                if actor.player >= 0 {Self::slide_move(self, index, actor, step_x, step_y)}
+                else if actor.flags.contains(MobjFlags::Missile) {
+                    //TODO the missiles don't explode against the sky
+                    return Self::explode_missile(self, index, actor)
+                }
+                else {
+                    actor.vel.x = 0.;
+                    actor.vel.y = 0.;
+                    break
+                }
+            }
+        }
+
+        //no friction for the missiles and in the air
+        if actor.flags.intersects(MobjFlags::Missile | MobjFlags::SkullFly) || actor.pos.z > actor.floor_z {return true}
+
+        if actor.flags.contains(MobjFlags::Corpse) {
+            //TODO the corpses slide down the stairs
+        }
+
+        if actor.vel.x.abs() < STOPSPEED && actor.vel.y.abs() < STOPSPEED {
+            actor.vel.x = 0.;
+            actor.vel.y = 0.;
+            //the player stops running
+            if actor.player >= 0 && (S_PLAY_RUN1..=S_PLAY_RUN4).contains(&actor.state) {
+                return Self::set_actor_state(self, index, actor, S_PLAY)
+            }
+        }
+        else {
+            let friction = if actor.sector >= 0 {self.sectors[actor.sector as usize].borrow().friction} else {FRICTION};
+            actor.vel.x *= friction;
+            actor.vel.y *= friction;
+        }
+        true
+    }
+
+    //P_ZMovement, false when the actor was removed
+    pub fn z_movement(&mut self, index: ActorIndex, actor: &mut Actor) -> bool {
+        //TODO the smooth step up of the view of the player
+        actor.pos.z += actor.vel.z;
+
+        //the floating monsters go to the height of their target
+        if actor.flags.contains(MobjFlags::Float) && !actor.flags.intersects(MobjFlags::SkullFly | MobjFlags::InFloat) {
+            if let Some(target) = self.actor(actor.target) {
+                let dist = approx_distance(actor.pos.x - target.pos.x, actor.pos.y - target.pos.y);
+                let delta = target.pos.z + actor.height / 2. - actor.pos.z;
+                if delta < 0. && dist < -(delta * 3.) {actor.pos.z -= FLOATSPEED}
+                else if delta > 0. && dist < delta * 3. {actor.pos.z += FLOATSPEED}
+            }
+        }
+
+        if actor.pos.z <= actor.floor_z {
+            if actor.flags.contains(MobjFlags::SkullFly) {actor.vel.z = -actor.vel.z}
+            if actor.vel.z < 0. {
+                //TODO the landing squat and the sound of the player
+                actor.vel.z = 0.;
+            }
+            actor.pos.z = actor.floor_z;
+            if actor.flags.contains(MobjFlags::Missile) && !actor.flags.contains(MobjFlags::NoClip) {
+                return Self::explode_missile(self, index, actor)
+            }
+        }
+        else if !actor.flags.contains(MobjFlags::NoGravity) {
+            let gravity = if actor.sector >= 0 {GRAVITY * self.sectors[actor.sector as usize].borrow().gravity} else {GRAVITY};
+            if actor.vel.z == 0. {actor.vel.z = -gravity * 2.}
+            else {actor.vel.z -= gravity}
+        }
+
+        if actor.top() > actor.ceiling_z {
+            if actor.flags.contains(MobjFlags::SkullFly) {actor.vel.z = -actor.vel.z}
+            if actor.vel.z > 0. {actor.vel.z = 0.}
+            actor.pos.z = actor.ceiling_z - actor.height;
+            if actor.flags.contains(MobjFlags::Missile) && !actor.flags.contains(MobjFlags::NoClip) {
+                return Self::explode_missile(self, index, actor)
+            }
+        }
+        true
+    }
+
+    /*
+     * P_ThingHeightClip, the actor follows the floor it stands on and is pushed down by the ceiling.
+     * Returns false when it doesn't fit anymore.
+     */
+    pub fn thing_height_clip(&self, index: ActorIndex, actor: &mut Actor) -> bool {
+        let on_floor = actor.pos.z == actor.floor_z;
+        let (_, tm) = Self::check_position(self, index, actor, actor.pos.x, actor.pos.y);
+        actor.floor_z = tm.floor_z;
+        actor.ceiling_z = tm.ceiling_z;
+        actor.dropoff_z = tm.dropoff_z;
+
+        if on_floor {actor.pos.z = actor.floor_z}
+        //don't adjust a floating monster unless forced to
+        else if actor.top() > actor.ceiling_z {actor.pos.z = actor.ceiling_z - actor.height}
+        actor.ceiling_z - actor.floor_z >= actor.height
+    }
+
+    //the things in the blocks around the lines of the sector, what P_ChangeSector goes through
+    pub fn sector_things(&self, sector: SectorIndex) -> Vec<ActorIndex> {
+        let mut bbox = [f64::MIN, f64::MAX, f64::MAX, f64::MIN];
+        for line in &self.sectors[sector as usize].borrow().lines {
+            let line = self.lines[*line as usize].borrow();
+            bbox[BOXTOP] = bbox[BOXTOP].max(line.bbox[BOXTOP]);
+            bbox[BOXBOTTOM] = bbox[BOXBOTTOM].min(line.bbox[BOXBOTTOM]);
+            bbox[BOXLEFT] = bbox[BOXLEFT].min(line.bbox[BOXLEFT]);
+            bbox[BOXRIGHT] = bbox[BOXRIGHT].max(line.bbox[BOXRIGHT]);
+        }
+        let mut things = vec![];
+        if bbox[BOXTOP] < bbox[BOXBOTTOM] {return things}
+
+        let block_map = &self.block_map;
+        for by in block_map.block_y(bbox[BOXBOTTOM] - MAXRADIUS)..=block_map.block_y(bbox[BOXTOP] + MAXRADIUS) {
+            for bx in block_map.block_x(bbox[BOXLEFT] - MAXRADIUS)..=block_map.block_x(bbox[BOXRIGHT] + MAXRADIUS) {
+                for thing in block_map.block_things(bx, by) {
+                    if !things.contains(&thing) {things.push(thing)}
+                }
+            }
+        }
+        things
+    }
+
+    //PIT_ChangeSector, false when the actor doesn't fit
+    pub fn change_sector_thing(&mut self, index: ActorIndex, crush: i32) -> bool {
+        let mut actor = match self.actor(index) {
+            Some(actor) => actor.clone(),
+            None => return true
+        };
+        let fits = Self::thing_height_clip(self, index, &mut actor);
+
+        let mut no_fit = false;
+        let mut crushed = false;
+        if !fits {
+            if actor.health <= 0 {
+                //the bodies are crunched to giblets
+                if !Self::set_actor_state(self, index, &mut actor, S_GIBS) {return true}
+                actor.flags.remove(MobjFlags::Solid);
+                actor.height = 0.;
+                actor.radius = 0.;
+            }
+            else if actor.flags.contains(MobjFlags::Dropped) {
+                //the dropped items are crunched
+                Self::destroy_thinker(self, index);
+                return true
+            }
+            else if actor.flags.contains(MobjFlags::Shootable) {
+                no_fit = true;
+                crushed = crush >= 0 && self.maptime & 3 == 0;
+            }
+        }
+
+        if let Some(current) = self.actor_mut(index) {*current = actor.clone()}
+        if crushed {
+            Self::damage_actor(self, index, None, -1, None, crush);
+            //the blood spurts out
+            let blood = Self::spawn_actor(self, MT_BLOOD, actor.pos.x, actor.pos.y, actor.pos.z + actor.height / 2.);
+            let vel = (self.random.random2() as f64 / 16., self.random.random2() as f64 / 16.);
+            if let Some(blood) = self.actor_mut(blood) {
+                blood.vel.x = vel.0;
+                blood.vel.y = vel.1;
+            }
+        }
+        !no_fit
+    }
+
+    //the activator of the specials the actor triggers
+    pub fn actor_activator(index: ActorIndex, actor: &Actor) -> Activator {
+        let type_ = if actor.player >= 0 {ActivatorType::Player}
+            else if actor.flags.contains(MobjFlags::Missile) {ActivatorType::Projectile}
+            else {ActivatorType::Monster};
+        Activator::new(index, type_)
+    }
+
+    //the height the attacks of the actor start at
+    pub fn shoot_z(actor: &Actor) -> f64 {
+        actor.pos.z + actor.height / 2. + 8.
+    }
+
+    /*
+     * P_AimLineAttack, the slope to the first shootable thing along the angle.
+     * Returns the slope and the thing, the slope is 0 when nothing is found.
+     */
+    pub fn aim_line_attack(&mut self, index: ActorIndex, actor: &Actor, angle: Angle<f64>, distance: f64) -> (f64, ActorIndex) {
+        let end = angle.to_vector(distance);
+        let shoot_z = Self::shoot_z(actor);
+        let (mut top_slope, mut bottom_slope) = (AIM_TOP_SLOPE, -AIM_TOP_SLOPE);
+        let mut aim = (0., -1);
+
+        Self::path_traverse(self, actor.pos.x, actor.pos.y, actor.pos.x + end.x, actor.pos.y + end.y, PathFlags::AddLines | PathFlags::AddThings, |level, intercept, _| {
+            let dist = distance * intercept.frac;
+            match intercept.target {
+                InterceptTarget::Line(line) => {
+                    if level.lines[line as usize].borrow().back_sector < 0 {return false}
+                    let opening = match level.line_opening(line, &actor.pos.xy()) {
+                        Some(opening) => opening,
+                        None => return false
+                    };
+                    if opening.bottom >= opening.top || dist <= 0. {return false}
+                    let (floors_differ, ceilings_differ) = Self::line_heights_differ(level, line);
+                    if floors_differ {bottom_slope = bottom_slope.max((opening.bottom - shoot_z) / dist)}
+                    if ceilings_differ {top_slope = top_slope.min((opening.top - shoot_z) / dist)}
+                    top_slope > bottom_slope
+                }
+                InterceptTarget::Thing(thing) => {
+                    if thing == index {return true}
+                    let other = match level.actor(thing) {
+                        Some(other) => other,
+                        None => return true
+                    };
+                    if !other.flags.contains(MobjFlags::Shootable) || dist <= 0. {return true}
+                    //TODO the friendly monsters aren't aimed at by the players
+
+                    let thing_top = (other.top() - shoot_z) / dist;
+                    let thing_bottom = (other.pos.z - shoot_z) / dist;
+                    //over or under the thing
+                    if thing_top < bottom_slope || thing_bottom > top_slope {return true}
+                    aim = ((thing_top.min(top_slope) + thing_bottom.max(bottom_slope)) / 2., thing);
+                    false
+                }
+            }
+        });
+        aim
+    }
+
+    //if the floors and the ceilings of the sides of the line are at another height
+    fn line_heights_differ(&self, line: LineIndex) -> (bool, bool) {
+        let line = self.lines[line as usize].borrow();
+        let front = self.sectors[line.front_sector as usize].borrow();
+        let back = self.sectors[line.back_sector as usize].borrow();
+        (front.floorplane.fd() != back.floorplane.fd() || front.floorplane.is_slope() || back.floorplane.is_slope(),
+            front.ceilingplane.fd() != back.ceilingplane.fd() || front.ceilingplane.is_slope() || back.ceilingplane.is_slope())
+    }
+
+    /*
+     * P_LineAttack, the hitscan along the angle and the slope.
+     * The first line or thing in the way gets a puff or blood, returns the thing that was hit.
+     */
+    #[allow(clippy::too_many_arguments)]
+    pub fn line_attack(&mut self, index: ActorIndex, actor: &Actor, angle: Angle<f64>, distance: f64, slope: f64, damage: i32) -> ActorIndex {
+        let end = angle.to_vector(distance);
+        let shoot_z = Self::shoot_z(actor);
+        let activator = Self::actor_activator(index, actor);
+        let mut hit = -1;
+
+        Self::path_traverse(self, actor.pos.x, actor.pos.y, actor.pos.x + end.x, actor.pos.y + end.y, PathFlags::AddLines | PathFlags::AddThings, |level, intercept, trace| {
+            let dist = distance * intercept.frac;
+            match intercept.target {
+                InterceptTarget::Line(line) => {
+                    let (special, back_sector) = {
+                        let line = level.lines[line as usize].borrow();
+                        (line.special, line.back_sector)
+                    };
+                    //P_ShootSpecialLine
+                    if special != 0 {
+                        let side = level.point_on_line_side(actor.pos.x, actor.pos.y, line);
+                        Self::activate_line(level, line, &activator, side, SpecialActivation::Impact.bits());
+                    }
+
+                    //the shot goes through the opening of a two sided line
+                    if back_sector >= 0 && dist > 0. {
+                        if let Some(opening) = level.line_opening(line, &actor.pos.xy()) {
+                            let (floors_differ, ceilings_differ) = Self::line_heights_differ(level, line);
+                            let under = floors_differ && (opening.bottom - shoot_z) / dist > slope;
+                            let over = ceilings_differ && (opening.top - shoot_z) / dist < slope;
+                            if !under && !over {return true}
+                        }
+                    }
+
+                    //the puff is a bit in front of the wall
+                    let frac = intercept.frac - 4. / distance;
+                    let (x, y) = trace.point_at(frac);
+                    //TODO no puffs on the sky
+                    Self::spawn_puff(level, x, y, shoot_z + slope * frac * distance, distance);
+                    false
+                }
+                InterceptTarget::Thing(thing) => {
+                    if thing == index {return true}
+                    let (top, bottom, no_blood) = match level.actor(thing) {
+                        Some(other) if other.flags.contains(MobjFlags::Shootable) => (other.top(), other.pos.z, other.flags.contains(MobjFlags::NoBlood)),
+                        _ => return true
+                    };
+                    if dist <= 0. {return true}
+                    //over or under the thing
+                    if (top - shoot_z) / dist < slope || (bottom - shoot_z) / dist > slope {return true}
+
+                    let frac = intercept.frac - 10. / distance;
+                    let (x, y) = trace.point_at(frac);
+                    let z = shoot_z + slope * frac * distance;
+                    if no_blood {Self::spawn_puff(level, x, y, z, distance);}
+                    else {Self::spawn_blood(level, x, y, z, damage);}
+                    if damage > 0 {Self::damage_actor(level, thing, Some(actor), index, Some(actor), damage)}
+                    hit = thing;
+                    false
+                }
+            }
+        });
+        hit
+    }
+
+    //P_BulletSlope, the autoaim of the guns also looks a bit to the sides
+    pub fn bullet_slope(&mut self, index: ActorIndex, actor: &Actor) -> f64 {
+        let aim_spread = Angle::<f64>::from_bam_u(1 << 26);
+        let mut angle = actor.angle;
+        let (mut slope, mut target) = Self::aim_line_attack(self, index, actor, angle, 16. * 64.);
+        if target < 0 {
+            angle.add(&aim_spread);
+            (slope, target) = Self::aim_line_attack(self, index, actor, angle, 16. * 64.);
+            if target < 0 {
+                angle.subtract(&aim_spread);
+                angle.subtract(&aim_spread);
+                (slope, _) = Self::aim_line_attack(self, index, actor, angle, 16. * 64.);
+            }
+        }
+        slope
+    }
+
+    //P_GunShot, one bullet of the guns, the refire isn't accurate
+    pub fn gun_shot(&mut self, index: ActorIndex, actor: &Actor, accurate: bool, slope: f64) -> ActorIndex {
+        let damage = 5 * (self.random.random() % 3 + 1);
+        let mut angle = actor.angle;
+        if !accurate {
+            angle.add(&Angle::<f64>::from_degrees(self.random.random2() as f64 * SPREAD_DEGREES));
+        }
+        Self::line_attack(self, index, actor, angle, MISSILERANGE, slope, damage)
+    }
+
+    /*
+     * P_UseLines, the first special line in front of the actor is used.
+     * A wall in between stops it, the lines without a special are looked through.
+     */
+    pub fn use_lines(&mut self, index: ActorIndex, actor: &Actor) {
+        let end = actor.angle.to_vector(USERANGE);
+        let activator = Self::actor_activator(index, actor);
+
+        Self::path_traverse(self, actor.pos.x, actor.pos.y, actor.pos.x + end.x, actor.pos.y + end.y, PathFlags::AddLines, |level, intercept, _| {
+            let line = match intercept.target {
+                InterceptTarget::Line(line) => line,
+                InterceptTarget::Thing(_) => return true
+            };
+            let (special, flags) = {
+                let line = level.lines[line as usize].borrow();
+                (line.special, line.flags)
+            };
+            if special == 0 || flags & LineFlags::BlockUse.bits() != 0 {
+                let open = level.line_opening(line, &actor.pos.xy()).is_some_and(|opening| opening.range > 0.);
+                if !open || flags & LineFlags::BlockUse.bits() != 0 {
+                    //TODO the noway sound of the player
+                    return false
+                }
+                return true
+            }
+            let side = level.point_on_line_side(actor.pos.x, actor.pos.y, line);
+            Self::activate_line(level, line, &activator, side, SpecialActivation::Use.bits());
+            //only one special line is used at a time
+            false
+        });
+    }
+
+    /*
+     * P_RadiusAttack, the things around the spot get the damage less their distance.
+     * The things the spot can't see are behind a wall and don't get hurt.
+     */
+    pub fn radius_attack(&mut self, spot: &Actor, source: ActorIndex, source_actor: Option<&Actor>, damage: i32) {
+        let dist = damage as f64 + MAXRADIUS;
+        let block_map = &self.block_map;
+        let mut things = vec![];
+        let mut checked = HashSet::new();
+        for bx in block_map.block_x(spot.pos.x - dist)..=block_map.block_x(spot.pos.x + dist) {
+            for by in block_map.block_y(spot.pos.y - dist)..=block_map.block_y(spot.pos.y + dist) {
+                for thing in block_map.block_things(bx, by) {
+                    if checked.insert(thing) {things.push(thing)}
+                }
+            }
+        }
+
+        for thing in things {
+            let dist = match self.actor(thing) {
+                Some(other) if other.flags.contains(MobjFlags::Shootable) => {
+                    //the cyberdemon and the spider mastermind don't get hurt by the explosions
+                    if other.type_ == MT_CYBORG || other.type_ == MT_SPIDER {continue}
+                    let dist = (other.pos.x - spot.pos.x).abs().max((other.pos.y - spot.pos.y).abs()) - other.radius;
+                    let dist = dist.floor().max(0.) as i32;
+                    if dist >= damage || !self.check_sight(other, spot, SightFlags::empty()) {continue}
+                    dist
+                }
+                _ => continue
+            };
+            Self::damage_actor(self, thing, Some(spot), source, source_actor, damage - dist);
+        }
+    }
+}
+
+//the monsters of the same kind don't hurt each other, the barons and the hell knights are the same kind
+pub fn same_species(type1: MobjType, type2: MobjType) -> bool {
+    type1 == type2 || (type1 == MT_KNIGHT && type2 == MT_BRUISER) || (type1 == MT_BRUISER && type2 == MT_KNIGHT)
+}
@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+
+use bitflags::bitflags;
+
+use super::LevelLocals;
+use super::level_actor::{ActorIndex, MAPBLOCKUNITS};
+use super::level_elements::LineIndex;
+
+bitflags! {
+    //what path_traverse collects
+    #[derive(Clone, Copy, Default, PartialEq, Debug)]
+    pub struct PathFlags: u32 {
+        const AddLines = 1;
+        const AddThings = 2;
+    }
+}
+
+//divline_t, a line from the point along the delta
+#[derive(Clone, Copy)]
+pub struct DivLine {
+    pub x: f64,
+    pub y: f64,
+    pub dx: f64,
+    pub dy: f64
+}
+
+impl DivLine {
+    //P_DivlineSide, 0 for the front, 1 for the back and 2 when the point is on the line
+    pub fn point_side(&self, x: f64, y: f64) -> usize {
+        let left = self.dy * (x - self.x);
+        let right = (y - self.y) * self.dx;
+        if right < left {0}
+        else if left == right {2}
+        else {1}
+    }
+
+    //P_InterceptVector, the fraction of this line where the other line crosses it
+    pub fn intercept(&self, other: &DivLine) -> f64 {
+        let den = other.dy * self.dx - other.dx * self.dy;
+        if den == 0. {return 0.}
+        ((other.x - self.x) * other.dy + (self.y - other.y) * other.dx) / den
+    }
+
+    //the point at the fraction of the line
+    pub fn point_at(&self, frac: f64) -> (f64, f64) {
+        (self.x + self.dx * frac, self.y + self.dy * frac)
+    }
+}
+
+//P_AproxDistance, the distance the monsters use, without the square root
+pub fn approx_distance(dx: f64, dy: f64) -> f64 {
+    let (dx, dy) = (dx.abs(), dy.abs());
+    if dx < dy {dx + dy - dx / 2.} else {dx + dy - dy / 2.}
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InterceptTarget {
+    Line(LineIndex),
+    Thing(ActorIndex)
+}
+
+//intercept_t, what the trace crosses and how far along the trace it is
+#[derive(Clone, Copy, Debug)]
+pub struct Intercept {
+    pub frac: f64,
+    pub target: InterceptTarget
+}
+
+/*
+ * Going through the blocks along a trace (p_maputl.cpp)
+ */
+impl LevelLocals {
+    /*
+     * P_PathTraverse, the lines and things the trace from (x1, y1) to (x2, y2) crosses are given to trav from the closest on.
+     * Returns false when trav stopped the traversal by returning false.
+     */
+    pub fn path_traverse<F>(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, flags: PathFlags, mut trav: F) -> bool
+            where F: FnMut(&mut LevelLocals, &Intercept, &DivLine) -> bool {
+        let block_map = &self.block_map;
+        //don't start exactly on the edge of a block
+        let x1 = if (x1 - block_map.blockmap_origin_x) % MAPBLOCKUNITS == 0. {x1 + 1.} else {x1};
+        let y1 = if (y1 - block_map.blockmap_origin_y) % MAPBLOCKUNITS == 0. {y1 + 1.} else {y1};
+        let trace = DivLine { x: x1, y: y1, dx: x2 - x1, dy: y2 - y1 };
+
+        let (mut block_x, mut block_y) = (block_map.block_x(x1), block_map.block_y(y1));
+        let (end_x, end_y) = (block_map.block_x(x2), block_map.block_y(y2));
+        let (step_x, step_y) = (trace.dx.signum() as i32, trace.dy.signum() as i32);
+
+        //the fraction of the trace where it goes in the next column and row of blocks
+        let next_edge = |block: i32, origin: f64, start: f64, delta: f64| {
+            if delta == 0. {return f64::INFINITY}
+            let edge = origin + (block + (delta > 0.) as i32) as f64 * MAPBLOCKUNITS;
+            (edge - start) / delta
+        };
+        let mut next_x = next_edge(block_x, block_map.blockmap_origin_x, x1, trace.dx);
+        let mut next_y = next_edge(block_y, block_map.blockmap_origin_y, y1, trace.dy);
+        let step_frac_x = if trace.dx == 0. {f64::INFINITY} else {MAPBLOCKUNITS / trace.dx.abs()};
+        let step_frac_y = if trace.dy == 0. {f64::INFINITY} else {MAPBLOCKUNITS / trace.dy.abs()};
+
+        let mut intercepts = vec![];
+        let mut checked_lines = HashSet::new();
+        let mut checked_things = HashSet::new();
+        let block_count = (end_x - block_x).abs() + (end_y - block_y).abs() + 1;
+        for _ in 0..block_count {
+            if flags.contains(PathFlags::AddLines) {
+                for line in block_map.block_lines(block_x, block_y) {
+                    if checked_lines.insert(line) {Self::add_line_intercept(self, line, &trace, &mut intercepts)}
+                }
+            }
+            if flags.contains(PathFlags::AddThings) {
+                for thing in block_map.block_things(block_x, block_y) {
+                    if checked_things.insert(thing) {Self::add_thing_intercept(self, thing, &trace, &mut intercepts)}
+                }
+            }
+            if block_x == end_x && block_y == end_y {break}
+            if next_x < next_y {
+                next_x += step_frac_x;
+                block_x += step_x;
+            }
+            else {
+                next_y += step_frac_y;
+                block_y += step_y;
+            }
+        }
+
+        //P_TraverseIntercepts, the closest first and the lines before the things at the same distance
+        intercepts.sort_by(|a: &Intercept, b: &Intercept| a.frac.total_cmp(&b.frac));
+        for intercept in intercepts.iter().take_while(|intercept| intercept.frac <= 1.) {
+            if !trav(self, intercept, &trace) {return false}
+        }
+        true
+    }
+
+    //PIT_AddLineIntercepts
+    fn add_line_intercept(&self, line_index: LineIndex, trace: &DivLine, intercepts: &mut Vec<Intercept>) {
+        let line = self.lines[line_index as usize].borrow();
+        let (v1x, v1y) = (line.v1.fx(), line.v1.fy());
+        let delta = line.delta();
+        //the side of the line the ends of the trace are on is more precise for the short traces
+        let (s1, s2) = if trace.dx.abs() > 16. || trace.dy.abs() > 16. {
+            (trace.point_side(v1x, v1y) & 1, trace.point_side(v1x + delta.x, v1y + delta.y) & 1)
+        } else {
+            (line.point_on_side(trace.x, trace.y) as usize, line.point_on_side(trace.x + trace.dx, trace.y + trace.dy) as usize)
+        };
+        //the line isn't crossed
+        if s1 == s2 {return}
+
+        let frac = trace.intercept(&DivLine { x: v1x, y: v1y, dx: delta.x, dy: delta.y });
+        //behind the start
+        if frac < 0. {return}
+        intercepts.push(Intercept { frac, target: InterceptTarget::Line(line_index) });
+    }
+
+    //PIT_AddThingIntercepts, the thing is crossed when the trace goes through a diagonal of its box
+    fn add_thing_intercept(&self, thing: ActorIndex, trace: &DivLine, intercepts: &mut Vec<Intercept>) {
+        let actor = match self.actor(thing) {
+            Some(actor) => actor,
+            None => return
+        };
+        let (x, y, radius) = (actor.pos.x, actor.pos.y, actor.radius);
+        let (x1, y1, x2, y2) = if (trace.dx < 0.) == (trace.dy < 0.) {
+            (x - radius, y + radius, x + radius, y - radius)
+        } else {
+            (x - radius, y - radius, x + radius, y + radius)
+        };
+        if trace.point_side(x1, y1) & 1 == trace.point_side(x2, y2) & 1 {return}
+
+        let frac = trace.intercept(&DivLine { x: x1, y: y1, dx: x2 - x1, dy: y2 - y1 });
+        if frac < 0. {return}
+        intercepts.push(Intercept { frac, target: InterceptTarget::Thing(thing) });
+    }
+}
@@ -1,24 +1,53 @@
 use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
+use macroquad::miniquad;
 use macroquad::models::Vertex;
-use macroquad::prelude::{Mesh, Vec3, Vec2, Color};
+use macroquad::prelude::{Mesh, Vec3, Vec2, Color, FilterMode};
 use macroquad::texture::Texture2D;
+use macroquad::window::get_internal_gl;
 
 use crate::vector::{Vector3, Vector2};
 
 use super::LevelLocals;
-use super::level_elements::{Sector, Side, SubSector, SectorE, SectorIndex, Sides};
+use super::level_elements::{Sector, Side, SectorE, SectorIndex, Sides, LineFlags, LevelElements, ChildNode, BOXTOP, BOXBOTTOM, BOXLEFT, BOXRIGHT};
 use super::level_lightmap::SurfaceType;
 use super::level_texture::{TextureID, TextureManager};
+use super::level_triangulate::triangulate_loops;
+
+//the biggest size of the box of a bsp subtree that is put in one chunk
+const CHUNK_SIZE: f64 = 1024.;
+//the most a macroquad draw_mesh call can take
+const MAX_MESH_VERTICES: usize = 10000;
+const MAX_MESH_INDICES: usize = 5000;
 
 #[derive(Default, Debug)]
 pub struct LevelMesh {
     pub vertices: Vec<Vector3<f32>>,
+    pub tex_coords: Vec<Vector2<f32>>, //the uv of each vertex
+    pub colors: Vec<Color>, //the light color of the sector in rgb and the light level in alpha for each vertex
     pub uv_index: Vec<i32>,
     elements: Vec<u32>,
     mesh_surfaces: Vec<i32>,
 
-    surfaces: Vec<Surface>
+    surfaces: Vec<Surface>,
+    sector_surfaces: Vec<Vec<usize>>, //the floors and ceilings on the planes of each sector
+    side_surfaces: Vec<Vec<usize>>, //the walls of each side
+    lit_surfaces: Vec<Vec<usize>>, //the surfaces that get their light from each sector
+    sector_lights: Vec<(i16, u32, u32)>, //the light level, light color and fade color of each sector when the surfaces were lit
+    fade_groups: HashSet<i32> //the groups with a surface whose fade color changed, their chunks are batched again
+}
+
+//a part of the mesh that is close together and has one texture, the bounds are for culling it
+pub struct MeshChunk {
+    pub mesh: Mesh,
+    pub min: Vec3,
+    pub max: Vec3,
+    pub group: i32,
+    pub fade: Color, //the fog color of the sectors in the chunk
+    pub alpha: f32, //less than 1 for a translucent line, it is drawn after the rest
+    sources: Vec<u32> //the vertex of the level mesh for each vertex of the mesh
 }
 
 #[derive(Debug)]
@@ -27,17 +56,32 @@ struct Surface {
     _type_index: i32,
     pub vert_count: u32,
     pub start_vert_index: u32,
+    pub texture: TextureID,
+    pub group: i32, //the chunk of the bsp the surface is in
+    pub triangles: Vec<u32>, //the triangles of the floors and ceilings, from the first vertex of the surface
+    pub sector: SectorIndex, //the sector of the plane of a flat or the front sector of a wall
+    pub fade: u32, //the fade color of the sector, the chunks are split by it
+    pub alpha: f32, //the alpha of a translucent line
     _plane: SectorPlane,
     _control_sector: SectorIndex,
     _b_sky: bool
 }
 
+//the points of a subsector or sector and its triangles
+struct FlatPolygon<'a> {
+    points: &'a [Vector2<f32>],
+    triangles: &'a [u32],
+    type_index: i32 //the subsector, -1 for the sectors that are triangulated as a whole
+}
+
 struct SidesS {
     v1_bottom: f64,
     v2_bottom: f64,
     v1_top: f64,
     v2_top: f64,
-    back: Option<SidesBack>
+    back: Option<SidesBack>,
+    tex_tops: [f64;3], //the height of the top of the texture for each part, it depends on the pegging
+    mid_bounds: Option<(f64, f64)> //the top and bottom of the middle texture of a two sided line, it isn't repeated up and down
 }
 
 #[derive(Clone, Copy)]
@@ -69,6 +113,36 @@ impl SectorPlane {
         (self.d + self.normal.x * f64::from(point.x) + self.normal.y * f64::from(point.y)) * self.neg_ic
     }
     
+    pub fn z_at(&self, point: &Vector2<f64>) -> f64 {
+        (self.d + self.normal.x * point.x + self.normal.y * point.y) * self.neg_ic
+    }
+
+    pub fn fd(&self) -> f64 {
+        self.d
+    }
+
+    pub fn set_d(&mut self, d: f64) {
+        self.d = d;
+    }
+
+    //the d of the plane when it goes through z at the point
+    pub fn point_to_dist(&self, point: &Vector2<f64>, z: f64) -> f64 {
+        -(self.normal.x * point.x + self.normal.y * point.y + self.normal.z * z)
+    }
+
+    pub fn get_changed_height(&self, hdiff: f64) -> f64 {
+        self.d - hdiff * self.normal.z
+    }
+
+    //the height difference between the current d and oldd
+    pub fn height_diff(&self, oldd: f64) -> f64 {
+        (self.d - oldd) * self.neg_ic
+    }
+
+    pub fn height_diff_between(&self, oldd: f64, newd: f64) -> f64 {
+        (newd - oldd) * self.neg_ic
+    }
+
     pub fn flip_verts(&mut self) {
         self.normal = Vector3::<f64>{x: -self.normal.x, y: -self.normal.y, z: -self.normal.z};
         self.d = -self.d;
@@ -93,141 +167,433 @@ impl LevelMesh {
     pub fn new(doom_map: &LevelLocals, tex_man: &TextureManager) -> LevelMesh {
         let mut level_mesh = LevelMesh {
             vertices: vec![],
+            tex_coords: vec![],
+            colors: vec![],
             uv_index: vec![],
             elements: vec![],
             mesh_surfaces: vec![],
-            surfaces: vec![]
+            surfaces: vec![],
+            sector_surfaces: vec![vec![]; doom_map.elements.sectors.len()],
+            side_surfaces: vec![vec![]; doom_map.elements.sides.len()],
+            lit_surfaces: vec![vec![]; doom_map.elements.sectors.len()],
+            sector_lights: vec![(0, 0, 0); doom_map.elements.sectors.len()],
+            fade_groups: HashSet::new()
         };
         for i in 0..doom_map.elements.sides.len() {
             Self::create_side_surfaces(&mut level_mesh, doom_map, &doom_map.elements.sides[i].borrow_mut(), tex_man);
         }
-        Self::create_subsector_surfaces(&mut level_mesh, doom_map);
+        Self::create_subsector_surfaces(&mut level_mesh, doom_map, tex_man);
 
         Self::create_uvs(&mut level_mesh);
+        Self::create_groups(&mut level_mesh, &doom_map.elements);
+
+        for (i, surface) in level_mesh.surfaces.iter().enumerate() {
+            match surface.type_ {
+                SurfaceType::STFloor | SurfaceType::STCeiling => level_mesh.sector_surfaces[surface.sector as usize].push(i),
+                _ if surface._control_sector < 0 => level_mesh.side_surfaces[surface._type_index as usize].push(i),
+                _ => ()
+            }
+            if surface.sector >= 0 {level_mesh.lit_surfaces[surface.sector as usize].push(i)}
+        }
+        Self::create_lights(&mut level_mesh, doom_map);
+        //the chunks aren't made yet, they start with these fade colors
+        level_mesh.fade_groups.clear();
+        //the mesh is up to date with the sectors now
+        for vertex in &doom_map.elements.vertexes {
+            vertex.borrow_mut().set_dirty(false);
+        }
         level_mesh
     }
 
-    pub fn to_macro_mesh(&self, texture: Texture2D) -> Mesh {
-        let mut vertices: Vec<Vertex> = vec![];
-        let mut indices: Vec<u16> = vec![];
+    /*
+     * Moves the surfaces of the sectors whose planes moved (the vertices of the sector were marked dirty) to the new heights.
+     * The flats of the sector and the walls of its lines change, the rest of the mesh stays.
+     * Returns the surfaces that changed so the chunks can be updated with update_chunks,
+     * and the groups with a new fade color so their chunks can be batched again with rebatch_fades.
+     */
+    pub fn update(&mut self, doom_map: &LevelLocals, tex_man: &TextureManager) -> (Vec<usize>, HashSet<i32>) {
+        let mut dirty_sectors = vec![];
+        for (i, sector) in doom_map.elements.sectors.iter().enumerate() {
+            let ext_sector = &doom_map.elements.extsectors[sector.borrow_mut().e as usize];
+            if ext_sector.vertices.iter().any(|vertex| doom_map.elements.vertexes[*vertex as usize].borrow_mut().is_dirty()) {
+                dirty_sectors.push(i);
+            }
+        }
+        let mut changed = Self::update_lights(self, doom_map);
+        let mut sides = HashSet::new();
+        for sector_index in &dirty_sectors {
+            for surface in self.sector_surfaces[*sector_index].clone() {
+                Self::update_flat_surface(self, doom_map, surface);
+                changed.push(surface);
+            }
+            let sector = doom_map.elements.sectors[*sector_index].borrow_mut();
+            for vertex in &doom_map.elements.extsectors[sector.e as usize].vertices {
+                doom_map.elements.vertexes[*vertex as usize].borrow_mut().set_dirty(false);
+            }
+            for line in &sector.lines {
+                sides.extend(doom_map.elements.lines[*line as usize].borrow_mut().sidedef.iter().filter(|side| **side >= 0).copied());
+            }
+        }
+        for side in sides {
+            changed.extend_from_slice(&self.side_surfaces[side as usize]);
+            Self::update_side_surfaces(self, doom_map, side as usize, tex_man);
+        }
+        (changed, std::mem::take(&mut self.fade_groups))
+    }
 
-        for i in 0..self.vertices.len() {
-            let vertex = self.vertices[i];
-            // let uv_index = self.uv_index[i];
-            let position: Vec3 = Vec3 { x: vertex.x, y: vertex.y, z: vertex.z};
-            let uv: Vec2 = Vec2 { x: 0., y: 0. }; //TODO calc the uvs
-            let color: Color = Color { r: 25., g: 45., b: 75., a: 1. };
-            let vertex_macro: Vertex = Vertex { position, uv, color };
-            indices.push(self.uv_index[i] as u16);
-            vertices.push(vertex_macro);
+    //colors the surfaces of the sectors whose light changed, a fade color that changed is batched again by rebatch_fades
+    fn update_lights(&mut self, doom_map: &LevelLocals) -> Vec<usize> {
+        let mut changed = vec![];
+        for (i, sector) in doom_map.elements.sectors.iter().enumerate() {
+            let light = Self::sector_light(&sector.borrow_mut());
+            if light == self.sector_lights[i] {continue}
+            self.sector_lights[i] = light;
+            for surface in self.lit_surfaces[i].clone() {
+                Self::light_surface(self, doom_map, surface);
+                changed.push(surface);
+            }
         }
+        changed
+    }
 
-        Mesh { vertices, indices, texture: Some(texture) }
+    fn update_flat_surface(&mut self, doom_map: &LevelLocals, index: usize) {
+        let surface = &mut self.surfaces[index];
+        let sector = &doom_map.elements.sectors[surface.sector as usize].borrow_mut();
+        let (plane, _) = Self::flat_plane(sector, &surface.type_, surface._control_sector >= 0);
+        let start = surface.start_vert_index as usize;
+        for vertex in &mut self.vertices[start..start + surface.vert_count as usize] {
+            vertex.z = plane.z_at_point(&vertex.xy()) as f32;
+        }
+        surface._plane = plane;
     }
 
-    //Functions for creating the mesh
-    fn create_subsector_surfaces(&mut self, doom_map: &LevelLocals) {
-        for i in 0..doom_map.elements.subsectors.len() {
-            let sub = &doom_map.elements.subsectors[i].borrow_mut();
+    fn update_side_surfaces(&mut self, doom_map: &LevelLocals, side_index: usize, tex_man: &TextureManager) {
+        let side = &doom_map.elements.sides[side_index].borrow_mut();
+        let (v1, v2, sides) = Self::side_geometry(doom_map, side, tex_man);
+        for surface_index in &self.side_surfaces[side_index] {
+            let surface = &mut self.surfaces[*surface_index];
+            let part = match surface.type_ {
+                SurfaceType::STUpperWall => Sides::Top.bits() as usize,
+                SurfaceType::STLowerWall => Sides::Bottom.bits() as usize,
+                _ => Sides::Mid.bits() as usize
+            };
+            let verts = Self::wall_verts(part, &v1, &v2, &sides);
+            let start = surface.start_vert_index as usize;
+            self.vertices[start..start + 4].copy_from_slice(&verts);
+            self.tex_coords[start..start + 4].copy_from_slice(&Self::wall_tex_coords(side, part, &v1, &verts, sides.tex_tops[part], tex_man));
+            surface._plane = Self::to_plane(&verts[0], &verts[1], &verts[2]);
+        }
+    }
 
-            if sub.line_count < 3 {continue;}
+    //copies the changed surfaces into the chunks they are in
+    pub fn update_chunks(&self, chunks: &mut [MeshChunk], changed: &[usize]) {
+        let groups: HashSet<i32> = changed.iter().map(|surface| self.surfaces[*surface].group).collect();
+        let mut changed_vertices = vec![false; self.vertices.len()];
+        for surface in changed.iter().map(|surface| &self.surfaces[*surface]) {
+            let start = surface.start_vert_index as usize;
+            changed_vertices[start..start + surface.vert_count as usize].fill(true);
+        }
 
-            let sector = sub.sector;
+        for chunk in chunks.iter_mut().filter(|chunk| groups.contains(&chunk.group)) {
+            for (vertex, source) in chunk.mesh.vertices.iter_mut().zip(&chunk.sources) {
+                if !changed_vertices[*source as usize] {continue}
+                let position = self.vertices[*source as usize];
+                let uv = self.tex_coords[*source as usize];
+                vertex.position = Vec3 { x: position.x, y: position.y, z: position.z };
+                vertex.uv = Vec2 { x: uv.x, y: uv.y };
+                vertex.color = self.colors[*source as usize];
+            }
+            Self::update_bounds(chunk);
+        }
+    }
 
-            if sector == -1 || Self::is_control_sector(sector) {continue;} 
-            let sec = sector;
+    /*
+     * macroquad draws a mesh with one texture and at most 10000 vertices and 5000 indices, so the triangles are put in a chunk
+     * for each group of the bsp and texture and a chunk that would go over those limits is split. The surfaces without a texture (missing textures) aren't drawn,
+     * the sky is drawn by to_sky_meshes and the translucent lines by to_translucent_meshes.
+     */
+    pub fn to_macro_meshes(&self, tex_man: &TextureManager, textures: &mut HashMap<TextureID, Texture2D>) -> Vec<MeshChunk> {
+        Self::group_macro_meshes(self, tex_man, textures, None)
+    }
 
-            Self::create_floor_surfaces(self, doom_map, sub, sec, i as i32, false);
-            Self::create_ceiling_surfaces(self, doom_map, sub, sec, i as i32, false);
-            let cur_sec = &doom_map.elements.sectors[sec as usize].borrow_mut();
-            let ext_sec = &doom_map.elements.extsectors[cur_sec.e as usize];
-            for j in 0..ext_sec.x_floor.f_floors.len() {
-                Self::create_floor_surfaces(self, doom_map, sub, ext_sec.x_floor.f_floors[j].model, i as i32, true);
-                Self::create_ceiling_surfaces(self, doom_map, sub, ext_sec.x_floor.f_floors[j].model, i as i32, true);
-            }
+    //the chunks of the groups, all of them when groups is None
+    fn group_macro_meshes(&self, tex_man: &TextureManager, textures: &mut HashMap<TextureID, Texture2D>, groups: Option<&HashSet<i32>>) -> Vec<MeshChunk> {
+        let mut batches: HashMap<(i32, TextureID, u32), Vec<u32>> = HashMap::new();
+        for (i, triangle) in self.elements.chunks(3).enumerate() {
+            let surface = &self.surfaces[self.mesh_surfaces[i] as usize];
+            if surface._b_sky || surface.alpha < 1. {continue}
+            if groups.is_some_and(|groups| !groups.contains(&surface.group)) {continue}
+            batches.entry((surface.group, surface.texture, surface.fade)).or_default().extend_from_slice(triangle);
+        }
+        let mut batches: Vec<((i32, TextureID, u32), Vec<u32>)> = batches.into_iter().collect();
+        batches.sort_by_key(|((group, texture, fade), _)| (*group, texture.get_index(), *fade));
+
+        let mut chunks = vec![];
+        for ((group, texture, fade), elements) in batches {
+            let texture = match Self::macro_texture(tex_man, texture, textures) {
+                Some(texture) => texture,
+                None => continue
+            };
+            Self::batch_chunks(self, &elements, group, Self::to_color(fade), Some(texture), 1., &mut chunks);
         }
+        chunks
     }
 
-    fn create_ceiling_surfaces(&mut self, doom_map: &LevelLocals, sub: &SubSector, sec_index: SectorIndex, type_index: i32, is_3d_floor: bool) {
-        let sector = &doom_map.elements.sectors[sec_index as usize].borrow_mut();
-        let b_sky = Self::is_sky_sector(sector);
-        let mut plane: SectorPlane;
+    //a chunk for each translucent line so they can be drawn from the back to the front
+    pub fn to_translucent_meshes(&self, tex_man: &TextureManager, textures: &mut HashMap<TextureID, Texture2D>) -> Vec<MeshChunk> {
+        Self::group_translucent_meshes(self, tex_man, textures, None)
+    }
 
-        if !is_3d_floor {
-            plane = sector.ceilingplane;
+    fn group_translucent_meshes(&self, tex_man: &TextureManager, textures: &mut HashMap<TextureID, Texture2D>, groups: Option<&HashSet<i32>>) -> Vec<MeshChunk> {
+        let mut batches: HashMap<usize, Vec<u32>> = HashMap::new();
+        for (i, triangle) in self.elements.chunks(3).enumerate() {
+            let surface = self.mesh_surfaces[i] as usize;
+            if groups.is_some_and(|groups| !groups.contains(&self.surfaces[surface].group)) {continue}
+            if !self.surfaces[surface]._b_sky && self.surfaces[surface].alpha < 1. {batches.entry(surface).or_default().extend_from_slice(triangle)}
         }
-        else {
-            plane = sector.floorplane;
-            plane.flip_verts();
+        let mut batches: Vec<(usize, Vec<u32>)> = batches.into_iter().collect();
+        batches.sort_by_key(|(surface, _)| *surface);
+
+        let mut chunks = vec![];
+        for (surface, elements) in batches {
+            let surface = &self.surfaces[surface];
+            let texture = match Self::macro_texture(tex_man, surface.texture, textures) {
+                Some(texture) => texture,
+                None => continue
+            };
+            Self::batch_chunks(self, &elements, surface.group, Self::to_color(surface.fade), Some(texture), surface.alpha, &mut chunks);
         }
+        chunks
+    }
 
-        let vert_count = sub.line_count;
-        let start_vert_index = self.vertices.len() as u32;
+    //the chunks of the groups whose fade color changed are made again, the chunks are split by it and their fade doesn't change
+    pub fn rebatch_fades(&self, chunks: &mut Vec<MeshChunk>, translucent_chunks: &mut Vec<MeshChunk>, groups: &HashSet<i32>, tex_man: &TextureManager, textures: &mut HashMap<TextureID, Texture2D>) {
+        chunks.retain(|chunk| !groups.contains(&chunk.group));
+        chunks.extend(Self::group_macro_meshes(self, tex_man, textures, Some(groups)));
+        translucent_chunks.retain(|chunk| !groups.contains(&chunk.group));
+        translucent_chunks.extend(Self::group_translucent_meshes(self, tex_man, textures, Some(groups)));
+    }
 
-        // self.vertices.resize_with((vert_count + start_vert_index) as usize, Default::default());
-        self.vertices.resize((vert_count + start_vert_index) as usize, Vector3::<f32>::new());
-        let verts = &mut self.vertices;
+    //the sky ceilings and walls in a chunk for each group, the sky shader gets its textures from the direction it looks in
+    pub fn to_sky_meshes(&self) -> Vec<MeshChunk> {
+        let mut batches: HashMap<i32, Vec<u32>> = HashMap::new();
+        for (i, triangle) in self.elements.chunks(3).enumerate() {
+            let surface = &self.surfaces[self.mesh_surfaces[i] as usize];
+            if surface._b_sky {batches.entry(surface.group).or_default().extend_from_slice(triangle)}
+        }
+        let mut batches: Vec<(i32, Vec<u32>)> = batches.into_iter().collect();
+        batches.sort_by_key(|(group, _)| *group);
 
-        for i in 0..vert_count as usize{
-            let seg = &doom_map.elements.segs[sub.first_line as usize + i].borrow_mut();
-            let v1_seg = doom_map.vertexes[seg.v1 as usize].borrow_mut();
-            let v1 = Self::to_f32_vector2(&v1_seg.f_pos());
+        let mut chunks = vec![];
+        for (group, elements) in batches {
+            Self::batch_chunks(self, &elements, group, Color::new(0., 0., 0., 1.), None, 1., &mut chunks);
+        }
+        chunks
+    }
 
-            verts[i + start_vert_index as usize].x = v1.x;
-            verts[i + start_vert_index as usize].y = v1.y;
-            verts[i + start_vert_index as usize].z = plane.z_at_point(&verts[i + start_vert_index as usize].xy()) as f32;
+    fn batch_chunks(&self, elements: &[u32], group: i32, fade: Color, texture: Option<Texture2D>, alpha: f32, chunks: &mut Vec<MeshChunk>) {
+        let mut mesh = Mesh { vertices: vec![], indices: vec![], texture };
+        let mut sources = vec![];
+        let mut remap: HashMap<u32, u16> = HashMap::new();
+        for triangle in elements.chunks(3) {
+            if mesh.vertices.len() + 3 > MAX_MESH_VERTICES || mesh.indices.len() + 3 > MAX_MESH_INDICES {
+                chunks.push(Self::to_chunk(mesh, group, fade, alpha, sources));
+                mesh = Mesh { vertices: vec![], indices: vec![], texture };
+                sources = vec![];
+                remap.clear();
+            }
+            for index in triangle {
+                let local = *remap.entry(*index).or_insert_with(|| {
+                    let vertex = self.vertices[*index as usize];
+                    let uv = self.tex_coords[*index as usize];
+                    mesh.vertices.push(Vertex { position: Vec3 { x: vertex.x, y: vertex.y, z: vertex.z }, uv: Vec2 { x: uv.x, y: uv.y }, color: self.colors[*index as usize] });
+                    sources.push(*index);
+                    (mesh.vertices.len() - 1) as u16
+                });
+                mesh.indices.push(local);
+            }
         }
-        let type_ = SurfaceType::STCeiling;
-        let control_sector = if is_3d_floor {sec_index} else {-1};
+        chunks.push(Self::to_chunk(mesh, group, fade, alpha, sources));
+    }
 
-        self.surfaces.push(Surface { type_, _type_index: type_index, vert_count, start_vert_index, _plane: plane, _control_sector: control_sector, _b_sky: b_sky });
+    fn to_chunk(mesh: Mesh, group: i32, fade: Color, alpha: f32, sources: Vec<u32>) -> MeshChunk {
+        let mut chunk = MeshChunk { mesh, min: Vec3::ZERO, max: Vec3::ZERO, group, fade, alpha, sources };
+        Self::update_bounds(&mut chunk);
+        chunk
     }
 
-    fn create_floor_surfaces(&mut self, doom_map: &LevelLocals, sub: &SubSector, sec_index: SectorIndex, type_index: i32, is_3d_floor: bool) {
-        let sector = &doom_map.elements.sectors[sec_index as usize].borrow_mut();
-        let b_sky = Self::is_sky_sector(sector);
-        let mut plane: SectorPlane;
+    fn update_bounds(chunk: &mut MeshChunk) {
+        chunk.min = Vec3::splat(f32::MAX);
+        chunk.max = Vec3::splat(f32::MIN);
+        for vertex in &chunk.mesh.vertices {
+            chunk.min = chunk.min.min(vertex.position);
+            chunk.max = chunk.max.max(vertex.position);
+        }
+    }
+
+    //the rgb of the pal entry without its alpha
+    fn to_color(color: u32) -> Color {
+        let [_, r, g, b] = color.to_be_bytes();
+        Color::new(r as f32 / 255., g as f32 / 255., b as f32 / 255., 1.)
+    }
 
-        if !is_3d_floor {
-            plane = sector.floorplane;
+    //the texture of the game texture is made once, it repeats because the uvs go past 1
+    pub fn macro_texture(tex_man: &TextureManager, texture: TextureID, textures: &mut HashMap<TextureID, Texture2D>) -> Option<Texture2D> {
+        if let Some(macro_texture) = textures.get(&texture) {return Some(*macro_texture)}
+        let game_texture = tex_man.get_game_texture(texture, true)?;
+        if !game_texture.is_valid() {return None}
+
+        let macro_texture = Texture2D::from_rgba8(game_texture.width as u16, game_texture.height as u16, &game_texture.pixels);
+        macro_texture.set_filter(FilterMode::Nearest);
+        unsafe {
+            let ctx = get_internal_gl().quad_context;
+            macro_texture.raw_miniquad_texture_handle().set_wrap(ctx, miniquad::TextureWrap::Repeat);
         }
-        else {
-            plane = sector.ceilingplane;
-            plane.flip_verts();
+        textures.insert(texture, macro_texture);
+        Some(macro_texture)
+    }
+
+    //Functions for creating the mesh
+    /*
+     * The floors and ceilings of the sectors with closed subsectors are a fan for each subsector.
+     * The other sectors (doom nodes) are triangulated as a whole from the loops of their lines.
+     */
+    fn create_subsector_surfaces(&mut self, doom_map: &LevelLocals, tex_man: &TextureManager) {
+        let mut closed_sectors = vec![true; doom_map.elements.sectors.len()];
+        for i in 0..doom_map.elements.subsectors.len() {
+            let sub = &doom_map.elements.subsectors[i].borrow_mut();
+            if sub.sector >= 0 && !doom_map.elements.is_subsector_closed(sub) {closed_sectors[sub.sector as usize] = false}
+        }
+
+        for i in 0..doom_map.elements.subsectors.len() {
+            let sub = &doom_map.elements.subsectors[i].borrow_mut();
+
+            if sub.line_count < 3 {continue;}
+
+            let sector = sub.sector;
+
+            if sector == -1 || Self::is_control_sector(sector) || !closed_sectors[sector as usize] {continue;}
+
+            let points: Vec<Vector2<f32>> = (0..sub.line_count as usize).map(|j| {
+                let seg = &doom_map.elements.segs[sub.first_line as usize + j].borrow_mut();
+                Self::to_f32_vector2(&doom_map.vertexes[seg.v1 as usize].borrow_mut().f_pos())
+            }).collect();
+            //the segs go clockwise around the subsector, the fan is turned so the triangles go counter clockwise
+            let triangles: Vec<u32> = (2..points.len() as u32).flat_map(|j| [0, j, j - 1]).collect();
+            Self::create_sector_surfaces(self, doom_map, &FlatPolygon { points: &points, triangles: &triangles, type_index: i as i32 }, sector, tex_man);
         }
 
-        let vert_count = sub.line_count;
+        for (sector, closed) in closed_sectors.into_iter().enumerate() {
+            if closed || Self::is_control_sector(sector as SectorIndex) {continue}
+            let (points, triangles) = triangulate_loops(&doom_map.elements.sector_loops(sector as SectorIndex));
+            if triangles.is_empty() {continue}
+            let points: Vec<Vector2<f32>> = points.iter().map(Self::to_f32_vector2).collect();
+            Self::create_sector_surfaces(self, doom_map, &FlatPolygon { points: &points, triangles: &triangles, type_index: -1 }, sector as SectorIndex, tex_man);
+        }
+    }
+
+    //the floor and ceiling of the polygon in the sector and in its 3d floors
+    fn create_sector_surfaces(&mut self, doom_map: &LevelLocals, polygon: &FlatPolygon, sec: SectorIndex, tex_man: &TextureManager) {
+        Self::create_floor_surfaces(self, doom_map, polygon, sec, false, tex_man);
+        Self::create_ceiling_surfaces(self, doom_map, polygon, sec, false, tex_man);
+        let cur_sec = &doom_map.elements.sectors[sec as usize].borrow_mut();
+        let ext_sec = &doom_map.elements.extsectors[cur_sec.e as usize];
+        for j in 0..ext_sec.x_floor.f_floors.len() {
+            Self::create_floor_surfaces(self, doom_map, polygon, ext_sec.x_floor.f_floors[j].model, true, tex_man);
+            Self::create_ceiling_surfaces(self, doom_map, polygon, ext_sec.x_floor.f_floors[j].model, true, tex_man);
+        }
+    }
+
+    fn create_ceiling_surfaces(&mut self, doom_map: &LevelLocals, polygon: &FlatPolygon, sec_index: SectorIndex, is_3d_floor: bool, tex_man: &TextureManager) {
+        let sector = &doom_map.elements.sectors[sec_index as usize].borrow_mut();
+        let (plane, pos) = Self::flat_plane(sector, &SurfaceType::STCeiling, is_3d_floor);
+
+        let vert_count = polygon.points.len() as u32;
         let start_vert_index = self.vertices.len() as u32;
 
-        self.vertices.resize((vert_count + start_vert_index) as usize, Vector3::<f32>::new());
-        let verts = &mut self.vertices;
+        for point in polygon.points {
+            self.vertices.push(Vector3::<f32> { x: point.x, y: point.y, z: plane.z_at_point(point) as f32 });
+        }
+        let texture = sector.get_texture(pos);
+        let b_sky = Self::is_sky_flat(texture, doom_map.sky_flat_num);
+        Self::create_flat_tex_coords(self, sector, pos, start_vert_index, tex_man);
+        let type_ = SurfaceType::STCeiling;
+        let control_sector = if is_3d_floor {sec_index} else {-1};
+        //the ceiling is seen from below so its triangles are turned around
+        let triangles: Vec<u32> = polygon.triangles.chunks(3).flat_map(|triangle| [triangle[0], triangle[2], triangle[1]]).collect();
+
+        self.surfaces.push(Surface { type_, _type_index: polygon.type_index, vert_count, start_vert_index, texture, fade: 0, alpha: 1., group: 0, triangles, sector: sec_index, _plane: plane, _control_sector: control_sector, _b_sky: b_sky });
+    }
+
+    fn create_floor_surfaces(&mut self, doom_map: &LevelLocals, polygon: &FlatPolygon, sec_index: SectorIndex, is_3d_floor: bool, tex_man: &TextureManager) {
+        let sector = &doom_map.elements.sectors[sec_index as usize].borrow_mut();
+        let (plane, pos) = Self::flat_plane(sector, &SurfaceType::STFloor, is_3d_floor);
 
-        for i in 0..vert_count as usize{
-            let seg = &doom_map.elements.segs[sub.first_line as usize + vert_count as usize - 1 - i].borrow_mut();
-            let v1_seg = doom_map.vertexes[seg.v1 as usize].borrow_mut();
-            let v1 = Self::to_f32_vector2(&v1_seg.f_pos());
+        let vert_count = polygon.points.len() as u32;
+        let start_vert_index = self.vertices.len() as u32;
 
-            verts[i + start_vert_index as usize].x = v1.x;
-            verts[i + start_vert_index as usize].y = v1.y;
-            verts[i + start_vert_index as usize].z = plane.z_at_point(&verts[i + start_vert_index as usize].xy()) as f32;
+        for point in polygon.points {
+            self.vertices.push(Vector3::<f32> { x: point.x, y: point.y, z: plane.z_at_point(point) as f32 });
         }
 
+        let texture = sector.get_texture(pos);
+        let b_sky = Self::is_sky_flat(texture, doom_map.sky_flat_num);
+        Self::create_flat_tex_coords(self, sector, pos, start_vert_index, tex_man);
         let type_ = SurfaceType::STFloor;
         let control_sector = if is_3d_floor {sec_index} else {-1};
 
-        self.surfaces.push(Surface { type_, _type_index: type_index, vert_count, start_vert_index, _plane: plane, _control_sector: control_sector, _b_sky: b_sky });
+        self.surfaces.push(Surface { type_, _type_index: polygon.type_index, vert_count, start_vert_index, texture, fade: 0, alpha: 1., group: 0, triangles: polygon.triangles.to_vec(), sector: sec_index, _plane: plane, _control_sector: control_sector, _b_sky: b_sky });
+    }
+
+    //the plane of the floor or ceiling, a 3d floor is seen from the other side of its model sector
+    fn flat_plane(sector: &Sector, type_: &SurfaceType, is_3d_floor: bool) -> (SectorPlane, usize) {
+        let mut pos = if *type_ == SurfaceType::STFloor {SectorE::Floor as usize} else {SectorE::Ceiling as usize};
+        if is_3d_floor {pos ^= 1}
+        let mut plane = if pos == SectorE::Floor as usize {sector.floorplane} else {sector.ceilingplane};
+        if is_3d_floor {plane.flip_verts()}
+        (plane, pos)
     }
 
     fn create_side_surfaces(&mut self, doom_map: &LevelLocals, side: &Side, tex_man: &TextureManager) {
+        if Self::is_control_sector(side.sector) { return }
+        let (v1, v2, sides) = Self::side_geometry(doom_map, side, tex_man);
+        let type_index = side.index();
+
+        match sides.back {
+            //the upper and lower walls are made even when they have no height yet, the sectors can move
+            Some(_) => {
+                let linedef = &doom_map.lines[side.linedef as usize].borrow_mut();
+                let back_index = if linedef.front_sector == side.sector {linedef.back_sector} else {linedef.front_sector};
+                let front_sector = &doom_map.elements.sectors[side.sector as usize].borrow_mut();
+                let back_sec = &doom_map.elements.sectors[back_index as usize].borrow_mut();
+                Self::create_side_surfaces_back_sector(self, doom_map, back_sec, front_sector, &v1, &v2, type_index);
+
+                if Self::is_bottom_side_visible(side, tex_man) {
+                    Self::create_wall_surface(self, side, Sides::Bottom.bits() as usize, &v1, &v2, &sides, false, tex_man);
+                }
+                //the masked middle textures, fences and grates, can be translucent
+                if Self::is_mid_side_visible(side, tex_man) {
+                    Self::create_wall_surface(self, side, Sides::Mid.bits() as usize, &v1, &v2, &sides, false, tex_man);
+                    if let Some(surface) = self.surfaces.last_mut() {surface.alpha = linedef.alpha as f32}
+                }
+                let b_sky = Self::is_top_side_sky(front_sector, back_sec, doom_map.sky_flat_num);
+                if b_sky || Self::is_top_side_visible(side, tex_man) {
+                    Self::create_wall_surface(self, side, Sides::Top.bits() as usize, &v1, &v2, &sides, b_sky, tex_man);
+                }
+            }
+            None => Self::create_wall_surface(self, side, Sides::Mid.bits() as usize, &v1, &v2, &sides, false, tex_man)
+        }
+    }
+
+    //the ends of the side and the heights of the sectors at them
+    fn side_geometry(doom_map: &LevelLocals, side: &Side, tex_man: &TextureManager) -> (Vector2<f32>, Vector2<f32>, SidesS) {
         let front_index = side.sector;
-        let linedef = &doom_map.lines[side.linedef as usize].borrow_mut();
-        let back_index = if linedef.front_sector == front_index {linedef.back_sector} else {linedef.front_sector};
-        if Self::is_control_sector(front_index) { return }
+        let line = &doom_map.lines[side.linedef as usize].borrow_mut();
+        let back_index = if line.front_sector == front_index {line.back_sector} else {line.front_sector};
 
-        let line = linedef;
         let v1;
         let v2;
-        if line.sidedef[0] >= 0 {
+        if line.sidedef[0] == side.index() {
             v1 = Self::to_f32_vector2(&line.v1.borrow().f_pos());
             v2 = Self::to_f32_vector2(&line.v2.borrow().f_pos());
         }
@@ -243,15 +609,35 @@ impl LevelMesh {
         let v2_top = front_sector.ceilingplane.z_at_point(&v2);
         let v2_bottom = front_sector.floorplane.z_at_point(&v2);
 
-        let mut sides: SidesS = SidesS { v1_bottom, v2_bottom, v1_top, v2_top, back: None};
-
-        let type_index = side.index();
+        //the walls are pegged to the heights of the sectors, the slopes don't change them
+        let front_floor = front_sector.get_plane_tex_z(SectorE::Floor as usize);
+        let front_ceiling = front_sector.get_plane_tex_z(SectorE::Ceiling as usize);
+        let peg_top = line.flags & LineFlags::DontPegTop.bits() != 0;
+        let peg_bottom = line.flags & LineFlags::DontPegBottom.bits() != 0;
+        let mut tex_tops = [front_ceiling; 3];
+        if peg_bottom {
+            tex_tops[Sides::Mid.bits() as usize] = front_floor + Self::texture_height(side, Sides::Mid.bits() as usize, tex_man);
+        }
 
-        // let dx = Vector2::<f32> {x: v2.x, y: v2.y};
+        let mut sides: SidesS = SidesS { v1_bottom, v2_bottom, v1_top, v2_top, back: None, tex_tops, mid_bounds: None};
 
         if back_index >= 0 {
             let back_sec = &doom_map.elements.sectors[back_index as usize].borrow_mut();
-            Self::create_side_surfaces_back_sector(self, doom_map, back_sec, front_sector, &v1, &v2, type_index);
+            if !peg_top {
+                sides.tex_tops[Sides::Top.bits() as usize] = back_sec.get_plane_tex_z(SectorE::Ceiling as usize) + Self::texture_height(side, Sides::Top.bits() as usize, tex_man);
+            }
+            if !peg_bottom {
+                sides.tex_tops[Sides::Bottom.bits() as usize] = back_sec.get_plane_tex_z(SectorE::Floor as usize);
+            }
+            //the middle texture hangs from the lower ceiling or stands on the higher floor
+            let mid = Sides::Mid.bits() as usize;
+            let mid_height = Self::texture_height(side, mid, tex_man);
+            sides.tex_tops[mid] = if peg_bottom {front_floor.max(back_sec.get_plane_tex_z(SectorE::Floor as usize)) + mid_height}
+                else {front_ceiling.min(back_sec.get_plane_tex_z(SectorE::Ceiling as usize))};
+            if line.flags & LineFlags::WrapMidTex.bits() == 0 {
+                let mid_top = sides.tex_tops[mid] + side.get_texture_y_offset(mid);
+                sides.mid_bounds = Some((mid_top, mid_top - mid_height));
+            }
 
             let v1_top_back = back_sec.ceilingplane.z_at_point(&v1);
             let v1_bottom_back = back_sec.floorplane.z_at_point(&v1);
@@ -259,21 +645,8 @@ impl LevelMesh {
             let v2_bottom_back = back_sec.floorplane.z_at_point(&v2);
 
             sides.back = Some(SidesBack {v1_bottom_back, v1_top_back, v2_bottom_back, v2_top_back});
-
-            if v1_top == v1_top_back && v1_bottom == v1_bottom_back && v2_top == v2_top_back && v2_bottom == v2_bottom_back {
-                return
-            }
-            if v1_bottom < v1_bottom_back || v2_bottom < v2_bottom_back {
-                Self::create_side_surfaces_bottom_seg(self, side, &v1, &v2, type_index, &mut sides, tex_man);
-            }
-            if v1_top > v1_top_back || v2_top > v2_top_back {
-                let b_sky = Self::is_top_side_sky(front_sector, back_sec);
-                Self::create_side_surfaces_top_seg(self, side, &v1, &v2, type_index, &mut sides, b_sky, tex_man);
-            }
-        }
-        if back_index == -1 {
-            Self::create_side_surfaces_middle_seg(self, &v1, &v2, type_index, &sides);
         }
+        (v1, v2, sides)
     }
 
     fn create_side_surfaces_back_sector(&mut self, doom_map: &LevelLocals, back: &Sector, front: &Sector, v1: &Vector2<f32>, v2: &Vector2<f32>, type_index: i32) {
@@ -317,108 +690,100 @@ impl LevelMesh {
             self.vertices.push(verts[1]);
             self.vertices.push(verts[2]);
             self.vertices.push(verts[3]);
+            //TODO the 3d floors use the middle texture of their master line
+            self.tex_coords.resize(self.vertices.len(), Vector2::<f32>::new());
 
             let plane = Self::to_plane(&verts[0], &verts[1], &verts[2]);
-            self.surfaces.push(Surface { type_, _type_index: type_index, vert_count, start_vert_index, _plane: plane, _control_sector: control_sector, _b_sky: false });
+            self.surfaces.push(Surface { type_, _type_index: type_index, vert_count, start_vert_index, texture: TextureID::new(), fade: 0, alpha: 1., group: 0, triangles: vec![], sector: control_sector, _plane: plane, _control_sector: control_sector, _b_sky: false });
         }
     }
 
-    fn create_side_surfaces_middle_seg(&mut self, v1: &Vector2<f32>, v2: &Vector2<f32>, type_index: i32, sides: &SidesS) {
-        
-
-        let mut verts: [Vector3<f32>;4] = [Vector3::<f32>::new(); 4];
-        verts[0].x = v1.x;
-        verts[2].x = v1.x;
-		verts[0].y = v1.y;
-        verts[2].y = v1.y;
-		verts[1].x = v2.x;
-        verts[3].x = v2.x;
-		verts[1].y = v2.y;
-        verts[3].y = v2.y;
-		verts[0].z = sides.v1_bottom as f32;
-		verts[1].z = sides.v2_bottom as f32;
-		verts[2].z = sides.v1_top as f32;
-		verts[3].z = sides.v2_top as f32;
-
+    #[allow(clippy::too_many_arguments)]
+    fn create_wall_surface(&mut self, side: &Side, part: usize, v1: &Vector2<f32>, v2: &Vector2<f32>, sides: &SidesS, b_sky: bool, tex_man: &TextureManager) {
+        let verts = Self::wall_verts(part, v1, v2, sides);
         let start_vert_index = self.vertices.len() as u32;
         let vert_count = 4;
-        self.vertices.push(verts[0]);
-        self.vertices.push(verts[1]);
-        self.vertices.push(verts[2]);
-        self.vertices.push(verts[3]);
+        self.vertices.extend_from_slice(&verts);
+        self.tex_coords.extend_from_slice(&Self::wall_tex_coords(side, part, v1, &verts, sides.tex_tops[part], tex_man));
 
         let plane = Self::to_plane(&verts[0], &verts[1], &verts[2]);
-        let type_ = SurfaceType::STMiddleWall;
+        let type_ = match part {
+            0 => SurfaceType::STUpperWall,
+            2 => SurfaceType::STLowerWall,
+            _ => SurfaceType::STMiddleWall
+        };
         let control_sector = -1;
 
-        self.surfaces.push(Surface { type_, _type_index: type_index, vert_count, start_vert_index, _plane: plane, _control_sector: control_sector, _b_sky: false });
-
-    }
-    fn create_side_surfaces_top_seg(&mut self, side: &Side, v1: &Vector2<f32>, v2: &Vector2<f32>, type_index: i32, sides: &mut SidesS, b_sky: bool, tex_man: &TextureManager) {
-        if b_sky || Self::is_top_side_visible(side, tex_man) {
-            let mut verts: [Vector3<f32>;4] = [Vector3::<f32>::new(); 4];
-            verts[0].x = v1.x;
-            verts[2].x = v1.x;
-            verts[0].y = v1.y;
-            verts[2].y = v1.y;
-            verts[1].x = v2.x;
-            verts[3].x = v2.x;
-            verts[1].y = v2.y;
-            verts[3].y = v2.y;
-            verts[0].z = sides.back.unwrap().v1_top_back as f32;
-            verts[1].z = sides.back.unwrap().v2_top_back as f32;
-            verts[2].z = sides.v1_top as f32;
-            verts[3].z = sides.v2_top as f32;
-
-            let start_vert_index = self.vertices.len() as u32;
-            let vert_count = 4;
-            self.vertices.push(verts[0]);
-            self.vertices.push(verts[1]);
-            self.vertices.push(verts[2]);
-            self.vertices.push(verts[3]);
-
-            let plane = Self::to_plane(&verts[0], &verts[1], &verts[2]);
-            let type_ = SurfaceType::STUpperWall;
-            let control_sector = -1;
-
-            self.surfaces.push(Surface { type_, _type_index: type_index, vert_count, start_vert_index, _plane: plane, _control_sector: control_sector, _b_sky: b_sky });
-        }
-
-        sides.v1_top = sides.back.unwrap().v1_top_back;
-        sides.v2_top = sides.back.unwrap().v2_top_back;
-    }
-    fn create_side_surfaces_bottom_seg(&mut self, side: &Side, v1: &Vector2<f32>, v2: &Vector2<f32>, type_index: i32, sides: &mut SidesS, tex_man: &TextureManager) {
-        if Self::is_bottom_side_visible(side, tex_man) {
-            let mut verts: [Vector3<f32>;4] = [Vector3::<f32>::new(); 4];
-            verts[0].x = v1.x;
-            verts[2].x = v1.x;
-            verts[0].y = v1.y;
-            verts[2].y = v1.y;
-            verts[1].x = v2.x;
-            verts[3].x = v2.x;
-            verts[1].y = v2.y;
-            verts[3].y = v2.y;
-            verts[0].z = sides.v1_bottom as f32;
-            verts[1].z = sides.v2_bottom as f32;
-            verts[2].z = sides.back.unwrap().v1_bottom_back as f32;
-            verts[3].z = sides.back.unwrap().v2_bottom_back as f32;
+        self.surfaces.push(Surface { type_, _type_index: side.index(), vert_count, start_vert_index, texture: side.get_texture(part), fade: 0, alpha: 1., group: 0, triangles: vec![], sector: side.sector, _plane: plane, _control_sector: control_sector, _b_sky: b_sky });
+    }
 
-            let start_vert_index = self.vertices.len() as u32;
-            let vert_count = 4;
-            self.vertices.push(verts[0]);
-            self.vertices.push(verts[1]);
-            self.vertices.push(verts[2]);
-            self.vertices.push(verts[3]);
+    /*
+     * The corners of the wall, the bottom ones come first.
+     * The upper and lower walls are cut off where the back sector goes past the front one, they are flat then.
+     */
+    fn wall_verts(part: usize, v1: &Vector2<f32>, v2: &Vector2<f32>, sides: &SidesS) -> [Vector3<f32>;4] {
+        let (v1_bottom, v2_bottom, v1_top, v2_top) = match (part, sides.back) {
+            (0, Some(back)) => (back.v1_top_back.min(sides.v1_top), back.v2_top_back.min(sides.v2_top), sides.v1_top, sides.v2_top),
+            (2, Some(back)) => (sides.v1_bottom, sides.v2_bottom, back.v1_bottom_back.max(sides.v1_bottom), back.v2_bottom_back.max(sides.v2_bottom)),
+            //the middle of a two sided line is in the opening between the sectors and only as high as its texture
+            (1, Some(back)) => {
+                let (mid_top, mid_bottom) = sides.mid_bounds.unwrap_or((f64::MAX, f64::MIN));
+                let v1_bottom = sides.v1_bottom.max(back.v1_bottom_back).max(mid_bottom);
+                let v2_bottom = sides.v2_bottom.max(back.v2_bottom_back).max(mid_bottom);
+                (v1_bottom, v2_bottom, sides.v1_top.min(back.v1_top_back).min(mid_top).max(v1_bottom), sides.v2_top.min(back.v2_top_back).min(mid_top).max(v2_bottom))
+            }
+            _ => (sides.v1_bottom, sides.v2_bottom, sides.v1_top, sides.v2_top)
+        };
+        [
+            Vector3::<f32> { x: v1.x, y: v1.y, z: v1_bottom as f32 },
+            Vector3::<f32> { x: v2.x, y: v2.y, z: v2_bottom as f32 },
+            Vector3::<f32> { x: v1.x, y: v1.y, z: v1_top as f32 },
+            Vector3::<f32> { x: v2.x, y: v2.y, z: v2_top as f32 }
+        ]
+    }
 
-            let plane = Self::to_plane(&verts[0], &verts[1], &verts[2]);
-            let type_ = SurfaceType::STLowerWall;
-            let control_sector = -1;
+    /*
+     * u goes along the wall from the start of the side and v goes down from the top of the texture.
+     * The offsets are in pixels of the texture, the scales make it repeat more often.
+     */
+    fn wall_tex_coords(side: &Side, part: usize, v1: &Vector2<f32>, verts: &[Vector3<f32>;4], tex_top: f64, tex_man: &TextureManager) -> [Vector2<f32>;4] {
+        let texture = tex_man.get_game_texture(side.get_texture(part), true).filter(|texture| texture.is_valid());
+        let (width, height) = texture.map_or((1., 1.), |texture| (texture.width as f64, texture.height as f64));
+        let x_scale = side.get_texture_x_scale(part);
+        let y_scale = side.get_texture_y_scale(part);
+
+        verts.map(|vert| {
+            let dist = Vector2::<f32>::new_params(vert.x - v1.x, vert.y - v1.y).length() as f64;
+            let u = (side.get_texture_x_offset(part) + dist * x_scale) / width;
+            let v = (tex_top - vert.z as f64 + side.get_texture_y_offset(part)) * y_scale / height;
+            Vector2::<f32>::new_params(u as f32, v as f32)
+        })
+    }
 
-            self.surfaces.push(Surface { type_, _type_index: type_index, vert_count, start_vert_index, _plane: plane, _control_sector: control_sector, _b_sky: false });
+    //the flats are aligned to the world, the offsets move them and the angle turns them around the origin
+    fn create_flat_tex_coords(&mut self, sector: &Sector, pos: usize, start_vert_index: u32, tex_man: &TextureManager) {
+        let texture = tex_man.get_game_texture(sector.get_texture(pos), true).filter(|texture| texture.is_valid());
+        let (width, height) = texture.map_or((64., 64.), |texture| (texture.width as f64, texture.height as f64));
+        let angle = sector.get_angle(pos).degrees().to_radians();
+        let (sin, cos) = (f64::sin(angle), f64::cos(angle));
+
+        for i in start_vert_index as usize..self.vertices.len() {
+            let x = self.vertices[i].x as f64;
+            let y = self.vertices[i].y as f64;
+            let rotated_x = x * cos - y * sin;
+            let rotated_y = x * sin + y * cos;
+            let u = (rotated_x + sector.get_x_offset(pos)) * sector.get_x_scale(pos) / width;
+            let v = (-rotated_y + sector.get_y_offset(pos)) * sector.get_y_scale(pos) / height;
+            self.tex_coords.push(Vector2::<f32>::new_params(u as f32, v as f32));
         }
+    }
 
-        sides.v1_bottom = sides.back.unwrap().v1_bottom_back;
-        sides.v2_bottom = sides.back.unwrap().v2_bottom_back;
+    //the height in the world of the texture of the part, for the pegging
+    fn texture_height(side: &Side, part: usize, tex_man: &TextureManager) -> f64 {
+        match tex_man.get_game_texture(side.get_texture(part), true) {
+            Some(texture) if texture.is_valid() => texture.get_display_height() / side.get_texture_y_scale(part),
+            _ => 0.
+        }
     }
 
     fn create_uvs(&mut self) {
@@ -434,38 +799,117 @@ impl LevelMesh {
             }
 
             if s.type_ == SurfaceType::STFloor || s.type_ == SurfaceType::STCeiling {
-                for j in 2..vert_count as u32 {
-                    if !Self::is_degenerate(&verts[pos as usize], &verts[(pos + j - 1) as usize], &verts[(pos + j) as usize]) {
-                        self.elements.push(pos);
-                        self.elements.push(pos + j - 1);
-                        self.elements.push(pos + j);
+                for triangle in s.triangles.chunks(3) {
+                    let (a, b, c) = (pos + triangle[0], pos + triangle[1], pos + triangle[2]);
+                    if !Self::is_degenerate(&verts[a as usize], &verts[b as usize], &verts[c as usize]) {
+                        self.elements.push(a);
+                        self.elements.push(b);
+                        self.elements.push(c);
                         self.mesh_surfaces.push(i as i32);
                     }
                 }
             }
+            //the walls keep their triangles when they have no height, they get it when the sectors move
             else if s.type_ == SurfaceType::STMiddleWall || s.type_ == SurfaceType::STLowerWall || s.type_ == SurfaceType::STUpperWall {
-                if !Self::is_degenerate(&verts[pos as usize], &verts[(pos + 1)as usize], &verts[(pos + 2) as usize]) {
-                    self.elements.push(pos + 0);
-                    self.elements.push(pos + 1);
-                    self.elements.push(pos + 2);
-                    self.mesh_surfaces.push(i as i32);
-                }
-                if !Self::is_degenerate(&verts[(pos + 1)as usize], &verts[(pos + 2) as usize], &verts[(pos + 3) as usize]) {
-                    self.elements.push(pos + 3);
-                    self.elements.push(pos + 2);
-                    self.elements.push(pos + 1);
-                    self.mesh_surfaces.push(i as i32);
-                }
+                self.elements.extend_from_slice(&[pos, pos + 1, pos + 2, pos + 3, pos + 2, pos + 1]);
+                self.mesh_surfaces.push(i as i32);
+                self.mesh_surfaces.push(i as i32);
             }
         }
     }
 
-    
+    fn sector_light(sector: &Sector) -> (i16, u32, u32) {
+        (sector.light_level, sector.color_map.light_color.d(), sector.color_map.fade_color.d())
+    }
+
+    fn create_lights(&mut self, doom_map: &LevelLocals) {
+        self.colors.resize(self.vertices.len(), Color::new(1., 1., 1., 1.));
+        for (i, sector) in doom_map.elements.sectors.iter().enumerate() {
+            self.sector_lights[i] = Self::sector_light(&sector.borrow_mut());
+        }
+        for i in 0..self.surfaces.len() {
+            Self::light_surface(self, doom_map, i);
+        }
+    }
+
+    /*
+     * The vertices of the surface get the light color of its sector and the light level in the alpha, the shader makes it darker with the distance.
+     * The walls along the x axis are lighter and the ones along the y axis are darker (the fake contrast, wall_hori_light and wall_vert_light).
+     */
+    fn light_surface(&mut self, doom_map: &LevelLocals, index: usize) {
+        let surface = &mut self.surfaces[index];
+        if surface.sector < 0 {return}
+        let sector = doom_map.elements.sectors[surface.sector as usize].borrow_mut();
+        let mut light = sector.light_level as i32;
+        let is_wall = !matches!(surface.type_, SurfaceType::STFloor | SurfaceType::STCeiling);
+        if is_wall && surface._control_sector < 0 {
+            let side = doom_map.elements.sides[surface._type_index as usize].borrow_mut();
+            let delta = doom_map.elements.lines[side.linedef as usize].borrow_mut().delta();
+            if delta.y == 0. {light += doom_map.wall_hori_light as i32}
+            else if delta.x == 0. {light += doom_map.wall_vert_light as i32}
+        }
+        let mut color = Self::to_color(sector.color_map.light_color.d());
+        color.a = light.clamp(0, 255) as f32 / 255.;
+        let fade = sector.color_map.fade_color.d() & 0xffffff;
+        if surface.fade != fade {
+            surface.fade = fade;
+            self.fade_groups.insert(surface.group);
+        }
+
+        let start = surface.start_vert_index as usize;
+        self.colors[start..start + surface.vert_count as usize].fill(color);
+    }
+
+    /*
+     * The bsp is split into subtrees that fit in CHUNK_SIZE, each of them is a group.
+     * A surface is in the group of the subsector at its center, the walls are on the line between two subsectors so either one is close.
+     */
+    fn create_groups(&mut self, elements: &LevelElements) {
+        let mut subsector_groups = vec![-1; elements.subsectors.len()];
+        let mut group_count = 0;
+        if let Some(root) = elements.root_child() {
+            Self::group_child(elements, root, None, -1, &mut subsector_groups, &mut group_count);
+        }
+
+        for surface in &mut self.surfaces {
+            let start = surface.start_vert_index as usize;
+            let verts = &self.vertices[start..start + surface.vert_count as usize];
+            let count = verts.len().max(1) as f64;
+            let x = verts.iter().map(|vert| vert.x as f64).sum::<f64>() / count;
+            let y = verts.iter().map(|vert| vert.y as f64).sum::<f64>() / count;
+            let subsector = elements.point_in_subsector(x, y);
+            surface.group = if subsector >= 0 {subsector_groups[subsector as usize]} else {0};
+        }
+    }
+
+    fn group_child(elements: &LevelElements, child: ChildNode, bbox: Option<[f64;4]>, group: i32, subsector_groups: &mut Vec<i32>, group_count: &mut i32) {
+        let mut group = group;
+        let fits = bbox.is_some_and(|bbox| (bbox[BOXTOP] - bbox[BOXBOTTOM]).max(bbox[BOXRIGHT] - bbox[BOXLEFT]) <= CHUNK_SIZE);
+        if group < 0 && (child.node < 0 || fits) {
+            group = *group_count;
+            *group_count += 1;
+        }
+        if child.node < 0 {
+            subsector_groups[child.subsector as usize] = group;
+            return
+        }
+        let children = elements.nodes[child.node as usize].borrow_mut().children;
+        for (side, child_node) in children.into_iter().enumerate() {
+            Self::group_child(elements, child_node, Some(elements.node_bbox(child.node, side)), group, subsector_groups, group_count);
+        }
+    }
+
     //Functions for checking the surfaces/sector
-    fn is_top_side_sky(front_sector: &Sector, back_sector: &Sector) -> bool {
-        Self::is_sky_sector(front_sector) && Self::is_sky_sector(back_sector)
+    //the sky hack, the upper wall between two sectors with a sky ceiling is sky too
+    fn is_top_side_sky(front_sector: &Sector, back_sector: &Sector, sky_flat_num: TextureID) -> bool {
+        Self::is_sky_sector(front_sector, sky_flat_num) && Self::is_sky_sector(back_sector, sky_flat_num)
     }
     
+    fn is_mid_side_visible(side: &Side, tex_man: &TextureManager) -> bool {
+        let tex = tex_man.get_game_texture(side.get_texture(Sides::Mid.bits() as usize), true);
+        tex.is_some() && tex.unwrap().is_valid()
+    }
+
     fn is_top_side_visible(side: &Side, tex_man: &TextureManager) -> bool {
         let tex = tex_man.get_game_texture(side.get_texture(Sides::Top.bits() as usize), true);
         tex.is_some() && tex.unwrap().is_valid()
@@ -476,11 +920,12 @@ impl LevelMesh {
         tex.is_some() && tex.unwrap().is_valid()
     }
     
-    fn is_sky_sector(sector: &Sector) -> bool {
+    fn is_sky_sector(sector: &Sector, sky_flat_num: TextureID) -> bool {
+        Self::is_sky_flat(sector.get_texture(SectorE::Ceiling as usize), sky_flat_num)
+    }
 
-        //TODO get sky_flat_num from somewehere? is global value in gzdoom
-        let temp_sky_num = TextureID{tex_num: 0}; //TODO ^
-        sector.get_texture(SectorE::Ceiling as usize) == temp_sky_num
+    fn is_sky_flat(texture: TextureID, sky_flat_num: TextureID) -> bool {
+        sky_flat_num.exists() && texture == sky_flat_num
     }
     
     fn is_control_sector(_sector: SectorIndex) -> bool {false}
@@ -522,4 +967,17 @@ impl LevelMesh {
         let limit: f32 = 1.0e-6;
         return crosslengthsqr <= limit;
     }
-}
\ No newline at end of file
+}
+
+impl LevelLocals {
+    //the level mesh is only held by the level, it is taken out so it can look at the level while it changes
+    pub fn update_level_mesh(&mut self, tex_man: &TextureManager) -> (Vec<usize>, HashSet<i32>) {
+        let mut level_mesh = std::mem::take(&mut self.level_mesh);
+        let changed = match Rc::get_mut(&mut level_mesh) {
+            Some(mesh) => mesh.update(self, tex_man),
+            None => (vec![], HashSet::new())
+        };
+        self.level_mesh = level_mesh;
+        changed
+    }
+}
@@ -0,0 +1,381 @@
+use num_derive::{FromPrimitive, ToPrimitive};
+
+use super::LevelLocals;
+use super::level_elements::{SectorIndex, SectorE};
+use super::level_floor::{Floor, Elevator};
+use super::level_ceiling::Ceiling;
+use super::level_doors::Door;
+use super::level_plats::Plat;
+use super::level_pillar::Pillar;
+use super::level_thinker::{ThinkerData, ThinkerIndex, STAT_SECTOREFFECT};
+use super::level_serializer::{Serializable, Serializer};
+
+//stair building flags (the use_specials argument of ev_build_stairs)
+pub const STAIR_USE_SPECIALS: i32 = 1;
+pub const STAIR_SYNC: i32 = 2;
+pub const STAIR_CRUSH: i32 = 4;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum CrushMode {
+    #[default]
+    Default,
+    Doom,
+    Hexen,
+    Slowdown
+}
+
+impl CrushMode {
+    //the crushmode argument of the specials, 0 takes the default of the special itself
+    pub fn from_arg(arg: i32, hexen: bool) -> CrushMode {
+        match arg {
+            1 => CrushMode::Doom,
+            2 => CrushMode::Hexen,
+            3 => CrushMode::Slowdown,
+            _ => if hexen {CrushMode::Hexen} else {CrushMode::Doom}
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum DoorType {
+    #[default]
+    Close,
+    Open,
+    Raise,
+    WaitRaise,
+    CloseWaitOpen,
+    WaitClose
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum FloorType {
+    #[default]
+    LowerToLowest,
+    LowerToNearest,
+    LowerToHighest,
+    LowerByValue,
+    RaiseByValue,
+    RaiseToHighest,
+    RaiseToNearest,
+    RaiseAndCrush,
+    RaiseAndCrushDoom,
+    CrushStop,
+    LowerInstant,
+    RaiseInstant,
+    MoveToValue,
+    RaiseToLowestCeiling,
+    RaiseByTexture,
+    LowerAndChange,
+    Raise24,
+    Raise24AndChange,
+    RaiseToCeiling,
+    RaiseToLowest,
+    LowerToCeiling,
+    LowerByTexture,
+    LowerToLowestCeiling,
+    ToCeilingInstant,
+
+    //only used by the movers themselves
+    DonutRaise,
+    BuildStair,
+    WaitStair,
+    ResetStair
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum CeilingType {
+    #[default]
+    LowerByValue,
+    RaiseByValue,
+    MoveToValue,
+    LowerToHighestFloor,
+    LowerInstant,
+    RaiseInstant,
+    CrushAndRaise,
+    LowerAndCrush,
+    CrushRaiseAndStay,
+    RaiseToNearest,
+    LowerToLowest,
+    LowerToFloor,
+    RaiseToHighest,
+    ToHighestInstant,
+    LowerToNearest,
+    RaiseToLowest,
+    RaiseToHighestFloor,
+    ToFloorInstant,
+    RaiseByTexture,
+    LowerByTexture,
+    LowerToHighest,
+    RaiseToFloor
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum PlatType {
+    #[default]
+    PerpetualRaise,
+    DownWaitUpStay,
+    DownWaitUpStayStone,
+    UpNearestWaitDownStay,
+    UpWaitDownStay,
+    DownByValue,
+    UpByValue,
+    UpByValueStay,
+    RaiseAndStay,
+    Toggle,
+    DownToNearestFloor,
+    DownToLowestCeiling,
+    RaiseAndStayLockout
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StairType {
+    BuildUp,
+    BuildDown
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum PillarType {
+    #[default]
+    Build,
+    Open
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum ElevatorType {
+    #[default]
+    Up,
+    Down,
+    Current,
+    Raise,
+    Lower
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ChangeType {
+    TriggerChangeOnly,
+    NumericChangeOnly
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MoveResult {
+    Ok,
+    Crushed,
+    PastDest
+}
+
+//the thinkers that move the floor and/or ceiling of a sector
+pub enum SectorMover {
+    Floor(Floor),
+    Ceiling(Ceiling),
+    Door(Door),
+    Plat(Plat),
+    Elevator(Elevator),
+    Pillar(Pillar)
+}
+
+impl SectorMover {
+    pub fn sector(&self) -> SectorIndex {
+        match self {
+            SectorMover::Floor(floor) => floor.sector,
+            SectorMover::Ceiling(ceiling) => ceiling.sector,
+            SectorMover::Door(door) => door.sector,
+            SectorMover::Plat(plat) => plat.sector,
+            SectorMover::Elevator(elevator) => elevator.sector,
+            SectorMover::Pillar(pillar) => pillar.sector
+        }
+    }
+
+    pub fn moves_floor(&self) -> bool {
+        !matches!(self, SectorMover::Ceiling(_) | SectorMover::Door(_))
+    }
+
+    pub fn moves_ceiling(&self) -> bool {
+        !matches!(self, SectorMover::Floor(_) | SectorMover::Plat(_))
+    }
+
+    pub fn class_name(&self) -> &'static str {
+        match self {
+            SectorMover::Floor(_) => "Floor",
+            SectorMover::Ceiling(_) => "Ceiling",
+            SectorMover::Door(_) => "Door",
+            SectorMover::Plat(_) => "Plat",
+            SectorMover::Elevator(_) => "Elevator",
+            SectorMover::Pillar(_) => "Pillar"
+        }
+    }
+
+    pub fn from_class(name: &str) -> Option<SectorMover> {
+        match name {
+            "Floor" => Some(SectorMover::Floor(Floor::default())),
+            "Ceiling" => Some(SectorMover::Ceiling(Ceiling::default())),
+            "Door" => Some(SectorMover::Door(Door::default())),
+            "Plat" => Some(SectorMover::Plat(Plat::default())),
+            "Elevator" => Some(SectorMover::Elevator(Elevator::default())),
+            "Pillar" => Some(SectorMover::Pillar(Pillar::default())),
+            _ => None
+        }
+    }
+}
+
+impl Serializable for SectorMover {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        match self {
+            SectorMover::Floor(floor) => floor.serialize(arc),
+            SectorMover::Ceiling(ceiling) => ceiling.serialize(arc),
+            SectorMover::Door(door) => door.serialize(arc),
+            SectorMover::Plat(plat) => plat.serialize(arc),
+            SectorMover::Elevator(elevator) => elevator.serialize(arc),
+            SectorMover::Pillar(pillar) => pillar.serialize(arc)
+        }
+    }
+}
+
+/*
+ * Moving the planes of the sectors (sector_t::MoveFloor/MoveCeiling of zdoom).
+ * The destinations are the d of the plane, not the height.
+ */
+impl LevelLocals {
+    //the mover becomes a thinker, the sector takes it as floor_data and/or ceiling_data
+    pub fn add_mover(&mut self, mover: SectorMover) -> ThinkerIndex {
+        let (sector, moves_floor, moves_ceiling) = (mover.sector(), mover.moves_floor(), mover.moves_ceiling());
+        let index = self.thinkers.create(ThinkerData::Mover(mover), STAT_SECTOREFFECT);
+        let mut sector = self.elements.sectors[sector as usize].borrow_mut();
+        if moves_floor {sector.floor_data = index}
+        if moves_ceiling {sector.ceiling_data = index}
+        index
+    }
+
+    pub fn release_sector(&mut self, mover: &SectorMover, index: ThinkerIndex) {
+        let mut sector = self.elements.sectors[mover.sector() as usize].borrow_mut();
+        if sector.floor_data == index {sector.floor_data = -1}
+        if sector.ceiling_data == index {sector.ceiling_data = -1}
+    }
+
+    pub fn mover(&self, index: ThinkerIndex) -> Option<&SectorMover> {
+        match self.thinkers.get(index) {
+            Some(ThinkerData::Mover(mover)) => Some(mover),
+            _ => None
+        }
+    }
+
+    pub fn mover_mut(&mut self, index: ThinkerIndex) -> Option<&mut SectorMover> {
+        match self.thinkers.get_mut(index) {
+            Some(ThinkerData::Mover(mover)) => Some(mover),
+            _ => None
+        }
+    }
+
+    //one tic of the mover, false when it is finished
+    pub fn tick_mover(&mut self, mover: &mut SectorMover) -> bool {
+        match mover {
+            SectorMover::Floor(floor) => Self::tick_floor(self, floor),
+            SectorMover::Ceiling(ceiling) => Self::tick_ceiling(self, ceiling),
+            SectorMover::Door(door) => Self::tick_door(self, door),
+            SectorMover::Plat(plat) => Self::tick_plat(self, plat),
+            SectorMover::Elevator(elevator) => Self::tick_elevator(self, elevator),
+            SectorMover::Pillar(pillar) => Self::tick_pillar(self, pillar)
+        }
+    }
+
+    /*
+     * Checks if everything in the sector still fits after the plane moved (P_ChangeSector of zdoom).
+     * Returns true when something is in the way, when crush >= 0 the things that don't fit take damage.
+     */
+    pub fn change_sector(&mut self, sector: SectorIndex, crush: i32) -> bool {
+        let mut blocked = false;
+        for thing in Self::sector_things(self, sector) {
+            if !Self::change_sector_thing(self, thing, crush) {blocked = true}
+        }
+        blocked
+    }
+
+    pub fn move_floor(&mut self, sector: SectorIndex, speed: f64, dest: f64, crush: i32, direction: i32, hexen_crush: bool) -> MoveResult {
+        let floor = SectorE::Floor as usize;
+        let sector_rc = self.elements.sectors[sector as usize].clone();
+        let last_pos = sector_rc.borrow().floorplane.fd();
+        let mut dest = dest;
+
+        let (move_dest, past_dest) = {
+            let sec = sector_rc.borrow();
+            if direction < 0 {
+                let move_dest = sec.floorplane.get_changed_height(-speed);
+                (move_dest, move_dest >= dest)
+            }
+            else {
+                //keep the floor from going through the ceiling
+                if !sec.floorplane.is_slope() && !sec.ceilingplane.is_slope() && -dest > sec.ceilingplane.fd() {
+                    dest = -sec.ceilingplane.fd();
+                }
+                let move_dest = sec.floorplane.get_changed_height(speed);
+                (move_dest, move_dest <= dest)
+            }
+        };
+
+        let new_pos = if past_dest {dest} else {move_dest};
+        sector_rc.borrow_mut().floorplane.set_d(new_pos);
+        let blocked = Self::change_sector(self, sector, crush);
+        if blocked && !past_dest && direction > 0 && crush >= 0 && !hexen_crush {
+            //doom style crushing keeps going
+            let mut sec = sector_rc.borrow_mut();
+            let diff = sec.floorplane.height_diff(last_pos);
+            sec.change_plane_tex_z(floor, diff, Some(&self.elements));
+            return MoveResult::Crushed
+        }
+        if blocked {
+            sector_rc.borrow_mut().floorplane.set_d(last_pos);
+            Self::change_sector(self, sector, crush);
+            if !past_dest {return MoveResult::Crushed}
+        }
+        else {
+            let mut sec = sector_rc.borrow_mut();
+            let diff = sec.floorplane.height_diff(last_pos);
+            sec.change_plane_tex_z(floor, diff, Some(&self.elements));
+        }
+        if past_dest {MoveResult::PastDest} else {MoveResult::Ok}
+    }
+
+    pub fn move_ceiling(&mut self, sector: SectorIndex, speed: f64, dest: f64, crush: i32, direction: i32, hexen_crush: bool) -> MoveResult {
+        let ceiling = SectorE::Ceiling as usize;
+        let sector_rc = self.elements.sectors[sector as usize].clone();
+        let last_pos = sector_rc.borrow().ceilingplane.fd();
+        let mut dest = dest;
+
+        let (move_dest, past_dest) = {
+            let sec = sector_rc.borrow();
+            if direction < 0 {
+                //keep the ceiling from going through the floor
+                if !sec.floorplane.is_slope() && !sec.ceilingplane.is_slope() && dest < -sec.floorplane.fd() {
+                    dest = -sec.floorplane.fd();
+                }
+                let move_dest = sec.ceilingplane.get_changed_height(-speed);
+                (move_dest, move_dest <= dest)
+            }
+            else {
+                let move_dest = sec.ceilingplane.get_changed_height(speed);
+                (move_dest, move_dest >= dest)
+            }
+        };
+
+        let new_pos = if past_dest {dest} else {move_dest};
+        sector_rc.borrow_mut().ceilingplane.set_d(new_pos);
+        let blocked = Self::change_sector(self, sector, crush);
+        if blocked && !past_dest && direction < 0 && crush >= 0 && !hexen_crush {
+            //doom style crushing keeps going
+            let mut sec = sector_rc.borrow_mut();
+            let diff = sec.ceilingplane.height_diff(last_pos);
+            sec.change_plane_tex_z(ceiling, diff, Some(&self.elements));
+            return MoveResult::Crushed
+        }
+        if blocked {
+            sector_rc.borrow_mut().ceilingplane.set_d(last_pos);
+            Self::change_sector(self, sector, crush);
+            if !past_dest {return MoveResult::Crushed}
+        }
+        else {
+            let mut sec = sector_rc.borrow_mut();
+            let diff = sec.ceilingplane.height_diff(last_pos);
+            sec.change_plane_tex_z(ceiling, diff, Some(&self.elements));
+        }
+        if past_dest {MoveResult::PastDest} else {MoveResult::Ok}
+    }
+}
@@ -0,0 +1,104 @@
+use super::LevelLocals;
+use super::level_elements::{LineIndex, SectorIndex, SectorE};
+use super::level_movers::*;
+use super::level_serializer::{Serializable, Serializer};
+
+//DPillar of zdoom, moves the floor and ceiling towards or away from each other
+#[derive(Default)]
+pub struct Pillar {
+    pub sector: SectorIndex,
+    pub type_: PillarType,
+    pub floor_speed: f64,
+    pub ceiling_speed: f64,
+    pub floor_target: f64,
+    pub ceiling_target: f64,
+    pub crush: i32,
+    pub hexen_crush: bool
+}
+
+impl Serializable for Pillar {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        arc.int("sector", &mut self.sector)
+            .enumeration("type", &mut self.type_)
+            .float("floorspeed", &mut self.floor_speed)
+            .float("ceilingspeed", &mut self.ceiling_speed)
+            .float("floortarget", &mut self.floor_target)
+            .float("ceilingtarget", &mut self.ceiling_target)
+            .int("crush", &mut self.crush)
+            .boolean("hexencrush", &mut self.hexen_crush);
+    }
+}
+
+/*
+ * The pillars (p_pillar.cpp of zdoom)
+ */
+#[allow(clippy::too_many_arguments)]
+impl LevelLocals {
+    pub fn ev_do_pillar(&mut self, type_: PillarType, line: LineIndex, tag: i32, speed: f64, height: f64, height2: f64, crush: i32, hexen_crush: bool) -> bool {
+        let mut rtn = false;
+        for sector in Self::tagged_sectors(self, tag, line) {
+            let (floor, ceiling) = {
+                let sec = self.sectors[sector as usize].borrow();
+                if sec.plane_moving(SectorE::Floor as usize) || sec.plane_moving(SectorE::Ceiling as usize) {continue}
+                (sec.center_floor(), sec.center_ceiling())
+            };
+            //build only when open and open only when closed
+            if type_ == PillarType::Build && floor == ceiling {continue}
+            if type_ == PillarType::Open && floor != ceiling {continue}
+
+            rtn = true;
+            let pillar = Self::create_pillar(self, sector, type_, speed, height, height2, crush, hexen_crush);
+            Self::add_mover(self, SectorMover::Pillar(pillar));
+        }
+        rtn
+    }
+
+    fn create_pillar(&self, sector: SectorIndex, type_: PillarType, speed: f64, floor_dist: f64, ceiling_dist: f64, crush: i32, hexen_crush: bool) -> Pillar {
+        let sec = self.sectors[sector as usize].borrow();
+        let spot = sec.center_spot;
+        let (center_floor, center_ceiling) = (sec.center_floor(), sec.center_ceiling());
+        let (mut floor_dist, mut ceiling_dist) = (floor_dist, ceiling_dist);
+
+        let (floor_target, ceiling_target) = if type_ == PillarType::Build {
+            //a height of 0 meets halfway
+            let new_height = if floor_dist == 0. {(center_floor + center_ceiling) / 2.} else {center_floor + floor_dist};
+            floor_dist = new_height - center_floor;
+            ceiling_dist = center_ceiling - new_height;
+            (sec.floorplane.point_to_dist(&spot, new_height), sec.ceilingplane.point_to_dist(&spot, new_height))
+        }
+        else {
+            //a height of 0 opens to the surrounding sectors
+            let floor_height = if floor_dist == 0. {Self::find_lowest_floor_surrounding(self, sector)} else {center_floor - floor_dist};
+            let ceiling_height = if ceiling_dist == 0. {Self::find_highest_ceiling_surrounding(self, sector)} else {center_ceiling + ceiling_dist};
+            floor_dist = center_floor - floor_height;
+            ceiling_dist = ceiling_height - center_ceiling;
+            (sec.floorplane.point_to_dist(&spot, floor_height), sec.ceilingplane.point_to_dist(&spot, ceiling_height))
+        };
+
+        //the part that travels the farthest gets the speed, the other one arrives at the same time
+        let (floor_speed, ceiling_speed) = if floor_dist > ceiling_dist {(speed, speed * ceiling_dist / floor_dist)}
+            else {(speed * floor_dist / ceiling_dist, speed)};
+
+        //TODO the pillar sound
+        Pillar { sector, type_, floor_speed, ceiling_speed, floor_target, ceiling_target, crush, hexen_crush }
+    }
+
+    pub fn tick_pillar(&mut self, pillar: &mut Pillar) -> bool {
+        let (old_floor, old_ceiling) = {
+            let sec = self.sectors[pillar.sector as usize].borrow();
+            (sec.floorplane.fd(), sec.ceilingplane.fd())
+        };
+        let direction = if pillar.type_ == PillarType::Build {1} else {-1};
+        let r = Self::move_floor(self, pillar.sector, pillar.floor_speed, pillar.floor_target, pillar.crush, direction, pillar.hexen_crush);
+        let s = Self::move_ceiling(self, pillar.sector, pillar.ceiling_speed, pillar.ceiling_target, pillar.crush, -direction, pillar.hexen_crush);
+
+        if r == MoveResult::PastDest && s == MoveResult::PastDest {return false}
+        if r == MoveResult::Crushed {
+            Self::move_floor(self, pillar.sector, pillar.floor_speed, old_floor, -1, -1, pillar.hexen_crush);
+        }
+        if s == MoveResult::Crushed {
+            Self::move_ceiling(self, pillar.sector, pillar.ceiling_speed, old_ceiling, -1, 1, pillar.hexen_crush);
+        }
+        true
+    }
+}
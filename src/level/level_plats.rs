@@ -0,0 +1,241 @@
+use num_derive::{FromPrimitive, ToPrimitive};
+
+use super::LevelLocals;
+use super::level_elements::{LineIndex, SectorIndex, SectorE, PlaneFlags};
+use super::level_movers::*;
+use super::level_serializer::{Serializable, Serializer};
+use super::level_thinker::STAT_SECTOREFFECT;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum PlatStatus {
+    #[default]
+    Up,
+    Down,
+    Waiting,
+    InStasis
+}
+
+//DPlat of zdoom, the lifts and platforms
+#[derive(Default)]
+pub struct Plat {
+    pub sector: SectorIndex,
+    pub type_: PlatType,
+    pub tag: i32,
+    pub speed: f64,
+    pub low: f64,
+    pub high: f64,
+    pub wait: i32,
+    pub count: i32,
+    pub status: PlatStatus,
+    pub old_status: PlatStatus,
+    pub crush: i32
+}
+
+impl Plat {
+    //doors close on lifts instead of the floor
+    pub fn is_lift(&self) -> bool {
+        self.type_ == PlatType::DownWaitUpStay || self.type_ == PlatType::DownWaitUpStayStone
+    }
+}
+
+impl Serializable for Plat {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        arc.int("sector", &mut self.sector)
+            .enumeration("type", &mut self.type_)
+            .int("tag", &mut self.tag)
+            .float("speed", &mut self.speed)
+            .float("low", &mut self.low)
+            .float("high", &mut self.high)
+            .int("wait", &mut self.wait)
+            .int("count", &mut self.count)
+            .enumeration("status", &mut self.status)
+            .enumeration("oldstatus", &mut self.old_status)
+            .int("crush", &mut self.crush);
+    }
+}
+
+/*
+ * The plats (p_plats.cpp of zdoom)
+ */
+#[allow(clippy::too_many_arguments)]
+impl LevelLocals {
+    pub fn ev_do_plat(&mut self, tag: i32, line: LineIndex, type_: PlatType, height: f64, speed: f64, delay: i32, lip: i32, change: i32) -> bool {
+        let mut rtn = false;
+        //activate the plats of the type that are in stasis
+        if tag != 0 {
+            match type_ {
+                PlatType::Toggle => {
+                    rtn = true;
+                    Self::activate_in_stasis_plat(self, tag);
+                }
+                PlatType::PerpetualRaise => Self::activate_in_stasis_plat(self, tag),
+                _ => ()
+            }
+        }
+
+        let lip = lip as f64;
+        for sector in Self::tagged_sectors(self, tag, line) {
+            let sector_rc = self.elements.sectors[sector as usize].clone();
+            if sector_rc.borrow().plane_moving(SectorE::Floor as usize) {continue}
+            rtn = true;
+
+            if change != 0 {
+                if line >= 0 {
+                    let front = self.lines[line as usize].borrow().front_sector;
+                    let texture = self.sectors[front as usize].borrow().get_texture(SectorE::Floor as usize);
+                    sector_rc.borrow_mut().set_texture(SectorE::Floor as usize, texture);
+                }
+                if change == 1 {sector_rc.borrow_mut().clear_special()}
+            }
+
+            let (spot, floor_d, center_floor) = {
+                let sec = sector_rc.borrow();
+                (sec.center_spot, sec.floorplane.fd(), sec.center_floor())
+            };
+            let dist = |height: f64| sector_rc.borrow().floorplane.point_to_dist(&spot, height);
+
+            //a raising plat that bounced off the ceiling goes back to where it started
+            let mut plat = Plat { sector, type_, tag, speed, low: floor_d, high: floor_d, wait: delay, count: 0, status: PlatStatus::Up, old_status: PlatStatus::Up, crush: -1 };
+
+            match type_ {
+                PlatType::RaiseAndStay | PlatType::RaiseAndStayLockout => {
+                    plat.high = dist(Self::find_next_highest_floor(self, sector));
+                    plat.status = PlatStatus::Up;
+                    sector_rc.borrow_mut().clear_special();
+                }
+                PlatType::UpByValue | PlatType::UpByValueStay => {
+                    plat.high = dist(center_floor + height);
+                    plat.status = PlatStatus::Up;
+                }
+                PlatType::DownByValue => {
+                    plat.low = dist(center_floor - height);
+                    plat.status = PlatStatus::Down;
+                }
+                PlatType::DownWaitUpStay | PlatType::DownWaitUpStayStone => {
+                    plat.low = dist(Self::find_lowest_floor_surrounding(self, sector) + lip).max(floor_d);
+                    plat.status = PlatStatus::Down;
+                }
+                PlatType::UpNearestWaitDownStay | PlatType::UpWaitDownStay => {
+                    let new_height = if type_ == PlatType::UpNearestWaitDownStay {Self::find_next_highest_floor(self, sector)}
+                        else {Self::find_highest_floor_surrounding(self, sector)};
+                    plat.high = dist(new_height).min(floor_d);
+                    plat.status = PlatStatus::Up;
+                }
+                PlatType::PerpetualRaise => {
+                    plat.low = dist(Self::find_lowest_floor_surrounding(self, sector) + lip).max(floor_d);
+                    plat.high = dist(Self::find_highest_floor_surrounding(self, sector)).min(floor_d);
+                    plat.status = if self.random.random() & 1 != 0 {PlatStatus::Up} else {PlatStatus::Down};
+                }
+                PlatType::Toggle => {
+                    //toggles between the ceiling and the floor and crushes anything in the way
+                    plat.crush = 10;
+                    plat.low = dist(Self::find_lowest_ceiling_point(self, sector));
+                    plat.status = PlatStatus::Down;
+                }
+                PlatType::DownToNearestFloor => {
+                    plat.low = dist(Self::find_next_lowest_floor(self, sector) + lip);
+                    plat.status = PlatStatus::Down;
+                }
+                PlatType::DownToLowestCeiling => {
+                    plat.low = dist(Self::find_lowest_ceiling_surrounding(self, sector)).max(floor_d);
+                    plat.status = PlatStatus::Down;
+                }
+            }
+            //TODO the plat sounds
+            Self::add_mover(self, SectorMover::Plat(plat));
+        }
+        rtn
+    }
+
+    pub fn tick_plat(&mut self, plat: &mut Plat) -> bool {
+        match plat.status {
+            PlatStatus::Up => {
+                let res = Self::move_floor(self, plat.sector, plat.speed, plat.high, plat.crush, 1, false);
+                if res == MoveResult::Crushed && plat.crush == -1 {
+                    plat.count = plat.wait;
+                    plat.status = PlatStatus::Down;
+                }
+                else if res == MoveResult::PastDest {
+                    if plat.type_ == PlatType::Toggle {
+                        //the toggles wait for the next activation
+                        plat.old_status = plat.status;
+                        plat.status = PlatStatus::InStasis;
+                        return true
+                    }
+                    plat.count = plat.wait;
+                    plat.status = PlatStatus::Waiting;
+                    match plat.type_ {
+                        PlatType::RaiseAndStayLockout => {
+                            //blocked instead of keeping the thinker like heretic did
+                            self.sectors[plat.sector as usize].borrow_mut().change_plane_flags(SectorE::Floor as usize, 0, PlaneFlags::Blocked.bits());
+                            return false
+                        }
+                        PlatType::RaiseAndStay | PlatType::DownByValue | PlatType::DownWaitUpStay | PlatType::DownWaitUpStayStone |
+                            PlatType::UpByValueStay | PlatType::DownToNearestFloor | PlatType::DownToLowestCeiling => return false,
+                        _ => ()
+                    }
+                }
+                true
+            }
+            PlatStatus::Down => {
+                let res = Self::move_floor(self, plat.sector, plat.speed, plat.low, -1, -1, false);
+                if res == MoveResult::PastDest {
+                    if plat.type_ == PlatType::Toggle {
+                        plat.old_status = plat.status;
+                        plat.status = PlatStatus::InStasis;
+                    }
+                    else {
+                        plat.count = plat.wait;
+                        plat.status = PlatStatus::Waiting;
+                        if matches!(plat.type_, PlatType::UpWaitDownStay | PlatType::UpNearestWaitDownStay | PlatType::UpByValue) {return false}
+                    }
+                }
+                else if res == MoveResult::Crushed && plat.crush < 0 && plat.type_ != PlatType::Toggle {
+                    plat.status = PlatStatus::Up;
+                    plat.count = plat.wait;
+                }
+                //the raise types are removed when they bounced, so they can be tried again
+                !matches!(plat.type_, PlatType::UpByValueStay | PlatType::RaiseAndStay | PlatType::RaiseAndStayLockout)
+            }
+            PlatStatus::Waiting => {
+                if plat.count > 0 {
+                    plat.count -= 1;
+                    if plat.count == 0 {
+                        let floor_d = self.sectors[plat.sector as usize].borrow().floorplane.fd();
+                        plat.status = if floor_d == plat.low {PlatStatus::Up} else {PlatStatus::Down};
+                    }
+                }
+                true
+            }
+            PlatStatus::InStasis => true
+        }
+    }
+
+    pub fn activate_in_stasis_plat(&mut self, tag: i32) {
+        for index in self.thinkers.stat_list(STAT_SECTOREFFECT) {
+            if let Some(SectorMover::Plat(plat)) = Self::mover_mut(self, index) {
+                if plat.tag != tag || plat.status != PlatStatus::InStasis {continue}
+                if plat.type_ == PlatType::Toggle {
+                    plat.status = if plat.old_status == PlatStatus::Up {PlatStatus::Down} else {PlatStatus::Up};
+                }
+                else {plat.status = plat.old_status}
+            }
+        }
+    }
+
+    pub fn ev_stop_plat(&mut self, tag: i32, remove: bool) -> bool {
+        for index in self.thinkers.stat_list(STAT_SECTOREFFECT) {
+            let plat = match Self::mover_mut(self, index) {
+                Some(SectorMover::Plat(plat)) => plat,
+                _ => continue
+            };
+            if plat.status == PlatStatus::InStasis || plat.tag != tag {continue}
+            if remove {Self::destroy_thinker(self, index)}
+            else {
+                plat.old_status = plat.status;
+                plat.status = PlatStatus::InStasis;
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,519 @@
+use crate::vector::Angle;
+
+use super::LevelLocals;
+use super::level_actor::{Actor, ActorIndex, PlayerState};
+use super::level_info::*;
+use super::level_map::{MELEERANGE, MISSILERANGE};
+
+//weapontype_t, the weapons of the player
+pub type WeaponType = usize;
+pub const WP_FIST: WeaponType = 0;
+pub const WP_PISTOL: WeaponType = 1;
+pub const WP_SHOTGUN: WeaponType = 2;
+pub const WP_CHAINGUN: WeaponType = 3;
+pub const WP_MISSILE: WeaponType = 4;
+pub const WP_PLASMA: WeaponType = 5;
+pub const WP_BFG: WeaponType = 6;
+pub const WP_CHAINSAW: WeaponType = 7;
+pub const WP_SUPERSHOTGUN: WeaponType = 8;
+pub const NUMWEAPONS: usize = 9;
+pub const WP_NOCHANGE: WeaponType = 10; //no weapon is pending
+
+//ammotype_t
+pub type AmmoType = usize;
+pub const AM_CLIP: AmmoType = 0;
+pub const AM_SHELL: AmmoType = 1;
+pub const AM_CELL: AmmoType = 2;
+pub const AM_MISL: AmmoType = 3;
+pub const NUMAMMO: usize = 4;
+pub const AM_NOAMMO: AmmoType = 5; //the fist and the chainsaw
+
+pub const MAXAMMO: [i32; NUMAMMO] = [200, 50, 300, 50];
+pub const CLIPAMMO: [i32; NUMAMMO] = [10, 4, 20, 1]; //the ammo of a clip, the boxes have five of them
+
+//the overlays of the player view, the weapon and its flash
+pub const PS_WEAPON: usize = 0;
+pub const PS_FLASH: usize = 1;
+pub const NUMPSPRITES: usize = 2;
+
+//the positions of the weapon on the screen of 320x200
+pub const WEAPONTOP: f64 = 32.;
+pub const WEAPONBOTTOM: f64 = 128.;
+const LOWERSPEED: f64 = 6.;
+const RAISESPEED: f64 = 6.;
+
+const BFGCELLS: i32 = 40; //the cells of one shot of the bfg
+pub const MAXBOB: f64 = 16.; //the most the weapon and the view bob
+
+const ANG90: u32 = 0x40000000;
+const FINEANGLES: i32 = 8192;
+
+//weaponinfo_t
+pub struct WeaponInfo {
+    pub ammo: AmmoType,
+    pub up_state: StateNum,
+    pub down_state: StateNum,
+    pub ready_state: StateNum,
+    pub attack_state: StateNum,
+    pub flash_state: StateNum
+}
+
+pub const WEAPONINFO: [WeaponInfo; NUMWEAPONS] = [
+    WeaponInfo { ammo: AM_NOAMMO, up_state: S_PUNCHUP, down_state: S_PUNCHDOWN, ready_state: S_PUNCH, attack_state: S_PUNCH1, flash_state: S_NULL }, //WP_FIST
+    WeaponInfo { ammo: AM_CLIP, up_state: S_PISTOLUP, down_state: S_PISTOLDOWN, ready_state: S_PISTOL, attack_state: S_PISTOL1, flash_state: S_PISTOLFLASH }, //WP_PISTOL
+    WeaponInfo { ammo: AM_SHELL, up_state: S_SGUNUP, down_state: S_SGUNDOWN, ready_state: S_SGUN, attack_state: S_SGUN1, flash_state: S_SGUNFLASH1 }, //WP_SHOTGUN
+    WeaponInfo { ammo: AM_CLIP, up_state: S_CHAINUP, down_state: S_CHAINDOWN, ready_state: S_CHAIN, attack_state: S_CHAIN1, flash_state: S_CHAINFLASH1 }, //WP_CHAINGUN
+    WeaponInfo { ammo: AM_MISL, up_state: S_MISSILEUP, down_state: S_MISSILEDOWN, ready_state: S_MISSILE, attack_state: S_MISSILE1, flash_state: S_MISSILEFLASH1 }, //WP_MISSILE
+    WeaponInfo { ammo: AM_CELL, up_state: S_PLASMAUP, down_state: S_PLASMADOWN, ready_state: S_PLASMA, attack_state: S_PLASMA1, flash_state: S_PLASMAFLASH1 }, //WP_PLASMA
+    WeaponInfo { ammo: AM_CELL, up_state: S_BFGUP, down_state: S_BFGDOWN, ready_state: S_BFG, attack_state: S_BFG1, flash_state: S_BFGFLASH1 }, //WP_BFG
+    WeaponInfo { ammo: AM_NOAMMO, up_state: S_SAWUP, down_state: S_SAWDOWN, ready_state: S_SAW, attack_state: S_SAW1, flash_state: S_NULL }, //WP_CHAINSAW
+    WeaponInfo { ammo: AM_SHELL, up_state: S_DSGUNUP, down_state: S_DSGUNDOWN, ready_state: S_DSGUN, attack_state: S_DSGUN1, flash_state: S_DSGUNFLASH1 } //WP_SUPERSHOTGUN
+];
+
+//the weapon slots of zdoom, the number keys go through the weapons of their slot
+pub const WEAPON_SLOTS: [&[WeaponType]; 7] = [
+    &[WP_FIST, WP_CHAINSAW],
+    &[WP_PISTOL],
+    &[WP_SHOTGUN, WP_SUPERSHOTGUN],
+    &[WP_CHAINGUN],
+    &[WP_MISSILE],
+    &[WP_PLASMA],
+    &[WP_BFG]
+];
+
+//pspdef_t, the state is S_NULL when the overlay isn't shown
+#[derive(Default, Clone, Copy)]
+pub struct PspDef {
+    pub state: StateNum,
+    pub tics: i32,
+    pub sx: f64,
+    pub sy: f64
+}
+
+/*
+ * The weapons of the players and their overlays (p_pspr.cpp)
+ */
+impl LevelLocals {
+    fn start_weapon_sound(&self, _actor: &Actor, _sound: &str) {
+        //TODO S_StartSound, there are no sounds yet
+    }
+
+    /*
+     * P_PlayerThink, the weapon part of it, the movement is done by the game.
+     * The bob is from P_CalcHeight, the weapon bobs with it.
+     */
+    pub fn player_think(&mut self, player: usize) {
+        let mo = self.players[player].mo;
+        Self::with_actor(self, mo, |level, actor| {
+            let p = &mut level.players[player];
+            p.bob = ((actor.vel.x * actor.vel.x + actor.vel.y * actor.vel.y) / 4.).min(MAXBOB);
+            if p.damage_count > 0 {p.damage_count -= 1}
+            if p.bonus_count > 0 {p.bonus_count -= 1}
+
+            if p.player_state == PlayerState::Dead {
+                //TODO P_DeathThink
+                Self::move_psprites(level, player, mo, actor);
+                return
+            }
+
+            //the weapon change of the command
+            if let Some(slot) = p.cmd.weapon_slot.take() {
+                let weapon = Self::pick_weapon(level, player, slot);
+                let p = &mut level.players[player];
+                if weapon != WP_NOCHANGE && weapon != p.ready_weapon {p.pending_weapon = weapon}
+            }
+            Self::move_psprites(level, player, mo, actor);
+        });
+    }
+
+    //the owned weapon of the slot after the current one, the strongest first
+    fn pick_weapon(&self, player: usize, slot: usize) -> WeaponType {
+        let weapons = match WEAPON_SLOTS.get(slot) {
+            Some(weapons) => *weapons,
+            None => return WP_NOCHANGE
+        };
+        let p = &self.players[player];
+        let start = weapons.iter().position(|weapon| *weapon == p.ready_weapon).unwrap_or(0);
+        (1..=weapons.len()).map(|i| weapons[(start + weapons.len() - i) % weapons.len()])
+            .find(|weapon| p.weapon_owned[*weapon]).unwrap_or(WP_NOCHANGE)
+    }
+
+    //P_SetPsprite, the actions of the states with no tics are run at once
+    pub fn set_psprite(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor, position: usize, state: StateNum) {
+        let mut state = state;
+        loop {
+            if state == S_NULL {
+                //the object removes itself
+                self.players[player].psprites[position].state = S_NULL;
+                break
+            }
+            let st = &STATES[state];
+            {
+                let psp = &mut self.players[player].psprites[position];
+                psp.state = state;
+                psp.tics = st.tics;
+            }
+
+            if let Some(action) = st.action {
+                Self::call_weapon_action(self, player, mo, actor, position, action);
+                if self.players[player].psprites[position].state == S_NULL {break}
+            }
+
+            let psp = self.players[player].psprites[position];
+            state = STATES[psp.state].next_state;
+            if psp.tics != 0 {break}
+        }
+    }
+
+    //P_SetupPsprites, the weapon comes up when the player spawns
+    pub fn setup_psprites(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        let p = &mut self.players[player];
+        p.psprites = [PspDef::default(); NUMPSPRITES];
+        p.pending_weapon = p.ready_weapon;
+        Self::bring_up_weapon(self, player, mo, actor);
+    }
+
+    //P_MovePsprites, the flash follows the weapon
+    pub fn move_psprites(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        for position in 0..NUMPSPRITES {
+            let psp = &mut self.players[player].psprites[position];
+            //a tics of -1 never changes
+            if psp.state == S_NULL || psp.tics == -1 {continue}
+            psp.tics -= 1;
+            if psp.tics == 0 {
+                let next = STATES[psp.state].next_state;
+                Self::set_psprite(self, player, mo, actor, position, next);
+            }
+        }
+        let p = &mut self.players[player];
+        p.psprites[PS_FLASH].sx = p.psprites[PS_WEAPON].sx;
+        p.psprites[PS_FLASH].sy = p.psprites[PS_WEAPON].sy;
+    }
+
+    //P_BringUpWeapon, the pending weapon starts coming up from the bottom
+    fn bring_up_weapon(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        let p = &mut self.players[player];
+        if p.pending_weapon == WP_NOCHANGE {p.pending_weapon = p.ready_weapon}
+        if p.pending_weapon == WP_CHAINSAW {Self::start_weapon_sound(self, actor, "sawup")}
+
+        let p = &mut self.players[player];
+        let new_state = WEAPONINFO[p.pending_weapon].up_state;
+        p.pending_weapon = WP_NOCHANGE;
+        p.psprites[PS_WEAPON].sy = WEAPONBOTTOM;
+        Self::set_psprite(self, player, mo, actor, PS_WEAPON, new_state);
+    }
+
+    /*
+     * P_CheckAmmo, true when the weapon has the ammo for a shot.
+     * Otherwise the best weapon with the ammo is picked and the empty one goes down.
+     */
+    fn check_ammo(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) -> bool {
+        let p = &mut self.players[player];
+        let ammo = WEAPONINFO[p.ready_weapon].ammo;
+        let count = match p.ready_weapon {
+            WP_BFG => BFGCELLS,
+            WP_SUPERSHOTGUN => 2,
+            _ => 1
+        };
+        if ammo == AM_NOAMMO || p.ammo[ammo] >= count {return true}
+
+        //TODO the weapons that are not in the shareware and the registered doom
+        p.pending_weapon = if p.weapon_owned[WP_PLASMA] && p.ammo[AM_CELL] > 0 {WP_PLASMA}
+            else if p.weapon_owned[WP_SUPERSHOTGUN] && p.ammo[AM_SHELL] > 2 {WP_SUPERSHOTGUN}
+            else if p.weapon_owned[WP_CHAINGUN] && p.ammo[AM_CLIP] > 0 {WP_CHAINGUN}
+            else if p.weapon_owned[WP_SHOTGUN] && p.ammo[AM_SHELL] > 0 {WP_SHOTGUN}
+            else if p.ammo[AM_CLIP] > 0 {WP_PISTOL}
+            else if p.weapon_owned[WP_CHAINSAW] {WP_CHAINSAW}
+            else if p.weapon_owned[WP_MISSILE] && p.ammo[AM_MISL] > 0 {WP_MISSILE}
+            else if p.weapon_owned[WP_BFG] && p.ammo[AM_CELL] > BFGCELLS {WP_BFG}
+            //if everything fails
+            else {WP_FIST};
+
+        let down_state = WEAPONINFO[p.ready_weapon].down_state;
+        Self::set_psprite(self, player, mo, actor, PS_WEAPON, down_state);
+        false
+    }
+
+    //P_FireWeapon, the monsters hear the shot
+    fn fire_weapon(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        if !Self::check_ammo(self, player, mo, actor) {return}
+        Self::set_actor_state(self, mo, actor, S_PLAY_ATK1);
+        let attack_state = WEAPONINFO[self.players[player].ready_weapon].attack_state;
+        Self::set_psprite(self, player, mo, actor, PS_WEAPON, attack_state);
+        Self::noise_alert(self, mo, actor);
+    }
+
+    //P_DropWeapon, the weapon of the dying player goes down
+    pub fn drop_weapon(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        let down_state = WEAPONINFO[self.players[player].ready_weapon].down_state;
+        Self::set_psprite(self, player, mo, actor, PS_WEAPON, down_state);
+    }
+
+    fn use_ammo(&mut self, player: usize, count: i32) {
+        let p = &mut self.players[player];
+        let ammo = WEAPONINFO[p.ready_weapon].ammo;
+        if ammo != AM_NOAMMO {p.ammo[ammo] = (p.ammo[ammo] - count).max(0)}
+    }
+
+    fn call_weapon_action(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor, position: usize, action: ActionFunction) {
+        match action {
+            ActionFunction::Light0 => self.players[player].extra_light = 0,
+            ActionFunction::Light1 => self.players[player].extra_light = 1,
+            ActionFunction::Light2 => self.players[player].extra_light = 2,
+            ActionFunction::WeaponReady => Self::a_weapon_ready(self, player, mo, actor, position),
+            ActionFunction::Lower => Self::a_lower(self, player, mo, actor, position),
+            ActionFunction::Raise => Self::a_raise(self, player, mo, actor, position),
+            ActionFunction::Punch => Self::a_punch(self, mo, actor),
+            ActionFunction::ReFire => Self::a_refire(self, player, mo, actor),
+            ActionFunction::FirePistol => Self::a_fire_pistol(self, player, mo, actor),
+            ActionFunction::FireShotgun => Self::a_fire_shotgun(self, player, mo, actor),
+            ActionFunction::FireShotgun2 => Self::a_fire_shotgun2(self, player, mo, actor),
+            ActionFunction::CheckReload => {Self::check_ammo(self, player, mo, actor);}
+            ActionFunction::OpenShotgun2 => Self::start_weapon_sound(self, actor, "dbopn"),
+            ActionFunction::LoadShotgun2 => Self::start_weapon_sound(self, actor, "dbload"),
+            ActionFunction::CloseShotgun2 => {
+                Self::start_weapon_sound(self, actor, "dbcls");
+                Self::a_refire(self, player, mo, actor);
+            }
+            ActionFunction::FireCGun => Self::a_fire_cgun(self, player, mo, actor, position),
+            ActionFunction::GunFlash => {
+                Self::set_actor_state(self, mo, actor, S_PLAY_ATK2);
+                let flash_state = WEAPONINFO[self.players[player].ready_weapon].flash_state;
+                Self::set_psprite(self, player, mo, actor, PS_FLASH, flash_state);
+            }
+            ActionFunction::FireMissile => {
+                Self::use_ammo(self, player, 1);
+                Self::spawn_player_missile(self, mo, actor, MT_ROCKET);
+            }
+            ActionFunction::Saw => Self::a_saw(self, mo, actor),
+            ActionFunction::FirePlasma => Self::a_fire_plasma(self, player, mo, actor),
+            ActionFunction::BFGsound => Self::start_weapon_sound(self, actor, "bfg"),
+            ActionFunction::FireBFG => {
+                Self::use_ammo(self, player, BFGCELLS);
+                Self::spawn_player_missile(self, mo, actor, MT_BFG);
+            }
+            _ => println!("Weapon: {:?} isn't an action of the weapons", action)
+        }
+    }
+
+    /*
+     * A_WeaponReady, the weapon bobs and fires when the attack is down.
+     * The missile launcher and the bfg don't fire again until the attack was let go.
+     */
+    fn a_weapon_ready(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor, position: usize) {
+        //get out of the attack state
+        if actor.state == S_PLAY_ATK1 || actor.state == S_PLAY_ATK2 {Self::set_actor_state(self, mo, actor, S_PLAY);}
+
+        let p = &self.players[player];
+        if p.ready_weapon == WP_CHAINSAW && p.psprites[position].state == S_SAW {Self::start_weapon_sound(self, actor, "sawidl")}
+
+        //put the weapon away when there is another one or the player is dead
+        let p = &self.players[player];
+        if p.pending_weapon != WP_NOCHANGE || p.health == 0 {
+            let down_state = WEAPONINFO[p.ready_weapon].down_state;
+            Self::set_psprite(self, player, mo, actor, PS_WEAPON, down_state);
+            return
+        }
+
+        //check for the fire, the missile launcher and the bfg don't auto fire
+        if p.cmd.attack {
+            if !p.attack_down || (p.ready_weapon != WP_MISSILE && p.ready_weapon != WP_BFG) {
+                self.players[player].attack_down = true;
+                Self::fire_weapon(self, player, mo, actor);
+                return
+            }
+        }
+        else {self.players[player].attack_down = false}
+
+        //bob the weapon with the movement of the player
+        let p = &mut self.players[player];
+        let angle = (128 * self.maptime) & (FINEANGLES - 1);
+        let to_radians = std::f64::consts::TAU / FINEANGLES as f64;
+        p.psprites[position].sx = 1. + p.bob * (angle as f64 * to_radians).cos();
+        let angle = angle & (FINEANGLES / 2 - 1);
+        p.psprites[position].sy = WEAPONTOP + p.bob * (angle as f64 * to_radians).sin();
+    }
+
+    //A_ReFire, the weapon fires again when the attack is still down
+    fn a_refire(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        let p = &mut self.players[player];
+        if p.cmd.attack && p.pending_weapon == WP_NOCHANGE && p.health != 0 {
+            p.refire += 1;
+            Self::fire_weapon(self, player, mo, actor);
+        }
+        else {
+            p.refire = 0;
+            Self::check_ammo(self, player, mo, actor);
+        }
+    }
+
+    //A_Lower, the pending weapon comes up when the old one is down
+    fn a_lower(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor, position: usize) {
+        let p = &mut self.players[player];
+        p.psprites[position].sy += LOWERSPEED;
+        //not lowered all the way yet
+        if p.psprites[position].sy < WEAPONBOTTOM {return}
+
+        //the dead player keeps the weapon down
+        if p.player_state == PlayerState::Dead {
+            p.psprites[position].sy = WEAPONBOTTOM;
+            return
+        }
+        //the player is dead, so don't bring up another weapon
+        if p.health == 0 {
+            Self::set_psprite(self, player, mo, actor, PS_WEAPON, S_NULL);
+            return
+        }
+        p.ready_weapon = p.pending_weapon;
+        Self::bring_up_weapon(self, player, mo, actor);
+    }
+
+    fn a_raise(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor, position: usize) {
+        let p = &mut self.players[player];
+        p.psprites[position].sy -= RAISESPEED;
+        if p.psprites[position].sy > WEAPONTOP {return}
+        p.psprites[position].sy = WEAPONTOP;
+
+        //the weapon is ready
+        let ready_state = WEAPONINFO[p.ready_weapon].ready_state;
+        Self::set_psprite(self, player, mo, actor, PS_WEAPON, ready_state);
+    }
+
+    fn a_fire_pistol(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        Self::start_weapon_sound(self, actor, "pistol");
+        Self::set_actor_state(self, mo, actor, S_PLAY_ATK2);
+        Self::use_ammo(self, player, 1);
+        let flash_state = WEAPONINFO[self.players[player].ready_weapon].flash_state;
+        Self::set_psprite(self, player, mo, actor, PS_FLASH, flash_state);
+
+        let slope = Self::bullet_slope(self, mo, actor);
+        let accurate = self.players[player].refire == 0;
+        Self::gun_shot(self, mo, actor, accurate, slope);
+    }
+
+    fn a_fire_shotgun(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        Self::start_weapon_sound(self, actor, "shotgn");
+        Self::set_actor_state(self, mo, actor, S_PLAY_ATK2);
+        Self::use_ammo(self, player, 1);
+        let flash_state = WEAPONINFO[self.players[player].ready_weapon].flash_state;
+        Self::set_psprite(self, player, mo, actor, PS_FLASH, flash_state);
+
+        let slope = Self::bullet_slope(self, mo, actor);
+        for _ in 0..7 {
+            Self::gun_shot(self, mo, actor, false, slope);
+        }
+    }
+
+    //A_FireShotgun2, the super shotgun spreads its pellets up and down too
+    fn a_fire_shotgun2(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        Self::start_weapon_sound(self, actor, "dshtgn");
+        Self::set_actor_state(self, mo, actor, S_PLAY_ATK2);
+        Self::use_ammo(self, player, 2);
+        let flash_state = WEAPONINFO[self.players[player].ready_weapon].flash_state;
+        Self::set_psprite(self, player, mo, actor, PS_FLASH, flash_state);
+
+        let slope = Self::bullet_slope(self, mo, actor);
+        for _ in 0..20 {
+            let damage = 5 * (self.random.random() % 3 + 1);
+            let mut angle = actor.angle;
+            angle.add(&Angle::<f64>::from_bam_i(self.random.random2() << 19));
+            let spread = (self.random.random2() << 5) as f64 / 65536.;
+            Self::line_attack(self, mo, actor, angle, MISSILERANGE, slope + spread, damage);
+        }
+    }
+
+    //A_FireCGun, the flash goes with the frame of the chaingun
+    fn a_fire_cgun(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor, position: usize) {
+        Self::start_weapon_sound(self, actor, "pistol");
+        let p = &self.players[player];
+        let ammo = WEAPONINFO[p.ready_weapon].ammo;
+        if ammo != AM_NOAMMO && p.ammo[ammo] == 0 {return}
+
+        Self::set_actor_state(self, mo, actor, S_PLAY_ATK2);
+        Self::use_ammo(self, player, 1);
+        let p = &self.players[player];
+        let flash_state = WEAPONINFO[p.ready_weapon].flash_state + p.psprites[position].state - S_CHAIN1;
+        Self::set_psprite(self, player, mo, actor, PS_FLASH, flash_state);
+
+        let slope = Self::bullet_slope(self, mo, actor);
+        let accurate = self.players[player].refire == 0;
+        Self::gun_shot(self, mo, actor, accurate, slope);
+    }
+
+    //A_FirePlasma, one of the two flashes
+    fn a_fire_plasma(&mut self, player: usize, mo: ActorIndex, actor: &mut Actor) {
+        Self::use_ammo(self, player, 1);
+        let flash_state = WEAPONINFO[self.players[player].ready_weapon].flash_state + (self.random.random() & 1) as StateNum;
+        Self::set_psprite(self, player, mo, actor, PS_FLASH, flash_state);
+        Self::spawn_player_missile(self, mo, actor, MT_PLASMA);
+    }
+
+    //A_Punch, the player turns to what was punched
+    fn a_punch(&mut self, mo: ActorIndex, actor: &mut Actor) {
+        //TODO the berserk does ten times the damage
+        let damage = (self.random.random() % 10 + 1) << 1;
+        let mut angle = actor.angle;
+        angle.add(&Angle::<f64>::from_bam_i(self.random.random2() << 18));
+        let (slope, target) = Self::aim_line_attack(self, mo, actor, angle, MELEERANGE);
+        Self::line_attack(self, mo, actor, angle, MELEERANGE, slope, damage);
+
+        if let Some(target) = self.actor(target) {
+            Self::start_weapon_sound(self, actor, "punch");
+            actor.angle = actor.angle_to(target);
+        }
+    }
+
+    //A_Saw, the player is pulled towards what is sawed
+    fn a_saw(&mut self, mo: ActorIndex, actor: &mut Actor) {
+        let damage = 2 * (self.random.random() % 10 + 1);
+        let mut angle = actor.angle;
+        angle.add(&Angle::<f64>::from_bam_i(self.random.random2() << 18));
+        //use the meleerange + 1 so the puff doesn't skip the flash
+        let (slope, target) = Self::aim_line_attack(self, mo, actor, angle, MELEERANGE + 1.);
+        Self::line_attack(self, mo, actor, angle, MELEERANGE + 1., slope, damage);
+
+        let target = match self.actor(target) {
+            Some(target) => actor.angle_to(target).bams(),
+            None => {
+                Self::start_weapon_sound(self, actor, "sawful");
+                return
+            }
+        };
+        Self::start_weapon_sound(self, actor, "sawhit");
+
+        //turn to face the target
+        let current = actor.angle.bams();
+        let delta = target.wrapping_sub(current);
+        let angle = if delta > ANG90 * 2 {
+            if (delta as i32) < -((ANG90 / 20) as i32) {target.wrapping_add(ANG90 / 21)} else {current.wrapping_sub(ANG90 / 20)}
+        } else if delta > ANG90 / 20 {target.wrapping_sub(ANG90 / 21)} else {current.wrapping_add(ANG90 / 20)};
+        actor.angle = Angle::<f64>::from_bam_u(angle);
+        actor.flags.insert(MobjFlags::JustAttacked);
+    }
+
+    //A_BFGSpray, the things the player sees in front of the ball get the damage of the player
+    pub fn a_bfg_spray(&mut self, _index: ActorIndex, actor: &mut Actor) {
+        let source = match self.actor(actor.target) {
+            Some(source) => source.clone(),
+            None => return
+        };
+        //offset the angles so they spread out in front of the ball
+        for i in 0..40 {
+            let mut angle = actor.angle;
+            angle.subtract(&Angle::<f64>::from_bam_u(ANG90 / 2));
+            angle.add(&Angle::<f64>::from_bam_u(ANG90 / 40 * i));
+            let (_, target) = Self::aim_line_attack(self, actor.target, &source, angle, 16. * 64.);
+            let (pos, height) = match self.actor(target) {
+                Some(thing) => (thing.pos, thing.height),
+                None => continue
+            };
+            Self::spawn_actor(self, MT_EXTRABFG, pos.x, pos.y, pos.z + height / 4.);
+
+            let mut damage = 0;
+            for _ in 0..15 {
+                damage += (self.random.random() & 7) + 1;
+            }
+            Self::damage_actor(self, target, Some(&source), actor.target, Some(&source), damage);
+        }
+    }
+}
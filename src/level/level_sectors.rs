@@ -0,0 +1,178 @@
+use super::LevelLocals;
+use super::level_elements::{LineIndex, SectorIndex, LineFlags, SectorE};
+
+/*
+ * Helpers to find the neighbouring sectors (the sector_t methods and P_Find* functions of zdoom)
+ */
+impl LevelLocals {
+    //returns the sector on the other side of the line or -1 if there is none
+    pub fn get_next_sector(&self, line: LineIndex, sector: SectorIndex) -> SectorIndex {
+        let line = self.lines[line as usize].borrow();
+        if line.flags & LineFlags::TwoSided.bits() == 0 {return -1}
+        if line.front_sector == sector {
+            if line.back_sector != sector {return line.back_sector}
+            return -1
+        }
+        line.front_sector
+    }
+
+    //all the sectors next to the sector
+    pub fn neighbour_sectors(&self, sector: SectorIndex) -> Vec<SectorIndex> {
+        let lines = self.sectors[sector as usize].borrow().lines.clone();
+        lines.iter().map(|line| Self::get_next_sector(self, *line, sector)).filter(|other| *other >= 0).collect()
+    }
+
+    pub fn find_min_surrounding_light(&self, sector: SectorIndex, max: i32) -> i32 {
+        let mut min = max;
+        for other in Self::neighbour_sectors(self, sector) {
+            let light = self.sectors[other as usize].borrow().light_level as i32;
+            if light < min {min = light}
+        }
+        min
+    }
+
+    pub fn find_max_surrounding_light(&self, sector: SectorIndex, min: i32) -> i32 {
+        let mut max = min;
+        for other in Self::neighbour_sectors(self, sector) {
+            let light = self.sectors[other as usize].borrow().light_level as i32;
+            if light > max {max = light}
+        }
+        max
+    }
+}
+
+//TODO the texture heights, this is what zdoom uses when there are no textures
+const DEFAULT_TEXTURE_HEIGHT: f64 = 64.;
+
+/*
+ * The heights the movers go to (the sector_t::Find* functions of zdoom).
+ * When there are no neighbours the height of the sector itself is used.
+ */
+impl LevelLocals {
+    //the floor or ceiling heights of the neighbouring sectors at both vertices of the lines between them
+    fn neighbour_heights(&self, sector: SectorIndex, pos: usize) -> Vec<f64> {
+        let lines = self.sectors[sector as usize].borrow().lines.clone();
+        let mut heights = vec![];
+        for line in lines {
+            let other = Self::get_next_sector(self, line, sector);
+            if other < 0 {continue}
+            let line = self.lines[line as usize].borrow();
+            let other = self.sectors[other as usize].borrow();
+            let plane = if pos == SectorE::Floor as usize {&other.floorplane} else {&other.ceilingplane};
+            heights.push(plane.z_at(&line.v1.f_pos()));
+            heights.push(plane.z_at(&line.v2.f_pos()));
+        }
+        heights
+    }
+
+    fn own_height(&self, sector: SectorIndex, pos: usize) -> f64 {
+        let sec = self.sectors[sector as usize].borrow();
+        if pos == SectorE::Floor as usize {sec.center_floor()} else {sec.center_ceiling()}
+    }
+
+    pub fn find_lowest_floor_surrounding(&self, sector: SectorIndex) -> f64 {
+        let own = Self::own_height(self, sector, SectorE::Floor as usize);
+        Self::neighbour_heights(self, sector, SectorE::Floor as usize).into_iter().fold(own, f64::min)
+    }
+
+    pub fn find_highest_floor_surrounding(&self, sector: SectorIndex) -> f64 {
+        let heights = Self::neighbour_heights(self, sector, SectorE::Floor as usize);
+        if heights.is_empty() {return Self::own_height(self, sector, SectorE::Floor as usize)}
+        heights.into_iter().fold(f64::MIN, f64::max)
+    }
+
+    pub fn find_next_highest_floor(&self, sector: SectorIndex) -> f64 {
+        let own = Self::own_height(self, sector, SectorE::Floor as usize);
+        Self::neighbour_heights(self, sector, SectorE::Floor as usize).into_iter().filter(|h| *h > own).reduce(f64::min).unwrap_or(own)
+    }
+
+    pub fn find_next_lowest_floor(&self, sector: SectorIndex) -> f64 {
+        let own = Self::own_height(self, sector, SectorE::Floor as usize);
+        Self::neighbour_heights(self, sector, SectorE::Floor as usize).into_iter().filter(|h| *h < own).reduce(f64::max).unwrap_or(own)
+    }
+
+    pub fn find_lowest_ceiling_surrounding(&self, sector: SectorIndex) -> f64 {
+        let heights = Self::neighbour_heights(self, sector, SectorE::Ceiling as usize);
+        if heights.is_empty() {return Self::own_height(self, sector, SectorE::Ceiling as usize)}
+        heights.into_iter().fold(f64::MAX, f64::min)
+    }
+
+    pub fn find_highest_ceiling_surrounding(&self, sector: SectorIndex) -> f64 {
+        let heights = Self::neighbour_heights(self, sector, SectorE::Ceiling as usize);
+        if heights.is_empty() {return Self::own_height(self, sector, SectorE::Ceiling as usize)}
+        heights.into_iter().fold(f64::MIN, f64::max)
+    }
+
+    pub fn find_next_lowest_ceiling(&self, sector: SectorIndex) -> f64 {
+        let own = Self::own_height(self, sector, SectorE::Ceiling as usize);
+        Self::neighbour_heights(self, sector, SectorE::Ceiling as usize).into_iter().filter(|h| *h < own).reduce(f64::max).unwrap_or(own)
+    }
+
+    pub fn find_next_highest_ceiling(&self, sector: SectorIndex) -> f64 {
+        let own = Self::own_height(self, sector, SectorE::Ceiling as usize);
+        Self::neighbour_heights(self, sector, SectorE::Ceiling as usize).into_iter().filter(|h| *h > own).reduce(f64::min).unwrap_or(own)
+    }
+
+    //the heights of the plane at the vertices of the sector, for sloped planes
+    fn plane_point_heights(&self, sector: SectorIndex, pos: usize) -> Vec<f64> {
+        let sec = self.sectors[sector as usize].borrow();
+        let plane = if pos == SectorE::Floor as usize {&sec.floorplane} else {&sec.ceilingplane};
+        if !plane.is_slope() {return vec![plane.z_at(&sec.center_spot)]}
+        let mut heights = vec![];
+        for line in &sec.lines {
+            let line = self.lines[*line as usize].borrow();
+            heights.push(plane.z_at(&line.v1.f_pos()));
+            heights.push(plane.z_at(&line.v2.f_pos()));
+        }
+        heights
+    }
+
+    pub fn find_highest_floor_point(&self, sector: SectorIndex) -> f64 {
+        Self::plane_point_heights(self, sector, SectorE::Floor as usize).into_iter().fold(f64::MIN, f64::max)
+    }
+
+    pub fn find_lowest_ceiling_point(&self, sector: SectorIndex) -> f64 {
+        Self::plane_point_heights(self, sector, SectorE::Ceiling as usize).into_iter().fold(f64::MAX, f64::min)
+    }
+
+    pub fn find_shortest_texture_around(&self, _sector: SectorIndex) -> f64 {
+        //TODO the smallest lower texture of the two sided lines
+        DEFAULT_TEXTURE_HEIGHT
+    }
+
+    pub fn find_shortest_upper_around(&self, _sector: SectorIndex) -> f64 {
+        //TODO the smallest upper texture of the two sided lines
+        DEFAULT_TEXTURE_HEIGHT
+    }
+
+    //the first neighbour with the floor/ceiling at the height, the model of the texture and special changes
+    fn find_model_sector(&self, sector: SectorIndex, height: f64, pos: usize) -> SectorIndex {
+        let lines = self.sectors[sector as usize].borrow().lines.clone();
+        for line in lines {
+            let other = Self::get_next_sector(self, line, sector);
+            if other < 0 {continue}
+            let l = self.lines[line as usize].borrow();
+            let sec = self.sectors[other as usize].borrow();
+            let plane = if pos == SectorE::Floor as usize {&sec.floorplane} else {&sec.ceilingplane};
+            if plane.z_at(&l.v1.f_pos()) == height || plane.z_at(&l.v2.f_pos()) == height {return other}
+        }
+        -1
+    }
+
+    pub fn find_model_floor_sector(&self, sector: SectorIndex, height: f64) -> SectorIndex {
+        Self::find_model_sector(self, sector, height, SectorE::Floor as usize)
+    }
+
+    pub fn find_model_ceiling_sector(&self, sector: SectorIndex, height: f64) -> SectorIndex {
+        Self::find_model_sector(self, sector, height, SectorE::Ceiling as usize)
+    }
+
+    //the next sector of the stairs that have the special (Stairs_Special1/2), skipping prev
+    pub fn next_special_sector(&self, sector: SectorIndex, special: i32, prev: SectorIndex) -> SectorIndex {
+        for other in Self::neighbour_sectors(self, sector) {
+            if other == prev {continue}
+            if self.sectors[other as usize].borrow().special & 0xff == special {return other}
+        }
+        -1
+    }
+}
@@ -0,0 +1,127 @@
+use num::{FromPrimitive, ToPrimitive};
+
+use super::level_elements::SecSpecial;
+use super::level_texture::TextureID;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum SerialValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String)
+}
+
+/*
+ * A simple key/value archive for the savegames (FSerializer of zdoom).
+ * The same serialize functions are used for writing and reading, the values are read back in the order they were written.
+ */
+#[derive(Default)]
+pub struct Serializer {
+    writing: bool,
+    pub values: Vec<(String, SerialValue)>,
+    pos: usize
+}
+
+impl Serializer {
+    pub fn new_writer() -> Serializer {
+        Serializer { writing: true, values: vec![], pos: 0 }
+    }
+
+    pub fn new_reader(values: Vec<(String, SerialValue)>) -> Serializer {
+        Serializer { writing: false, values, pos: 0 }
+    }
+
+    pub fn is_writing(&self) -> bool {
+        self.writing
+    }
+
+    pub fn is_reading(&self) -> bool {
+        !self.writing
+    }
+
+    fn write(&mut self, key: &str, value: SerialValue) {
+        self.values.push((key.to_string(), value));
+    }
+
+    fn read(&mut self, key: &str) -> Option<SerialValue> {
+        let (name, value) = self.values.get(self.pos)?;
+        if name != key {
+            println!("Serializer: expected {} but found {}", key, name);
+            return None
+        }
+        self.pos += 1;
+        Some(value.clone())
+    }
+
+    pub fn int(&mut self, key: &str, value: &mut i32) -> &mut Self {
+        if self.writing {Self::write(self, key, SerialValue::Int(*value as i64))}
+        else if let Some(SerialValue::Int(v)) = Self::read(self, key) {*value = v as i32}
+        self
+    }
+
+    pub fn float(&mut self, key: &str, value: &mut f64) -> &mut Self {
+        if self.writing {Self::write(self, key, SerialValue::Float(*value))}
+        else if let Some(SerialValue::Float(v)) = Self::read(self, key) {*value = v}
+        self
+    }
+
+    pub fn boolean(&mut self, key: &str, value: &mut bool) -> &mut Self {
+        if self.writing {Self::write(self, key, SerialValue::Bool(*value))}
+        else if let Some(SerialValue::Bool(v)) = Self::read(self, key) {*value = v}
+        self
+    }
+
+    pub fn string(&mut self, key: &str, value: &mut String) -> &mut Self {
+        if self.writing {Self::write(self, key, SerialValue::Str(value.clone()))}
+        else if let Some(SerialValue::Str(v)) = Self::read(self, key) {*value = v}
+        self
+    }
+
+    //the enums are stored as their number
+    pub fn enumeration<T: Copy + FromPrimitive + ToPrimitive>(&mut self, key: &str, value: &mut T) -> &mut Self {
+        let mut num = value.to_i32().unwrap_or(0);
+        Self::int(self, key, &mut num);
+        if self.is_reading() {
+            if let Some(v) = T::from_i32(num) {*value = v}
+        }
+        self
+    }
+
+    pub fn texture(&mut self, key: &str, value: &mut TextureID) -> &mut Self {
+        Self::int(self, key, &mut value.tex_num)
+    }
+
+    pub fn special(&mut self, key: &str, value: &mut SecSpecial) -> &mut Self {
+        let mut damage_interval = value.damage_interval as i32;
+        let mut leaky_damage = value.leaky_damage as i32;
+        let mut flags = value.flags as i32;
+        Self::int(self, key, &mut value.special)
+            .int("damageamount", &mut value.damage_amount)
+            .int("damageinterval", &mut damage_interval)
+            .int("leakydamage", &mut leaky_damage)
+            .string("damagetype", &mut value.damage_type)
+            .int("flags", &mut flags);
+        value.damage_interval = damage_interval as i16;
+        value.leaky_damage = leaky_damage as i16;
+        value.flags = flags as u32;
+        self
+    }
+
+    //a bool tells if the value is there
+    pub fn option<T: Default>(&mut self, key: &str, value: &mut Option<T>, f: impl FnOnce(&mut Self, &str, &mut T)) -> &mut Self {
+        let mut some = value.is_some();
+        Self::boolean(self, key, &mut some);
+        if !some {
+            *value = None;
+            return self
+        }
+        let v = value.get_or_insert_with(T::default);
+        f(self, key, v);
+        self
+    }
+}
+
+//everything that is saved with the level
+pub trait Serializable {
+    fn serialize(&mut self, arc: &mut Serializer);
+}
@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use bitflags::bitflags;
+
+use crate::vector::{Vector2, Vector3};
+
+use super::LevelLocals;
+use super::level_actor::Actor;
+use super::level_elements::{ChildNode, LineFlags, LineIndex, SectorIndex, SubSectorIndex};
+use super::level_maputl::DivLine;
+
+bitflags! {
+    //the flags of P_CheckSight
+    #[derive(Clone, Copy, Default, PartialEq, Debug)]
+    pub struct SightFlags: u32 {
+        const IgnoreVisibility = 1; //the invisible actors can be seen
+        const SeePastBlockEverything = 2; //the lines that block everything don't block the sight
+        const IgnoreWaterBoundary = 4; //the sight goes through the surface of the deep water
+    }
+}
+
+//the trace of one sight check, the slopes are the z difference over the whole trace
+struct SightTrace {
+    trace: DivLine,
+    z_start: f64,
+    top_slope: f64,
+    bottom_slope: f64,
+    flags: SightFlags,
+    checked: HashSet<LineIndex>
+}
+
+/*
+ * The line of sight between two points (p_sight.cpp of doom)
+ */
+impl LevelLocals {
+    //the reject table says the sectors can't see each other
+    pub fn sight_rejected(&self, s1: SectorIndex, s2: SectorIndex) -> bool {
+        if self.reject_matrix.is_empty() || s1 < 0 || s2 < 0 {return false}
+        let bit = s1 as usize * self.sectors.len() + s2 as usize;
+        self.reject_matrix.get(bit / 8).is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+    }
+
+    /*
+     * P_CheckSight, true when the eyes of t1 can see any part of t2.
+     * The actors don't need to be in the thinkers, a ticking actor can look around.
+     */
+    pub fn check_sight(&self, t1: &Actor, t2: &Actor, flags: SightFlags) -> bool {
+        if self.sight_rejected(t1.sector, t2.sector) {return false}
+        if !flags.contains(SightFlags::IgnoreVisibility) {
+            //TODO the invisible actors (RenderStyle None) can't be seen
+        }
+        //TODO the deep water of the fake floors (IgnoreWaterBoundary)
+
+        let eye = Vector3 { x: t1.pos.x, y: t1.pos.y, z: t1.pos.z + t1.height - t1.height / 4. };
+        Self::check_sight_trace(self, &eye, &t2.pos.xy(), t2.pos.z, t2.top(), flags)
+    }
+
+    /*
+     * The sight from the point to the column of the other point between bottom and top.
+     * The sectors of the points need to be found first, for the reject table.
+     */
+    pub fn check_sight_points(&self, from: &Vector3<f64>, to: &Vector3<f64>, flags: SightFlags) -> bool {
+        let s1 = self.point_in_sector(from.x, from.y);
+        let s2 = self.point_in_sector(to.x, to.y);
+        if self.sight_rejected(s1, s2) {return false}
+        Self::check_sight_trace(self, from, &to.xy(), to.z, to.z, flags)
+    }
+
+    fn check_sight_trace(&self, from: &Vector3<f64>, to: &Vector2<f64>, bottom: f64, top: f64, flags: SightFlags) -> bool {
+        let mut sight = SightTrace {
+            trace: DivLine { x: from.x, y: from.y, dx: to.x - from.x, dy: to.y - from.y },
+            z_start: from.z,
+            top_slope: top - from.z,
+            bottom_slope: bottom - from.z,
+            flags,
+            checked: HashSet::new()
+        };
+        //TODO the portals, the trace goes on in the group behind the line portal or the sector portal it hits
+
+        match self.root_child() {
+            Some(root) => Self::cross_bsp_node(self, root, to, &mut sight),
+            None => true
+        }
+    }
+
+    //P_CrossBSPNode, false when the sight is blocked in the part of the tree
+    fn cross_bsp_node(&self, child: ChildNode, to: &Vector2<f64>, sight: &mut SightTrace) -> bool {
+        if child.node < 0 {return Self::cross_subsector(self, child.subsector, sight)}
+
+        let (node_line, children) = {
+            let node = self.nodes[child.node as usize].borrow();
+            (DivLine { x: node.x as f64, y: node.y as f64, dx: node.dx as f64, dy: node.dy as f64 }, node.children)
+        };
+        //on the line counts as the front
+        let side = node_line.point_side(sight.trace.x, sight.trace.y) & 1;
+        if !Self::cross_bsp_node(self, children[side], to, sight) {return false}
+        //the trace doesn't get to the other side
+        if side == node_line.point_side(to.x, to.y) {return true}
+        Self::cross_bsp_node(self, children[side ^ 1], to, sight)
+    }
+
+    //P_CrossSubsector, the lines of the subsector the trace crosses lower the opening of the sight
+    fn cross_subsector(&self, subsector: SubSectorIndex, sight: &mut SightTrace) -> bool {
+        let (first, count) = {
+            let subsector = self.subsectors[subsector as usize].borrow();
+            (subsector.first_line, subsector.line_count as i32)
+        };
+        if first < 0 {return true}
+
+        for seg in first..first + count {
+            let (line_index, front_sector, back_sector) = match self.segs.get(seg as usize) {
+                Some(seg) => {
+                    let seg = seg.borrow();
+                    (seg.linedef, seg.front_sector, seg.back_sector)
+                }
+                None => break
+            };
+            //the minisegs of the gl nodes aren't lines
+            if line_index < 0 || !sight.checked.insert(line_index) {continue}
+
+            let (line, flags) = {
+                let line = self.lines[line_index as usize].borrow();
+                (DivLine { x: line.v1.fx(), y: line.v1.fy(), dx: line.delta().x, dy: line.delta().y }, line.flags)
+            };
+            //the line isn't crossed
+            let trace = &sight.trace;
+            if trace.point_side(line.x, line.y) == trace.point_side(line.x + line.dx, line.y + line.dy) {continue}
+            if line.point_side(trace.x, trace.y) == line.point_side(trace.x + trace.dx, trace.y + trace.dy) {continue}
+
+            //one sided lines and the lines that block the sight stop it
+            if back_sector < 0 || front_sector < 0 || flags & LineFlags::BlockSight.bits() != 0 {return false}
+            if flags & LineFlags::BlockEverything.bits() != 0 && !sight.flags.contains(SightFlags::SeePastBlockEverything) {return false}
+
+            let frac = trace.intercept(&line);
+            let cross = Vector2 { x: trace.x + trace.dx * frac, y: trace.y + trace.dy * frac };
+            let (front_floor, front_ceiling, back_floor, back_ceiling) = {
+                let front = self.sectors[front_sector as usize].borrow();
+                let back = self.sectors[back_sector as usize].borrow();
+                (front.floorplane.z_at(&cross), front.ceilingplane.z_at(&cross), back.floorplane.z_at(&cross), back.ceilingplane.z_at(&cross))
+            };
+            //no height change
+            if front_floor == back_floor && front_ceiling == back_ceiling {continue}
+
+            let open_top = front_ceiling.min(back_ceiling);
+            let open_bottom = front_floor.max(back_floor);
+            //a closed door
+            if open_bottom >= open_top {return false}
+            if frac <= 0. {continue}
+
+            if front_floor != back_floor {
+                let slope = (open_bottom - sight.z_start) / frac;
+                if slope > sight.bottom_slope {sight.bottom_slope = slope}
+            }
+            if front_ceiling != back_ceiling {
+                let slope = (open_top - sight.z_start) / frac;
+                if slope < sight.top_slope {sight.top_slope = slope}
+            }
+            if sight.top_slope <= sight.bottom_slope {return false}
+        }
+        //the trace got through the subsector
+        true
+    }
+}
@@ -0,0 +1,990 @@
+use bitflags::bitflags;
+use num::FromPrimitive;
+use num_derive::{FromPrimitive, ToPrimitive};
+
+use crate::game::TICRATE;
+use super::{LevelLocals, ActionSpecials, LevelFlags, SectorSpecials};
+use super::level_elements::{LineIndex, SectorIndex, LineFlags, SectorFlags, SPAC_SHIFT};
+use super::level_lightmap::PalEntry;
+use super::level_actor::ActorIndex;
+use super::level_behavior::ScriptAction;
+use super::level_movers::*;
+use super::level_teleport::TeleportFlags;
+use super::level_lighting::{LightEffect, STROBEBRIGHT, FASTDARK, SLOWDARK};
+
+bitflags! {
+    pub struct SpecialActivation: u32 {
+        const Cross = 1;            // when player crosses line
+        const Use = 2;              // when player uses line
+        const MCross = 4;           // when monster crosses line
+        const Impact = 8;           // when projectile hits line
+        const Push = 16;            // when player pushes line
+        const PCross = 32;          // when projectile crosses line
+        const UseThrough = 64;      // when player uses line (doesn't block)
+        const AnyCross = 128;       // when anything without the TELEPORT flag crosses the line
+        const MUse = 256;           // monsters can use
+        const MPush = 512;          // monsters can push
+        const UseBack = 1024;       // can be used from the backside
+
+        const PlayerActivate = Self::Cross.bits() | Self::Use.bits() | Self::Impact.bits() | Self::Push.bits() | Self::AnyCross.bits() | Self::UseThrough.bits() | Self::UseBack.bits();
+    }
+}
+
+//the activation of the hexen linedefs is stored in 3 bits of the flags
+const HEXEN_SPAC_TRANSLATION: [u32;8] = [
+    SpecialActivation::Cross.bits(),
+    SpecialActivation::Use.bits(),
+    SpecialActivation::MCross.bits(),
+    SpecialActivation::Impact.bits(),
+    SpecialActivation::Push.bits(),
+    SpecialActivation::PCross.bits(),
+    SpecialActivation::Use.bits() | SpecialActivation::UseThrough.bits(),
+    SpecialActivation::PCross.bits() | SpecialActivation::Impact.bits()
+];
+
+pub fn hexen_activation(flags: u32) -> u32 {
+    HEXEN_SPAC_TRANSLATION[((flags & LineFlags::SpacMask.bits()) >> SPAC_SHIFT) as usize]
+}
+
+/*
+ * min_args and max_args are the amount of arguments a script needs to give when calling the special,
+ * -1 means the special can't be called from a script. args_on_line is the amount the line can hold.
+ */
+pub struct LineSpecialInfo {
+    pub special: ActionSpecials,
+    pub name: &'static str,
+    pub min_args: i8,
+    pub max_args: i8,
+    pub args_on_line: i8
+}
+
+impl LineSpecialInfo {
+    const fn new(special: ActionSpecials, name: &'static str, min_args: i8, max_args: i8, args_on_line: i8) -> LineSpecialInfo {
+        LineSpecialInfo { special, name, min_args, max_args, args_on_line }
+    }
+}
+
+//sorted on the special number
+pub static LINE_SPECIALS: &[LineSpecialInfo] = &[
+    LineSpecialInfo::new(ActionSpecials::PolyobjStartLine, "Polyobj_StartLine", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PolyobjRotateLeft, "Polyobj_RotateLeft", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PolyobjRotateRight, "Polyobj_RotateRight", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PolyobjMove, "Polyobj_Move", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PolyobjExplicitLine, "Polyobj_ExplicitLine", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::PolyobjMoveTimes8, "Polyobj_MoveTimes8", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PolyobjDoorSwing, "Polyobj_DoorSwing", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PolyobjDoorSlide, "Polyobj_DoorSlide", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::LineHorizon, "Line_Horizon", -1, -1, 0),
+    LineSpecialInfo::new(ActionSpecials::DoorClose, "Door_Close", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::DoorOpen, "Door_Open", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::DoorRaise, "Door_Raise", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::DoorLockedRaise, "Door_LockedRaise", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::DoorAnimated, "Door_Animated", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::Autosave, "Autosave", 0, 0, 0),
+    LineSpecialInfo::new(ActionSpecials::TransferWallLight, "Transfer_WallLight", -1, -1, 2),
+    LineSpecialInfo::new(ActionSpecials::ThingRaise, "Thing_Raise", 1, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::StartConversation, "StartConversation", 1, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::ThingStop, "Thing_Stop", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerByValue, "Floor_LowerByValue", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerToLowest, "Floor_LowerToLowest", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerToNearest, "Floor_LowerToNearest", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseByValue, "Floor_RaiseByValue", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseToHighest, "Floor_RaiseToHighest", 2, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseToNearest, "Floor_RaiseToNearest", 2, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::StairsBuildDown, "Stairs_BuildDown", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::StairsBuildUp, "Stairs_BuildUp", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseAndCrush, "Floor_RaiseAndCrush", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PillarBuild, "Pillar_Build", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PillarOpen, "Pillar_Open", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::StairsBuildDownSync, "Stairs_BuildDownSync", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::StairsBuildUpSync, "Stairs_BuildUpSync", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ForceField, "ForceField", 0, 0, 0),
+    LineSpecialInfo::new(ActionSpecials::ClearForceField, "ClearForceField", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseByValueTimes8, "Floor_RaiseByValueTimes8", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerByValueTimes8, "Floor_LowerByValueTimes8", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorMoveToValue, "Floor_MoveToValue", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingWaggle, "Ceiling_Waggle", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::TeleportZombieChanger, "Teleport_ZombieChanger", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerByValue, "Ceiling_LowerByValue", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingRaiseByValue, "Ceiling_RaiseByValue", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::CeilingCrushAndRaise, "Ceiling_CrushAndRaise", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerAndCrush, "Ceiling_LowerAndCrush", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::CeilingCrushStop, "Ceiling_CrushStop", 1, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::CeilingCrushRaiseAndStay, "Ceiling_CrushRaiseAndStay", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorCrushStop, "Floor_CrushStop", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::CeilingMoveToValue, "Ceiling_MoveToValue", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorAttach3dMidtex, "Sector_Attach3dMidtex", -1, -1, 3),
+    LineSpecialInfo::new(ActionSpecials::GlassBreak, "GlassBreak", 0, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::ExtraFloorLightOnly, "ExtraFloor_LightOnly", -1, -1, 2),
+    LineSpecialInfo::new(ActionSpecials::SectorSetLink, "Sector_SetLink", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ScrollWall, "Scroll_Wall", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::LineSetTextureOffset, "Line_SetTextureOffset", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorChangeFlags, "Sector_ChangeFlags", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::LineSetBlocking, "Line_SetBlocking", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::LineSetTextureScale, "Line_SetTextureScale", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetPortal, "Sector_SetPortal", -1, -1, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorCopyScroller, "Sector_CopyScroller", -1, -1, 2),
+    LineSpecialInfo::new(ActionSpecials::PolyobjOrMoveToSpot, "Polyobj_OR_MoveToSpot", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PlatPerpetualRaise, "Plat_PerpetualRaise", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PlatStop, "Plat_Stop", 1, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::PlatDownWaitUpStay, "Plat_DownWaitUpStay", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PlatDownByValue, "Plat_DownByValue", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PlatUpWaitDownStay, "Plat_UpWaitDownStay", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PlatUpByValue, "Plat_UpByValue", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerInstant, "Floor_LowerInstant", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseInstant, "Floor_RaiseInstant", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorMoveToValueTimes8, "Floor_MoveToValueTimes8", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingMoveToValueTimes8, "Ceiling_MoveToValueTimes8", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::Teleport, "Teleport", 1, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::TeleportNoFog, "Teleport_NoFog", 1, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ThrustThing, "ThrustThing", 2, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::DamageThing, "DamageThing", 1, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::TeleportNewMap, "Teleport_NewMap", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::TeleportEndGame, "Teleport_EndGame", 0, 0, 0),
+    LineSpecialInfo::new(ActionSpecials::TeleportOther, "TeleportOther", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::TeleportGroup, "TeleportGroup", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::TeleportInSector, "TeleportInSector", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::ThingSetConversation, "Thing_SetConversation", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::AcsExecute, "ACS_Execute", 1, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::AcsSuspend, "ACS_Suspend", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::AcsTerminate, "ACS_Terminate", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::AcsLockedExecute, "ACS_LockedExecute", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::AcsExecuteWithResult, "ACS_ExecuteWithResult", 1, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::AcsLockedExecuteDoor, "ACS_LockedExecuteDoor", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::PolyobjMoveToSpot, "Polyobj_MoveToSpot", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PolyobjStop, "Polyobj_Stop", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::PolyobjMoveTo, "Polyobj_MoveTo", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PolyobjOrMoveTo, "Polyobj_OR_MoveTo", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PolyobjOrRotateLeft, "Polyobj_OR_RotateLeft", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PolyobjOrRotateRight, "Polyobj_OR_RotateRight", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PolyobjOrMove, "Polyobj_OR_Move", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PolyobjOrMoveTimes8, "Polyobj_OR_MoveTimes8", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PillarBuildAndCrush, "Pillar_BuildAndCrush", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorAndCeilingLowerByValue, "FloorAndCeiling_LowerByValue", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::FloorAndCeilingRaiseByValue, "FloorAndCeiling_RaiseByValue", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerAndCrushDist, "Ceiling_LowerAndCrushDist", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetTranslucent, "Sector_SetTranslucent", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseAndCrushDoom, "Floor_RaiseAndCrushDoom", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ScrollTextureLeft, "Scroll_Texture_Left", -1, -1, 2),
+    LineSpecialInfo::new(ActionSpecials::ScrollTextureRight, "Scroll_Texture_Right", -1, -1, 2),
+    LineSpecialInfo::new(ActionSpecials::ScrollTextureUp, "Scroll_Texture_Up", -1, -1, 2),
+    LineSpecialInfo::new(ActionSpecials::ScrollTextureDown, "Scroll_Texture_Down", -1, -1, 2),
+    LineSpecialInfo::new(ActionSpecials::CeilingCrushAndRaiseSilentDist, "Ceiling_CrushAndRaiseSilentDist", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::DoorWaitRaise, "Door_WaitRaise", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::DoorWaitClose, "Door_WaitClose", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::LineSetPortalTarget, "Line_SetPortalTarget", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::LightForceLightning, "Light_ForceLightning", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::LightRaiseByValue, "Light_RaiseByValue", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::LightLowerByValue, "Light_LowerByValue", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::LightChangeToValue, "Light_ChangeToValue", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::LightFade, "Light_Fade", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::LightGlow, "Light_Glow", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::LightFlicker, "Light_Flicker", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::LightStrobe, "Light_Strobe", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::LightStop, "Light_Stop", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::PlaneCopy, "Plane_Copy", -1, -1, 5),
+    LineSpecialInfo::new(ActionSpecials::ThingDamage, "Thing_Damage", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::RadiusQuake, "Radius_Quake", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::LineSetIdentification, "Line_SetIdentification", -1, -1, 5),
+    LineSpecialInfo::new(ActionSpecials::ThingMove, "Thing_Move", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::ThingSetSpecial, "Thing_SetSpecial", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::ThrustThingZ, "ThrustThingZ", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::UsePuzzleItem, "UsePuzzleItem", 2, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::ThingActivate, "Thing_Activate", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::ThingDeactivate, "Thing_Deactivate", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::ThingRemove, "Thing_Remove", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::ThingDestroy, "Thing_Destroy", 1, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::ThingProjectile, "Thing_Projectile", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::ThingSpawn, "Thing_Spawn", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ThingProjectileGravity, "Thing_ProjectileGravity", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::ThingSpawnNoFog, "Thing_SpawnNoFog", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorWaggle, "Floor_Waggle", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::ThingSpawnFacing, "Thing_SpawnFacing", 2, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::SectorChangeSound, "Sector_ChangeSound", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::PlayerSetTeam, "Player_SetTeam", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::TeamScore, "Team_Score", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::TeamGivePoints, "Team_GivePoints", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::TeleportNoStop, "Teleport_NoStop", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::LineSetPortal, "Line_SetPortal", -1, -1, 4),
+    LineSpecialInfo::new(ActionSpecials::SetGlobalFogParameter, "SetGlobalFogParameter", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::FsExecute, "FS_Execute", 1, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::SectorSetPlaneReflection, "Sector_SetPlaneReflection", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::SectorSet3dFloor, "Sector_Set3DFloor", -1, -1, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetContents, "Sector_SetContents", -1, -1, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingCrushAndRaiseDist, "Ceiling_CrushAndRaiseDist", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::GenericCrusher2, "Generic_Crusher2", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetCeilingScale2, "Sector_SetCeilingScale2", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::SectorSetFloorScale2, "Sector_SetFloorScale2", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PlatUpNearestWaitDownStay, "Plat_UpNearestWaitDownStay", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::NoiseAlert, "NoiseAlert", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::SendToCommunicator, "SendToCommunicator", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ThingProjectileIntercept, "Thing_ProjectileIntercept", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::ThingChangeTID, "Thing_ChangeTID", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::ThingHate, "Thing_Hate", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::ThingProjectileAimed, "Thing_ProjectileAimed", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::ChangeSkill, "ChangeSkill", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::ThingSetTranslation, "Thing_SetTranslation", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::PlaneAlign, "Plane_Align", -1, -1, 3),
+    LineSpecialInfo::new(ActionSpecials::LineMirror, "Line_Mirror", -1, -1, 0),
+    LineSpecialInfo::new(ActionSpecials::LineAlignCeiling, "Line_AlignCeiling", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::LineAlignFloor, "Line_AlignFloor", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::SectorSetRotation, "Sector_SetRotation", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::SectorSetCeilingPanning, "Sector_SetCeilingPanning", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetFloorPanning, "Sector_SetFloorPanning", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetCeilingScale, "Sector_SetCeilingScale", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetFloorScale, "Sector_SetFloorScale", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::StaticInit, "Static_Init", -1, -1, 4),
+    LineSpecialInfo::new(ActionSpecials::SetPlayerProperty, "SetPlayerProperty", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerToHighestFloor, "Ceiling_LowerToHighestFloor", 2, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerInstant, "Ceiling_LowerInstant", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingRaiseInstant, "Ceiling_RaiseInstant", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::CeilingCrushRaiseAndStayA, "Ceiling_CrushRaiseAndStayA", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingCrushAndRaiseA, "Ceiling_CrushAndRaiseA", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingCrushAndRaiseSilentA, "Ceiling_CrushAndRaiseSilentA", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingRaiseByValueTimes8, "Ceiling_RaiseByValueTimes8", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerByValueTimes8, "Ceiling_LowerByValueTimes8", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::GenericFloor, "Generic_Floor", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::GenericCeiling, "Generic_Ceiling", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::GenericDoor, "Generic_Door", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::GenericLift, "Generic_Lift", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::GenericStairs, "Generic_Stairs", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::GenericCrusher, "Generic_Crusher", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::PlatDownWaitUpStayLip, "Plat_DownWaitUpStayLip", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::PlatPerpetualRaiseLip, "Plat_PerpetualRaiseLip", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::TranslucentLine, "TranslucentLine", -1, -1, 3),
+    LineSpecialInfo::new(ActionSpecials::TransferHeights, "Transfer_Heights", -1, -1, 2),
+    LineSpecialInfo::new(ActionSpecials::TransferFloorLight, "Transfer_FloorLight", -1, -1, 1),
+    LineSpecialInfo::new(ActionSpecials::TransferCeilingLight, "Transfer_CeilingLight", -1, -1, 1),
+    LineSpecialInfo::new(ActionSpecials::SectorSetColor, "Sector_SetColor", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetFade, "Sector_SetFade", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::SectorSetDamage, "Sector_SetDamage", 3, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::TeleportLine, "Teleport_Line", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::SectorSetGravity, "Sector_SetGravity", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::StairsBuildUpDoom, "Stairs_BuildUpDoom", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetWind, "Sector_SetWind", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::SectorSetFriction, "Sector_SetFriction", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::SectorSetCurrent, "Sector_SetCurrent", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ScrollTextureBoth, "Scroll_Texture_Both", -1, -1, 5),
+    LineSpecialInfo::new(ActionSpecials::ScrollTextureModel, "Scroll_Texture_Model", -1, -1, 2),
+    LineSpecialInfo::new(ActionSpecials::ScrollFloor, "Scroll_Floor", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ScrollCeiling, "Scroll_Ceiling", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ScrollTextureOffsets, "Scroll_Texture_Offsets", -1, -1, 1),
+    LineSpecialInfo::new(ActionSpecials::AcsExecuteAlways, "ACS_ExecuteAlways", 1, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::PointPushSetForce, "PointPush_SetForce", -1, -1, 4),
+    LineSpecialInfo::new(ActionSpecials::PlatRaiseAndStayTx0, "Plat_RaiseAndStayTx0", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::ThingSetGoal, "Thing_SetGoal", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::PlatUpByValueStayTx, "Plat_UpByValueStayTx", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::PlatToggleCeiling, "Plat_ToggleCeiling", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::LightStrobeDoom, "Light_StrobeDoom", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::LightMinNeighbor, "Light_MinNeighbor", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::LightMaxNeighbor, "Light_MaxNeighbor", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::FloorTransferTrigger, "Floor_TransferTrigger", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::FloorTransferNumeric, "Floor_TransferNumeric", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::ChangeCamera, "ChangeCamera", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseToLowestCeiling, "Floor_RaiseToLowestCeiling", 2, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseByValueTxTy, "Floor_RaiseByValueTxTy", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseByTexture, "Floor_RaiseByTexture", 2, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerToLowestTxTy, "Floor_LowerToLowestTxTy", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerToHighest, "Floor_LowerToHighest", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::ExitNormal, "Exit_Normal", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::ExitSecret, "Exit_Secret", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::ElevatorRaiseToNearest, "Elevator_RaiseToNearest", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::ElevatorMoveToFloor, "Elevator_MoveToFloor", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::ElevatorLowerToNearest, "Elevator_LowerToNearest", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::HealThing, "HealThing", 1, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::DoorCloseWaitOpen, "Door_CloseWaitOpen", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorDonut, "Floor_Donut", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::FloorAndCeilingLowerRaise, "FloorAndCeiling_LowerRaise", 3, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::CeilingRaiseToNearest, "Ceiling_RaiseToNearest", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerToLowest, "Ceiling_LowerToLowest", 2, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerToFloor, "Ceiling_LowerToFloor", 2, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingCrushRaiseAndStaySilA, "Ceiling_CrushRaiseAndStaySilA", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerToHighestEE, "Floor_LowerToHighestEE", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseToLowest, "Floor_RaiseToLowest", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerToLowestCeiling, "Floor_LowerToLowestCeiling", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::FloorRaiseToCeiling, "Floor_RaiseToCeiling", 2, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorToCeilingInstant, "Floor_ToCeilingInstant", 1, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::FloorLowerByTexture, "Floor_LowerByTexture", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingRaiseToHighest, "Ceiling_RaiseToHighest", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingToHighestInstant, "Ceiling_ToHighestInstant", 1, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerToNearest, "Ceiling_LowerToNearest", 2, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::CeilingRaiseToLowest, "Ceiling_RaiseToLowest", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingRaiseToHighestFloor, "Ceiling_RaiseToHighestFloor", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingToFloorInstant, "Ceiling_ToFloorInstant", 1, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::CeilingRaiseByTexture, "Ceiling_RaiseByTexture", 2, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::CeilingLowerByTexture, "Ceiling_LowerByTexture", 2, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::StairsBuildDownDoom, "Stairs_BuildDownDoom", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::StairsBuildUpDoomSync, "Stairs_BuildUpDoomSync", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::StairsBuildDownDoomSync, "Stairs_BuildDownDoomSync", 4, 4, 4),
+    LineSpecialInfo::new(ActionSpecials::StairsBuildUpDoomCrush, "Stairs_BuildUpDoomCrush", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::DoorAnimatedClose, "Door_AnimatedClose", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::FloorStop, "Floor_Stop", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::CeilingStop, "Ceiling_Stop", 1, 1, 1),
+    LineSpecialInfo::new(ActionSpecials::SectorSetFloorGlow, "Sector_SetFloorGlow", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::SectorSetCeilingGlow, "Sector_SetCeilingGlow", 5, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::FloorMoveToValueAndCrush, "Floor_MoveToValueAndCrush", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::CeilingMoveToValueAndCrush, "Ceiling_MoveToValueAndCrush", 4, 5, 5),
+    LineSpecialInfo::new(ActionSpecials::LineSetAutomapFlags, "Line_SetAutomapFlags", 3, 3, 3),
+    LineSpecialInfo::new(ActionSpecials::LineSetAutomapStyle, "Line_SetAutomapStyle", 2, 2, 2),
+    LineSpecialInfo::new(ActionSpecials::PolyobjStopSound, "Polyobj_StopSound", 1, 1, 1),
+];
+
+pub fn line_special_info(special: i32) -> Option<&'static LineSpecialInfo> {
+    LINE_SPECIALS.binary_search_by_key(&special, |info| info.special as i32).ok().map(|index| &LINE_SPECIALS[index])
+}
+
+pub fn line_special_name(special: i32) -> &'static str {
+    match line_special_info(special) {
+        Some(info) => info.name,
+        None => "Unknown"
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, FromPrimitive, ToPrimitive)]
+pub enum ActivatorType {
+    #[default]
+    World,
+    Player,
+    Monster,
+    Projectile
+}
+
+//the thing that triggered the special, actor is -1 when it was not triggered by an actor (scripts, the level itself)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Activator {
+    pub actor: ActorIndex,
+    pub type_: ActivatorType
+}
+
+impl Activator {
+    pub fn new(actor: ActorIndex, type_: ActivatorType) -> Activator {
+        Activator { actor, type_ }
+    }
+
+    pub fn world() -> Activator {
+        Activator { actor: -1, type_: ActivatorType::World }
+    }
+
+    pub fn is_player(&self) -> bool {
+        self.type_ == ActivatorType::Player
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LevelExit {
+    Normal { position: i32 },
+    Secret { position: i32 },
+    NewMap { map: i32, position: i32 },
+    EndGame
+}
+
+//the args of the specials to the values used by the movers
+fn speed(arg: i32) -> f64 { arg as f64 / 8. }
+fn tics(arg: i32) -> i32 { arg * TICRATE / 35 }
+fn octics(arg: i32) -> i32 { arg * TICRATE / 8 }
+fn crush(arg: i32) -> i32 { if arg > 0 {arg} else {-1} }
+fn change(arg: i32) -> i32 { if (0..=3).contains(&arg) {arg} else {0} }
+
+//Line_SetBlocking arg bits to the line flags
+const BLOCKING_FLAGS: [LineFlags;11] = [
+    LineFlags::Blocking, LineFlags::BlockMonsters, LineFlags::BlockPlayers, LineFlags::BlockFloaters,
+    LineFlags::BlockProjectile, LineFlags::BlockEverything, LineFlags::Railing, LineFlags::BlockUse,
+    LineFlags::BlockSight, LineFlags::BlockHitscan, LineFlags::SoundBlock
+];
+
+const ORIG_FRICTION: i32 = 0xe800;
+
+fn damage_type_name(mod_: i32) -> &'static str {
+    match mod_ {
+        9 => "BFGSplash",
+        12 => "Drowning",
+        13 => "Slime",
+        14 => "Fire",
+        15 => "Crush",
+        16 => "Telefrag",
+        17 => "Falling",
+        18 => "Suicide",
+        20 => "Exit",
+        21 => "Railgun",
+        22 => "Ice",
+        23 => "Disintegrate",
+        24 => "Poison",
+        25 => "Electric",
+        _ => "None"
+    }
+}
+
+impl LevelLocals {
+    /* Checks if the activator is allowed to activate the line this way (P_TestActivateLine).
+     * side is 0 for the front and 1 for the back */
+    pub fn test_activate_line(&self, line_index: LineIndex, activator: &Activator, side: i32, activation_type: u32) -> bool {
+        let line = self.lines[line_index as usize].borrow();
+        let mut line_activation = line.activation;
+
+        if line.flags & LineFlags::FirstSideOnly.bits() != 0 && side == 1 {return false}
+
+        if line_activation & SpecialActivation::UseThrough.bits() != 0 {
+            line_activation |= SpecialActivation::Use.bits();
+        }
+        else if line.special == ActionSpecials::Teleport as i32 && line_activation & SpecialActivation::Cross.bits() != 0 &&
+                activation_type == SpecialActivation::PCross.bits() && activator.type_ == ActivatorType::Projectile {
+            //let missiles use the regular player teleports
+            line_activation |= SpecialActivation::PCross.bits();
+        }
+        //the generalized boom lines that monsters can use can be activated by anything except projectiles
+        if line_activation & SpecialActivation::AnyCross.bits() != 0 {
+            line_activation |= SpecialActivation::Cross.bits() | SpecialActivation::MCross.bits();
+        }
+        if activation_type == SpecialActivation::Use.bits() || activation_type == SpecialActivation::UseBack.bits() {
+            //TODO check_switch_range when the actors have a position
+        }
+
+        let monster = activator.type_ == ActivatorType::Monster;
+        if activation_type == SpecialActivation::Use.bits() && line_activation & SpecialActivation::MUse.bits() != 0 && monster {
+            return true
+        }
+        if activation_type == SpecialActivation::Push.bits() && line_activation & SpecialActivation::MPush.bits() != 0 && monster {
+            return true
+        }
+        if line_activation & activation_type == 0 &&
+                (activation_type != SpecialActivation::MCross.bits() || line_activation != SpecialActivation::Cross.bits()) {
+            return false
+        }
+        if activation_type == SpecialActivation::AnyCross.bits() {return true}
+
+        if monster && line.flags & LineFlags::MonstersCanActivate.bits() == 0 &&
+                (activation_type != SpecialActivation::MCross.bits() || line_activation != SpecialActivation::MCross.bits()) {
+            /* In hexen only the MCross lines could be activated by monsters.
+             * With lax activation monsters can also open some doors and use the teleporters */
+            if self.flags2 & LevelFlags::Level2LaxMonsterActivation.bits() == 0 {return false}
+            if (activation_type == SpecialActivation::Use.bits() || activation_type == SpecialActivation::Push.bits()) &&
+                    line.flags & LineFlags::Secret.bits() != 0 {
+                return false //never open secret doors
+            }
+
+            let special = num::FromPrimitive::from_i32(line.special);
+            let door_or_teleport = match special {
+                Some(ActionSpecials::DoorRaise) => line.args[0] == 0 && line.args[1] < 64,
+                Some(ActionSpecials::Teleport) | Some(ActionSpecials::TeleportNoFog) => true,
+                _ => false
+            };
+            let activation = line_activation & activation_type;
+            if activation == SpecialActivation::MCross.bits() {return true}
+            if activation == SpecialActivation::Cross.bits() || activation == SpecialActivation::Use.bits() ||
+                    activation == SpecialActivation::Push.bits() {
+                return door_or_teleport
+            }
+            return true
+        }
+        if activation_type == SpecialActivation::MCross.bits() && !monster && line_activation & activation_type == 0 {
+            return false
+        }
+        true
+    }
+
+    //P_ActivateLine, executes the special of the line when the activator is allowed to and removes it if it isn't repeatable
+    pub fn activate_line(&mut self, line_index: LineIndex, activator: &Activator, side: i32, activation_type: u32) -> bool {
+        if !Self::test_activate_line(self, line_index, activator, side, activation_type) {return false}
+
+        let (special, args, repeat) = {
+            let line = self.lines[line_index as usize].borrow();
+            (line.special, line.args, line.flags & LineFlags::RepeatSpecial.bits() != 0)
+        };
+        let success = Self::execute_special(self, special, line_index, activator, side == 1, &args) != 0;
+
+        let mut line = self.lines[line_index as usize].borrow_mut();
+        //don't remove the special if it was changed by the special itself
+        if !repeat && success && line.special == special {
+            line.special = 0;
+        }
+        //TODO change the switch texture for the use/push/impact lines
+        success
+    }
+
+    //for the acs vm, the args need to be in the range of the special
+    pub fn execute_line_special(&mut self, special: i32, activator: &Activator, args: &[i32]) -> i32 {
+        let info = match line_special_info(special) {
+            Some(info) => info,
+            None => {
+                println!("execute_line_special: unknown special {}", special);
+                return 0
+            }
+        };
+        if info.min_args < 0 || args.len() < info.min_args as usize || args.len() > info.max_args as usize {
+            println!("execute_line_special: {} called with {} args", info.name, args.len());
+            return 0
+        }
+        let mut special_args = [0;5];
+        special_args[..args.len()].copy_from_slice(args);
+        Self::execute_special(self, special, -1, activator, false, &special_args)
+    }
+
+    //the sectors the special works on, tag 0 uses the sector behind the line
+    pub fn tagged_sectors(&self, tag: i32, line: LineIndex) -> Vec<SectorIndex> {
+        if tag == 0 {
+            if line < 0 {return vec![]}
+            let back_sector = self.lines[line as usize].borrow().back_sector;
+            if back_sector < 0 {return vec![]}
+            return vec![back_sector]
+        }
+        Self::sectors_with_tag(self, tag)
+    }
+
+    //the lines the special works on, id 0 uses the line itself
+    pub fn tagged_lines(&self, id: i32, line: LineIndex) -> Vec<LineIndex> {
+        if id == 0 {
+            if line < 0 {return vec![]}
+            return vec![line]
+        }
+        Self::lines_with_id(self, id)
+    }
+
+    pub fn check_lock(&self, _activator: &Activator, _lock: i32) -> bool {
+        //TODO keys
+        true
+    }
+
+    fn check_if_exit_is_good(&self, activator: &Activator) -> bool {
+        //the world can always exit, monsters can't
+        activator.type_ == ActivatorType::World || activator.is_player()
+    }
+
+    pub fn exit_level(&mut self, exit: LevelExit) {
+        println!("exit_level: {:?}", exit);
+        self.pending_exit = Some(exit);
+    }
+
+    fn try_exit(&mut self, activator: &Activator, exit: LevelExit) -> bool {
+        if !Self::check_if_exit_is_good(self, activator) {return false}
+        Self::exit_level(self, exit);
+        true
+    }
+
+    fn set_sector_damage(&mut self, tag: i32, line: LineIndex, amount: i32, mod_: i32, interval: i32, leaky: i32) {
+        //the old way that sets the interval and leakiness from the damage amount
+        let (interval, leaky) = if interval <= 0 {
+            if amount < 20 {(32, 0)} else if amount < 50 {(32, 5)} else {(1, 256)}
+        } else {(interval, leaky)};
+
+        for sector in Self::tagged_sectors(self, tag, line) {
+            let mut sec = self.sectors[sector as usize].borrow_mut();
+            sec.damage_amount = amount;
+            sec.damage_type = String::from(damage_type_name(mod_));
+            sec.damage_interval = interval as i16;
+            sec.leaky_damage = leaky as i16;
+        }
+    }
+
+    //the amount is the friction as given to Sector_SetFriction, 100 is the normal friction
+    pub fn set_sector_friction(&mut self, tag: i32, line: LineIndex, amount: i32, alter_flag: bool) {
+        let friction = ((0x1eb8 * amount) / 0x80 + 0xd001).clamp(0, 0x10000);
+        let move_factor = if friction >= ORIG_FRICTION {
+            ((0x10092 - friction) * 0x70) / 0x158 //ice
+        } else {
+            ((friction - 0xdb34) * 0xa) / 0x80 //mud
+        }.max(32);
+
+        for sector in Self::tagged_sectors(self, tag, line) {
+            let mut sec = self.sectors[sector as usize].borrow_mut();
+            sec.friction = friction as f64 / 65536.;
+            sec.move_factor = move_factor as f64 / 65536.;
+            if alter_flag {
+                if friction == ORIG_FRICTION {sec.flags &= !SectorFlags::Friction.bits()}
+                else {sec.flags |= SectorFlags::Friction.bits()}
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn start_script(&mut self, action: ScriptAction, script: i32, map: i32, args: &[i32], activator: &Activator, line: LineIndex, backside: bool) -> i32 {
+        self.behaviors.request_script(action, script, map, args, *activator, line, backside);
+        1
+    }
+
+    /*
+     * Executes the special with the args (P_ExecuteSpecial of zdoom).
+     * line is -1 if it wasn't activated by a line.
+     * Returns 0 when the special did nothing.
+     */
+    pub fn execute_special(&mut self, special: i32, line: LineIndex, activator: &Activator, backside: bool, args: &[i32;5]) -> i32 {
+        let special = match num::FromPrimitive::from_i32(special) {
+            Some(s) => s,
+            None => return 0
+        };
+        let [arg0, arg1, arg2, arg3, arg4] = *args;
+
+        let result = match special {
+            //doors
+            ActionSpecials::DoorClose => Self::ev_do_door(self, DoorType::Close, line, activator, arg0, speed(arg1), 0, 0, arg2, false, 0),
+            ActionSpecials::DoorOpen => Self::ev_do_door(self, DoorType::Open, line, activator, arg0, speed(arg1), 0, 0, arg2, false, 0),
+            ActionSpecials::DoorRaise => Self::ev_do_door(self, DoorType::Raise, line, activator, arg0, speed(arg1), tics(arg2), 0, arg3, false, 0),
+            ActionSpecials::DoorLockedRaise => {
+                let type_ = if arg2 != 0 {DoorType::Raise} else {DoorType::Open};
+                Self::ev_do_door(self, type_, line, activator, arg0, speed(arg1), tics(arg2), arg3, arg4, false, 0)
+            }
+            ActionSpecials::DoorCloseWaitOpen => Self::ev_do_door(self, DoorType::CloseWaitOpen, line, activator, arg0, speed(arg1), octics(arg2), 0, arg3, false, 0),
+            ActionSpecials::DoorWaitRaise => Self::ev_do_door(self, DoorType::WaitRaise, line, activator, arg0, speed(arg1), tics(arg2), 0, arg4, false, tics(arg3)),
+            ActionSpecials::DoorWaitClose => Self::ev_do_door(self, DoorType::WaitClose, line, activator, arg0, speed(arg1), 0, 0, arg3, false, tics(arg2)),
+            ActionSpecials::GenericDoor => {
+                let type_ = match arg2 & 63 {
+                    0 => Some(DoorType::Raise),
+                    1 => Some(DoorType::Open),
+                    2 => Some(DoorType::CloseWaitOpen),
+                    3 => Some(DoorType::Close),
+                    _ => None
+                };
+                match type_ {
+                    Some(type_) => {
+                        //the boom local door light effect
+                        let (tag, light_tag) = if arg2 & 128 != 0 && arg0 != 0 {(0, arg0)} else {(arg0, 0)};
+                        Self::ev_do_door(self, type_, line, activator, tag, speed(arg1), octics(arg3), arg4, light_tag, arg2 & 64 != 0, 0)
+                    }
+                    None => false
+                }
+            }
+
+            //floors
+            ActionSpecials::FloorLowerByValue => Self::ev_do_floor(self, FloorType::LowerByValue, line, arg0, speed(arg1), arg2 as f64, -1, change(arg3), false, false),
+            ActionSpecials::FloorLowerByValueTimes8 => Self::ev_do_floor(self, FloorType::LowerByValue, line, arg0, speed(arg1), arg2 as f64 * 8., -1, change(arg3), false, false),
+            ActionSpecials::FloorLowerToLowest => Self::ev_do_floor(self, FloorType::LowerToLowest, line, arg0, speed(arg1), 0., -1, change(arg2), false, false),
+            ActionSpecials::FloorLowerToNearest => Self::ev_do_floor(self, FloorType::LowerToNearest, line, arg0, speed(arg1), 0., -1, change(arg2), false, false),
+            ActionSpecials::FloorLowerToHighest => Self::ev_do_floor(self, FloorType::LowerToHighest, line, arg0, speed(arg1), (arg2 - 128) as f64, -1, 0, false, arg3 == 1),
+            ActionSpecials::FloorLowerToHighestEE => Self::ev_do_floor(self, FloorType::LowerToHighest, line, arg0, speed(arg1), 0., -1, change(arg2), false, false),
+            ActionSpecials::FloorLowerToLowestCeiling => Self::ev_do_floor(self, FloorType::LowerToLowestCeiling, line, arg0, speed(arg1), 0., -1, change(arg2), false, false),
+            ActionSpecials::FloorLowerByTexture => Self::ev_do_floor(self, FloorType::LowerByTexture, line, arg0, speed(arg1), 0., crush(arg3), change(arg2), false, false),
+            ActionSpecials::FloorLowerToLowestTxTy => Self::ev_do_floor(self, FloorType::LowerAndChange, line, arg0, speed(arg1), 0., -1, 0, false, false),
+            ActionSpecials::FloorLowerInstant => Self::ev_do_floor(self, FloorType::LowerInstant, line, arg0, 0., arg2 as f64 * 8., -1, change(arg3), false, false),
+            ActionSpecials::FloorRaiseByValue => Self::ev_do_floor(self, FloorType::RaiseByValue, line, arg0, speed(arg1), arg2 as f64, crush(arg4), change(arg3), true, false),
+            ActionSpecials::FloorRaiseByValueTimes8 => Self::ev_do_floor(self, FloorType::RaiseByValue, line, arg0, speed(arg1), arg2 as f64 * 8., crush(arg4), change(arg3), true, false),
+            ActionSpecials::FloorRaiseToHighest => Self::ev_do_floor(self, FloorType::RaiseToHighest, line, arg0, speed(arg1), 0., crush(arg3), change(arg2), true, false),
+            ActionSpecials::FloorRaiseToNearest => Self::ev_do_floor(self, FloorType::RaiseToNearest, line, arg0, speed(arg1), 0., crush(arg3), change(arg2), true, false),
+            ActionSpecials::FloorRaiseToLowest => Self::ev_do_floor(self, FloorType::RaiseToLowest, line, arg0, 2., 0., crush(arg2), change(arg1), true, false),
+            ActionSpecials::FloorRaiseToLowestCeiling => Self::ev_do_floor(self, FloorType::RaiseToLowestCeiling, line, arg0, speed(arg1), arg4 as f64, crush(arg3), change(arg2), true, false),
+            ActionSpecials::FloorRaiseToCeiling => Self::ev_do_floor(self, FloorType::RaiseToCeiling, line, arg0, speed(arg1), arg4 as f64, crush(arg3), change(arg2), true, false),
+            ActionSpecials::FloorRaiseByTexture => Self::ev_do_floor(self, FloorType::RaiseByTexture, line, arg0, speed(arg1), 0., crush(arg3), change(arg2), true, false),
+            ActionSpecials::FloorRaiseByValueTxTy => Self::ev_do_floor(self, FloorType::Raise24AndChange, line, arg0, speed(arg1), arg2 as f64, -1, 0, false, false),
+            ActionSpecials::FloorRaiseInstant => Self::ev_do_floor(self, FloorType::RaiseInstant, line, arg0, 0., arg2 as f64 * 8., crush(arg4), change(arg3), true, false),
+            ActionSpecials::FloorRaiseAndCrush => Self::ev_do_floor(self, FloorType::RaiseAndCrush, line, arg0, speed(arg1), 0., arg2, change(arg3), true, false),
+            ActionSpecials::FloorRaiseAndCrushDoom => Self::ev_do_floor(self, FloorType::RaiseAndCrushDoom, line, arg0, speed(arg1), 0., arg2, change(arg3), false, false),
+            ActionSpecials::FloorToCeilingInstant => Self::ev_do_floor(self, FloorType::ToCeilingInstant, line, arg0, 0., arg3 as f64, crush(arg2), change(arg1), true, false),
+            ActionSpecials::FloorMoveToValue => {
+                let height = if arg3 != 0 {-arg2} else {arg2};
+                Self::ev_do_floor(self, FloorType::MoveToValue, line, arg0, speed(arg1), height as f64, -1, change(arg4), false, false)
+            }
+            ActionSpecials::FloorMoveToValueTimes8 => {
+                let height = if arg3 != 0 {-arg2} else {arg2};
+                Self::ev_do_floor(self, FloorType::MoveToValue, line, arg0, speed(arg1), height as f64 * 8., -1, change(arg4), false, false)
+            }
+            ActionSpecials::FloorMoveToValueAndCrush => Self::ev_do_floor(self, FloorType::MoveToValue, line, arg0, speed(arg1), arg2 as f64, arg3, 0, CrushMode::from_arg(arg4, false) == CrushMode::Hexen, false),
+            ActionSpecials::FloorCrushStop => Self::ev_floor_crush_stop(self, arg0, line),
+            ActionSpecials::FloorStop => Self::ev_stop_floor(self, arg0, line),
+            ActionSpecials::FloorDonut => Self::ev_do_donut(self, arg0, line, speed(arg1), speed(arg2)),
+            ActionSpecials::FloorTransferTrigger => Self::ev_do_change(self, line, ChangeType::TriggerChangeOnly, arg0),
+            ActionSpecials::FloorTransferNumeric => Self::ev_do_change(self, line, ChangeType::NumericChangeOnly, arg0),
+            ActionSpecials::GenericFloor => {
+                let up = arg4 & 8 != 0;
+                let type_ = match (arg3, up) {
+                    (1, true) => FloorType::RaiseToHighest,
+                    (2, true) => FloorType::RaiseToLowest,
+                    (3, true) => FloorType::RaiseToNearest,
+                    (4, true) => FloorType::RaiseToLowestCeiling,
+                    (5, true) => FloorType::RaiseToCeiling,
+                    (6, true) => FloorType::RaiseByTexture,
+                    (_, true) => FloorType::RaiseByValue,
+                    (1, false) => FloorType::LowerToHighest,
+                    (2, false) => FloorType::LowerToLowest,
+                    (3, false) => FloorType::LowerToNearest,
+                    (4, false) => FloorType::LowerToLowestCeiling,
+                    (5, false) => FloorType::LowerToCeiling,
+                    (6, false) => FloorType::LowerByTexture,
+                    (_, false) => FloorType::LowerByValue
+                };
+                let crush = if arg4 & 16 != 0 {20} else {-1};
+                Self::ev_do_floor(self, type_, line, arg0, speed(arg1), arg2 as f64, crush, arg4 & 7, false, false)
+            }
+
+            //floor and ceiling
+            ActionSpecials::FloorAndCeilingLowerByValue => Self::ev_do_elevator(self, line, ElevatorType::Lower, speed(arg1), arg2 as f64, arg0),
+            ActionSpecials::FloorAndCeilingRaiseByValue => Self::ev_do_elevator(self, line, ElevatorType::Raise, speed(arg1), arg2 as f64, arg0),
+            ActionSpecials::FloorAndCeilingLowerRaise => {
+                let floor = Self::ev_do_floor(self, FloorType::LowerToLowest, line, arg0, speed(arg1), 0., -1, 0, false, false);
+                let ceiling = Self::ev_do_ceiling(self, CeilingType::RaiseToHighest, line, arg0, speed(arg2), 0., 0., -1, 0, 0, CrushMode::Default);
+                floor || ceiling
+            }
+            ActionSpecials::ElevatorRaiseToNearest => Self::ev_do_elevator(self, line, ElevatorType::Up, speed(arg1), 0., arg0),
+            ActionSpecials::ElevatorMoveToFloor => Self::ev_do_elevator(self, line, ElevatorType::Current, speed(arg1), 0., arg0),
+            ActionSpecials::ElevatorLowerToNearest => Self::ev_do_elevator(self, line, ElevatorType::Down, speed(arg1), 0., arg0),
+
+            //ceilings
+            ActionSpecials::CeilingLowerByValue => Self::ev_do_ceiling(self, CeilingType::LowerByValue, line, arg0, speed(arg1), speed(arg1), arg2 as f64, crush(arg4), 0, change(arg3), CrushMode::Default),
+            ActionSpecials::CeilingRaiseByValue => Self::ev_do_ceiling(self, CeilingType::RaiseByValue, line, arg0, speed(arg1), speed(arg1), arg2 as f64, -1, 0, change(arg3), CrushMode::Default),
+            ActionSpecials::CeilingLowerByValueTimes8 => Self::ev_do_ceiling(self, CeilingType::LowerByValue, line, arg0, speed(arg1), speed(arg1), arg2 as f64 * 8., -1, 0, change(arg3), CrushMode::Default),
+            ActionSpecials::CeilingRaiseByValueTimes8 => Self::ev_do_ceiling(self, CeilingType::RaiseByValue, line, arg0, speed(arg1), speed(arg1), arg2 as f64 * 8., -1, 0, change(arg3), CrushMode::Default),
+            ActionSpecials::CeilingCrushAndRaise => Self::ev_do_ceiling(self, CeilingType::CrushAndRaise, line, arg0, speed(arg1), speed(arg1) / 2., 8., arg2, 0, 0, CrushMode::from_arg(arg3, false)),
+            ActionSpecials::CeilingLowerAndCrush => Self::ev_do_ceiling(self, CeilingType::LowerAndCrush, line, arg0, speed(arg1), speed(arg1), 8., arg2, 0, 0, CrushMode::from_arg(arg3, arg1 == 8)),
+            ActionSpecials::CeilingLowerAndCrushDist => Self::ev_do_ceiling(self, CeilingType::LowerAndCrush, line, arg0, speed(arg1), speed(arg1), arg3 as f64, arg2, 0, 0, CrushMode::from_arg(arg4, arg1 == 8)),
+            ActionSpecials::CeilingCrushStop => Self::ev_ceiling_crush_stop(self, arg0, arg1 != 0),
+            ActionSpecials::CeilingCrushRaiseAndStay => Self::ev_do_ceiling(self, CeilingType::CrushRaiseAndStay, line, arg0, speed(arg1), speed(arg1) / 2., 8., arg2, 0, 0, CrushMode::from_arg(arg3, false)),
+            ActionSpecials::CeilingMoveToValue => {
+                let height = if arg3 != 0 {-arg2} else {arg2};
+                Self::ev_do_ceiling(self, CeilingType::MoveToValue, line, arg0, speed(arg1), speed(arg1), height as f64, -1, 0, change(arg4), CrushMode::Default)
+            }
+            ActionSpecials::CeilingMoveToValueTimes8 => {
+                let height = if arg3 != 0 {-arg2} else {arg2};
+                Self::ev_do_ceiling(self, CeilingType::MoveToValue, line, arg0, speed(arg1), speed(arg1), height as f64 * 8., -1, 0, change(arg4), CrushMode::Default)
+            }
+            ActionSpecials::CeilingMoveToValueAndCrush => Self::ev_do_ceiling(self, CeilingType::MoveToValue, line, arg0, speed(arg1), speed(arg1), arg2 as f64, arg3, 0, 0, CrushMode::from_arg(arg4, false)),
+            ActionSpecials::CeilingLowerToHighestFloor => Self::ev_do_ceiling(self, CeilingType::LowerToHighestFloor, line, arg0, speed(arg1), speed(arg1), arg4 as f64, crush(arg3), 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingLowerInstant => Self::ev_do_ceiling(self, CeilingType::LowerInstant, line, arg0, 0., 0., arg2 as f64 * 8., crush(arg4), 0, change(arg3), CrushMode::Default),
+            ActionSpecials::CeilingRaiseInstant => Self::ev_do_ceiling(self, CeilingType::RaiseInstant, line, arg0, 0., 0., arg2 as f64 * 8., -1, 0, change(arg3), CrushMode::Default),
+            ActionSpecials::CeilingCrushRaiseAndStayA => Self::ev_do_ceiling(self, CeilingType::CrushRaiseAndStay, line, arg0, speed(arg1), speed(arg2), 0., arg3, 0, 0, CrushMode::from_arg(arg4, false)),
+            ActionSpecials::CeilingCrushRaiseAndStaySilA => Self::ev_do_ceiling(self, CeilingType::CrushRaiseAndStay, line, arg0, speed(arg1), speed(arg2), 0., arg3, 1, 0, CrushMode::from_arg(arg4, false)),
+            ActionSpecials::CeilingCrushAndRaiseA => Self::ev_do_ceiling(self, CeilingType::CrushAndRaise, line, arg0, speed(arg1), speed(arg2), 0., arg3, 0, 0, CrushMode::from_arg(arg4, false)),
+            ActionSpecials::CeilingCrushAndRaiseSilentA => Self::ev_do_ceiling(self, CeilingType::CrushAndRaise, line, arg0, speed(arg1), speed(arg2), 0., arg3, 1, 0, CrushMode::from_arg(arg4, false)),
+            ActionSpecials::CeilingCrushAndRaiseDist => Self::ev_do_ceiling(self, CeilingType::CrushAndRaise, line, arg0, speed(arg2), speed(arg2), arg1 as f64, arg3, 0, 0, CrushMode::from_arg(arg4, false)),
+            ActionSpecials::CeilingCrushAndRaiseSilentDist => Self::ev_do_ceiling(self, CeilingType::CrushAndRaise, line, arg0, speed(arg2), speed(arg2), arg1 as f64, arg3, 1, 0, CrushMode::from_arg(arg4, false)),
+            ActionSpecials::CeilingRaiseToNearest => Self::ev_do_ceiling(self, CeilingType::RaiseToNearest, line, arg0, speed(arg1), speed(arg1), 0., -1, 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingRaiseToHighest => Self::ev_do_ceiling(self, CeilingType::RaiseToHighest, line, arg0, speed(arg1), speed(arg1), 0., -1, 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingRaiseToLowest => Self::ev_do_ceiling(self, CeilingType::RaiseToLowest, line, arg0, speed(arg1), speed(arg1), 0., -1, 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingRaiseToHighestFloor => Self::ev_do_ceiling(self, CeilingType::RaiseToHighestFloor, line, arg0, speed(arg1), speed(arg1), 0., -1, 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingRaiseByTexture => Self::ev_do_ceiling(self, CeilingType::RaiseByTexture, line, arg0, speed(arg1), speed(arg1), 0., -1, 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingLowerToLowest => Self::ev_do_ceiling(self, CeilingType::LowerToLowest, line, arg0, speed(arg1), speed(arg1), 0., crush(arg3), 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingLowerToNearest => Self::ev_do_ceiling(self, CeilingType::LowerToNearest, line, arg0, speed(arg1), speed(arg1), 0., crush(arg3), 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingLowerToFloor => Self::ev_do_ceiling(self, CeilingType::LowerToFloor, line, arg0, speed(arg1), speed(arg1), arg4 as f64, crush(arg3), 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingLowerByTexture => Self::ev_do_ceiling(self, CeilingType::LowerByTexture, line, arg0, speed(arg1), speed(arg1), 0., crush(arg3), 0, change(arg2), CrushMode::Default),
+            ActionSpecials::CeilingToHighestInstant => Self::ev_do_ceiling(self, CeilingType::ToHighestInstant, line, arg0, 2., 0., 0., crush(arg2), 0, change(arg1), CrushMode::Default),
+            ActionSpecials::CeilingToFloorInstant => Self::ev_do_ceiling(self, CeilingType::ToFloorInstant, line, arg0, 2., 0., arg3 as f64, crush(arg2), 0, change(arg1), CrushMode::Default),
+            ActionSpecials::CeilingStop => Self::ev_stop_ceiling(self, arg0, line),
+            ActionSpecials::GenericCeiling => {
+                let up = arg4 & 8 != 0;
+                let type_ = match (arg3, up) {
+                    (1, true) => CeilingType::RaiseToHighest,
+                    (2, true) => CeilingType::RaiseToLowest,
+                    (3, true) => CeilingType::RaiseToNearest,
+                    (4, true) => CeilingType::RaiseToHighestFloor,
+                    (5, true) => CeilingType::RaiseToFloor,
+                    (6, true) => CeilingType::RaiseByTexture,
+                    (_, true) => CeilingType::RaiseByValue,
+                    (1, false) => CeilingType::LowerToHighest,
+                    (2, false) => CeilingType::LowerToLowest,
+                    (3, false) => CeilingType::LowerToNearest,
+                    (4, false) => CeilingType::LowerToHighestFloor,
+                    (5, false) => CeilingType::LowerToFloor,
+                    (6, false) => CeilingType::LowerByTexture,
+                    (_, false) => CeilingType::LowerByValue
+                };
+                let crush = if arg4 & 16 != 0 {20} else {-1};
+                Self::ev_do_ceiling(self, type_, line, arg0, speed(arg1), speed(arg1), arg2 as f64, crush, 0, arg4 & 7, CrushMode::Default)
+            }
+            ActionSpecials::GenericCrusher => Self::ev_do_ceiling(self, CeilingType::CrushAndRaise, line, arg0, speed(arg1), speed(arg2), 0., arg4, if arg3 != 0 {2} else {0}, 0, CrushMode::Slowdown),
+            ActionSpecials::GenericCrusher2 => Self::ev_do_ceiling(self, CeilingType::CrushAndRaise, line, arg0, speed(arg1), speed(arg2), 0., arg4, if arg3 != 0 {2} else {0}, 0, CrushMode::Hexen),
+
+            //platforms
+            ActionSpecials::PlatPerpetualRaise => Self::ev_do_plat(self, arg0, line, PlatType::PerpetualRaise, 0., speed(arg1), tics(arg2), 8, 0),
+            ActionSpecials::PlatPerpetualRaiseLip => Self::ev_do_plat(self, arg0, line, PlatType::PerpetualRaise, 0., speed(arg1), tics(arg2), arg3, 0),
+            ActionSpecials::PlatStop => Self::ev_stop_plat(self, arg0, arg1 != 0),
+            ActionSpecials::PlatDownWaitUpStay => Self::ev_do_plat(self, arg0, line, PlatType::DownWaitUpStay, 0., speed(arg1), tics(arg2), 8, 0),
+            ActionSpecials::PlatDownWaitUpStayLip => {
+                let type_ = if arg4 != 0 {PlatType::DownWaitUpStayStone} else {PlatType::DownWaitUpStay};
+                Self::ev_do_plat(self, arg0, line, type_, 0., speed(arg1), tics(arg2), arg3, 0)
+            }
+            ActionSpecials::PlatDownByValue => Self::ev_do_plat(self, arg0, line, PlatType::DownByValue, arg3 as f64 * 8., speed(arg1), tics(arg2), 0, 0),
+            ActionSpecials::PlatUpByValue => Self::ev_do_plat(self, arg0, line, PlatType::UpByValue, arg3 as f64 * 8., speed(arg1), tics(arg2), 0, 0),
+            ActionSpecials::PlatUpWaitDownStay => Self::ev_do_plat(self, arg0, line, PlatType::UpWaitDownStay, 0., speed(arg1), tics(arg2), 0, 0),
+            ActionSpecials::PlatUpNearestWaitDownStay => Self::ev_do_plat(self, arg0, line, PlatType::UpNearestWaitDownStay, 0., speed(arg1), tics(arg2), 0, 0),
+            ActionSpecials::PlatRaiseAndStayTx0 => {
+                let type_ = if arg2 == 1 {PlatType::RaiseAndStayLockout} else {PlatType::RaiseAndStay};
+                Self::ev_do_plat(self, arg0, line, type_, 0., speed(arg1), 0, 0, 1)
+            }
+            ActionSpecials::PlatUpByValueStayTx => Self::ev_do_plat(self, arg0, line, PlatType::UpByValueStay, arg2 as f64 * 8., speed(arg1), 0, 0, 2),
+            ActionSpecials::PlatToggleCeiling => Self::ev_do_plat(self, arg0, line, PlatType::Toggle, 0., 0., 0, 0, 0),
+            ActionSpecials::GenericLift => {
+                let type_ = match arg3 {
+                    1 => PlatType::DownWaitUpStay,
+                    2 => PlatType::DownToNearestFloor,
+                    3 => PlatType::DownToLowestCeiling,
+                    4 => PlatType::PerpetualRaise,
+                    _ => PlatType::UpByValue
+                };
+                Self::ev_do_plat(self, arg0, line, type_, arg4 as f64 * 8., speed(arg1), octics(arg2), 0, 0)
+            }
+
+            //stairs
+            ActionSpecials::StairsBuildDown => Self::ev_build_stairs(self, arg0, StairType::BuildDown, line, arg2 as f64, speed(arg1), tics(arg3), arg4, false, STAIR_USE_SPECIALS),
+            ActionSpecials::StairsBuildUp => Self::ev_build_stairs(self, arg0, StairType::BuildUp, line, arg2 as f64, speed(arg1), tics(arg3), arg4, false, STAIR_USE_SPECIALS),
+            ActionSpecials::StairsBuildDownSync => Self::ev_build_stairs(self, arg0, StairType::BuildDown, line, arg2 as f64, speed(arg1), 0, arg3, false, STAIR_SYNC),
+            ActionSpecials::StairsBuildUpSync => Self::ev_build_stairs(self, arg0, StairType::BuildUp, line, arg2 as f64, speed(arg1), 0, arg3, false, STAIR_SYNC),
+            ActionSpecials::StairsBuildDownDoom => Self::ev_build_stairs(self, arg0, StairType::BuildDown, line, arg2 as f64, speed(arg1), tics(arg3), arg4, false, 0),
+            ActionSpecials::StairsBuildUpDoom => Self::ev_build_stairs(self, arg0, StairType::BuildUp, line, arg2 as f64, speed(arg1), tics(arg3), arg4, false, 0),
+            ActionSpecials::StairsBuildDownDoomSync => Self::ev_build_stairs(self, arg0, StairType::BuildDown, line, arg2 as f64, speed(arg1), 0, arg3, false, STAIR_SYNC),
+            ActionSpecials::StairsBuildUpDoomSync => Self::ev_build_stairs(self, arg0, StairType::BuildUp, line, arg2 as f64, speed(arg1), 0, arg3, false, STAIR_SYNC),
+            ActionSpecials::StairsBuildUpDoomCrush => Self::ev_build_stairs(self, arg0, StairType::BuildUp, line, arg2 as f64, speed(arg1), tics(arg3), arg4, false, STAIR_CRUSH),
+            ActionSpecials::GenericStairs => {
+                let type_ = if arg3 & 1 != 0 {StairType::BuildUp} else {StairType::BuildDown};
+                Self::ev_build_stairs(self, arg0, type_, line, arg2 as f64, speed(arg1), 0, arg4, arg3 & 2 != 0, 0)
+            }
+
+            //pillars
+            ActionSpecials::PillarBuild => Self::ev_do_pillar(self, PillarType::Build, line, arg0, speed(arg1), arg2 as f64, 0., -1, false),
+            ActionSpecials::PillarBuildAndCrush => Self::ev_do_pillar(self, PillarType::Build, line, arg0, speed(arg1), arg2 as f64, 0., arg3, CrushMode::from_arg(arg4, true) == CrushMode::Hexen),
+            ActionSpecials::PillarOpen => Self::ev_do_pillar(self, PillarType::Open, line, arg0, speed(arg1), arg2 as f64, arg3 as f64, -1, false),
+
+            //lights
+            ActionSpecials::LightRaiseByValue => { Self::ev_light_change(self, arg0, arg1); true }
+            ActionSpecials::LightLowerByValue => { Self::ev_light_change(self, arg0, -arg1); true }
+            ActionSpecials::LightChangeToValue => { Self::ev_light_turn_on(self, arg0, arg1); true }
+            ActionSpecials::LightMaxNeighbor => { Self::ev_light_turn_on(self, arg0, -1); true }
+            ActionSpecials::LightMinNeighbor => { Self::ev_turn_tag_lights_off(self, arg0); true }
+            ActionSpecials::LightFade => { Self::ev_start_light_fading(self, arg0, arg1, tics(arg2)); true }
+            ActionSpecials::LightGlow => { Self::ev_start_light_glowing(self, arg0, arg1, arg2, tics(arg3)); true }
+            ActionSpecials::LightFlicker => { Self::ev_start_light_flickering(self, arg0, arg1, arg2); true }
+            ActionSpecials::LightStrobe => { Self::ev_start_light_strobing(self, arg0, arg1, arg2, tics(arg3), tics(arg4)); true }
+            ActionSpecials::LightStrobeDoom => { Self::ev_start_light_strobing_doom(self, arg0, tics(arg1), tics(arg2)); true }
+            ActionSpecials::LightStop => { Self::ev_stop_light_effect(self, arg0); true }
+            ActionSpecials::LightForceLightning => { Self::ev_force_lightning(self, arg0); true }
+
+            //sectors
+            ActionSpecials::SectorSetDamage => { Self::set_sector_damage(self, arg0, line, arg1, arg2, arg3, arg4); true }
+            ActionSpecials::SectorSetGravity => {
+                let gravity = arg1 as f64 + arg2.min(99) as f64 * 0.01;
+                for sector in Self::tagged_sectors(self, arg0, line) {
+                    self.sectors[sector as usize].borrow_mut().gravity = gravity;
+                }
+                true
+            }
+            ActionSpecials::SectorSetFriction => { Self::set_sector_friction(self, arg0, line, arg1, true); true }
+            ActionSpecials::SectorSetColor => {
+                for sector in Self::tagged_sectors(self, arg0, line) {
+                    let mut sec = self.sectors[sector as usize].borrow_mut();
+                    sec.color_map.light_color = PalEntry::new_rgb(arg1 as u8, arg2 as u8, arg3 as u8);
+                    sec.color_map.desaturation = arg4.clamp(0, 255) as u8;
+                }
+                true
+            }
+            ActionSpecials::SectorSetFade => {
+                for sector in Self::tagged_sectors(self, arg0, line) {
+                    self.sectors[sector as usize].borrow_mut().color_map.fade_color = PalEntry::new_rgb(arg1 as u8, arg2 as u8, arg3 as u8);
+                }
+                true
+            }
+            ActionSpecials::SectorChangeFlags => {
+                //the secret flags can't be changed
+                let set = arg1 as u32 & !SectorFlags::NoModify.bits();
+                let clear = arg2 as u32 & !SectorFlags::NoModify.bits();
+                for sector in Self::tagged_sectors(self, arg0, line) {
+                    let mut sec = self.sectors[sector as usize].borrow_mut();
+                    sec.flags = (sec.flags | set) & !clear;
+                }
+                true
+            }
+
+            //lines
+            ActionSpecials::LineSetBlocking => {
+                if arg0 == 0 {return 0}
+                let mut set = 0;
+                let mut clear = 0;
+                for (i, flag) in BLOCKING_FLAGS.iter().enumerate() {
+                    if arg1 & (1 << i) != 0 {set |= flag.bits()}
+                    if arg2 & (1 << i) != 0 {clear |= flag.bits()}
+                }
+                for index in Self::tagged_lines(self, arg0, -1) {
+                    let mut l = self.lines[index as usize].borrow_mut();
+                    l.flags = (l.flags & !clear) | set;
+                }
+                true
+            }
+
+            //exits
+            ActionSpecials::ExitNormal => Self::try_exit(self, activator, LevelExit::Normal { position: arg0 }),
+            ActionSpecials::ExitSecret => Self::try_exit(self, activator, LevelExit::Secret { position: arg0 }),
+            ActionSpecials::TeleportNewMap => !backside && Self::try_exit(self, activator, LevelExit::NewMap { map: arg0, position: arg1 }),
+            ActionSpecials::TeleportEndGame => !backside && Self::try_exit(self, activator, LevelExit::EndGame),
+
+            //acs
+            ActionSpecials::AcsExecute => return Self::start_script(self, ScriptAction::Execute, arg0, arg1, &args[2..], activator, line, backside),
+            ActionSpecials::AcsExecuteAlways => return Self::start_script(self, ScriptAction::ExecuteAlways, arg0, arg1, &args[2..], activator, line, backside),
+            ActionSpecials::AcsExecuteWithResult => return Self::start_script(self, ScriptAction::ExecuteWithResult, arg0, 0, &args[1..], activator, line, backside),
+            ActionSpecials::AcsSuspend => return Self::start_script(self, ScriptAction::Suspend, arg0, arg1, &[], activator, line, backside),
+            ActionSpecials::AcsTerminate => return Self::start_script(self, ScriptAction::Terminate, arg0, arg1, &[], activator, line, backside),
+            ActionSpecials::AcsLockedExecute | ActionSpecials::AcsLockedExecuteDoor => {
+                if arg4 != 0 && !Self::check_lock(self, activator, arg4) {return 0}
+                return Self::start_script(self, ScriptAction::Execute, arg0, arg1, &args[2..4], activator, line, backside)
+            }
+
+            //teleports
+            ActionSpecials::Teleport => {
+                let flags = if arg2 == 0 {TeleportFlags::DestFog | TeleportFlags::SourceFog} else {TeleportFlags::DestFog};
+                Self::ev_teleport(self, arg0, arg1, line, activator, backside, flags)
+            }
+            ActionSpecials::TeleportNoFog => {
+                let mut flags = match arg1 {
+                    0 => TeleportFlags::KeepOrientation,
+                    2 if line >= 0 => TeleportFlags::KeepOrientation | TeleportFlags::RotateBoom,
+                    3 if line >= 0 => TeleportFlags::KeepOrientation | TeleportFlags::RotateBoomInverse,
+                    _ => TeleportFlags::empty()
+                };
+                if arg3 != 0 {flags |= TeleportFlags::KeepHeight}
+                Self::ev_teleport(self, arg0, arg2, line, activator, backside, flags)
+            }
+            ActionSpecials::TeleportLine => Self::ev_silent_line_teleport(self, line, activator, backside, arg1, arg2 != 0),
+
+            _ => {
+                //TODO the polyobjects, scrollers etc
+                println!("execute_special: {} isn't supported yet", line_special_name(special as i32));
+                false
+            }
+        };
+        result as i32
+    }
+}
+
+/*
+ * The sector specials that start a thinker when the level is loaded (P_SpawnSpecials of zdoom)
+ */
+impl LevelLocals {
+    pub fn spawn_specials(&mut self) {
+        for sector in 0..self.sectors.len() as SectorIndex {
+            let special = self.sectors[sector as usize].borrow().special & 0xff;
+            let special = match SectorSpecials::from_i32(special) {
+                Some(special) => special,
+                None => continue
+            };
+            match special {
+                SectorSpecials::DLightFlicker => {
+                    let flash = Self::create_light_flash(self, sector);
+                    Self::add_light_effect(self, LightEffect::LightFlash(flash));
+                }
+                SectorSpecials::DLightStrobeFast | SectorSpecials::DLightStrobeHurt | SectorSpecials::SLightStrobeHurt => {
+                    let strobe = Self::create_strobe(self, sector, STROBEBRIGHT, FASTDARK, false);
+                    Self::add_light_effect(self, LightEffect::Strobe(strobe));
+                }
+                SectorSpecials::DLightStrobeSlow => {
+                    let strobe = Self::create_strobe(self, sector, STROBEBRIGHT, SLOWDARK, false);
+                    Self::add_light_effect(self, LightEffect::Strobe(strobe));
+                }
+                SectorSpecials::DLightStrobeSlowSync => {
+                    let strobe = Self::create_strobe(self, sector, STROBEBRIGHT, SLOWDARK, true);
+                    Self::add_light_effect(self, LightEffect::Strobe(strobe));
+                }
+                SectorSpecials::DLightStrobeFastSync => {
+                    let strobe = Self::create_strobe(self, sector, STROBEBRIGHT, FASTDARK, true);
+                    Self::add_light_effect(self, LightEffect::Strobe(strobe));
+                }
+                SectorSpecials::DLightGlow => {
+                    let glow = Self::create_glow(self, sector);
+                    Self::add_light_effect(self, LightEffect::Glow(glow));
+                }
+                SectorSpecials::DLightFireFlicker => {
+                    let flicker = Self::create_fire_flicker(self, sector);
+                    Self::add_light_effect(self, LightEffect::FireFlicker(flicker));
+                }
+                SectorSpecials::DSectorDoorCloseIn30 => Self::create_door(self, sector, DoorType::WaitClose, 2., 0, 0, 30 * TICRATE),
+                SectorSpecials::DSectorDoorRaiseIn5Mins => Self::create_door(self, sector, DoorType::WaitRaise, 2., TICRATE * 30 / 7, 0, 5 * 60 * TICRATE),
+                SectorSpecials::LightPhased | SectorSpecials::LightSequenceStart => {
+                    //TODO DPhased and the light sequences
+                }
+                _ => ()
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use bitflags::bitflags;
+
+use crate::vector::{Angle, Vector2};
+
+use super::LevelLocals;
+use super::level_actor::{Actor, ActorIndex};
+use super::level_elements::{LineIndex, BOXBOTTOM, BOXLEFT, BOXRIGHT, BOXTOP};
+use super::level_info::*;
+use super::level_map::MAXRADIUS;
+use super::level_specials::{Activator, ActivatorType};
+
+//the player can't move for half a second after a teleport
+const TELEPORT_FREEZE: i32 = 18;
+//the fog at the destination is in front of the actor
+const FOG_DISTANCE: f64 = 20.;
+//the tries to get on the right side of the exit line
+const FUDGE_FACTOR: i32 = 10;
+
+bitflags! {
+    //how the actor comes out of the teleport (TELF_* of zdoom)
+    #[derive(Clone, Copy, Default, PartialEq, Debug)]
+    pub struct TeleportFlags: u32 {
+        const DestFog = 1;
+        const SourceFog = 2;
+        const KeepOrientation = 4; //the angle and velocity are turned by the angle of the line to the destination
+        const KeepHeight = 8; //the same height above the floor
+        const RotateBoom = 16; //turned by the angle between the line and the destination like the boom silent teleports
+        const RotateBoomInverse = 32; //the other way around
+    }
+}
+
+/*
+ * A teleport of the actor, they are done by run_teleports.
+ * The actor that crossed the line is held by the code that moves it, so it can't be teleported right away.
+ */
+#[derive(Clone, Copy, Debug)]
+pub enum TeleportMove {
+    //to the teleport destination thing
+    Spot { dest: ActorIndex, line: LineIndex, flags: TeleportFlags },
+    //to the same place on the other line (the silent line teleports of boom)
+    Line { from: LineIndex, to: LineIndex, reverse: bool }
+}
+
+/*
+ * The teleports (p_teleport.cpp of zdoom)
+ */
+impl LevelLocals {
+    /*
+     * EV_Teleport, the activator goes to the teleport destination with the tid or in the sectors with the tag.
+     * The lines teleport from their front so the actor can walk out of the teleporter.
+     */
+    pub fn ev_teleport(&mut self, tid: i32, tag: i32, line: LineIndex, activator: &Activator, backside: bool, flags: TeleportFlags) -> bool {
+        //the projectiles don't teleport (NOTELEPORT)
+        if activator.actor < 0 || activator.type_ == ActivatorType::Projectile {return false}
+        if line >= 0 && backside {return false}
+        let dest = Self::select_tele_dest(self, tid, tag);
+        if dest < 0 {return false}
+        Self::request_teleport(self, activator.actor, TeleportMove::Spot { dest, line, flags });
+        true
+    }
+
+    //EV_SilentLineTeleport, the activator comes out of the line with the id at the same place along it
+    pub fn ev_silent_line_teleport(&mut self, line: LineIndex, activator: &Activator, backside: bool, id: i32, reverse: bool) -> bool {
+        if line < 0 || backside || activator.actor < 0 || activator.type_ == ActivatorType::Projectile {return false}
+        if self.lines[line as usize].borrow().back_sector < 0 {return false}
+        let to = Self::lines_with_id(self, id).into_iter().find(|to| *to != line && self.lines[*to as usize].borrow().back_sector >= 0);
+        match to {
+            Some(to) => {
+                Self::request_teleport(self, activator.actor, TeleportMove::Line { from: line, to, reverse });
+                true
+            }
+            None => false
+        }
+    }
+
+    /*
+     * SelectTeleDest, a random teleport destination with the tid, only the ones in the sectors with the tag when it isn't 0.
+     * Without a tid it is the first teleport destination in the sectors with the tag.
+     */
+    fn select_tele_dest(&mut self, tid: i32, tag: i32) -> ActorIndex {
+        let actors = self.thinkers.all();
+        let is_dest = |level: &LevelLocals, thing: ActorIndex| level.actor(thing).is_some_and(|actor| actor.type_ == MT_TELEPORTMAN);
+        if tid != 0 {
+            let in_tag = |level: &LevelLocals, actor: &Actor| tag == 0 || (actor.sector >= 0 && level.sector_has_tag(actor.sector as usize, tag));
+            let mut spots: Vec<ActorIndex> = actors.iter().copied()
+                .filter(|thing| is_dest(self, *thing) && self.actor(*thing).is_some_and(|actor| actor.tid == tid && in_tag(self, actor))).collect();
+            //any thing with the tid will do when there is no destination
+            if spots.is_empty() && tag == 0 {
+                spots = actors.iter().copied().filter(|thing| self.actor(*thing).is_some_and(|actor| actor.tid == tid)).collect();
+            }
+            if spots.is_empty() {return -1}
+            return spots[self.random.random() as usize % spots.len()]
+        }
+        if tag != 0 {
+            for sector in Self::sectors_with_tag(self, tag) {
+                let spot = actors.iter().copied().find(|thing| is_dest(self, *thing) && self.actor(*thing).is_some_and(|actor| actor.sector == sector));
+                if let Some(spot) = spot {return spot}
+            }
+        }
+        -1
+    }
+
+    //the teleport is done now when the actor isn't held, otherwise by the code holding it
+    fn request_teleport(&mut self, thing: ActorIndex, teleport: TeleportMove) {
+        self.teleports.push((thing, teleport));
+        Self::with_actor(self, thing, |level, actor| level.run_teleports(thing, actor));
+    }
+
+    //the teleports of the actor that were asked for while it was held
+    pub fn run_teleports(&mut self, index: ActorIndex, actor: &mut Actor) {
+        if !self.teleports.iter().any(|(thing, _)| *thing == index) {return}
+        let (teleports, others) = std::mem::take(&mut self.teleports).into_iter().partition(|(thing, _)| *thing == index);
+        self.teleports = others;
+        for (_, teleport) in teleports {
+            match teleport {
+                TeleportMove::Spot { dest, line, flags } => {Self::teleport_to_spot(self, index, actor, dest, line, flags);}
+                TeleportMove::Line { from, to, reverse } => {Self::line_teleport(self, index, actor, from, to, reverse);}
+            }
+        }
+    }
+
+    //the end of EV_Teleport, the actor keeps its angle and velocity with KeepOrientation, turned with the line by the boom silent teleports
+    fn teleport_to_spot(&mut self, index: ActorIndex, actor: &mut Actor, dest: ActorIndex, line: LineIndex, flags: TeleportFlags) -> bool {
+        let (spot, spot_angle) = match self.actor(dest) {
+            Some(spot) => (spot.pos, spot.angle),
+            None => return false
+        };
+        if !flags.contains(TeleportFlags::KeepOrientation) {
+            return Self::teleport(self, index, actor, spot.x, spot.y, spot_angle, flags)
+        }
+        if !flags.intersects(TeleportFlags::RotateBoom | TeleportFlags::RotateBoomInverse) || line < 0 {
+            return Self::teleport(self, index, actor, spot.x, spot.y, actor.angle, flags)
+        }
+
+        //walking straight into the line comes out in the direction of the destination
+        let line_angle = self.lines[line as usize].borrow().delta().angle();
+        let mut angle = line_angle.degrees() - spot_angle.degrees() + 90.;
+        if flags.contains(TeleportFlags::RotateBoomInverse) {angle = -angle}
+        if !Self::teleport(self, index, actor, spot.x, spot.y, actor.angle, flags) {return false}
+        actor.angle.subtract(&Angle::<f64>::from_degrees(angle));
+        if actor.player >= 0 {self.players[actor.player as usize].fix_angle = true}
+        let (sin, cos) = (-angle).to_radians().sin_cos();
+        let vel = actor.vel;
+        actor.vel.x = vel.x * cos - vel.y * sin;
+        actor.vel.y = vel.y * cos + vel.x * sin;
+        true
+    }
+
+    /*
+     * P_Teleport, the actor goes to the position on the floor, or as high above it as it was with KeepHeight.
+     * The fog shows where it went from and to.
+     */
+    fn teleport(&mut self, index: ActorIndex, actor: &mut Actor, x: f64, y: f64, angle: Angle<f64>, flags: TeleportFlags) -> bool {
+        let old = actor.pos;
+        let height = actor.pos.z - actor.floor_z;
+        if !Self::teleport_move(self, index, actor, x, y) {return false}
+        actor.pos.z = if flags.contains(TeleportFlags::KeepHeight) {actor.floor_z + height} else {actor.floor_z};
+
+        //TODO the teleport sound
+        if flags.contains(TeleportFlags::SourceFog) {
+            Self::spawn_actor(self, MT_TFOG, old.x, old.y, old.z);
+        }
+        if flags.contains(TeleportFlags::DestFog) {
+            let fog = angle.to_vector(FOG_DISTANCE);
+            Self::spawn_actor(self, MT_TFOG, x + fog.x, y + fog.y, actor.pos.z);
+        }
+
+        actor.angle = angle;
+        if actor.player >= 0 {self.players[actor.player as usize].fix_angle = true}
+        if !flags.contains(TeleportFlags::KeepOrientation) {
+            actor.vel.x = 0.;
+            actor.vel.y = 0.;
+            actor.vel.z = 0.;
+            if actor.player >= 0 {actor.reaction_time = TELEPORT_FREEZE}
+        }
+        //the view doesn't slide there
+        actor.prev = actor.pos;
+        true
+    }
+
+    /*
+     * P_TeleportMove, the things in the way are killed (the telefrag).
+     * Only the players can do that, except on MAP30 where the boss shooter spawns the monsters on top of each other.
+     */
+    fn teleport_move(&mut self, index: ActorIndex, actor: &mut Actor, x: f64, y: f64) -> bool {
+        let can_stomp = actor.player >= 0 || self.map_name.eq_ignore_ascii_case("MAP30");
+        let bbox = [y + actor.radius, y - actor.radius, x - actor.radius, x + actor.radius];
+        let block_map = &self.block_map;
+        let mut stomped = vec![];
+        for by in block_map.block_y(bbox[BOXBOTTOM] - MAXRADIUS)..=block_map.block_y(bbox[BOXTOP] + MAXRADIUS) {
+            for bx in block_map.block_x(bbox[BOXLEFT] - MAXRADIUS)..=block_map.block_x(bbox[BOXRIGHT] + MAXRADIUS) {
+                for thing in block_map.block_things(bx, by) {
+                    if thing == index || stomped.contains(&thing) {continue}
+                    let other = match self.actor(thing) {
+                        Some(other) => other,
+                        None => continue
+                    };
+                    //PIT_StompThing
+                    if !other.flags.contains(MobjFlags::Shootable) {continue}
+                    let block_dist = other.radius + actor.radius;
+                    if (other.pos.x - x).abs() >= block_dist || (other.pos.y - y).abs() >= block_dist {continue}
+                    if !can_stomp {return false}
+                    stomped.push(thing);
+                }
+            }
+        }
+        for thing in stomped {
+            Self::damage_actor(self, thing, Some(&*actor), index, Some(&*actor), 10000);
+        }
+
+        Self::unlink_from_world(self, index, actor);
+        actor.pos.x = x;
+        actor.pos.y = y;
+        Self::link_to_world(self, index, actor);
+        if actor.sector >= 0 {
+            let sector = self.sectors[actor.sector as usize].borrow();
+            let xy = Vector2 { x, y };
+            actor.floor_z = sector.floorplane.z_at(&xy);
+            actor.dropoff_z = actor.floor_z;
+            actor.ceiling_z = sector.ceilingplane.z_at(&xy);
+        }
+        true
+    }
+
+    /*
+     * The end of EV_SilentLineTeleport, the actor comes out of the other line at the same place along it and height above the floor.
+     * The angle and the velocity are turned by the angle between the lines, without reverse the actor is turned around and comes out on the other end.
+     */
+    fn line_teleport(&mut self, index: ActorIndex, actor: &mut Actor, from: LineIndex, to: LineIndex, reverse: bool) -> bool {
+        let (from_v1, from_delta) = {
+            let line = self.lines[from as usize].borrow();
+            (line.v1.f_pos(), line.delta())
+        };
+        let (to_v1, to_delta, front_sector, back_sector) = {
+            let line = self.lines[to as usize].borrow();
+            (line.v1.f_pos(), line.delta(), line.front_sector, line.back_sector)
+        };
+
+        //where the actor is along the line and how far from it
+        let length = from_delta.x * from_delta.x + from_delta.y * from_delta.y;
+        let (mut pos, offset) = if length == 0. {(0., Vector2 { x: 0., y: 0. })}
+        else {
+            let dot = (actor.pos.x - from_v1.x) * from_delta.x + (actor.pos.y - from_v1.y) * from_delta.y;
+            let pos = (dot / length).clamp(0., 1.);
+            (pos, Vector2 { x: actor.pos.x - from_v1.x - from_delta.x * pos, y: actor.pos.y - from_v1.y - from_delta.y * pos })
+        };
+
+        let mut angle = to_delta.angle().degrees() - from_delta.angle().degrees();
+        if !reverse {
+            angle += 180.;
+            pos = 1. - pos;
+        }
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let mut p = Vector2 { x: offset.x * cos - offset.y * sin + to_v1.x + to_delta.x * pos, y: offset.y * cos + offset.x * sin + to_v1.y + to_delta.y * pos };
+
+        //walking to the front of the exit line steps down
+        let (front_floor, back_floor) = (self.sectors[front_sector as usize].borrow().floorplane.z_at(&p), self.sectors[back_sector as usize].borrow().floorplane.z_at(&p));
+        let step_down = front_floor < back_floor;
+        let height = actor.pos.z - actor.floor_z;
+
+        //the actor has to end up on the exit side of the line or it goes back and forth
+        let side = (reverse || (actor.player >= 0 && step_down)) as i32;
+        let mut fudge = FUDGE_FACTOR;
+        while self.point_on_line_side(p.x, p.y, to) != side && fudge > 0 {
+            fudge -= 1;
+            if to_delta.x.abs() > to_delta.y.abs() {p.y -= if (to_delta.x < 0.) != (side == 1) {-1.} else {1.}}
+            else {p.x += if (to_delta.y < 0.) != (side == 1) {-1.} else {1.}}
+        }
+
+        let floor = if step_down {back_floor} else {front_floor};
+        if !Self::teleport_move(self, index, actor, p.x, p.y) {return false}
+        actor.pos.z = floor + height;
+
+        actor.angle.add(&Angle::<f64>::from_degrees(angle));
+        if actor.player >= 0 {self.players[actor.player as usize].fix_angle = true}
+        let vel = actor.vel;
+        actor.vel.x = vel.x * cos - vel.y * sin;
+        actor.vel.y = vel.y * cos + vel.x * sin;
+        actor.prev = actor.pos;
+        true
+    }
+}
@@ -1,12 +1,15 @@
 use std::collections::HashMap;
 use bitflags::bitflags;
 
+use crate::parser::WADData;
+use crate::parser::parse_graphics::{WADPaletteColor, WADSprite};
+
 #[derive(Default)]
 pub struct CanvasTextureInfo {
 
 }
 
-#[derive(PartialEq, Clone, Copy, Default)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
 pub struct TextureID {
     pub tex_num: i32
 }
@@ -16,9 +19,13 @@ impl TextureID {
         TextureID { tex_num: 0 }
     }
 
+    //-1 is a texture that wasn't found, 0 is the null texture of "-"
     pub fn exists(&self) ->bool {
-        //TODO
-        false
+        self.tex_num >= 0
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.tex_num > 0
     }
 
     pub fn get_index(&self) -> i32 {
@@ -51,36 +58,132 @@ pub struct TextureDescriptor {
     texture: GameTexture
 }
 
+//the texture with its pixels in rgba, the holes of the patches are transparent
 pub struct GameTexture {
-
+    pub name: String,
+    pub use_type: TextureType,
+    pub width: u32,
+    pub height: u32,
+    pub scale_x: f64,
+    pub scale_y: f64,
+    pub pixels: Vec<u8>,
+    pub indices: Vec<u8> //the pixels in the palette for the software renderer, the alpha of the rgba pixels has the holes
 }
 
 impl GameTexture {
+    pub fn new(name: &str, use_type: TextureType, width: u32, height: u32) -> GameTexture {
+        GameTexture { name: name.to_string(), use_type, width, height, scale_x: 1., scale_y: 1., pixels: vec![0; (width * height * 4) as usize], indices: vec![0; (width * height) as usize] }
+    }
+
     pub fn is_valid(&self) -> bool {
-        //TODO
-        false
+        self.use_type != TextureType::Null
+    }
+
+    //the size in the world, the scale makes the hires textures as big as the ones they replace
+    pub fn get_display_width(&self) -> f64 {
+        self.width as f64 / self.scale_x
+    }
+
+    pub fn get_display_height(&self) -> f64 {
+        self.height as f64 / self.scale_y
+    }
+
+    //copies the columns of the patch into the texture at the origin
+    fn draw_patch(&mut self, patch: &WADSprite, origin_x: i32, origin_y: i32, palette: &[WADPaletteColor]) {
+        for post in &patch.posts {
+            let x = origin_x + post.col as i32;
+            if x < 0 || x >= self.width as i32 {continue}
+            for (i, pixel) in post.pixels.iter().enumerate() {
+                let y = origin_y + post.row as i32 + i as i32;
+                if y < 0 || y >= self.height as i32 {continue}
+                let color = &palette[*pixel as usize];
+                let index = ((y as u32 * self.width + x as u32) * 4) as usize;
+                self.pixels[index..index + 4].copy_from_slice(&[color.r, color.g, color.b, 255]);
+                self.indices[index / 4] = *pixel;
+            }
+        }
     }
 }
 
 pub struct TextureManager {
     //TODO
     textures: Vec<TextureDescriptor>,
-    translation: Vec<i32>
+    translation: Vec<i32>,
+    name_hash: HashMap<String, Vec<i32>> //the textures with the name, there can be a wall and a flat
 }
 
 impl TextureManager {
     pub fn new() -> TextureManager {
-        TextureManager { textures: vec![], translation: vec![] }
+        TextureManager { textures: vec![], translation: vec![], name_hash: HashMap::new() }
     }
 
+    /*
+     * TexMan.Init, the textures of TEXTURE1 and TEXTURE2 are put together from their patches, the flats are used as they are.
+     * The first texture is the null texture of "-".
+     */
+    pub fn init(&mut self, wad: &WADData) {
+        self.textures.clear();
+        self.name_hash.clear();
+        Self::add_game_texture(self, GameTexture::new("-", TextureType::Null, 0, 0));
+        let palette = match wad.palletes.first() {
+            Some(palette) => palette,
+            None => {
+                println!("TextureManager: there is no palette");
+                return
+            }
+        };
+
+        let graphics: HashMap<&str, &WADSprite> = wad.sprites.iter().map(|(name, sprite)| (name.as_str(), sprite)).collect();
+        for texture in &wad.textures {
+            let mut game_texture = GameTexture::new(&texture.name, TextureType::Wall, texture.width as u32, texture.height as u32);
+            for patch in &texture.patches {
+                let graphic = wad.patch_names.get(patch.patch as usize).and_then(|name| graphics.get(name.as_str()));
+                match graphic {
+                    Some(graphic) => game_texture.draw_patch(graphic, patch.origin_x as i32, patch.origin_y as i32, palette),
+                    None => println!("TextureManager: texture {} is missing patch {}", texture.name, patch.patch)
+                }
+            }
+            Self::add_game_texture(self, game_texture);
+        }
+
+        for (name, pixels) in &wad.flats {
+            let mut game_texture = GameTexture::new(name, TextureType::Flat, 64, 64);
+            for (i, pixel) in pixels.iter().enumerate() {
+                let color = &palette[*pixel as usize];
+                game_texture.pixels[i * 4..i * 4 + 4].copy_from_slice(&[color.r, color.g, color.b, 255]);
+                game_texture.indices[i] = *pixel;
+            }
+            Self::add_game_texture(self, game_texture);
+        }
+        self.translation = (0..self.textures.len() as i32).collect();
+        println!("TextureManager: {} textures", self.textures.len());
+    }
 
-    pub fn check_for_texture(&self, _name: &String, _tex_type: TextureType, _flag: u32) -> TextureID {
-        //TODO
-        TextureID { tex_num: 0 }
+    fn add_game_texture(&mut self, texture: GameTexture) -> TextureID {
+        let tex_num = self.textures.len() as i32;
+        self.name_hash.entry(texture.name.to_uppercase()).or_default().push(tex_num);
+        self.textures.push(TextureDescriptor { _paletted: 0, _front_sky_layer: 0, _raw_texture: 0, _hash_next: 0, _flags: 0, texture });
+        TextureID { tex_num }
+    }
+
+    //the texture of the name and the type, -1 when there is none
+    pub fn check_for_texture(&self, name: &String, tex_type: TextureType, flag: u32) -> TextureID {
+        if name.is_empty() || name == "-" {return TextureID { tex_num: 0 }}
+        let candidates = match self.name_hash.get(&name.to_uppercase()) {
+            Some(candidates) => candidates,
+            None => return TextureID { tex_num: -1 }
+        };
+        let use_type = |tex_num: &&i32| self.textures[**tex_num as usize].texture.use_type;
+        let found = candidates.iter().find(|tex_num| tex_type == TextureType::Any || use_type(tex_num) == tex_type)
+            .or_else(|| if flag & TexManFlags::TryAny.bits() != 0 {candidates.first()} else {None});
+        match found {
+            Some(tex_num) => TextureID { tex_num: *tex_num },
+            None => TextureID { tex_num: -1 }
+        }
     }
 
     pub fn get_default_texture(&self) -> TextureID {
-        //TODO
+        //TODO the texture of the missing textures
         TextureID { tex_num: 0 }
     }
 
@@ -95,7 +198,7 @@ impl TextureManager {
     }
 
     fn resolve_texture_index(&self, tex_num: i32, animate: bool) -> i32 {
-        if tex_num as usize >= self.textures.len() {return -1}
+        if tex_num < 0 || tex_num as usize >= self.textures.len() {return -1}
         let mut tex_num = tex_num;
         if animate {tex_num = self.translation[tex_num as usize]}
         tex_num
@@ -108,6 +211,7 @@ pub struct MapSideDef {
     pub middle_texture: String
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum TextureType {
     Any,
 	Wall,
@@ -0,0 +1,467 @@
+use std::collections::HashSet;
+
+use super::LevelLocals;
+use super::level_actor::Actor;
+use super::level_behavior::AcsThinker;
+use super::level_lighting::LightEffect;
+use super::level_movers::SectorMover;
+use super::level_serializer::{Serializable, Serializer};
+
+pub type ThinkerIndex = i32; //if -1 -> does not exist (NULL)
+
+//the index holds the slot of the thinker and the generation of the slot, so a handle to a freed slot stays invalid when it is reused
+const SLOT_BITS: u32 = 20;
+const SLOT_MASK: i32 = (1 << SLOT_BITS) - 1;
+const GENERATION_MASK: i32 = 0x7ff;
+
+//the stat lists, the thinkers are ticked in the order of the lists (the statnums of zdoom)
+pub const STAT_FREE: i32 = 0;
+pub const STAT_INFO: i32 = 1;
+pub const STAT_DECAL: i32 = 2;
+pub const STAT_AUTODECAL: i32 = 3;
+pub const STAT_CORPSEPOINTER: i32 = 4;
+pub const STAT_TRAVELLING: i32 = 5;
+pub const STAT_STATIC: i32 = 6;
+
+//the thinkers below this don't think
+pub const STAT_FIRST_THINKING: i32 = 32;
+pub const STAT_SCROLLER: i32 = 32;
+pub const STAT_PLAYER: i32 = 33;
+pub const STAT_BOSSTARGET: i32 = 34;
+pub const STAT_LIGHTNING: i32 = 35;
+pub const STAT_DECALTHINKER: i32 = 36;
+pub const STAT_INVENTORY: i32 = 37;
+pub const STAT_LIGHT: i32 = 38;
+pub const STAT_LIGHTTRANSFER: i32 = 39;
+pub const STAT_EARTHQUAKE: i32 = 40;
+pub const STAT_MAPMARKER: i32 = 41;
+pub const STAT_DLIGHT: i32 = 42;
+pub const STAT_USER: i32 = 70;
+pub const STAT_USER_MAX: i32 = 90;
+pub const STAT_DEFAULT: i32 = 100;
+pub const STAT_SECTOREFFECT: i32 = 101;
+pub const STAT_ACTORMOVER: i32 = 102;
+pub const STAT_SCRIPTS: i32 = 103;
+pub const STAT_BOT: i32 = 104;
+pub const MAX_STATNUM: i32 = 127;
+
+//everything that thinks every tic
+pub enum ThinkerData {
+    Mover(SectorMover),
+    Light(LightEffect),
+    Scripts(AcsThinker),
+    Actor(Box<Actor>)
+}
+
+impl ThinkerData {
+    pub fn class_name(&self) -> &'static str {
+        match self {
+            ThinkerData::Mover(mover) => mover.class_name(),
+            ThinkerData::Light(light) => light.class_name(),
+            ThinkerData::Scripts(_) => "ACSThinker",
+            ThinkerData::Actor(_) => "Actor"
+        }
+    }
+
+    //an empty thinker of the class, filled when reading a savegame
+    pub fn from_class(name: &str) -> Option<ThinkerData> {
+        if name == "ACSThinker" {return Some(ThinkerData::Scripts(AcsThinker::default()))}
+        if name == "Actor" {return Some(ThinkerData::Actor(Box::default()))}
+        if let Some(mover) = SectorMover::from_class(name) {return Some(ThinkerData::Mover(mover))}
+        LightEffect::from_class(name).map(ThinkerData::Light)
+    }
+}
+
+impl Serializable for ThinkerData {
+    fn serialize(&mut self, arc: &mut Serializer) {
+        match self {
+            ThinkerData::Mover(mover) => mover.serialize(arc),
+            ThinkerData::Light(light) => light.serialize(arc),
+            ThinkerData::Scripts(scripts) => scripts.serialize(arc),
+            ThinkerData::Actor(actor) => actor.serialize(arc)
+        }
+    }
+}
+
+struct ThinkerNode {
+    data: Option<ThinkerData>, //None while the thinker is ticking
+    stat_num: i32,
+    destroyed: bool, //removed at the end of the tic (EuthanizeMe)
+    just_spawned: bool
+}
+
+/*
+ * All the thinkers of the level in their stat lists (FThinkerCollection of zdoom).
+ * The thinkers keep their index until they are destroyed, other things refer to them by it.
+ * An index of a destroyed thinker doesn't refer to the thinker that gets its slot after (the TObjPtr of zdoom).
+ * Thinkers created while ticking go in the fresh lists and get their first tic after the list they are in.
+ */
+pub struct ThinkerCollection {
+    nodes: Vec<Option<ThinkerNode>>,
+    generations: Vec<i32>, //of each slot, incremented when it is freed
+    free: Vec<usize>,
+    lists: Vec<Vec<ThinkerIndex>>,
+    fresh: Vec<Vec<ThinkerIndex>>
+}
+
+impl Default for ThinkerCollection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThinkerCollection {
+    pub fn new() -> ThinkerCollection {
+        ThinkerCollection { nodes: vec![], generations: vec![], free: vec![], lists: vec![vec![]; MAX_STATNUM as usize + 1], fresh: vec![vec![]; MAX_STATNUM as usize + 1] }
+    }
+
+    //DestroyAllThinkers
+    pub fn destroy_all(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn create(&mut self, data: ThinkerData, stat_num: i32) -> ThinkerIndex {
+        let stat_num = stat_num.clamp(0, MAX_STATNUM);
+        let node = ThinkerNode { data: Some(data), stat_num, destroyed: false, just_spawned: true };
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.nodes[slot] = Some(node);
+                slot
+            }
+            None => {
+                assert!(self.nodes.len() <= SLOT_MASK as usize, "ThinkerCollection: too many thinkers");
+                self.nodes.push(Some(node));
+                self.generations.push(0);
+                self.nodes.len() - 1
+            }
+        };
+        let index = Self::index_of(self, slot);
+        self.fresh[stat_num as usize].push(index);
+        index
+    }
+
+    fn index_of(&self, slot: usize) -> ThinkerIndex {
+        (self.generations[slot] << SLOT_BITS) | slot as i32
+    }
+
+    //the slot of the index, None when the index is stale
+    fn slot(&self, index: ThinkerIndex) -> Option<usize> {
+        if index < 0 {return None}
+        let slot = (index & SLOT_MASK) as usize;
+        if self.generations.get(slot)? << SLOT_BITS != index & !SLOT_MASK {return None}
+        Some(slot)
+    }
+
+    fn node(&self, index: ThinkerIndex) -> Option<&ThinkerNode> {
+        let slot = Self::slot(self, index)?;
+        self.nodes[slot].as_ref().filter(|node| !node.destroyed)
+    }
+
+    fn node_mut(&mut self, index: ThinkerIndex) -> Option<&mut ThinkerNode> {
+        let slot = Self::slot(self, index)?;
+        self.nodes[slot].as_mut().filter(|node| !node.destroyed)
+    }
+
+    pub fn exists(&self, index: ThinkerIndex) -> bool {
+        self.node(index).is_some()
+    }
+
+    pub fn get(&self, index: ThinkerIndex) -> Option<&ThinkerData> {
+        self.node(index)?.data.as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: ThinkerIndex) -> Option<&mut ThinkerData> {
+        self.node_mut(index)?.data.as_mut()
+    }
+
+    pub fn stat_num(&self, index: ThinkerIndex) -> Option<i32> {
+        self.node(index).map(|node| node.stat_num)
+    }
+
+    //the data is returned so the level can clean up after it, None when it is ticking
+    pub fn destroy(&mut self, index: ThinkerIndex) -> Option<ThinkerData> {
+        let node = self.node_mut(index)?;
+        node.destroyed = true;
+        node.data.take()
+    }
+
+    pub fn change_stat_num(&mut self, index: ThinkerIndex, stat_num: i32) {
+        let stat_num = stat_num.clamp(0, MAX_STATNUM);
+        let node = match self.node_mut(index) {
+            Some(node) => node,
+            None => return
+        };
+        if node.stat_num == stat_num {return}
+        node.stat_num = stat_num;
+        //the old list is cleaned up after the tic
+        if node.just_spawned {self.fresh[stat_num as usize].push(index)}
+        else {self.lists[stat_num as usize].push(index)}
+    }
+
+    //the thinkers in the stat list in ticking order (TThinkerIterator)
+    pub fn stat_list(&self, stat_num: i32) -> Vec<ThinkerIndex> {
+        let stat = stat_num.clamp(0, MAX_STATNUM) as usize;
+        let mut seen = HashSet::new();
+        self.lists[stat].iter().chain(self.fresh[stat].iter())
+            .filter(|index| self.stat_num(**index) == Some(stat_num) && seen.insert(**index)).copied().collect()
+    }
+
+    pub fn all(&self) -> Vec<ThinkerIndex> {
+        (0..=MAX_STATNUM).flat_map(|stat| Self::stat_list(self, stat)).collect()
+    }
+
+    pub fn count(&self) -> usize {
+        self.nodes.iter().flatten().filter(|node| !node.destroyed).count()
+    }
+
+    //takes the data out so it can tick with the level borrowed
+    fn take(&mut self, index: ThinkerIndex) -> Option<ThinkerData> {
+        self.node_mut(index)?.data.take()
+    }
+
+    //returns the data when the thinker was destroyed while it was ticking
+    fn put_back(&mut self, index: ThinkerIndex, data: ThinkerData, ticked: bool) -> Option<ThinkerData> {
+        match Self::slot(self, index).and_then(|slot| self.nodes[slot].as_mut()) {
+            Some(node) if !node.destroyed => {
+                node.data = Some(data);
+                if ticked {node.just_spawned = false}
+                None
+            }
+            _ => Some(data)
+        }
+    }
+
+    fn list_entry(&self, stat_num: i32, pos: usize) -> Option<ThinkerIndex> {
+        self.lists[stat_num as usize].get(pos).copied()
+    }
+
+    //the fresh thinkers move to their list before their first tic
+    fn take_fresh(&mut self, stat_num: i32) -> Vec<ThinkerIndex> {
+        let fresh = std::mem::take(&mut self.fresh[stat_num as usize]);
+        let fresh: Vec<ThinkerIndex> = fresh.into_iter().filter(|index| self.stat_num(*index) == Some(stat_num)).collect();
+        self.lists[stat_num as usize].extend(fresh.iter());
+        fresh
+    }
+
+    //removes the destroyed thinkers and the stale list entries
+    pub fn collect_garbage(&mut self) {
+        for slot in 0..self.nodes.len() {
+            if matches!(&self.nodes[slot], Some(node) if node.destroyed) {
+                self.nodes[slot] = None;
+                self.generations[slot] = (self.generations[slot] + 1) & GENERATION_MASK;
+                self.free.push(slot);
+            }
+        }
+        let mut lists = std::mem::take(&mut self.lists);
+        for (stat, list) in lists.iter_mut().enumerate() {
+            let mut seen = HashSet::new();
+            list.retain(|index| self.stat_num(*index) == Some(stat as i32) && seen.insert(*index));
+        }
+        self.lists = lists;
+        let mut fresh = std::mem::take(&mut self.fresh);
+        for (stat, list) in fresh.iter_mut().enumerate() {
+            list.retain(|index| self.stat_num(*index) == Some(stat as i32));
+        }
+        self.fresh = fresh;
+    }
+
+    //the ordering of the thinkers is kept, their indices too because the level refers to them
+    pub fn serialize(&mut self, arc: &mut Serializer) {
+        let mut count = self.count() as i32;
+        arc.int("numthinkers", &mut count);
+        if arc.is_writing() {
+            for index in Self::all(self) {
+                let node = self.nodes[(index & SLOT_MASK) as usize].as_mut().unwrap();
+                let mut index = index;
+                let mut class_name = node.data.as_ref().map(|data| data.class_name()).unwrap_or_default().to_string();
+                let mut stat_num = node.stat_num;
+                arc.int("index", &mut index).string("class", &mut class_name).int("statnum", &mut stat_num);
+                if let Some(data) = node.data.as_mut() {data.serialize(arc)}
+            }
+            return
+        }
+
+        Self::destroy_all(self);
+        for _ in 0..count {
+            let (mut index, mut class_name, mut stat_num) = (-1, String::new(), STAT_DEFAULT);
+            arc.int("index", &mut index).string("class", &mut class_name).int("statnum", &mut stat_num);
+            let mut data = match ThinkerData::from_class(&class_name) {
+                Some(data) => data,
+                None => {
+                    println!("ThinkerCollection: unknown thinker class {}", class_name);
+                    return
+                }
+            };
+            data.serialize(arc);
+            if index < 0 {continue}
+            let slot = (index & SLOT_MASK) as usize;
+            if self.nodes.len() <= slot {
+                self.nodes.resize_with(slot + 1, || None);
+                self.generations.resize(slot + 1, 0);
+            }
+            let stat_num = stat_num.clamp(0, MAX_STATNUM);
+            self.nodes[slot] = Some(ThinkerNode { data: Some(data), stat_num, destroyed: false, just_spawned: false });
+            self.generations[slot] = index >> SLOT_BITS;
+            self.lists[stat_num as usize].push(index);
+        }
+        self.free = self.nodes.iter().enumerate().filter(|(_, node)| node.is_none()).map(|(slot, _)| slot).collect();
+    }
+}
+
+/*
+ * Running the thinkers (DThinker::RunThinkers and P_Ticker of zdoom)
+ */
+impl LevelLocals {
+    //one game tic of the level
+    pub fn tick(&mut self) {
+        //the players think before the thinkers (P_Ticker)
+        for player in 0..self.players.len() {
+            if self.players[player].mo >= 0 {Self::player_think(self, player)}
+        }
+        Self::run_thinkers(self);
+        self.maptime += 1;
+        self.totaltime += 1;
+        self.time += 1;
+        //the skies scroll around the 1024 columns of the full circle
+        self.sky_pos1 = (self.sky_pos1 + self.sky_speed1 as f64).rem_euclid(1024.);
+        self.sky_pos2 = (self.sky_pos2 + self.sky_speed2 as f64).rem_euclid(1024.);
+    }
+
+    pub fn run_thinkers(&mut self) {
+        for stat_num in STAT_FIRST_THINKING..=MAX_STATNUM {
+            let mut pos = 0;
+            while let Some(index) = self.thinkers.list_entry(stat_num, pos) {
+                pos += 1;
+                //moved to another list
+                if self.thinkers.stat_num(index) != Some(stat_num) {continue}
+                Self::tick_thinker(self, index);
+            }
+            //the thinkers that were created while ticking, they can create more
+            loop {
+                let fresh = self.thinkers.take_fresh(stat_num);
+                if fresh.is_empty() {break}
+                for index in fresh {
+                    Self::tick_thinker(self, index);
+                }
+            }
+        }
+        self.thinkers.collect_garbage();
+    }
+
+    fn tick_thinker(&mut self, index: ThinkerIndex) {
+        let mut data = match self.thinkers.take(index) {
+            Some(data) => data,
+            None => return
+        };
+        let active = match &mut data {
+            ThinkerData::Mover(mover) => Self::tick_mover(self, mover),
+            ThinkerData::Light(light) => Self::tick_light_effect(self, light),
+            ThinkerData::Scripts(scripts) => Self::tick_scripts(self, scripts),
+            ThinkerData::Actor(actor) => {
+                let active = Self::tick_actor(self, index, actor);
+                Self::run_teleports(self, index, actor);
+                active
+            }
+        };
+        if let Some(data) = self.thinkers.put_back(index, data, true) {
+            //destroyed while it was ticking
+            Self::thinker_destroyed(self, index, &data);
+        }
+        else if !active {Self::destroy_thinker(self, index)}
+    }
+
+    /*
+     * Runs f with the actor taken out of the thinkers, like when it is ticking.
+     * Returns None when there is no actor, when f removed it, it is destroyed after.
+     */
+    pub fn with_actor<R>(&mut self, index: ThinkerIndex, f: impl FnOnce(&mut LevelLocals, &mut Actor) -> R) -> Option<R> {
+        let mut data = match self.thinkers.get(index) {
+            Some(ThinkerData::Actor(_)) => self.thinkers.take(index)?,
+            _ => return None
+        };
+        let result = match &mut data {
+            ThinkerData::Actor(actor) => {
+                let result = f(self, actor);
+                Self::run_teleports(self, index, actor);
+                result
+            }
+            _ => unreachable!()
+        };
+        if let Some(data) = self.thinkers.put_back(index, data, false) {
+            Self::thinker_destroyed(self, index, &data);
+        }
+        Some(result)
+    }
+
+    pub fn create_thinker(&mut self, data: ThinkerData, stat_num: i32) -> ThinkerIndex {
+        self.thinkers.create(data, stat_num)
+    }
+
+    pub fn destroy_thinker(&mut self, index: ThinkerIndex) {
+        if let Some(data) = self.thinkers.destroy(index) {
+            Self::thinker_destroyed(self, index, &data);
+        }
+    }
+
+    //the things that refer to the thinker let go of it (OnDestroy)
+    fn thinker_destroyed(&mut self, index: ThinkerIndex, data: &ThinkerData) {
+        match data {
+            ThinkerData::Mover(mover) => Self::release_sector(self, mover, index),
+            ThinkerData::Light(light) => Self::release_light_sector(self, light, index),
+            ThinkerData::Scripts(_) => if self.acs_thinker == index {self.acs_thinker = -1},
+            ThinkerData::Actor(actor) => {
+                Self::unlink_from_sector(self, index, actor.sector);
+                self.block_map.unlink(actor.block_node);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scripts() -> ThinkerData {
+        ThinkerData::Scripts(AcsThinker::default())
+    }
+
+    #[test]
+    fn stale_index_of_a_reused_slot_is_invalid() {
+        let mut thinkers = ThinkerCollection::new();
+        let first = thinkers.create(scripts(), STAT_SCRIPTS);
+        assert!(thinkers.exists(first));
+
+        assert!(thinkers.destroy(first).is_some());
+        assert!(!thinkers.exists(first));
+        thinkers.collect_garbage();
+
+        let second = thinkers.create(scripts(), STAT_SCRIPTS);
+        assert_ne!(first, second);
+        assert_eq!(first & SLOT_MASK, second & SLOT_MASK);
+        assert!(thinkers.get(first).is_none());
+        assert!(thinkers.destroy(first).is_none());
+        assert!(thinkers.exists(second));
+        assert_eq!(thinkers.stat_list(STAT_SCRIPTS), [second]);
+    }
+
+    #[test]
+    fn indices_survive_a_savegame() {
+        let mut thinkers = ThinkerCollection::new();
+        let first = thinkers.create(scripts(), STAT_SCRIPTS);
+        thinkers.destroy(first);
+        thinkers.collect_garbage();
+        let second = thinkers.create(scripts(), STAT_SCRIPTS);
+
+        let mut arc = Serializer::new_writer();
+        thinkers.serialize(&mut arc);
+        let mut arc = Serializer::new_reader(arc.values);
+        let mut loaded = ThinkerCollection::new();
+        loaded.serialize(&mut arc);
+
+        assert!(loaded.exists(second));
+        assert!(!loaded.exists(first));
+        let third = loaded.create(scripts(), STAT_SCRIPTS);
+        assert_ne!(third, first);
+        assert_eq!(loaded.count(), 2);
+    }
+}
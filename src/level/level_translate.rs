@@ -0,0 +1,620 @@
+use crate::parser::parse_level::WADLevelLinedef;
+
+use super::{LevelLocals, ActionSpecials, SectorSpecials, SpecialActivation};
+use super::ActionSpecials::*;
+use super::level_elements::{Line, LineFlags, Sector, SectorFlags};
+
+/*
+ * Translation of the doom (and boom) linedef and sector specials to the hexen style specials,
+ * this is the doom.txt xlat table of zdoom.
+ */
+
+//the trigger flags of the table
+const WALK: u32 = 1;
+const USE: u32 = 2;
+const SHOOT: u32 = 4;
+const MONWALK: u32 = 8;
+const REP: u32 = 16;
+const MONST: u32 = 32;
+
+//arg that gets replaced with the tag of the linedef
+const TAG: i32 = i32::MIN;
+
+//the speeds of the movers (in 1/8 units per tic)
+const D_SLOW: i32 = 16;
+const D_FAST: i32 = 64;
+const C_SLOW: i32 = 8;
+const C_NORMAL: i32 = 16;
+const C_FAST: i32 = 32;
+const P_SLOW: i32 = 8;
+const P_FAST: i32 = 32;
+const P_TURBO: i32 = 64;
+const F_SLOW: i32 = 8;
+const F_FAST: i32 = 32;
+const ST_SLOW: i32 = 2;
+const ST_TURBO: i32 = 32;
+const DORATE: i32 = 4;
+const ELEVATORSPEED: i32 = 32;
+
+const VDOORWAIT: i32 = 150; //tics
+const PLATWAIT: i32 = 105; //tics
+const SCROLL_UNIT: i32 = 64;
+
+//the keys of the locked doors
+const RCARD: i32 = 1;
+const BCARD: i32 = 2;
+const YCARD: i32 = 3;
+const CARD_IS_SKULL: i32 = 128;
+
+//doom line flags that are not in the hexen flags
+const BOOM_PASSUSE: u32 = 0x0200;
+const ETERNITY_RESERVED: u32 = 0x0800;
+const MBF21_BLOCK_PLAYERS: u32 = 0x2000;
+
+//the boom generalized linedef types
+const GEN_FLOOR_BASE: u16 = 0x6000;
+const GEN_CEILING_BASE: u16 = 0x4000;
+const GEN_DOOR_BASE: u16 = 0x3c00;
+const GEN_LOCKED_BASE: u16 = 0x3800;
+const GEN_LIFT_BASE: u16 = 0x3400;
+const GEN_STAIRS_BASE: u16 = 0x3000;
+const GEN_CRUSHER_BASE: u16 = 0x2f80;
+
+//the boom sector special bits are shifted to these
+pub const DAMAGE_MASK: i32 = 0x0300;
+pub const SECRET_MASK: i32 = 0x0400;
+pub const FRICTION_MASK: i32 = 0x0800;
+pub const PUSH_MASK: i32 = 0x1000;
+
+fn doom_line_translation(special: u16) -> Option<(u32, ActionSpecials, [i32;5])> {
+    let translation = match special {
+        1 => (USE|MONST|REP, DoorRaise, [0, D_SLOW, VDOORWAIT, 0, 0]),
+        2 => (WALK, DoorOpen, [TAG, D_SLOW, 0, 0, 0]),
+        3 => (WALK, DoorClose, [TAG, D_SLOW, 0, 0, 0]),
+        4 => (WALK|MONST, DoorRaise, [TAG, D_SLOW, VDOORWAIT, 0, 0]),
+        5 => (WALK, FloorRaiseToLowestCeiling, [TAG, F_SLOW, 0, 0, 0]),
+        6 => (WALK, CeilingCrushAndRaiseA, [TAG, C_NORMAL, C_NORMAL, 10, 0]),
+        7 => (USE, StairsBuildUpDoom, [TAG, ST_SLOW, 8, 0, 0]),
+        8 => (WALK, StairsBuildUpDoom, [TAG, ST_SLOW, 8, 0, 0]),
+        9 => (USE, FloorDonut, [TAG, DORATE, DORATE, 0, 0]),
+        10 => (WALK|MONST, PlatDownWaitUpStayLip, [TAG, P_FAST, PLATWAIT, 0, 0]),
+        11 => (USE, ExitNormal, [0, 0, 0, 0, 0]),
+        12 => (WALK, LightMaxNeighbor, [TAG, 0, 0, 0, 0]),
+        13 => (WALK, LightChangeToValue, [TAG, 255, 0, 0, 0]),
+        14 => (USE, PlatUpByValueStayTx, [TAG, P_SLOW / 2, 4, 0, 0]),
+        15 => (USE, PlatUpByValueStayTx, [TAG, P_SLOW / 2, 3, 0, 0]),
+        16 => (WALK, DoorCloseWaitOpen, [TAG, D_SLOW, 240, 0, 0]),
+        17 => (WALK, LightStrobeDoom, [TAG, 5, 35, 0, 0]),
+        18 => (USE, FloorRaiseToNearest, [TAG, F_SLOW, 0, 0, 0]),
+        19 => (WALK, FloorLowerToHighest, [TAG, F_SLOW, 128, 0, 0]),
+        20 => (USE, PlatRaiseAndStayTx0, [TAG, P_SLOW / 2, 0, 0, 0]),
+        21 => (USE, PlatDownWaitUpStayLip, [TAG, P_FAST, PLATWAIT, 0, 0]),
+        22 => (WALK, PlatRaiseAndStayTx0, [TAG, P_SLOW / 2, 0, 0, 0]),
+        23 => (USE, FloorLowerToLowest, [TAG, F_SLOW, 0, 0, 0]),
+        24 => (SHOOT, FloorRaiseToLowestCeiling, [TAG, F_SLOW, 0, 0, 0]),
+        25 => (WALK, CeilingCrushAndRaiseA, [TAG, C_SLOW, C_SLOW, 10, 0]),
+        26 => (USE|REP, DoorLockedRaise, [0, D_SLOW, VDOORWAIT, BCARD | CARD_IS_SKULL, 0]),
+        27 => (USE|REP, DoorLockedRaise, [0, D_SLOW, VDOORWAIT, YCARD | CARD_IS_SKULL, 0]),
+        28 => (USE|REP, DoorLockedRaise, [0, D_SLOW, VDOORWAIT, RCARD | CARD_IS_SKULL, 0]),
+        29 => (USE, DoorRaise, [TAG, D_SLOW, VDOORWAIT, 0, 0]),
+        30 => (WALK, FloorRaiseByTexture, [TAG, F_SLOW, 0, 0, 0]),
+        31 => (USE, DoorOpen, [0, D_SLOW, 0, 0, 0]),
+        32 => (USE|MONST, DoorLockedRaise, [0, D_SLOW, 0, BCARD | CARD_IS_SKULL, 0]),
+        33 => (USE|MONST, DoorLockedRaise, [0, D_SLOW, 0, RCARD | CARD_IS_SKULL, 0]),
+        34 => (USE|MONST, DoorLockedRaise, [0, D_SLOW, 0, YCARD | CARD_IS_SKULL, 0]),
+        35 => (WALK, LightChangeToValue, [TAG, 35, 0, 0, 0]),
+        36 => (WALK, FloorLowerToHighest, [TAG, F_FAST, 136, 0, 0]),
+        37 => (WALK, FloorLowerToLowestTxTy, [TAG, F_SLOW, 0, 0, 0]),
+        38 => (WALK, FloorLowerToLowest, [TAG, F_SLOW, 0, 0, 0]),
+        39 => (WALK|MONST, Teleport, [0, TAG, 0, 0, 0]),
+        40 => (WALK, GenericCeiling, [TAG, C_SLOW, 0, 1, 8]),
+        41 => (USE, CeilingLowerToFloor, [TAG, C_SLOW, 0, 0, 0]),
+        42 => (USE|REP, DoorClose, [TAG, D_SLOW, 0, 0, 0]),
+        43 => (USE|REP, CeilingLowerToFloor, [TAG, C_SLOW, 0, 0, 0]),
+        44 => (WALK, CeilingLowerAndCrush, [TAG, C_SLOW, 0, 2, 0]),
+        45 => (USE|REP, FloorLowerToHighest, [TAG, F_SLOW, 128, 0, 0]),
+        46 => (SHOOT|REP|MONST, DoorOpen, [TAG, D_SLOW, 0, 0, 0]),
+        47 => (SHOOT, PlatRaiseAndStayTx0, [TAG, P_SLOW / 2, 0, 0, 0]),
+        48 => (0, ScrollTextureLeft, [SCROLL_UNIT, 0, 0, 0, 0]),
+        49 => (USE, CeilingCrushAndRaiseA, [TAG, C_SLOW, C_SLOW, 10, 0]),
+        50 => (USE, DoorClose, [TAG, D_SLOW, 0, 0, 0]),
+        51 => (USE, ExitSecret, [0, 0, 0, 0, 0]),
+        52 => (WALK, ExitNormal, [0, 0, 0, 0, 0]),
+        53 => (WALK, PlatPerpetualRaiseLip, [TAG, P_SLOW, PLATWAIT, 0, 0]),
+        54 => (WALK, PlatStop, [TAG, 0, 0, 0, 0]),
+        55 => (USE, FloorRaiseAndCrushDoom, [TAG, F_SLOW, 10, 2, 0]),
+        56 => (WALK, FloorRaiseAndCrushDoom, [TAG, F_SLOW, 10, 2, 0]),
+        57 => (WALK, CeilingCrushStop, [TAG, 0, 0, 0, 0]),
+        58 => (WALK, FloorRaiseByValue, [TAG, F_SLOW, 24, 0, 0]),
+        59 => (WALK, FloorRaiseByValueTxTy, [TAG, F_SLOW, 24, 0, 0]),
+        60 => (USE|REP, FloorLowerToLowest, [TAG, F_SLOW, 0, 0, 0]),
+        61 => (USE|REP, DoorOpen, [TAG, D_SLOW, 0, 0, 0]),
+        62 => (USE|REP, PlatDownWaitUpStayLip, [TAG, P_FAST, PLATWAIT, 0, 0]),
+        63 => (USE|REP, DoorRaise, [TAG, D_SLOW, VDOORWAIT, 0, 0]),
+        64 => (USE|REP, FloorRaiseToLowestCeiling, [TAG, F_SLOW, 0, 0, 0]),
+        65 => (USE|REP, FloorRaiseAndCrushDoom, [TAG, F_SLOW, 10, 2, 0]),
+        66 => (USE|REP, PlatUpByValueStayTx, [TAG, P_SLOW / 2, 3, 0, 0]),
+        67 => (USE|REP, PlatUpByValueStayTx, [TAG, P_SLOW / 2, 4, 0, 0]),
+        68 => (USE|REP, PlatRaiseAndStayTx0, [TAG, P_SLOW / 2, 0, 0, 0]),
+        69 => (USE|REP, FloorRaiseToNearest, [TAG, F_SLOW, 0, 0, 0]),
+        70 => (USE|REP, FloorLowerToHighest, [TAG, F_FAST, 136, 0, 0]),
+        71 => (USE, FloorLowerToHighest, [TAG, F_FAST, 136, 0, 0]),
+        72 => (WALK|REP, CeilingLowerAndCrush, [TAG, C_SLOW, 0, 2, 0]),
+        73 => (WALK|REP, CeilingCrushAndRaiseA, [TAG, C_SLOW, C_SLOW, 10, 0]),
+        74 => (WALK|REP, CeilingCrushStop, [TAG, 0, 0, 0, 0]),
+        75 => (WALK|REP, DoorClose, [TAG, D_SLOW, 0, 0, 0]),
+        76 => (WALK|REP, DoorCloseWaitOpen, [TAG, D_SLOW, 240, 0, 0]),
+        77 => (WALK|REP, CeilingCrushAndRaiseA, [TAG, C_NORMAL, C_NORMAL, 10, 0]),
+        78 => (USE|REP, FloorTransferNumeric, [TAG, 0, 0, 0, 0]),
+        79 => (WALK|REP, LightChangeToValue, [TAG, 35, 0, 0, 0]),
+        80 => (WALK|REP, LightMaxNeighbor, [TAG, 0, 0, 0, 0]),
+        81 => (WALK|REP, LightChangeToValue, [TAG, 255, 0, 0, 0]),
+        82 => (WALK|REP, FloorLowerToLowest, [TAG, F_SLOW, 0, 0, 0]),
+        83 => (WALK|REP, FloorLowerToHighest, [TAG, F_SLOW, 128, 0, 0]),
+        84 => (WALK|REP, FloorLowerToLowestTxTy, [TAG, F_SLOW, 0, 0, 0]),
+        85 => (0, ScrollTextureRight, [SCROLL_UNIT, 0, 0, 0, 0]),
+        86 => (WALK|REP, DoorOpen, [TAG, D_SLOW, 0, 0, 0]),
+        87 => (WALK|REP, PlatPerpetualRaiseLip, [TAG, P_SLOW, PLATWAIT, 0, 0]),
+        88 => (WALK|REP|MONST, PlatDownWaitUpStayLip, [TAG, P_FAST, PLATWAIT, 0, 0]),
+        89 => (WALK|REP, PlatStop, [TAG, 0, 0, 0, 0]),
+        90 => (WALK|REP, DoorRaise, [TAG, D_SLOW, VDOORWAIT, 0, 0]),
+        91 => (WALK|REP, FloorRaiseToLowestCeiling, [TAG, F_SLOW, 0, 0, 0]),
+        92 => (WALK|REP, FloorRaiseByValue, [TAG, F_SLOW, 24, 0, 0]),
+        93 => (WALK|REP, FloorRaiseByValueTxTy, [TAG, F_SLOW, 24, 0, 0]),
+        94 => (WALK|REP, FloorRaiseAndCrushDoom, [TAG, F_SLOW, 10, 2, 0]),
+        95 => (WALK|REP, PlatRaiseAndStayTx0, [TAG, P_SLOW / 2, 0, 0, 0]),
+        96 => (WALK|REP, FloorRaiseByTexture, [TAG, F_SLOW, 0, 0, 0]),
+        97 => (WALK|REP|MONST, Teleport, [0, TAG, 0, 0, 0]),
+        98 => (WALK|REP, FloorLowerToHighest, [TAG, F_FAST, 136, 0, 0]),
+        99 => (USE|REP, DoorLockedRaise, [TAG, D_FAST, 0, BCARD | CARD_IS_SKULL, 0]),
+        100 => (WALK, StairsBuildUpDoom, [TAG, ST_TURBO, 16, 0, 0]),
+        101 => (USE, FloorRaiseToLowestCeiling, [TAG, F_SLOW, 0, 0, 0]),
+        102 => (USE, FloorLowerToHighest, [TAG, F_SLOW, 128, 0, 0]),
+        103 => (USE, DoorOpen, [TAG, D_SLOW, 0, 0, 0]),
+        104 => (WALK, LightMinNeighbor, [TAG, 0, 0, 0, 0]),
+        105 => (WALK|REP, DoorRaise, [TAG, D_FAST, VDOORWAIT, 0, 0]),
+        106 => (WALK|REP, DoorOpen, [TAG, D_FAST, 0, 0, 0]),
+        107 => (WALK|REP, DoorClose, [TAG, D_FAST, 0, 0, 0]),
+        108 => (WALK, DoorRaise, [TAG, D_FAST, VDOORWAIT, 0, 0]),
+        109 => (WALK, DoorOpen, [TAG, D_FAST, 0, 0, 0]),
+        110 => (WALK, DoorClose, [TAG, D_FAST, 0, 0, 0]),
+        111 => (USE, DoorRaise, [TAG, D_FAST, VDOORWAIT, 0, 0]),
+        112 => (USE, DoorOpen, [TAG, D_FAST, 0, 0, 0]),
+        113 => (USE, DoorClose, [TAG, D_FAST, 0, 0, 0]),
+        114 => (USE|REP, DoorRaise, [TAG, D_FAST, VDOORWAIT, 0, 0]),
+        115 => (USE|REP, DoorOpen, [TAG, D_FAST, 0, 0, 0]),
+        116 => (USE|REP, DoorClose, [TAG, D_FAST, 0, 0, 0]),
+        117 => (USE|REP, DoorRaise, [0, D_FAST, VDOORWAIT, 0, 0]),
+        118 => (USE, DoorOpen, [0, D_FAST, 0, 0, 0]),
+        119 => (WALK, FloorRaiseToNearest, [TAG, F_SLOW, 0, 0, 0]),
+        120 => (WALK|REP, PlatDownWaitUpStayLip, [TAG, P_TURBO, PLATWAIT, 0, 0]),
+        121 => (WALK, PlatDownWaitUpStayLip, [TAG, P_TURBO, PLATWAIT, 0, 0]),
+        122 => (USE, PlatDownWaitUpStayLip, [TAG, P_TURBO, PLATWAIT, 0, 0]),
+        123 => (USE|REP, PlatDownWaitUpStayLip, [TAG, P_TURBO, PLATWAIT, 0, 0]),
+        124 => (WALK, ExitSecret, [0, 0, 0, 0, 0]),
+        125 => (MONWALK, Teleport, [0, TAG, 0, 0, 0]),
+        126 => (MONWALK|REP, Teleport, [0, TAG, 0, 0, 0]),
+        127 => (USE, StairsBuildUpDoom, [TAG, ST_TURBO, 16, 0, 0]),
+        128 => (WALK|REP, FloorRaiseToNearest, [TAG, F_SLOW, 0, 0, 0]),
+        129 => (WALK|REP, FloorRaiseToNearest, [TAG, F_FAST, 0, 0, 0]),
+        130 => (WALK, FloorRaiseToNearest, [TAG, F_FAST, 0, 0, 0]),
+        131 => (USE, FloorRaiseToNearest, [TAG, F_FAST, 0, 0, 0]),
+        132 => (USE|REP, FloorRaiseToNearest, [TAG, F_FAST, 0, 0, 0]),
+        133 => (USE, DoorLockedRaise, [TAG, D_FAST, 0, BCARD | CARD_IS_SKULL, 0]),
+        134 => (USE|REP, DoorLockedRaise, [TAG, D_FAST, 0, RCARD | CARD_IS_SKULL, 0]),
+        135 => (USE, DoorLockedRaise, [TAG, D_FAST, 0, RCARD | CARD_IS_SKULL, 0]),
+        136 => (USE|REP, DoorLockedRaise, [TAG, D_FAST, 0, YCARD | CARD_IS_SKULL, 0]),
+        137 => (USE, DoorLockedRaise, [TAG, D_FAST, 0, YCARD | CARD_IS_SKULL, 0]),
+        138 => (USE|REP, LightChangeToValue, [TAG, 255, 0, 0, 0]),
+        139 => (USE|REP, LightChangeToValue, [TAG, 35, 0, 0, 0]),
+        140 => (USE, FloorRaiseByValue, [TAG, F_SLOW, 512, 0, 0]),
+        141 => (WALK, CeilingCrushAndRaiseSilentA, [TAG, C_SLOW, C_SLOW, 10, 0]),
+
+        //boom extended types
+        142 => (WALK, FloorRaiseByValue, [TAG, F_SLOW, 512, 0, 0]),
+        143 => (WALK, PlatUpByValueStayTx, [TAG, P_SLOW / 2, 3, 0, 0]),
+        144 => (WALK, PlatUpByValueStayTx, [TAG, P_SLOW / 2, 4, 0, 0]),
+        145 => (WALK, CeilingLowerToFloor, [TAG, C_FAST, 0, 0, 0]),
+        146 => (WALK, FloorDonut, [TAG, DORATE, DORATE, 0, 0]),
+        147 => (WALK|REP, FloorRaiseByValue, [TAG, F_SLOW, 512, 0, 0]),
+        148 => (WALK|REP, PlatUpByValueStayTx, [TAG, P_SLOW / 2, 3, 0, 0]),
+        149 => (WALK|REP, PlatUpByValueStayTx, [TAG, P_SLOW / 2, 4, 0, 0]),
+        150 => (WALK|REP, CeilingCrushAndRaiseSilentA, [TAG, C_SLOW, C_SLOW, 10, 0]),
+        151 => (WALK|REP, FloorAndCeilingLowerRaise, [TAG, F_SLOW, C_SLOW, 0, 0]),
+        152 => (WALK|REP, CeilingLowerToFloor, [TAG, C_FAST, 0, 0, 0]),
+        153 => (WALK, FloorTransferTrigger, [TAG, 0, 0, 0, 0]),
+        154 => (WALK|REP, FloorTransferTrigger, [TAG, 0, 0, 0, 0]),
+        155 => (WALK|REP, FloorDonut, [TAG, DORATE, DORATE, 0, 0]),
+        156 => (WALK|REP, LightStrobeDoom, [TAG, 5, 35, 0, 0]),
+        157 => (WALK|REP, LightMinNeighbor, [TAG, 0, 0, 0, 0]),
+        158 => (USE, FloorRaiseByTexture, [TAG, F_SLOW, 0, 0, 0]),
+        159 => (USE, FloorLowerToLowestTxTy, [TAG, F_SLOW, 0, 0, 0]),
+        160 => (USE, FloorRaiseByValueTxTy, [TAG, F_SLOW, 24, 0, 0]),
+        161 => (USE, FloorRaiseByValue, [TAG, F_SLOW, 24, 0, 0]),
+        162 => (USE, PlatPerpetualRaiseLip, [TAG, P_SLOW, PLATWAIT, 0, 0]),
+        163 => (USE, PlatStop, [TAG, 0, 0, 0, 0]),
+        164 => (USE, CeilingCrushAndRaiseA, [TAG, C_NORMAL, C_NORMAL, 10, 0]),
+        165 => (USE, CeilingCrushAndRaiseSilentA, [TAG, C_SLOW, C_SLOW, 10, 0]),
+        166 => (USE, FloorAndCeilingLowerRaise, [TAG, F_SLOW, C_SLOW, 0, 0]),
+        167 => (USE, CeilingLowerAndCrush, [TAG, C_SLOW, 0, 2, 0]),
+        168 => (USE, CeilingCrushStop, [TAG, 0, 0, 0, 0]),
+        169 => (USE, LightMaxNeighbor, [TAG, 0, 0, 0, 0]),
+        170 => (USE, LightChangeToValue, [TAG, 35, 0, 0, 0]),
+        171 => (USE, LightChangeToValue, [TAG, 255, 0, 0, 0]),
+        172 => (USE, LightStrobeDoom, [TAG, 5, 35, 0, 0]),
+        173 => (USE, LightMinNeighbor, [TAG, 0, 0, 0, 0]),
+        174 => (USE, Teleport, [0, TAG, 0, 0, 0]),
+        175 => (USE, DoorCloseWaitOpen, [TAG, D_SLOW, 240, 0, 0]),
+        176 => (USE|REP, FloorRaiseByTexture, [TAG, F_SLOW, 0, 0, 0]),
+        177 => (USE|REP, FloorLowerToLowestTxTy, [TAG, F_SLOW, 0, 0, 0]),
+        178 => (USE|REP, FloorRaiseByValue, [TAG, F_SLOW, 512, 0, 0]),
+        179 => (USE|REP, FloorRaiseByValueTxTy, [TAG, F_SLOW, 24, 0, 0]),
+        180 => (USE|REP, FloorRaiseByValue, [TAG, F_SLOW, 24, 0, 0]),
+        181 => (USE|REP, PlatPerpetualRaiseLip, [TAG, P_SLOW, PLATWAIT, 0, 0]),
+        182 => (USE|REP, PlatStop, [TAG, 0, 0, 0, 0]),
+        183 => (USE|REP, CeilingCrushAndRaiseA, [TAG, C_NORMAL, C_NORMAL, 10, 0]),
+        184 => (USE|REP, CeilingCrushAndRaiseA, [TAG, C_SLOW, C_SLOW, 10, 0]),
+        185 => (USE|REP, CeilingCrushAndRaiseSilentA, [TAG, C_SLOW, C_SLOW, 10, 0]),
+        186 => (USE|REP, FloorAndCeilingLowerRaise, [TAG, F_SLOW, C_SLOW, 0, 0]),
+        187 => (USE|REP, CeilingLowerAndCrush, [TAG, C_SLOW, 0, 2, 0]),
+        188 => (USE|REP, CeilingCrushStop, [TAG, 0, 0, 0, 0]),
+        189 => (USE, FloorTransferTrigger, [TAG, 0, 0, 0, 0]),
+        190 => (USE|REP, FloorTransferTrigger, [TAG, 0, 0, 0, 0]),
+        191 => (USE|REP, FloorDonut, [TAG, DORATE, DORATE, 0, 0]),
+        192 => (USE|REP, LightMaxNeighbor, [TAG, 0, 0, 0, 0]),
+        193 => (USE|REP, LightStrobeDoom, [TAG, 5, 35, 0, 0]),
+        194 => (USE|REP, LightMinNeighbor, [TAG, 0, 0, 0, 0]),
+        195 => (USE|REP, Teleport, [0, TAG, 0, 0, 0]),
+        196 => (USE|REP, DoorCloseWaitOpen, [TAG, D_SLOW, 240, 0, 0]),
+        197 => (SHOOT, ExitNormal, [0, 0, 0, 0, 0]),
+        198 => (SHOOT, ExitSecret, [0, 0, 0, 0, 0]),
+        199 => (WALK, CeilingLowerToHighestFloor, [TAG, C_SLOW, 0, 0, 0]),
+        200 => (WALK|REP, CeilingLowerToHighestFloor, [TAG, C_SLOW, 0, 0, 0]),
+        201 => (USE, CeilingLowerToHighestFloor, [TAG, C_SLOW, 0, 0, 0]),
+        202 => (USE|REP, CeilingLowerToHighestFloor, [TAG, C_SLOW, 0, 0, 0]),
+        203 => (WALK, CeilingLowerToLowest, [TAG, C_SLOW, 0, 0, 0]),
+        204 => (WALK|REP, CeilingLowerToLowest, [TAG, C_SLOW, 0, 0, 0]),
+        205 => (USE, CeilingLowerToLowest, [TAG, C_SLOW, 0, 0, 0]),
+        206 => (USE|REP, CeilingLowerToLowest, [TAG, C_SLOW, 0, 0, 0]),
+        207 => (WALK|MONST, TeleportNoFog, [0, 1, TAG, 0, 0]),
+        208 => (WALK|REP|MONST, TeleportNoFog, [0, 1, TAG, 0, 0]),
+        209 => (USE, TeleportNoFog, [0, 1, TAG, 0, 0]),
+        210 => (USE|REP, TeleportNoFog, [0, 1, TAG, 0, 0]),
+        211 => (USE|REP, PlatToggleCeiling, [TAG, 0, 0, 0, 0]),
+        212 => (WALK|REP, PlatToggleCeiling, [TAG, 0, 0, 0, 0]),
+        213 => (0, TransferFloorLight, [TAG, 0, 0, 0, 0]),
+        214 => (0, ScrollCeiling, [TAG, 6, 0, 0, 0]),
+        215 => (0, ScrollFloor, [TAG, 6, 0, 0, 0]),
+        216 => (0, ScrollFloor, [TAG, 6, 1, 0, 0]),
+        217 => (0, ScrollFloor, [TAG, 6, 2, 0, 0]),
+        218 => (0, ScrollTextureModel, [TAG, 2, 0, 0, 0]),
+        219 => (WALK, FloorLowerToNearest, [TAG, F_SLOW, 0, 0, 0]),
+        220 => (WALK|REP, FloorLowerToNearest, [TAG, F_SLOW, 0, 0, 0]),
+        221 => (USE, FloorLowerToNearest, [TAG, F_SLOW, 0, 0, 0]),
+        222 => (USE|REP, FloorLowerToNearest, [TAG, F_SLOW, 0, 0, 0]),
+        223 => (0, SectorSetFriction, [TAG, 0, 0, 0, 0]),
+        224 => (0, SectorSetWind, [TAG, 0, 0, 1, 0]),
+        225 => (0, SectorSetCurrent, [TAG, 0, 0, 1, 0]),
+        226 => (0, PointPushSetForce, [TAG, 0, 0, 1, 0]),
+        227 => (WALK, ElevatorRaiseToNearest, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        228 => (WALK|REP, ElevatorRaiseToNearest, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        229 => (USE, ElevatorRaiseToNearest, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        230 => (USE|REP, ElevatorRaiseToNearest, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        231 => (WALK, ElevatorLowerToNearest, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        232 => (WALK|REP, ElevatorLowerToNearest, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        233 => (USE, ElevatorLowerToNearest, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        234 => (USE|REP, ElevatorLowerToNearest, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        235 => (WALK, ElevatorMoveToFloor, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        236 => (WALK|REP, ElevatorMoveToFloor, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        237 => (USE, ElevatorMoveToFloor, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        238 => (USE|REP, ElevatorMoveToFloor, [TAG, ELEVATORSPEED, 0, 0, 0]),
+        239 => (WALK, FloorTransferNumeric, [TAG, 0, 0, 0, 0]),
+        240 => (WALK|REP, FloorTransferNumeric, [TAG, 0, 0, 0, 0]),
+        241 => (USE, FloorTransferNumeric, [TAG, 0, 0, 0, 0]),
+        242 => (0, TransferHeights, [TAG, 0, 0, 0, 0]),
+        243 => (WALK|MONST, TeleportLine, [TAG, TAG, 0, 0, 0]),
+        244 => (WALK|REP|MONST, TeleportLine, [TAG, TAG, 0, 0, 0]),
+        245 => (0, ScrollCeiling, [TAG, 5, 0, 0, 0]),
+        246 => (0, ScrollFloor, [TAG, 5, 0, 0, 0]),
+        247 => (0, ScrollFloor, [TAG, 5, 1, 0, 0]),
+        248 => (0, ScrollFloor, [TAG, 5, 2, 0, 0]),
+        249 => (0, ScrollTextureModel, [TAG, 1, 0, 0, 0]),
+        250 => (0, ScrollCeiling, [TAG, 4, 0, 0, 0]),
+        251 => (0, ScrollFloor, [TAG, 4, 0, 0, 0]),
+        252 => (0, ScrollFloor, [TAG, 4, 1, 0, 0]),
+        253 => (0, ScrollFloor, [TAG, 4, 2, 0, 0]),
+        254 => (0, ScrollTextureModel, [TAG, 0, 0, 0, 0]),
+        255 => (0, ScrollTextureOffsets, [0, 0, 0, 0, 0]),
+        256 => (WALK|REP, StairsBuildUpDoom, [TAG, ST_SLOW, 8, 0, 0]),
+        257 => (WALK|REP, StairsBuildUpDoom, [TAG, ST_TURBO, 16, 0, 0]),
+        258 => (USE|REP, StairsBuildUpDoom, [TAG, ST_SLOW, 8, 0, 0]),
+        259 => (USE|REP, StairsBuildUpDoom, [TAG, ST_TURBO, 16, 0, 0]),
+        260 => (0, TranslucentLine, [TAG, 128, 0, 0, 0]),
+        261 => (0, TransferCeilingLight, [TAG, 0, 0, 0, 0]),
+        262 => (WALK|MONST, TeleportLine, [TAG, TAG, 1, 0, 0]),
+        263 => (WALK|REP|MONST, TeleportLine, [TAG, TAG, 1, 0, 0]),
+        264 => (MONWALK, TeleportLine, [TAG, TAG, 1, 0, 0]),
+        265 => (MONWALK|REP, TeleportLine, [TAG, TAG, 1, 0, 0]),
+        266 => (MONWALK, TeleportLine, [TAG, TAG, 0, 0, 0]),
+        267 => (MONWALK|REP, TeleportLine, [TAG, TAG, 0, 0, 0]),
+        268 => (MONWALK, TeleportNoFog, [0, 1, TAG, 0, 0]),
+        269 => (MONWALK|REP, TeleportNoFog, [0, 1, TAG, 0, 0]),
+        _ => return None
+    };
+    Some(translation)
+}
+
+//the speed of the generalized types (bits 3-4) for each kind of mover
+const GEN_FLOOR_SPEEDS: [i32;4] = [8, 16, 32, 64];
+const GEN_DOOR_SPEEDS: [i32;4] = [16, 32, 64, 128];
+const GEN_LIFT_SPEEDS: [i32;4] = [16, 32, 64, 128];
+const GEN_STAIR_SPEEDS: [i32;4] = [2, 4, 16, 32];
+const GEN_DOOR_DELAYS: [i32;4] = [8, 32, 72, 240]; //octics
+const GEN_LIFT_DELAYS: [i32;4] = [8, 24, 40, 80]; //octics
+const GEN_STAIR_STEPS: [i32;4] = [4, 8, 16, 24];
+
+//the lock numbers of the generalized locked doors, (card, card or skull)
+const GEN_LOCKS: [(i32, i32);8] = [(100, 100), (1, 129), (2, 130), (3, 131), (4, 129), (5, 130), (6, 131), (101, 229)];
+
+/* Decodes the bitfields of the boom generalized linedef types.
+ * Returns the special, args and the trigger flags */
+fn generalized_translation(special: u16, tag: i32) -> Option<(u32, ActionSpecials, [i32;5])> {
+    let special = special as i32;
+    //W1, WR, S1, SR, G1, GR, D1, DR
+    let trigger = special & 7;
+    let mut flags = match trigger >> 1 {
+        0 => WALK,
+        1 | 3 => USE,
+        _ => SHOOT
+    };
+    if trigger & 1 != 0 {flags |= REP}
+    //the manual (D) types work on the sector behind the line
+    let tag = if trigger >= 6 {0} else {tag};
+    let speed = ((special & 0x18) >> 3) as usize;
+
+    let translation = if special >= GEN_CEILING_BASE as i32 {
+        let floor = special >= GEN_FLOOR_BASE as i32;
+        let change = (special & 0xc00) >> 10;
+        let model = special & 0x20 != 0;
+        if change == 0 && model {flags |= MONST}
+        let up = special & 0x40 != 0;
+        let target = (special & 0x380) >> 7;
+        let (height, target) = match target {
+            6 => (24, 0),
+            7 => (32, 0),
+            t => (0, t + 1)
+        };
+        let mut gen_flags = change;
+        if change != 0 && model {gen_flags |= 4}
+        if up {gen_flags |= 8}
+        if special & 0x1000 != 0 {gen_flags |= 16}
+        let action = if floor {GenericFloor} else {GenericCeiling};
+        (flags, action, [tag, GEN_FLOOR_SPEEDS[speed], height, target, gen_flags])
+    }
+    else if special >= GEN_DOOR_BASE as i32 {
+        let kind = (special & 0x60) >> 5;
+        if special & 0x80 != 0 {flags |= MONST}
+        let delay = GEN_DOOR_DELAYS[((special & 0x300) >> 8) as usize];
+        (flags, GenericDoor, [tag, GEN_DOOR_SPEEDS[speed], kind | 64, delay, 0])
+    }
+    else if special >= GEN_LOCKED_BASE as i32 {
+        let kind = (special & 0x20) >> 5;
+        let (card, any) = GEN_LOCKS[((special & 0x1c0) >> 6) as usize];
+        let lock = if special & 0x200 != 0 {any} else {card};
+        (flags, GenericDoor, [tag, GEN_DOOR_SPEEDS[speed], kind | 64, 34, lock])
+    }
+    else if special >= GEN_LIFT_BASE as i32 {
+        if special & 0x20 != 0 {flags |= MONST}
+        let delay = GEN_LIFT_DELAYS[((special & 0xc0) >> 6) as usize];
+        let type_ = ((special & 0x300) >> 8) + 1;
+        (flags, GenericLift, [tag, GEN_LIFT_SPEEDS[speed], delay, type_, 0])
+    }
+    else if special >= GEN_STAIRS_BASE as i32 {
+        if special & 0x20 != 0 {flags |= MONST}
+        let step = GEN_STAIR_STEPS[((special & 0xc0) >> 6) as usize];
+        let mut stair_flags = 0;
+        if special & 0x100 != 0 {stair_flags |= 1}
+        if special & 0x200 != 0 {stair_flags |= 2}
+        (flags, GenericStairs, [tag, GEN_STAIR_SPEEDS[speed], step, stair_flags, 0])
+    }
+    else if special >= GEN_CRUSHER_BASE as i32 {
+        if special & 0x20 != 0 {flags |= MONST}
+        let silent = (special & 0x40) >> 6;
+        (flags, GenericCrusher, [tag, GEN_FLOOR_SPEEDS[speed], GEN_FLOOR_SPEEDS[speed], silent, 10])
+    }
+    else {return None};
+    Some(translation)
+}
+
+impl LevelLocals {
+
+    pub fn translate_linedef(&self, line: &mut Line, linedef: &WADLevelLinedef, _line_index: i32) {
+        let (special, tag) = match &linedef.doom {
+            Some(doom) => (doom.types, doom.tag as i32),
+            None => (0, 0)
+        };
+        let mut flags = linedef.flags as u32;
+        let passthrough = flags & BOOM_PASSUSE != 0;
+
+        //the eternity reserved flag means the higher bits are garbage
+        if flags & ETERNITY_RESERVED != 0 {flags &= 0x1ff}
+        let block_players = flags & MBF21_BLOCK_PLAYERS != 0;
+        flags &= 0x1ff;
+        if block_players {flags |= LineFlags::BlockPlayers.bits()}
+
+        line.flags = flags;
+        line.special = 0;
+        line.args = [0;5];
+        line.activation = 0;
+        if special == 0 {return}
+
+        let translation = if special >= GEN_CRUSHER_BASE {generalized_translation(special, tag)} else {doom_line_translation(special)};
+        let (xlat_flags, action, args) = match translation {
+            Some(t) => t,
+            None => {
+                println!("translate_linedef: unknown linedef special {}", special);
+                return
+            }
+        };
+
+        line.special = action as i32;
+        for (dest, arg) in line.args.iter_mut().zip(args) {
+            *dest = if arg == TAG {tag} else {arg};
+        }
+
+        let mut activation = 0;
+        if xlat_flags & WALK != 0 {activation |= SpecialActivation::Cross.bits()}
+        if xlat_flags & USE != 0 {
+            activation |= if passthrough {SpecialActivation::UseThrough.bits()} else {SpecialActivation::Use.bits()};
+        }
+        if xlat_flags & SHOOT != 0 {activation |= SpecialActivation::Impact.bits()}
+        if xlat_flags & MONWALK != 0 {activation |= SpecialActivation::MCross.bits()}
+        if xlat_flags & REP != 0 {line.flags |= LineFlags::RepeatSpecial.bits()}
+        if xlat_flags & MONST != 0 {
+            line.flags |= LineFlags::MonstersCanActivate.bits();
+            //in boom anything can activate the generalized walk lines that monsters can use
+            if special >= GEN_CRUSHER_BASE && activation == SpecialActivation::Cross.bits() {
+                activation = SpecialActivation::AnyCross.bits();
+            }
+        }
+        line.activation = activation;
+    }
+
+    /* The lower 5 bits are the doom sector type, the boom bits above it are shifted
+     * to the zdoom damage/secret/friction/push masks */
+    pub fn translate_sector_special(&self, special: i16) -> i32 {
+        let special = special as u16 as i32;
+        //any zdoom special can be used by setting the highest bit
+        if special & 0x8000 != 0 {return special & 0x7fff}
+
+        let high = (special & 0xfe0) << 3;
+        let new_special = match special & 0x1f {
+            1 => SectorSpecials::DLightFlicker as i32,
+            2 => SectorSpecials::DLightStrobeFast as i32,
+            3 => SectorSpecials::DLightStrobeSlow as i32,
+            4 => SectorSpecials::DLightStrobeHurt as i32,
+            5 => SectorSpecials::DDamageHellslime as i32,
+            7 => SectorSpecials::DDamageNukage as i32,
+            8 => SectorSpecials::DLightGlow as i32,
+            9 => SECRET_MASK,
+            10 => SectorSpecials::DSectorDoorCloseIn30 as i32,
+            11 => SectorSpecials::DDamageEnd as i32,
+            12 => SectorSpecials::DLightStrobeSlowSync as i32,
+            13 => SectorSpecials::DLightStrobeFastSync as i32,
+            14 => SectorSpecials::DSectorDoorRaiseIn5Mins as i32,
+            15 => SectorSpecials::DFrictionLow as i32,
+            16 => SectorSpecials::DDamageSuperHellslime as i32,
+            17 => SectorSpecials::DLightFireFlicker as i32,
+            _ => 0
+        };
+        new_special | high
+    }
+
+    //the flags and damage of the boom bits of the sector special
+    pub fn init_sector_special_bits(&mut self, sector: &mut Sector) {
+        if sector.special & SECRET_MASK != 0 {
+            sector.flags |= SectorFlags::Secret.bits() | SectorFlags::WasSecret.bits();
+            self.total_secrets += 1;
+        }
+        if sector.special & FRICTION_MASK != 0 {sector.flags |= SectorFlags::Friction.bits()}
+        if sector.special & PUSH_MASK != 0 {sector.flags |= SectorFlags::Push.bits()}
+
+        let (amount, leaky) = match sector.special & DAMAGE_MASK {
+            0x100 => (5, 0),
+            0x200 => (10, 0),
+            0x300 => (20, 5),
+            _ => (0, 0)
+        };
+        if amount > 0 {
+            sector.damage_amount = amount;
+            sector.damage_interval = 32;
+            sector.leaky_damage = leaky;
+            sector.damage_type = String::from("None");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doom_lines() {
+        let table = [
+            (1, Some((USE|MONST|REP, DoorRaise, [0, D_SLOW, VDOORWAIT, 0, 0]))),
+            (39, Some((WALK|MONST, Teleport, [0, TAG, 0, 0, 0]))),
+            (126, Some((MONWALK|REP, Teleport, [0, TAG, 0, 0, 0]))),
+            (208, Some((WALK|REP|MONST, TeleportNoFog, [0, 1, TAG, 0, 0]))),
+            (263, Some((WALK|REP|MONST, TeleportLine, [TAG, TAG, 1, 0, 0]))),
+            (0, None),
+            (270, None)
+        ];
+        for (special, translation) in table {
+            assert_eq!(doom_line_translation(special), translation, "special {}", special);
+        }
+    }
+
+    #[test]
+    fn generalized_lines() {
+        let table = [
+            //WR floor, speed 1, model with monsters, up to the 3rd target with crush
+            (0x7169, Some((WALK|REP|MONST, GenericFloor, [5, 16, 0, 3, 24]))),
+            //D1 floor by 24, the manual types have no tag
+            (0x6306, Some((USE, GenericFloor, [0, 8, 24, 0, 0]))),
+            //S1 ceiling
+            (0x4002, Some((USE, GenericCeiling, [5, 8, 0, 1, 0]))),
+            //SR door with monsters, fast open and close after 32 octics
+            (0x3db3, Some((USE|REP|MONST, GenericDoor, [5, 64, 65, 32, 0]))),
+            //S1 locked door with the red card or skull
+            (0x3842, Some((USE, GenericDoor, [5, 16, 64, 34, 1]))),
+            (0x3a42, Some((USE, GenericDoor, [5, 16, 64, 34, 129]))),
+            //GR lift
+            (0x3415, Some((SHOOT|REP, GenericLift, [5, 64, 8, 1, 0]))),
+            //W1 stairs with monsters, step 8, ignoring the textures
+            (0x3260, Some((WALK|MONST, GenericStairs, [5, 2, 8, 2, 0]))),
+            //S1 silent crusher
+            (0x2fc2, Some((USE, GenericCrusher, [5, 8, 8, 1, 10]))),
+            (0x2f7f, None)
+        ];
+        for (special, translation) in table {
+            assert_eq!(generalized_translation(special, 5), translation, "special {:#x}", special);
+        }
+    }
+
+    #[test]
+    fn sector_special_bits() {
+        let level = LevelLocals::default();
+        let table = [
+            (9, SECRET_MASK),
+            (7, SectorSpecials::DDamageNukage as i32),
+            //the boom damage, secret and friction bits above the doom type
+            (0x67, SectorSpecials::DDamageNukage as i32 | DAMAGE_MASK),
+            (0x80, SECRET_MASK),
+            (0x100, FRICTION_MASK),
+            (0x200, PUSH_MASK),
+            (0x8000 | 115, 115)
+        ];
+        for (special, translated) in table {
+            assert_eq!(level.translate_sector_special(special as i16), translated, "special {:#x}", special);
+        }
+    }
+
+    #[test]
+    fn sector_flags_and_damage() {
+        let mut level = LevelLocals::default();
+        let table = [
+            (0x100, 0, 5, 0),
+            (0x200, 0, 10, 0),
+            (DAMAGE_MASK, 0, 20, 5),
+            (SECRET_MASK, SectorFlags::Secret.bits() | SectorFlags::WasSecret.bits(), 0, 0),
+            (FRICTION_MASK | PUSH_MASK, SectorFlags::Friction.bits() | SectorFlags::Push.bits(), 0, 0)
+        ];
+        for (special, flags, damage, leaky) in table {
+            let mut sector = Sector::new(0);
+            sector.special = special;
+            level.init_sector_special_bits(&mut sector);
+            assert_eq!((sector.flags, sector.damage_amount, sector.leaky_damage), (flags, damage, leaky), "special {:#x}", special);
+        }
+        assert_eq!(level.total_secrets, 1);
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use crate::vector::Vector2;
+
+use super::level_elements::{LevelElements, SectorIndex, SubSector};
+
+/*
+ * Triangulating the floors and ceilings (earcut.hpp and hw_vertexbuilder.cpp)
+ * The subsectors of gl nodes are closed and convex so they are a fan, the subsectors of the doom nodes miss the
+ * parts along the partition lines so those sectors are triangulated from the loops of their lines instead.
+ */
+impl LevelElements {
+    //the segs of a gl subsector go around it, every seg starts where the last one ended
+    pub fn is_subsector_closed(&self, sub: &SubSector) -> bool {
+        let count = sub.line_count as usize;
+        if count < 3 {return false}
+        (0..count).all(|i| {
+            let seg = self.segs[sub.first_line as usize + i].borrow_mut();
+            let next = self.segs[sub.first_line as usize + (i + 1) % count].borrow_mut();
+            seg.v2 == next.v1
+        })
+    }
+
+    /*
+     * The loops of the lines around the sector, the sector is on the right of them.
+     * The lines that have the sector on both sides are inside it and are left out.
+     */
+    pub fn sector_loops(&self, sector: SectorIndex) -> Vec<Vec<Vector2<f64>>> {
+        let mut edges: Vec<(Vector2<f64>, Vector2<f64>)> = vec![];
+        for line_index in &self.sectors[sector as usize].borrow_mut().lines {
+            let line = self.lines[*line_index as usize].borrow_mut();
+            if line.front_sector == sector && line.back_sector == sector {continue}
+            if line.front_sector == sector {edges.push((line.v1.f_pos(), line.v2.f_pos()))}
+            else if line.back_sector == sector {edges.push((line.v2.f_pos(), line.v1.f_pos()))}
+        }
+
+        let key = |point: &Vector2<f64>| (point.x.to_bits(), point.y.to_bits());
+        let mut starts: HashMap<(u64, u64), Vec<usize>> = HashMap::new();
+        for (i, edge) in edges.iter().enumerate() {
+            starts.entry(key(&edge.0)).or_default().push(i);
+        }
+
+        let mut used = vec![false; edges.len()];
+        let mut loops = vec![];
+        for first in 0..edges.len() {
+            if used[first] {continue}
+            let mut points = vec![];
+            let mut current = first;
+            loop {
+                used[current] = true;
+                points.push(edges[current].0);
+                let end = key(&edges[current].1);
+                if end == key(&edges[first].0) {break}
+                //an open loop is ended where it stops, the map is broken there
+                match starts.get(&end).and_then(|next| next.iter().find(|next| !used[**next])) {
+                    Some(next) => current = *next,
+                    None => break
+                }
+            }
+            if points.len() >= 3 {loops.push(points)}
+        }
+        loops
+    }
+}
+
+/*
+ * Triangulates the loops of a polygon, a loop inside an odd number of other loops is a hole.
+ * Returns the points and the triangles as indices into them, the triangles go counter clockwise.
+ */
+pub fn triangulate_loops(loops: &[Vec<Vector2<f64>>]) -> (Vec<Vector2<f64>>, Vec<u32>) {
+    let mut points: Vec<Vector2<f64>> = vec![];
+    let mut triangles: Vec<u32> = vec![];
+
+    let contains = |outer: usize, inner: usize| outer != inner && point_in_loop(&loops[inner][0], &loops[outer]);
+    let depths: Vec<usize> = (0..loops.len()).map(|i| (0..loops.len()).filter(|j| contains(*j, i)).count()).collect();
+
+    for outer in 0..loops.len() {
+        if depths[outer] % 2 == 1 {continue}
+        //the holes right inside this loop and not in a deeper one
+        let holes: Vec<usize> = (0..loops.len()).filter(|hole| depths[*hole] == depths[outer] + 1 && contains(outer, *hole)).collect();
+
+        let mut polygon = add_loop(&mut points, &loops[outer], true);
+        let mut hole_polygons: Vec<Vec<usize>> = holes.iter().map(|hole| add_loop(&mut points, &loops[*hole], false)).collect();
+        //the holes furthest to the right are joined first so the bridges don't cross the ones after them
+        hole_polygons.sort_by(|a, b| rightmost_x(&points, b).total_cmp(&rightmost_x(&points, a)));
+        while !hole_polygons.is_empty() {
+            let hole = hole_polygons.remove(0);
+            polygon = bridge_hole(&points, polygon, &hole, &hole_polygons);
+        }
+        ear_clip(&points, polygon, &mut triangles);
+    }
+    (points, triangles)
+}
+
+//the points of the loop turned counter clockwise for the outer loops and clockwise for the holes
+fn add_loop(points: &mut Vec<Vector2<f64>>, points_loop: &[Vector2<f64>], counter_clockwise: bool) -> Vec<usize> {
+    let start = points.len();
+    points.extend_from_slice(points_loop);
+    let mut polygon: Vec<usize> = (start..points.len()).collect();
+    if (signed_area(points, &polygon) > 0.) != counter_clockwise {polygon.reverse()}
+    polygon
+}
+
+fn signed_area(points: &[Vector2<f64>], polygon: &[usize]) -> f64 {
+    let mut area = 0.;
+    for i in 0..polygon.len() {
+        let a = &points[polygon[i]];
+        let b = &points[polygon[(i + 1) % polygon.len()]];
+        area += a.x * b.y - b.x * a.y;
+    }
+    area / 2.
+}
+
+fn rightmost_x(points: &[Vector2<f64>], polygon: &[usize]) -> f64 {
+    polygon.iter().map(|index| points[*index].x).fold(f64::MIN, f64::max)
+}
+
+fn point_in_loop(point: &Vector2<f64>, points_loop: &[Vector2<f64>]) -> bool {
+    let mut inside = false;
+    let mut j = points_loop.len() - 1;
+    for i in 0..points_loop.len() {
+        let (a, b) = (&points_loop[i], &points_loop[j]);
+        if (a.y > point.y) != (b.y > point.y) && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+//twice the signed area of the triangle, positive when it goes counter clockwise
+fn cross(a: &Vector2<f64>, b: &Vector2<f64>, c: &Vector2<f64>) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn same_point(a: &Vector2<f64>, b: &Vector2<f64>) -> bool {
+    a.x == b.x && a.y == b.y
+}
+
+//the segments cross each other, touching at the ends doesn't count
+fn segments_cross(a: &Vector2<f64>, b: &Vector2<f64>, c: &Vector2<f64>, d: &Vector2<f64>) -> bool {
+    if same_point(a, c) || same_point(a, d) || same_point(b, c) || same_point(b, d) {return false}
+    let d1 = cross(a, b, c);
+    let d2 = cross(a, b, d);
+    let d3 = cross(c, d, a);
+    let d4 = cross(c, d, b);
+    ((d1 > 0. && d2 < 0.) || (d1 < 0. && d2 > 0.)) && ((d3 > 0. && d4 < 0.) || (d3 < 0. && d4 > 0.))
+}
+
+fn crosses_polygon(points: &[Vector2<f64>], a: &Vector2<f64>, b: &Vector2<f64>, polygon: &[usize]) -> bool {
+    (0..polygon.len()).any(|i| segments_cross(a, b, &points[polygon[i]], &points[polygon[(i + 1) % polygon.len()]]))
+}
+
+/*
+ * Joins the hole to the polygon with two edges from its rightmost point to the closest point of the polygon it can see.
+ * The points at the ends of the bridge are in the polygon twice.
+ */
+fn bridge_hole(points: &[Vector2<f64>], polygon: Vec<usize>, hole: &[usize], other_holes: &[Vec<usize>]) -> Vec<usize> {
+    let hole_start = (0..hole.len()).max_by(|a, b| points[hole[*a]].x.total_cmp(&points[hole[*b]].x)).unwrap();
+    let m = &points[hole[hole_start]];
+
+    let mut candidates: Vec<usize> = (0..polygon.len()).collect();
+    let distance = |i: &usize| {
+        let p = &points[polygon[*i]];
+        (p.x - m.x) * (p.x - m.x) + (p.y - m.y) * (p.y - m.y)
+    };
+    candidates.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+    let visible = candidates.iter().find(|i| {
+        let p = &points[polygon[**i]];
+        !crosses_polygon(points, m, p, &polygon) && !crosses_polygon(points, m, p, hole)
+            && !other_holes.iter().any(|other| crosses_polygon(points, m, p, other))
+    });
+    //when nothing can be seen the closest point is used, the triangles may overlap there
+    let bridge = *visible.unwrap_or(&candidates[0]);
+
+    let mut joined = Vec::with_capacity(polygon.len() + hole.len() + 2);
+    joined.extend_from_slice(&polygon[..=bridge]);
+    for i in 0..=hole.len() {
+        joined.push(hole[(hole_start + i) % hole.len()]);
+    }
+    joined.extend_from_slice(&polygon[bridge..]);
+    joined
+}
+
+/*
+ * Cuts off the ears of the counter clockwise polygon until there is one triangle left.
+ * A corner is an ear when it is convex and no other corner is in its triangle.
+ */
+fn ear_clip(points: &[Vector2<f64>], mut polygon: Vec<usize>, triangles: &mut Vec<u32>) {
+    let mut i = 0;
+    let mut tries = 0;
+    while polygon.len() > 3 {
+        let count = polygon.len();
+        let (prev, cur, next) = (polygon[(i + count - 1) % count], polygon[i % count], polygon[(i + 1) % count]);
+        let (a, b, c) = (&points[prev], &points[cur], &points[next]);
+        let area = cross(a, b, c);
+
+        //the corners on a straight line are removed without a triangle
+        let is_ear = area == 0. || (area > 0. && !polygon.iter().any(|other| {
+            let p = &points[*other];
+            !same_point(p, a) && !same_point(p, b) && !same_point(p, c)
+                && cross(a, b, p) >= 0. && cross(b, c, p) >= 0. && cross(c, a, p) >= 0.
+        }));
+
+        //a broken polygon has no ears left, a corner is cut off anyway so it ends
+        if is_ear || tries >= count {
+            if area != 0. {triangles.extend_from_slice(&[prev as u32, cur as u32, next as u32])}
+            polygon.remove(i % count);
+            tries = 0;
+            i %= polygon.len();
+        }
+        else {
+            i = (i + 1) % count;
+            tries += 1;
+        }
+    }
+    if polygon.len() == 3 && cross(&points[polygon[0]], &points[polygon[1]], &points[polygon[2]]) != 0. {
+        triangles.extend(polygon.iter().map(|index| *index as u32));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::level::level_elements::{Line, Sector, Vertex};
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Vector2<f64>> {
+        vec![Vector2 { x, y }, Vector2 { x: x + size, y }, Vector2 { x: x + size, y: y + size }, Vector2 { x, y: y + size }]
+    }
+
+    //the area of every triangle, they all have to go counter clockwise
+    fn triangle_areas(points: &[Vector2<f64>], triangles: &[u32]) -> Vec<f64> {
+        triangles.chunks(3).map(|triangle| {
+            let area = cross(&points[triangle[0] as usize], &points[triangle[1] as usize], &points[triangle[2] as usize]) / 2.;
+            assert!(area > 0., "the triangle {:?} doesn't go counter clockwise", triangle);
+            area
+        }).collect()
+    }
+
+    #[test]
+    fn square_with_a_hole() {
+        let loops = vec![square(0., 0., 4.), square(1., 1., 2.)];
+        let (points, triangles) = triangulate_loops(&loops);
+        let areas = triangle_areas(&points, &triangles);
+        assert_eq!(areas.iter().sum::<f64>(), 12.);
+
+        //nothing covers the hole
+        let hole = &loops[1];
+        for triangle in triangles.chunks(3) {
+            let (a, b, c) = (&points[triangle[0] as usize], &points[triangle[1] as usize], &points[triangle[2] as usize]);
+            let center = Vector2 { x: (a.x + b.x + c.x) / 3., y: (a.y + b.y + c.y) / 3. };
+            assert!(!point_in_loop(&center, hole));
+        }
+    }
+
+    #[test]
+    fn collinear_vertices_get_no_triangles() {
+        let polygon = vec![Vector2 { x: 0., y: 0. }, Vector2 { x: 1., y: 0. }, Vector2 { x: 2., y: 0. }, Vector2 { x: 2., y: 2. }, Vector2 { x: 0., y: 2. }];
+        let (points, triangles) = triangulate_loops(&[polygon]);
+        let areas = triangle_areas(&points, &triangles);
+        assert_eq!(areas.len(), 3);
+        assert_eq!(areas.iter().sum::<f64>(), 4.);
+    }
+
+    #[test]
+    fn ear_clip_turns_a_clockwise_loop() {
+        let mut loop_ = square(0., 0., 2.);
+        loop_.reverse();
+        let (points, triangles) = triangulate_loops(&[loop_]);
+        assert_eq!(triangle_areas(&points, &triangles).iter().sum::<f64>(), 4.);
+    }
+
+    fn add_line(elements: &mut LevelElements, from: (i16, i16), to: (i16, i16), front: SectorIndex, back: SectorIndex) {
+        let mut line = Line::new();
+        line.v1 = Vertex::new(from.0, from.1);
+        line.v2 = Vertex::new(to.0, to.1);
+        line.front_sector = front;
+        line.back_sector = back;
+        elements.lines.push(Rc::new(RefCell::new(line)));
+        let index = elements.lines.len() as i32 - 1;
+        for sector in [front, back] {
+            if sector >= 0 {elements.sectors[sector as usize].borrow_mut().lines.push(index)}
+        }
+    }
+
+    #[test]
+    fn sector_loops_leave_out_the_inner_lines() {
+        let mut elements = LevelElements::default();
+        elements.sectors.push(Rc::new(RefCell::new(Sector::new(-1))));
+        add_line(&mut elements, (0, 0), (0, 64), 0, -1);
+        add_line(&mut elements, (0, 64), (64, 64), 0, -1);
+        add_line(&mut elements, (64, 64), (64, 0), 0, -1);
+        add_line(&mut elements, (64, 0), (0, 0), 0, -1);
+        add_line(&mut elements, (0, 0), (64, 64), 0, 0);
+
+        let loops = elements.sector_loops(0);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 4);
+        let (points, triangles) = triangulate_loops(&loops);
+        assert_eq!(triangle_areas(&points, &triangles).iter().sum::<f64>(), 4096.);
+    }
+
+    #[test]
+    fn open_loop_ends_where_it_stops() {
+        let mut elements = LevelElements::default();
+        elements.sectors.push(Rc::new(RefCell::new(Sector::new(-1))));
+        add_line(&mut elements, (0, 0), (0, 64), 0, -1);
+        add_line(&mut elements, (0, 64), (64, 64), 0, -1);
+        add_line(&mut elements, (64, 64), (64, 0), 0, -1);
+
+        let loops = elements.sector_loops(0);
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 3);
+        let (points, triangles) = triangulate_loops(&loops);
+        assert_eq!(triangles.len(), 3);
+        assert_eq!(triangle_areas(&points, &triangles).iter().sum::<f64>(), 2048.);
+    }
+}
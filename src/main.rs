@@ -11,15 +11,19 @@ pub mod game;
 pub mod behavior;
 pub mod file_system;
 
-pub use movement::movement;
-pub use render::render;
+pub use movement::{movement, look};
+pub use render::{render, draw_psprites, draw_sprites, LevelShaders, SoftwareRenderer, SpriteFrames, ViewPoint};
 pub use player::Player;
 pub use parser::parse_map;
 
-use crate::level::level_load::MapLoader;
+use std::collections::HashMap;
+use std::path::Path;
+
 use crate::game::Game;
-use crate::level::level_texture::TextureManager;
-use crate::level::LevelLocals;
+use crate::level::{MeshChunk, VIEWHEIGHT};
+use crate::vector::Vector2;
+
+const DEFAULT_WAD: &str = "Assets/DOOM1.WAD";
 
 fn conf() -> Conf {
     Conf {
@@ -31,69 +35,171 @@ fn conf() -> Conf {
     }
 }
 
-fn init_world()->Player {
-    let world_up = vec3(0.0, 1.0, 0.0);
+//the camera, it needs no window
+fn new_player() -> Player {
+    let world_up = vec3(0.0, 0.0, 1.0);
     let yaw:f32 = 1.18;
     let pitch:f32 = 0.0;
     let front = vec3(
         yaw.cos() * pitch.cos(),
-        pitch.sin(),
         yaw.sin() * pitch.cos(),
+        pitch.sin(),
     )
     .normalize();
-    let player = Player {
+    Player {
         yaw: 1.18,
         pitch: 0.0,
-        front: vec3(
-            yaw.cos() * pitch.cos(),
-            pitch.sin(),
-            yaw.sin() * pitch.cos(),
-        )
-        .normalize(),
+        front,
         right: front.cross(world_up).normalize(),
         up: Vec3 { x: 0., y: 0., z: 0. },
-        position: vec3(0.0, 1.0, 0.0),
+        position: vec3(0.0, 0.0, 41.0),
+        prev_position: vec3(0.0, 0.0, 41.0),
         grabbed: true,
-        last_mouse_position: mouse_position().into()
-    };
+        use_down: false,
+        weapon_down: false,
+        last_mouse_position: Vec2::ZERO
+    }
+}
+
+fn init_world()->Player {
+    let mut player = new_player();
+    player.last_mouse_position = mouse_position().into();
     set_cursor_grab(player.grabbed);
     show_mouse(false);
     player
 }
 
-#[macroquad::main(conf)]
-async fn main() {
+//--screenshot draws a level with the software renderer into a png without opening a window
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("--screenshot") {
+        if let Err(error) = screenshot(&args[2..]) {
+            eprintln!("screenshot: {}", error);
+            eprintln!("usage: --screenshot <map> <x,y[,z]> <angle> <file.png> [<width>x<height>] [--wad <file>]");
+            std::process::exit(1);
+        }
+        return
+    }
+    macroquad::Window::from_config(conf(), run());
+}
 
-    let mut wad = parse_map("Assets/DOOM1.WAD");
+/*
+ * Loads the map and draws it from the position looking at the angle in degrees, the things are where the map puts them.
+ * Without a z the view is at the height of the eyes of a player standing there, the size is 320x200 when it isn't given.
+ * The map is in DOOM1.WAD unless --wad gives another wad.
+ */
+fn screenshot(args: &[String]) -> Result<(), String> {
+    let (wad_path, args) = match args.iter().position(|arg| arg == "--wad") {
+        Some(i) => {
+            let wad_path = args.get(i + 1).ok_or("--wad needs a file")?;
+            (wad_path.as_str(), [&args[..i], &args[i + 2..]].concat())
+        }
+        None => (DEFAULT_WAD, args.to_vec())
+    };
+    if !Path::new(wad_path).is_file() {return Err(format!("there is no wad {}", wad_path))}
+
+    let (map, position, angle, path) = match args.as_slice() {
+        [map, position, angle, path, ..] => (map, position, angle, path),
+        _ => return Err("not enough arguments".to_string())
+    };
+    let coords = position.split(',').map(|coord| coord.parse::<f64>()).collect::<Result<Vec<f64>, _>>()
+        .map_err(|_| format!("bad position {}", position))?;
+    if coords.len() < 2 || coords.len() > 3 {return Err(format!("bad position {}", position))}
+    let angle = angle.parse::<f64>().map_err(|_| format!("bad angle {}", angle))?;
+    let (width, height) = match args.get(4) {
+        Some(size) => size.split_once('x').and_then(|(width, height)| Some((width.parse::<usize>().ok()?, height.parse::<usize>().ok()?)))
+            .filter(|(width, height)| *width > 0 && *height > 0).ok_or(format!("bad size {}", size))?,
+        None => (320, 200)
+    };
+
+    let wad = parse_map(wad_path);
+    let level_index = wad.levels.iter().position(|level| level.name.eq_ignore_ascii_case(map)).ok_or(format!("there is no map {}", map))?;
+    let mut game = Game::new(wad, new_player());
+    game.init_new(level_index);
+
+    let (x, y) = (coords[0], coords[1]);
+    let z = match coords.get(2) {
+        Some(z) => *z,
+        None => {
+            let sector = game.level.point_in_sector(x, y);
+            if sector < 0 {return Err(format!("{},{} is outside of the map", x, y))}
+            let sector = game.level.sectors[sector as usize].borrow();
+            sector.floorplane.z_at(&Vector2 { x, y }) + VIEWHEIGHT
+        }
+    };
+    let view = ViewPoint { x, y, z, angle: angle.to_radians(), actor: game.level.players[0].mo };
+    let mut software = SoftwareRenderer::new(&game.wad, width, height);
+    software.render(&game.level, &game.tex_manager, &game.wad, &SpriteFrames::new(&game.wad), view, 1.);
+    software.save_png(path).map_err(|error| format!("{}: {}", path, error))
+}
+
+async fn run() {
+
+    let wad = parse_map(DEFAULT_WAD);
 
     let wall = load_texture("Assets/greystone_128.png").await.unwrap();
 
-    let mut player = init_world();
+    let player = init_world();
     let mut level_layer: i32 = 0;
     let mut height: f32 = 0.;
     let mut x_pos: f32 = 0.;
     let mut scale: f32 = 1.;
     println!("Making Game");
-    let game = Game::new();
+    let mut game = Game::new(wad, player);
     println!("Made Game");
-    let mut level = LevelLocals::default();
-    println!("Made Level");
-    let tex_manager = TextureManager::new();
-    println!("Made Texture Manager");
-    let mut maploader: MapLoader = MapLoader::new(&mut level, &tex_manager);
-    println!("Made MapLoader");
-    maploader.load_level(&mut wad.levels[0], &game);
+    game.init_new(0);
     println!("Loaded level");
     // println!("levelmesh: vertexes len: {}, indices: {:?}", level.level_mesh.vertices.len(), level.level_mesh.uv_index);
     // println!("levelmesh: vertexes: {:?}", level.level_mesh.vertices);
     println!("level: {}", level_layer);
 
     // let mesh: Mesh = Mesh { vertices: level.level_mesh.vertices, indices: level.level_mesh.uv_index, texture: None };
-    let mesh: Mesh = level.level_mesh.to_macro_mesh(wall);
+    let mut level_textures = HashMap::new();
+    let mut chunks: Vec<MeshChunk> = game.level.level_mesh.to_macro_meshes(&game.tex_manager, &mut level_textures);
+    let mut sky_chunks: Vec<MeshChunk> = game.level.level_mesh.to_sky_meshes();
+    let mut translucent_chunks: Vec<MeshChunk> = game.level.level_mesh.to_translucent_meshes(&game.tex_manager, &mut level_textures);
+    let mut sprite_textures = HashMap::new();
+    let shaders = LevelShaders::new(&game.wad);
+    let sprite_frames = SpriteFrames::new(&game.wad);
+    shaders.set_sky(&game.level, &game.tex_manager, &mut level_textures);
+    let mut software = SoftwareRenderer::new(&game.wad, 320, 200);
+    let mut software_view = false;
+    //the lines typed in the terminal are the commands of the console
+    let (console_sender, console) = std::sync::mpsc::channel::<String>();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines().map_while(Result::ok) {
+            if console_sender.send(line).is_err() {break}
+        }
+    });
     loop {
         let delta = get_frame_time();
         clear_background(LIGHTGRAY);
 
+        //the game runs its tics, the rest of the frame only draws
+        let frac = game.run_frame(delta as f64) as f32;
+        if game.new_level {
+            game.new_level = false;
+            chunks = game.level.level_mesh.to_macro_meshes(&game.tex_manager, &mut level_textures);
+            sky_chunks = game.level.level_mesh.to_sky_meshes();
+            translucent_chunks = game.level.level_mesh.to_translucent_meshes(&game.tex_manager, &mut level_textures);
+            shaders.set_sky(&game.level, &game.tex_manager, &mut level_textures);
+        }
+        else {
+            let (changed, fade_groups) = game.level.update_level_mesh(&game.tex_manager);
+            if !changed.is_empty() {
+                game.level.level_mesh.update_chunks(&mut chunks, &changed);
+                game.level.level_mesh.update_chunks(&mut sky_chunks, &changed);
+                game.level.level_mesh.update_chunks(&mut translucent_chunks, &changed);
+            }
+            if !fade_groups.is_empty() {
+                game.level.level_mesh.rebatch_fades(&mut chunks, &mut translucent_chunks, &fade_groups, &game.tex_manager, &mut level_textures);
+            }
+        }
+
+        while let Ok(line) = console.try_recv() {
+            print!("{}", game.console_command(&line));
+        }
+
         if is_key_pressed(KeyCode::Escape) {
             break;
         }
@@ -102,7 +208,7 @@ async fn main() {
             height = 0.;
             x_pos = 0.;
             level_layer += 1;
-            if level_layer > (wad.levels.len() - 1) as i32 {level_layer = 0}
+            if level_layer > (game.wad.levels.len() - 1) as i32 {level_layer = 0}
             println!("level: {}", level_layer);
         }
     
@@ -110,7 +216,7 @@ async fn main() {
             height = 0.;
             level_layer -= 1;
             x_pos = 0.;
-            if level_layer < 0 {level_layer = (wad.levels.len() - 1) as i32}
+            if level_layer < 0 {level_layer = (game.wad.levels.len() - 1) as i32}
             println!("level: {}", level_layer);
         }
 
@@ -120,13 +226,33 @@ async fn main() {
         if is_key_down(KeyCode::Kp6) {x_pos -= 1.}
         if is_key_released(KeyCode::KpMultiply) {scale *= 1.25}
         if is_key_released(KeyCode::KpDivide) {scale /= 1.25}
+        if is_key_pressed(KeyCode::L) {
+            game.level.light_mode = game.level.light_mode.next();
+            println!("light mode: {:?}", game.level.light_mode);
+        }
+        if is_key_pressed(KeyCode::R) {
+            software_view = !software_view;
+            println!("software renderer: {}", software_view);
+        }
 
         
         
         
-        movement(delta, &mut player);
+        look(delta, &mut game.player);
         // println!("level: {}", level);
-        render(wall, & player, &wad.levels[level_layer as usize].vertexes, &wad.levels[level_layer as usize].linedefs, MapTransform{height, x_pos, scale}, &mesh);
+        let map = &game.wad.levels[level_layer as usize];
+        if software_view {
+            let position = game.player.view_position(frac);
+            let view = ViewPoint { x: position.x as f64, y: position.y as f64, z: position.z as f64, angle: game.player.front.y.atan2(game.player.front.x) as f64, actor: game.level.players[0].mo };
+            software.render(&game.level, &game.tex_manager, &game.wad, &sprite_frames, view, frac as f64);
+            software.draw_player_sprites(&game.level, &game.wad, &sprite_frames);
+            software.draw_to_screen();
+        }
+        else {
+            render(wall, &game.player, frac, &map.vertexes, &map.linedefs, MapTransform{height, x_pos, scale}, &chunks, &sky_chunks, &shaders, &game.level);
+            draw_sprites(&game.wad, &game.level, &sprite_frames, &shaders, &mut sprite_textures, &translucent_chunks, &game.player, frac);
+            draw_psprites(&game.wad, &game.level, &mut sprite_textures);
+        }
 
         next_frame().await
     }
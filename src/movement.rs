@@ -1,55 +1,133 @@
 use macroquad::prelude::*;
 
 
+use crate::level::{LevelLocals, VIEWHEIGHT};
 use crate::player::Player;
+use crate::vector::Angle;
 
-const MOVE_SPEED: f32 = 0.1;
-const LOOK_SPEED: f32 = 0.2;
+//the moves of a tic when walking and running, forwardmove and sidemove of doom
+const FORWARD_MOVE: [f64; 2] = [25., 50.];
+const SIDE_MOVE: [f64; 2] = [24., 40.];
+const TURN_SPEED: f32 = 1.4; //per second
+const MOUSE_SENSITIVITY: f32 = 0.003; //per pixel
+//the keys of the weapon slots
+const WEAPON_KEYS: [KeyCode; 7] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6, KeyCode::Key7];
 
-pub fn movement(delta: f32, player: &mut Player) {
-    let world_up = vec3(0.0, 1.0, 0.0);
+//the movement is part of the game tic, so it is the same at any frame rate (P_MovePlayer)
+pub fn movement(player: &mut Player, level: &mut LevelLocals) {
+    let run = (is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift)) as usize;
+    let mut forward = 0.;
+    let mut side = 0.;
+    if is_key_down(KeyCode::Up) || is_key_down(KeyCode::W) {forward += FORWARD_MOVE[run]}
+    if is_key_down(KeyCode::Down) || is_key_down(KeyCode::S) {forward -= FORWARD_MOVE[run]}
+    if is_key_down(KeyCode::Left) || is_key_down(KeyCode::A) {side -= SIDE_MOVE[run]}
+    if is_key_down(KeyCode::Right) || is_key_down(KeyCode::D) {side += SIDE_MOVE[run]}
 
-    if is_key_pressed(KeyCode::Tab) {
-        player.grabbed = !player.grabbed;
-        set_cursor_grab(player.grabbed);
-        show_mouse(!player.grabbed);
+    let mo = level.players[0].mo;
+    //the use key has to be let go before it uses again
+    if is_key_down(KeyCode::Space) {
+        if !player.use_down {
+            player.use_down = true;
+            level.with_actor(mo, |level, actor| level.use_lines(mo, actor));
+        }
+    }
+    else {player.use_down = false}
+
+    //the weapons fire and change in the player think
+    let cmd = &mut level.players[0].cmd;
+    cmd.attack = is_mouse_button_down(MouseButton::Left) || is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+    match WEAPON_KEYS.iter().position(|key| is_key_down(*key)) {
+        Some(slot) => if !player.weapon_down {
+            player.weapon_down = true;
+            cmd.weapon_slot = Some(slot);
+        }
+        None => player.weapon_down = false
     }
 
-    if is_key_down(KeyCode::Up) || is_key_down(KeyCode::W) {
-        player.position += player.front * MOVE_SPEED;
+    //the view turns with the teleports
+    let level_player = &mut level.players[0];
+    if level_player.fix_angle {
+        level_player.fix_angle = false;
+        if let Some(actor) = level.actor(mo) {player.yaw = actor.angle.degrees().to_radians() as f32}
     }
-    if is_key_down(KeyCode::Down) || is_key_down(KeyCode::S) {
-        player.position -= player.front * MOVE_SPEED;
+
+    let move_factor = match level.actor(mo) {
+        Some(actor) if actor.sector >= 0 => level.sectors[actor.sector as usize].borrow().move_factor,
+        Some(_) => 2048. / 65536.,
+        None => return
+    };
+    let actor = match level.actor_mut(mo) {
+        Some(actor) => actor,
+        None => return
+    };
+    //the player can't move for a while after a teleport
+    if actor.reaction_time > 0 {
+        actor.reaction_time -= 1;
+        return
     }
-    if is_key_down(KeyCode::Left) || is_key_down(KeyCode::A) {
-        player.position -= player.right * MOVE_SPEED;
+    actor.angle = Angle::<f64>::from_radians(player.yaw as f64);
+
+    //there is no air control, the player only pushes off the ground
+    if actor.pos.z > actor.floor_z {return}
+    let (sin, cos) = (player.yaw as f64).sin_cos();
+    actor.vel.x += (forward * cos + side * sin) * move_factor;
+    actor.vel.y += (forward * sin - side * cos) * move_factor;
+    //TODO the running animation of the player sprite
+}
+
+//the view goes with the actor of the player, at eye height (P_CalcHeight)
+pub fn follow_actor(player: &mut Player, level: &LevelLocals) {
+    if let Some(actor) = level.actor(level.players[0].mo) {
+        let eye = |z: f64| (z + VIEWHEIGHT).min(actor.ceiling_z - 4.) as f32;
+        player.prev_position = vec3(actor.prev.x as f32, actor.prev.y as f32, eye(actor.prev.z));
+        player.position = vec3(actor.pos.x as f32, actor.pos.y as f32, eye(actor.pos.z));
     }
-    if is_key_down(KeyCode::Right) || is_key_down(KeyCode::D) {
-        player.position += player.right * MOVE_SPEED;
+}
+
+//a new level puts the view at the start of the player
+pub fn reset_view(player: &mut Player, level: &LevelLocals) {
+    if let Some(actor) = level.actor(level.players[0].mo) {
+        player.yaw = actor.angle.degrees().to_radians() as f32;
+        player.pitch = 0.;
+    }
+    follow_actor(player, level);
+    player.prev_position = player.position;
+}
+
+//looking around is done every frame so the view stays smooth
+pub fn look(delta: f32, player: &mut Player) {
+    //the map has z up
+    let world_up = vec3(0.0, 0.0, 1.0);
+
+    if is_key_pressed(KeyCode::Tab) {
+        player.grabbed = !player.grabbed;
+        set_cursor_grab(player.grabbed);
+        show_mouse(!player.grabbed);
     }
 
     if is_key_down(KeyCode::Q) {
-        player.yaw -= 7. * delta * LOOK_SPEED;
+        player.yaw += delta * TURN_SPEED;
     }
 
     if is_key_down(KeyCode::E) {
-        player.yaw += 7. * delta * LOOK_SPEED;
+        player.yaw -= delta * TURN_SPEED;
     }
 
+    //the mouse moved the same distance whatever the frame time was
     let mouse_position: Vec2 = mouse_position().into();
     let mouse_delta = mouse_position - player.last_mouse_position;
     player.last_mouse_position = mouse_position;
 
-    player.yaw += mouse_delta.x * delta * LOOK_SPEED;
-    player.pitch += mouse_delta.y * delta * -LOOK_SPEED;
+    player.yaw -= mouse_delta.x * MOUSE_SENSITIVITY;
+    player.pitch += mouse_delta.y * -MOUSE_SENSITIVITY;
 
     player.pitch = if player.pitch > 1.5 { 1.5 } else { player.pitch };
     player.pitch = if player.pitch < -1.5 { -1.5 } else { player.pitch };
 
     player.front = vec3(
         player.yaw.cos() * player.pitch.cos(),
-        player.pitch.sin(),
         player.yaw.sin() * player.pitch.cos(),
+        player.pitch.sin(),
     )
     .normalize();
 
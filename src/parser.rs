@@ -1,5 +1,5 @@
 
-mod parse_graphics;
+pub mod parse_graphics;
 pub mod parse_level;
 
 use parse_graphics::*;
@@ -94,10 +94,12 @@ pub struct WADData {
     directory: Vec<WADEntry>,
     lump_map: Vec<HashMap<String, u32>>,
     wad_header: WADHeader,
-    palletes: Vec<Vec<WADPaletteColor>>,
-    color_maps: Vec<Vec<u8>>,
-    sprites: Vec<(String, WADSprite)>,
-    flats: Vec<u8>,
+    pub palletes: Vec<Vec<WADPaletteColor>>,
+    pub color_maps: Vec<Vec<u8>>,
+    pub sprites: Vec<(String, WADSprite)>, //all the graphics, the sprites and the patches
+    pub flats: Vec<(String, Vec<u8>)>,
+    pub patch_names: Vec<String>,
+    pub textures: Vec<WADTexture>,
     pub levels: Vec<WADLevel>
 }
 
@@ -206,6 +208,8 @@ fn read_data_lumps(wad_data: &Vec<u8>, wad_parsed: &mut WADData) {
         match detect_lump_type(wad_parsed, i, wad_data) {
             LumpTypes::Graphic => {read_sprites(wad_data, wad_parsed, i);}
             LumpTypes::Flat => {read_flats(wad_data, wad_parsed, i);}
+            LumpTypes::PNames => {read_pnames(wad_data, wad_parsed, i);}
+            LumpTypes::Texture1 | LumpTypes::Texture2 => {read_textures(wad_data, wad_parsed, i);}
             LumpTypes::Map => {read_levels(wad_data, wad_parsed, i);}
             _o => {/* println!("not implemented {:?} yet", o)*/}
         }
@@ -229,6 +233,8 @@ pub fn parse_map(path: &str) -> WADData {
         color_maps: vec![],
         sprites: vec![],
         flats: vec![],
+        patch_names: vec![],
+        textures: vec![],
         levels: vec![]
     };
 
@@ -10,8 +10,8 @@ pub struct WADPaletteColor
 pub struct WADSprite {
     pub width: u32,
     pub height: u32,
-    pub left_offset: u32,
-    pub top_offset: u32,
+    pub left_offset: i32, //the offsets are signed, the weapons are placed with them
+    pub top_offset: i32,
     pub posts: Vec<WADSpritePost>
 }
 
@@ -23,6 +23,21 @@ pub struct WADSpritePost
     pub pixels: Vec<u8>
 }
 
+//maptexture_t, a wall texture is made of patches
+pub struct WADTexture {
+    pub name: String,
+    pub width: u16,
+    pub height: u16,
+    pub patches: Vec<WADTexturePatch>
+}
+
+//mappatch_t, the patch is the index in the pnames
+pub struct WADTexturePatch {
+    pub origin_x: i16,
+    pub origin_y: i16,
+    pub patch: u16
+}
+
 pub fn read_pallete(wad_data: &Vec<u8>, offset: &mut usize, wad_parsed: &mut WADData) {
     println!("levels: {}", wad_parsed.levels.len());
     let i: usize = wad_parsed.levels.len();
@@ -69,8 +84,8 @@ pub fn read_sprites(wad_data: &Vec<u8>, wad_parsed: &mut WADData, index: usize)
 
     let width = u32::from(read_ushort(wad_data, &mut offset).unwrap());
     let height = u32::from(read_ushort(wad_data, &mut offset).unwrap());
-    let left_offset = u32::from(read_ushort(wad_data, &mut offset).unwrap());
-    let top_offset = u32::from(read_ushort(wad_data, &mut offset).unwrap());
+    let left_offset = i32::from(read_short(wad_data, &mut offset).unwrap());
+    let top_offset = i32::from(read_short(wad_data, &mut offset).unwrap());
 
     let mut sprite = WADSprite { width, height, left_offset, top_offset, posts: vec![] };
 
@@ -101,9 +116,55 @@ pub fn read_sprites(wad_data: &Vec<u8>, wad_parsed: &mut WADData, index: usize)
 }
 
 pub fn read_flats(wad_data: &Vec<u8>, wad_parsed: &mut WADData, index: usize) {
-    let offset = wad_parsed.directory[index].offset as usize;
+    let flat = &wad_parsed.directory[index];
+    //the flats are 64x64, the bigger ones of the ports are cut
+    if flat.size < 4096 {return}
+    let offset = flat.offset as usize;
     let pixels = wad_data[offset..offset + 4096].to_vec();
-    wad_parsed.flats = pixels;
+    wad_parsed.flats.push((flat.name.to_string(), pixels));
+}
+
+//the names of the patches the textures use
+pub fn read_pnames(wad_data: &Vec<u8>, wad_parsed: &mut WADData, index: usize) {
+    let mut offset = wad_parsed.directory[index].offset as usize;
+    let count = read_uint(wad_data, &mut offset).unwrap();
+    for _i in 0..count {
+        let mut name = String::new();
+        copy_and_capitalize_buffer(&mut name, wad_data, &mut offset, 8);
+        wad_parsed.patch_names.push(name);
+    }
+}
+
+//TEXTURE1 and TEXTURE2, the textures of the strife format are not read
+pub fn read_textures(wad_data: &Vec<u8>, wad_parsed: &mut WADData, index: usize) {
+    let lump_offset = wad_parsed.directory[index].offset as usize;
+    let mut offset = lump_offset;
+    let count = read_uint(wad_data, &mut offset).unwrap();
+    let mut texture_offsets = vec![];
+    for _i in 0..count {
+        texture_offsets.push(read_uint(wad_data, &mut offset).unwrap());
+    }
+
+    for texture_offset in texture_offsets {
+        offset = lump_offset + texture_offset as usize;
+        let mut name = String::new();
+        copy_and_capitalize_buffer(&mut name, wad_data, &mut offset, 8);
+        offset += 4; // the masked flag
+        let width = read_ushort(wad_data, &mut offset).unwrap();
+        let height = read_ushort(wad_data, &mut offset).unwrap();
+        offset += 4; // the obsolete column directory
+        let patch_count = read_ushort(wad_data, &mut offset).unwrap();
+
+        let mut patches = vec![];
+        for _i in 0..patch_count {
+            let origin_x = read_short(wad_data, &mut offset).unwrap();
+            let origin_y = read_short(wad_data, &mut offset).unwrap();
+            let patch = read_ushort(wad_data, &mut offset).unwrap();
+            offset += 4; // the unused stepdir and colormap
+            patches.push(WADTexturePatch { origin_x, origin_y, patch });
+        }
+        wad_parsed.textures.push(WADTexture { name, width, height, patches });
+    }
 }
 
 pub fn is_doom_gfx(dv: &Vec<u8>,lump: WADEntry, offset: usize) ->bool {
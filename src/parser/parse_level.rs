@@ -1,4 +1,3 @@
-use bitreader::BitReader;
 
 use crate::parser::*;
 use crate::behavior::*;
@@ -536,28 +535,15 @@ fn parse_blockmap(lump: &Vec<u8>) -> WADLevelBlockmap {
     blockmap
 }
 
+//the bit of sector s2 seen from sector s1 is s1 * sectors + s2, the lowest bit of a byte comes first
 fn parse_rejects(lump: &Vec<u8>, sector_size: usize) -> Vec<Vec<bool>> {
     let mut rejects: Vec<Vec<bool>> = vec![vec![false; sector_size]; sector_size];
-    let mut col = 0;
-    let mut row = 0;
-
-    let mut offset = 0;
-    while offset < sector_size {
-        let mut reader = BitReader::new(&lump[offset..offset+1]);
-        for _i in 0..8 {
-            let bit = reader.read_u8(1).unwrap();
-            if col == sector_size {
-                col = 0;
-                row += 1;
-            }
-
-            // Check if we have already filled the table even if we still have bits left (rounding)
-            if row >= sector_size {break}
-
-            rejects[col][row] = bit != 0;
-            col += 1;
+    for (s1, row) in rejects.iter_mut().enumerate() {
+        for (s2, reject) in row.iter_mut().enumerate() {
+            let bit = s1 * sector_size + s2;
+            //a short lump doesn't reject the rest
+            if let Some(byte) = lump.get(bit / 8) {*reject = byte & (1 << (bit % 8)) != 0}
         }
-        offset += 1;
     }
     rejects
 }
@@ -1,11 +1,21 @@
 use macroquad::prelude::*;
 pub struct Player {
     pub position: Vec3,
+    pub prev_position: Vec3, //the position at the previous tic, for the interpolation
     pub yaw: f32,
     pub pitch: f32,
     pub grabbed: bool,
+    pub use_down: bool, //the use key is held
+    pub weapon_down: bool, //a weapon key is held
     pub front: Vec3,
     pub right: Vec3,
     pub up: Vec3,
     pub last_mouse_position: Vec2
-}
\ No newline at end of file
+}
+
+impl Player {
+    //the position between the last two tics, frac is how far the game is into the next tic
+    pub fn view_position(&self, frac: f32) -> Vec3 {
+        self.prev_position.lerp(self.position, frac)
+    }
+}
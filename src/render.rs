@@ -1,15 +1,29 @@
+use std::collections::HashMap;
+
 use macroquad::prelude::*;
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Comparison, Equation, PipelineParams};
 
 use crate::{parser::parse_level::{WADLevelVertex, WADLevelLinedef}, vector::Vector2, MapTransform};
+use crate::parser::WADData;
+use crate::parser::parse_graphics::{WADPaletteColor, WADSprite};
+use crate::level::level_texture::{TextureID, TextureManager};
+use crate::level::{LevelLocals, LevelMesh, MeshChunk, NUMPSPRITES, S_NULL, SPRITE_NAMES, STATES};
 pub use crate::player::Player;
 
-pub fn render(wall: Texture2D, player: &Player, verts: &Vec<WADLevelVertex>, linedefs: &Vec<WADLevelLinedef>, transform: MapTransform, mesh: &Mesh) {
-    set_camera(&Camera3D {
-        position: player.position,
-        up: player.up,
-        target: player.position.clone() + player.front.clone(),
-        ..Default::default()
-    });
+mod render_sprites;
+mod render_soft;
+mod render_soft_bsp;
+mod render_soft_things;
+mod render_png;
+
+pub use render_sprites::{SpriteFrames, draw_sprites};
+pub use render_soft::{SoftwareRenderer, ViewPoint};
+pub use render_png::write_png;
+
+//frac is how far the game is into the next tic, the view is interpolated between the tics
+#[allow(clippy::too_many_arguments)]
+pub fn render(wall: Texture2D, player: &Player, frac: f32, verts: &[WADLevelVertex], linedefs: &[WADLevelLinedef], transform: MapTransform, chunks: &[MeshChunk], sky_chunks: &[MeshChunk], shaders: &LevelShaders, level: &LevelLocals) {
+    let position = set_view_camera(player, frac);
 
     draw_grid(20, 1., BLACK, GRAY);
 
@@ -24,6 +38,295 @@ pub fn render(wall: Texture2D, player: &Player, verts: &Vec<WADLevelVertex>, lin
         let v2: Vector2<f32> = Vector2 { x: (v2i.x as f32 / 128. + transform.x_pos) * transform.scale, y: (v2i.y as f32 / 128. + transform.height) * transform.scale };
         draw_line(v1.x, v1.y, v2.x, v2.y, 0.01, GREEN);
     }
-    draw_mesh(mesh);
+    shaders.level.set_uniform("LightMode", level.light_mode.resolve() as i32 as f32);
+    gl_use_material(shaders.level);
+    for chunk in chunks {
+        if !chunk_in_front(chunk, position, player.front) {continue}
+        shaders.level.set_uniform("FadeColor", (chunk.fade.r, chunk.fade.g, chunk.fade.b));
+        draw_mesh(&chunk.mesh);
+    }
+
+    shaders.sky.set_uniform("ViewPos", (position.x, position.y, position.z));
+    shaders.sky.set_uniform("SkyPos", (level.sky_pos1 as f32, level.sky_pos2 as f32));
+    shaders.sky.set_uniform("DoubleSky", if level.is_double_sky() {1f32} else {0.});
+    shaders.sky.set_uniform("SkyStretch", if level.sky_stretch {1f32} else {0.});
+    gl_use_material(shaders.sky);
+    for chunk in sky_chunks.iter().filter(|chunk| chunk_in_front(chunk, position, player.front)) {
+        draw_mesh(&chunk.mesh);
+    }
+    gl_use_default_material();
     set_default_camera();
-}
\ No newline at end of file
+}
+
+//the camera at the eyes of the player, returns where it is
+fn set_view_camera(player: &Player, frac: f32) -> Vec3 {
+    let position = player.view_position(frac);
+    set_camera(&Camera3D {
+        position,
+        up: player.up,
+        target: position + player.front,
+        ..Default::default()
+    });
+    position
+}
+
+/*
+ * The shader of the level (the doom lighting equation of gzdoom), the alpha of the vertex color is the light level.
+ * The light gets darker with the distance to the camera, smooth or in the bands of the COLORMAP, and goes to the fade color of the sector.
+ */
+const LEVEL_VERTEX_SHADER: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+varying highp float dist;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+    dist = gl_Position.w;
+}
+"#;
+
+const LEVEL_FRAGMENT_SHADER: &str = r#"#version 100
+precision mediump float;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+varying highp float dist;
+
+uniform sampler2D Texture;
+uniform sampler2D Bands;
+uniform float LightMode;
+uniform vec3 FadeColor;
+uniform float GlobVis;
+uniform float Alpha;
+
+void main() {
+    vec4 tex = texture2D(Texture, uv);
+    if (tex.a < 0.5) discard;
+
+    float light = color.a;
+    if (LightMode > 0.5) {
+        float vis = min(GlobVis / max(dist, 1.0), 24.0 / 32.0);
+        float colormap = clamp((2.0 - (color.a * 255.0 + 12.0) / 128.0 - vis) * 32.0, 0.0, 31.0);
+        if (LightMode > 15.5) light = texture2D(Bands, vec2((floor(colormap) + 0.5) / 32.0, 0.5)).r;
+        else light = 1.0 - colormap / 32.0;
+    }
+    gl_FragColor = vec4(mix(FadeColor, tex.rgb * color.rgb, light), tex.a * Alpha);
+}
+"#;
+
+/*
+ * The sky is drawn on the sky ceilings and walls from the direction they are seen in, like a cylinder around the camera.
+ * The 1024 columns of doom go around the full circle and the horizon is row 100, the rows go as far as on the screen of 320x200.
+ * The second sky of a double sky is seen through the holes of the first one.
+ */
+const SKY_VERTEX_SHADER: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying highp vec3 world;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    world = position;
+}
+"#;
+
+const SKY_FRAGMENT_SHADER: &str = r#"#version 100
+precision highp float;
+
+varying highp vec3 world;
+
+uniform sampler2D Sky1;
+uniform sampler2D Sky2;
+uniform vec3 ViewPos;
+uniform vec2 SkyPos;
+uniform vec4 SkySize;
+uniform float DoubleSky;
+uniform float SkyStretch;
+
+vec4 sky(sampler2D tex, float column, float row, vec2 size) {
+    //a stretched sky fills the 200 rows of the screen and the top row goes on above it
+    float v = SkyStretch > 0.5 ? max(row, 0.0) / 200.0 : row / size.y;
+    return texture2D(tex, vec2(column / size.x, v));
+}
+
+void main() {
+    vec3 dir = world - ViewPos;
+    float column = -atan(dir.y, dir.x) / 6.28318530718 * 1024.0;
+    float row = 100.0 - dir.z / max(length(dir.xy), 0.001) * 160.0;
+
+    vec4 color = sky(Sky1, column + SkyPos.x, row, SkySize.xy);
+    if (DoubleSky > 0.5) {
+        vec4 back = sky(Sky2, column + SkyPos.y, row, SkySize.zw);
+        color = vec4(mix(back.rgb, color.rgb, color.a), 1.0);
+    }
+    gl_FragColor = vec4(color.rgb, 1.0);
+}
+"#;
+
+//the materials the level mesh and the sprites are drawn with, the translucent one doesn't hide what is drawn after it
+pub struct LevelShaders {
+    pub level: Material,
+    pub translucent: Material,
+    pub sky: Material
+}
+
+impl LevelShaders {
+    pub fn new(wad: &WADData) -> LevelShaders {
+        let sky = load_material(SKY_VERTEX_SHADER, SKY_FRAGMENT_SHADER, MaterialParams {
+            pipeline_params: PipelineParams {
+                depth_test: Comparison::LessOrEqual,
+                depth_write: true,
+                ..Default::default()
+            },
+            uniforms: vec![
+                ("ViewPos".to_string(), UniformType::Float3),
+                ("SkyPos".to_string(), UniformType::Float2),
+                ("SkySize".to_string(), UniformType::Float4),
+                ("DoubleSky".to_string(), UniformType::Float1),
+                ("SkyStretch".to_string(), UniformType::Float1)
+            ],
+            textures: vec!["Sky1".to_string(), "Sky2".to_string()]
+        }).unwrap();
+        LevelShaders { level: level_material(wad, true), translucent: level_material(wad, false), sky }
+    }
+
+    //the sky textures of the level, a sky that isn't there is black
+    pub fn set_sky(&self, level: &LevelLocals, tex_man: &TextureManager, textures: &mut HashMap<TextureID, Texture2D>) {
+        let mut size = [1f32; 4];
+        for (i, (name, sky)) in [("Sky1", level.sky_texture1), ("Sky2", level.sky_texture2)].into_iter().enumerate() {
+            let texture = match LevelMesh::macro_texture(tex_man, sky, textures) {
+                Some(texture) => {
+                    let game_texture = tex_man.get_game_texture(sky, true).unwrap();
+                    size[i * 2] = game_texture.width as f32;
+                    size[i * 2 + 1] = game_texture.height as f32;
+                    texture
+                }
+                None => Texture2D::from_rgba8(1, 1, &[0, 0, 0, 255])
+            };
+            self.sky.set_texture(name, texture);
+        }
+        self.sky.set_uniform("SkySize", (size[0], size[1], size[2], size[3]));
+    }
+}
+
+//the distance where the light of the software renderer starts getting darker (r_visibility 8 at 320x200)
+const GLOB_VIS: f32 = 1280. / 31.;
+
+fn level_material(wad: &WADData, depth_write: bool) -> Material {
+    let material = load_material(LEVEL_VERTEX_SHADER, LEVEL_FRAGMENT_SHADER, MaterialParams {
+        pipeline_params: PipelineParams {
+            depth_test: Comparison::LessOrEqual,
+            depth_write,
+            color_blend: Some(BlendState::new(Equation::Add, BlendFactor::Value(BlendValue::SourceAlpha), BlendFactor::OneMinusValue(BlendValue::SourceAlpha))),
+            ..Default::default()
+        },
+        uniforms: vec![
+            ("LightMode".to_string(), UniformType::Float1),
+            ("FadeColor".to_string(), UniformType::Float3),
+            ("GlobVis".to_string(), UniformType::Float1),
+            ("Alpha".to_string(), UniformType::Float1)
+        ],
+        textures: vec!["Bands".to_string()]
+    }).unwrap();
+    material.set_uniform("GlobVis", GLOB_VIS);
+    material.set_uniform("Alpha", 1f32);
+    material.set_texture("Bands", light_bands(wad));
+    material
+}
+
+/*
+ * How light each of the 32 light levels of the COLORMAP is, the brightness of the colors it maps to against the colors of the palette.
+ * The shader looks them up so the light goes down in the same steps as the software renderer.
+ */
+fn light_bands(wad: &WADData) -> Texture2D {
+    let luminance = |color: &WADPaletteColor| color.r as f32 * 0.299 + color.g as f32 * 0.587 + color.b as f32 * 0.114;
+    let palette = &wad.palletes[0];
+    let mut pixels = vec![255u8; 32 * 4];
+    for (i, map) in wad.color_maps.iter().take(32).enumerate() {
+        let (mut sum, mut count) = (0., 0);
+        for (color, mapped) in palette.iter().zip(map) {
+            //the dark colors have no room to get darker so they are left out
+            let lum = luminance(color);
+            if lum < 32. {continue}
+            sum += luminance(&palette[*mapped as usize]) / lum;
+            count += 1;
+        }
+        let brightness = if count > 0 {sum / count as f32} else {1. - i as f32 / 32.};
+        pixels[i * 4..i * 4 + 3].fill((brightness.clamp(0., 1.) * 255.) as u8);
+    }
+    let texture = Texture2D::from_rgba8(32, 1, &pixels);
+    texture.set_filter(FilterMode::Nearest);
+    texture
+}
+
+//a chunk is skipped when all the corners of its box are behind the camera
+fn chunk_in_front(chunk: &MeshChunk, position: Vec3, front: Vec3) -> bool {
+    (0..8).any(|corner| {
+        let x = if corner & 1 == 0 {chunk.min.x} else {chunk.max.x};
+        let y = if corner & 2 == 0 {chunk.min.y} else {chunk.max.y};
+        let z = if corner & 4 == 0 {chunk.min.z} else {chunk.max.z};
+        (vec3(x, y, z) - position).dot(front) >= 0.
+    })
+}
+
+/*
+ * R_DrawPSprite, the weapon and its flash over the view.
+ * The psprites are placed on a screen of 320x200 that is scaled to the height of the window.
+ */
+pub fn draw_psprites(wad: &WADData, level: &LevelLocals, textures: &mut HashMap<String, Texture2D>) {
+    let player = &level.players[0];
+    if player.mo < 0 {return}
+    let scale = screen_height() / 200.;
+    let left = (screen_width() - 320. * scale) / 2.;
+
+    for position in 0..NUMPSPRITES {
+        let psp = &player.psprites[position];
+        if psp.state == S_NULL {continue}
+        let state = &STATES[psp.state];
+        //the psprites only have the first rotation
+        let name = format!("{}{}0", SPRITE_NAMES[state.sprite], (b'A' + state.frame_index() as u8) as char);
+        let sprite = match wad.sprites.iter().find(|(lump, _)| *lump == name) {
+            Some((_, sprite)) => sprite,
+            None => continue
+        };
+        let texture = *textures.entry(name).or_insert_with(|| sprite_texture(sprite, &wad.palletes[0]));
+
+        //TODO the light of the sector and the full bright frames
+        let x = left + (psp.sx as f32 - sprite.left_offset as f32) * scale;
+        let y = (psp.sy as f32 - sprite.top_offset as f32) * scale;
+        draw_texture_ex(texture, x, y, WHITE, DrawTextureParams {
+            dest_size: Some(vec2(sprite.width as f32 * scale, sprite.height as f32 * scale)),
+            ..Default::default()
+        });
+    }
+}
+
+//the columns of the sprite in the colors of the palette, the space between the posts is transparent
+pub fn sprite_texture(sprite: &WADSprite, palette: &[WADPaletteColor]) -> Texture2D {
+    let mut image = Image::gen_image_color(sprite.width as u16, sprite.height as u16, Color::new(0., 0., 0., 0.));
+    for post in &sprite.posts {
+        for (i, pixel) in post.pixels.iter().enumerate() {
+            let y = post.row as u32 + i as u32;
+            if y >= sprite.height {break}
+            let color = &palette[*pixel as usize];
+            image.set_pixel(post.col as u32, y, Color::from_rgba(color.r, color.g, color.b, 255));
+        }
+    }
+    let texture = Texture2D::from_image(&image);
+    texture.set_filter(FilterMode::Nearest);
+    texture
+}
@@ -0,0 +1,148 @@
+use std::fs;
+use std::io;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+//the most a stored block of deflate can have
+const MAX_BLOCK: usize = 65535;
+
+/*
+ * Writes the rgba pixels as a png, row after row from the top.
+ * The image data isn't compressed, deflate has stored blocks for that, so it needs nothing but the standard library.
+ */
+pub fn write_png(path: &str, width: usize, height: usize, rgba: &[u8]) -> io::Result<()> {
+    fs::write(path, encode_png(width, height, rgba)?)
+}
+
+//a png can't be empty and the pixels have to fill the image
+pub fn encode_png(width: usize, height: usize, rgba: &[u8]) -> io::Result<Vec<u8>> {
+    if width == 0 || height == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("the image is {}x{}", width, height)))
+    }
+    if rgba.len() < width * height * 4 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} bytes for a {}x{} image", rgba.len(), width, height)))
+    }
+
+    let mut png = SIGNATURE.to_vec();
+
+    let mut header = vec![];
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    //8 bits for each of the rgba channels, the default compression and filters and no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    add_chunk(&mut png, b"IHDR", &header);
+
+    //each row starts with the filter, 0 leaves it as it is
+    let mut raw = Vec::with_capacity(height * (width * 4 + 1));
+    for row in rgba.chunks(width * 4).take(height) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    add_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    add_chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+//a chunk has its length, type, data and the crc of the type and data
+fn add_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+//the zlib stream of the data in stored blocks of deflate, the adler32 of the data is at the end
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let blocks = data.chunks(MAX_BLOCK).count().max(1);
+    for i in 0..blocks {
+        let block = &data[i * MAX_BLOCK..((i + 1) * MAX_BLOCK).min(data.len())];
+        stream.push((i == blocks - 1) as u8);
+        stream.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        stream.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {(crc >> 1) ^ 0xedb88320} else {crc >> 1};
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn be_u32(bytes: &[u8]) -> u32 {
+        u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    #[test]
+    fn checksums_of_the_known_vectors() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn one_pixel_png() {
+        let png = encode_png(1, 1, &[10, 20, 30, 255]).unwrap();
+        assert_eq!(png[..8], SIGNATURE);
+
+        //IHDR
+        assert_eq!(be_u32(&png[8..]), 13);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(be_u32(&png[16..]), 1);
+        assert_eq!(be_u32(&png[20..]), 1);
+        assert_eq!(png[24..29], [8, 6, 0, 0, 0]);
+        assert_eq!(be_u32(&png[29..]), crc32(&png[12..29]));
+
+        //IDAT, one stored block with the filter byte and the pixel
+        let raw = [0, 10, 20, 30, 255];
+        assert_eq!(be_u32(&png[33..]), 16);
+        assert_eq!(&png[37..41], b"IDAT");
+        assert_eq!(png[41..48], [0x78, 0x01, 1, 5, 0, !5, 0xff]);
+        assert_eq!(png[48..53], raw);
+        assert_eq!(be_u32(&png[53..]), adler32(&raw));
+        assert_eq!(be_u32(&png[57..]), crc32(&png[37..57]));
+
+        assert_eq!(png[61..], [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+    }
+
+    #[test]
+    fn empty_or_short_images_are_errors() {
+        assert!(encode_png(0, 1, &[]).is_err());
+        assert!(encode_png(1, 0, &[]).is_err());
+        assert!(encode_png(2, 1, &[0; 7]).is_err());
+        assert!(encode_png(2, 1, &[0; 8]).is_ok());
+    }
+
+    #[test]
+    fn long_data_is_split_into_blocks() {
+        let data = vec![7; MAX_BLOCK + 1];
+        let stream = zlib_stored(&data);
+        assert_eq!(stream.len(), 2 + (5 + MAX_BLOCK) + (5 + 1) + 4);
+        assert_eq!(stream[2..7], [0, 0xff, 0xff, 0, 0]);
+        assert_eq!(stream[7 + MAX_BLOCK..12 + MAX_BLOCK], [1, 1, 0, 0xfe, 0xff]);
+    }
+}
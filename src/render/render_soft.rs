@@ -0,0 +1,324 @@
+use macroquad::prelude::*;
+
+use crate::parser::WADData;
+use crate::level::{ActorIndex, LevelLocals};
+use crate::level::level_texture::{GameTexture, TextureID, TextureManager};
+
+use super::SpriteFrames;
+use super::render_png::write_png;
+use super::render_soft_bsp::DrawSeg;
+use super::render_soft_things::VisSprite;
+
+//the 16 light levels of the sectors go to the 32 colormaps from bright to dark (r_main.h)
+const LIGHT_LEVELS: i32 = 16;
+const NUM_COLORMAPS: i32 = 32;
+const MAX_LIGHT_SCALE: i32 = 48;
+const MAX_LIGHT_Z: i32 = 128;
+//doom is drawn on a screen of 320x200, a bigger buffer sees the same and has the same light
+pub(super) const BASE_WIDTH: f64 = 320.;
+pub(super) const BASE_HEIGHT: f64 = 200.;
+//the top of a column of a visplane that has nothing in it
+const UNSET: i32 = i32::MAX;
+
+//where the frame is seen from, the angle is in radians and the actor of the view isn't drawn
+#[derive(Clone, Copy, Debug)]
+pub struct ViewPoint {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub angle: f64,
+    pub actor: ActorIndex
+}
+
+//what a frame is drawn from, frac is how far the game is into the next tic
+pub(super) struct Frame<'a> {
+    pub level: &'a LevelLocals,
+    pub tex_man: &'a TextureManager,
+    pub wad: &'a WADData,
+    pub frames: &'a SpriteFrames,
+    pub frac: f64
+}
+
+//visplane_t, the columns of a floor or ceiling with the same height, flat and light
+struct VisPlane {
+    height: f64,
+    texture: TextureID,
+    light_level: i32,
+    sky: bool,
+    min_x: i32,
+    max_x: i32,
+    top: Vec<i32>,
+    bottom: Vec<i32>
+}
+
+/*
+ * The renderer of doom on the cpu (r_main.c, r_bsp.c, r_segs.c, r_plane.c and r_things.c).
+ * The level is drawn in columns into a buffer of indices in the palette, it needs no window so it also works headless.
+ * The buffer can have any size, it is the view of a screen of 320x200 and draw_to_screen stretches it over the window.
+ */
+pub struct SoftwareRenderer {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>, //the indices in the palette, row after row
+    palette: Vec<[u8; 3]>,
+    colormaps: Vec<Vec<u8>>,
+
+    pub(super) view: ViewPoint,
+    view_cos: f64,
+    view_sin: f64,
+    pub(super) center_x: f64,
+    pub(super) center_y: f64,
+    pub(super) projection: f64,
+    extra_light: i32,
+
+    pub(super) solid_segs: Vec<(i32, i32)>, //the columns closed by a solid wall, sorted and apart
+    pub(super) ceiling_clip: Vec<i32>,
+    pub(super) floor_clip: Vec<i32>,
+    pub(super) draw_segs: Vec<DrawSeg>,
+    pub(super) vis_sprites: Vec<VisSprite>,
+    pub(super) sectors_done: Vec<bool>, //the sectors that have their sprites added
+    pub(super) fuzz_pos: usize,
+    visplanes: Vec<VisPlane>,
+    texture: Option<Texture2D>
+}
+
+impl SoftwareRenderer {
+    pub fn new(wad: &WADData, width: usize, height: usize) -> SoftwareRenderer {
+        let palette = wad.palletes[0].iter().map(|color| [color.r, color.g, color.b]).collect();
+        SoftwareRenderer {
+            width, height, pixels: vec![0; width * height], palette, colormaps: wad.color_maps.clone(),
+            view: ViewPoint { x: 0., y: 0., z: 0., angle: 0., actor: -1 }, view_cos: 1., view_sin: 0.,
+            center_x: width as f64 / 2., center_y: height as f64 / 2., projection: width as f64 / 2., extra_light: 0,
+            solid_segs: vec![], ceiling_clip: vec![-1; width], floor_clip: vec![height as i32; width], draw_segs: vec![],
+            vis_sprites: vec![], sectors_done: vec![], fuzz_pos: 0, visplanes: vec![], texture: None
+        }
+    }
+
+    /*
+     * R_RenderPlayerView, the walls are drawn front to back while the bsp is walked and they mark the floors and ceilings.
+     * Then the visplanes are drawn and last the sprites and the masked walls from back to front.
+     */
+    pub fn render(&mut self, level: &LevelLocals, tex_man: &TextureManager, wad: &WADData, frames: &SpriteFrames, view: ViewPoint, frac: f64) {
+        Self::setup_frame(self, level, view);
+        let frame = Frame { level, tex_man, wad, frames, frac };
+        if let Some(root) = level.root_child() {Self::render_bsp_node(self, &frame, root)}
+        Self::draw_planes(self, &frame);
+        Self::draw_masked(self, &frame);
+    }
+
+    //R_SetupFrame and R_ClearClipSegs, R_ClearPlanes and R_ClearSprites
+    fn setup_frame(&mut self, level: &LevelLocals, view: ViewPoint) {
+        self.view = view;
+        (self.view_sin, self.view_cos) = view.angle.sin_cos();
+        let player = &level.players[0];
+        self.extra_light = if view.actor >= 0 && view.actor == player.mo {player.extra_light} else {0};
+
+        self.pixels.fill(0);
+        self.solid_segs.clear();
+        self.ceiling_clip.fill(-1);
+        self.floor_clip.fill(self.height as i32);
+        self.draw_segs.clear();
+        self.vis_sprites.clear();
+        self.visplanes.clear();
+        self.sectors_done = vec![false; level.sectors.len()];
+    }
+
+    //the position in the view, x goes to the right and z is the distance in front of it
+    pub(super) fn to_view(&self, x: f64, y: f64) -> (f64, f64) {
+        let (dx, dy) = (x - self.view.x, y - self.view.y);
+        (dx * self.view_sin - dy * self.view_cos, dx * self.view_cos + dy * self.view_sin)
+    }
+
+    pub(super) fn screen_x(&self, point: (f64, f64)) -> f64 {
+        self.center_x + point.0 * self.projection / point.1
+    }
+
+    fn start_map(&self, light_level: i32) -> i32 {
+        let light_num = ((light_level >> 4) + self.extra_light).clamp(0, LIGHT_LEVELS - 1);
+        (LIGHT_LEVELS - 1 - light_num) * 2 * NUM_COLORMAPS / LIGHT_LEVELS
+    }
+
+    //scalelight, the colormap of a wall or a sprite gets brighter the bigger it is on the screen
+    pub(super) fn scale_light(&self, light_level: i32, scale: f64) -> usize {
+        let index = ((scale * BASE_WIDTH / self.width as f64 * 16.) as i32).min(MAX_LIGHT_SCALE - 1);
+        (Self::start_map(self, light_level) - index / 2).clamp(0, NUM_COLORMAPS - 1) as usize
+    }
+
+    //zlight, the colormap of a flat gets darker with the distance
+    fn z_light(&self, light_level: i32, dist: f64) -> usize {
+        let index = ((dist / 16.) as i32).clamp(0, MAX_LIGHT_Z - 1);
+        let scale = (BASE_WIDTH as i32 / 2) / (index + 1);
+        (Self::start_map(self, light_level) - scale / 2).clamp(0, NUM_COLORMAPS - 1) as usize
+    }
+
+    pub(super) fn put(&mut self, x: i32, y: i32, colormap: usize, index: u8) {
+        self.pixels[y as usize * self.width + x as usize] = self.colormaps[colormap][index as usize];
+    }
+
+    //the pixel is the one the colormap makes of the pixel at from, for the fuzz of the spectres
+    pub(super) fn remap(&mut self, x: i32, y: i32, from: i32, colormap: usize) {
+        let index = self.pixels[from as usize * self.width + x as usize];
+        self.put(x, y, colormap, index);
+    }
+
+    /*
+     * R_DrawColumn, texture_mid is the height in the texture at the center of the view.
+     * The walls repeat the texture up and down, a masked column only has the texture once and leaves its holes.
+     */
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn draw_column(&mut self, texture: &GameTexture, x: i32, column: f64, texture_mid: f64, y1: i32, y2: i32, scale: f64, colormap: usize, masked: bool) {
+        let (width, height) = (texture.width as i64, texture.height as i64);
+        if width == 0 || height == 0 {return}
+        let column = (column.floor() as i64).rem_euclid(width);
+        for y in y1.max(0)..=y2.min(self.height as i32 - 1) {
+            let row = (texture_mid + (y as f64 + 0.5 - self.center_y) / scale).floor() as i64;
+            if masked && (row < 0 || row >= height) {continue}
+            let index = (row.rem_euclid(height) * width + column) as usize;
+            if masked && texture.pixels[index * 4 + 3] == 0 {continue}
+            Self::put(self, x, y, colormap, texture.indices[index]);
+        }
+    }
+
+    //R_FindPlane, a sky has no height or light so all of it is one plane
+    pub(super) fn find_plane(&mut self, height: f64, texture: TextureID, light_level: i32, sky: bool) -> usize {
+        let (height, light_level) = if sky {(0., 0)} else {(height, light_level)};
+        let found = self.visplanes.iter().position(|plane| plane.sky == sky && plane.height == height && plane.texture == texture && plane.light_level == light_level);
+        found.unwrap_or_else(|| {
+            self.visplanes.push(VisPlane { height, texture, light_level, sky, min_x: self.width as i32, max_x: -1, top: vec![UNSET; self.width], bottom: vec![-1; self.width] });
+            self.visplanes.len() - 1
+        })
+    }
+
+    //R_CheckPlane, the plane gets the columns from start to stop or a new plane is made when some of them are taken
+    pub(super) fn check_plane(&mut self, index: usize, start: i32, stop: i32) -> usize {
+        let plane = &mut self.visplanes[index];
+        let (inter_low, union_low) = if start < plane.min_x {(plane.min_x, start)} else {(start, plane.min_x)};
+        let (inter_high, union_high) = if stop > plane.max_x {(plane.max_x, stop)} else {(stop, plane.max_x)};
+        if (inter_low..=inter_high).all(|x| plane.top[x as usize] == UNSET) {
+            plane.min_x = union_low;
+            plane.max_x = union_high;
+            return index
+        }
+        let new = VisPlane { height: plane.height, texture: plane.texture, light_level: plane.light_level, sky: plane.sky, min_x: start, max_x: stop, top: vec![UNSET; self.width], bottom: vec![-1; self.width] };
+        self.visplanes.push(new);
+        self.visplanes.len() - 1
+    }
+
+    pub(super) fn set_plane_column(&mut self, index: usize, x: i32, top: i32, bottom: i32) {
+        let plane = &mut self.visplanes[index];
+        plane.top[x as usize] = top;
+        plane.bottom[x as usize] = bottom;
+    }
+
+    //R_DrawPlanes
+    fn draw_planes(&mut self, frame: &Frame) {
+        let planes = std::mem::take(&mut self.visplanes);
+        for plane in planes.iter().filter(|plane| plane.min_x <= plane.max_x) {
+            if plane.sky {
+                Self::draw_sky(self, frame, plane);
+                continue
+            }
+            if let Some(texture) = frame.tex_man.get_game_texture(plane.texture, true).filter(|texture| texture.is_valid() && texture.width > 0 && texture.height > 0) {
+                Self::make_spans(self, plane, texture);
+            }
+        }
+        self.visplanes = planes;
+    }
+
+    //R_MakeSpans, the plane is drawn in rows, a row of a flat has the same distance and light
+    fn make_spans(&mut self, plane: &VisPlane, texture: &GameTexture) {
+        let columns = plane.min_x as usize..=plane.max_x as usize;
+        let top = columns.clone().map(|x| plane.top[x]).min().unwrap_or(UNSET).max(0);
+        let bottom = columns.map(|x| plane.bottom[x]).max().unwrap_or(-1).min(self.height as i32 - 1);
+        for y in top..=bottom {
+            let mut start = None;
+            for x in plane.min_x..=plane.max_x + 1 {
+                let inside = x <= plane.max_x && plane.top[x as usize] <= y && y <= plane.bottom[x as usize];
+                match (inside, start) {
+                    (true, None) => start = Some(x),
+                    (false, Some(x1)) => {
+                        Self::map_plane(self, plane, texture, y, x1, x - 1);
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    //R_MapPlane, the flats are aligned to the world with u along x and v against y
+    fn map_plane(&mut self, plane: &VisPlane, texture: &GameTexture, y: i32, x1: i32, x2: i32) {
+        let dy = (y as f64 + 0.5 - self.center_y).abs();
+        if dy < 0.01 {return}
+        let dist = (plane.height - self.view.z).abs() * self.projection / dy;
+        let colormap = Self::z_light(self, plane.light_level, dist);
+
+        let step = dist / self.projection;
+        let lateral = (x1 as f64 + 0.5 - self.center_x) * step;
+        let mut world_x = self.view.x + self.view_cos * dist + self.view_sin * lateral;
+        let mut world_y = self.view.y + self.view_sin * dist - self.view_cos * lateral;
+        let (step_x, step_y) = (self.view_sin * step, -self.view_cos * step);
+        let (width, height) = (texture.width as i64, texture.height as i64);
+        for x in x1..=x2 {
+            let u = (world_x.floor() as i64).rem_euclid(width);
+            let v = ((-world_y).floor() as i64).rem_euclid(height);
+            Self::put(self, x, y, colormap, texture.indices[(v * width + u) as usize]);
+            world_x += step_x;
+            world_y += step_y;
+        }
+    }
+
+    /*
+     * The sky goes around the view with 1024 columns for the full circle, the rows are as on the screen of 320x200.
+     * It has no light and the second sky of a double sky isn't drawn, like in doom.
+     */
+    fn draw_sky(&mut self, frame: &Frame, plane: &VisPlane) {
+        let level = frame.level;
+        let texture = match frame.tex_man.get_game_texture(level.sky_texture1, true).filter(|texture| texture.is_valid() && texture.width > 0 && texture.height > 0) {
+            Some(texture) => texture,
+            None => return
+        };
+        let row_scale = BASE_WIDTH / self.width as f64;
+        for x in plane.min_x..=plane.max_x {
+            let (top, bottom) = (plane.top[x as usize], plane.bottom[x as usize]);
+            if top > bottom {continue}
+            let angle = self.view.angle + ((self.center_x - (x as f64 + 0.5)) / self.projection).atan();
+            let column = angle / std::f64::consts::TAU * 1024. + level.sky_pos1;
+            Self::draw_column(self, texture, x, column, BASE_HEIGHT / 2., top, bottom, 1. / row_scale, 0, false);
+        }
+    }
+
+    //the pixels in the colors of the palette, four bytes each
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|index| {
+            let [r, g, b] = self.palette[*index as usize];
+            [r, g, b, 255]
+        }).collect()
+    }
+
+    //the frame as a png file, it doesn't need a window
+    pub fn save_png(&self, path: &str) -> std::io::Result<()> {
+        write_png(path, self.width, self.height, &Self::to_rgba(self))
+    }
+
+    //the buffer stretched over the window, the texture is made once and updated after that
+    pub fn draw_to_screen(&mut self) {
+        let image = Image { bytes: Self::to_rgba(self), width: self.width as u16, height: self.height as u16 };
+        let texture = match self.texture {
+            Some(texture) => {
+                texture.update(&image);
+                texture
+            }
+            None => {
+                let texture = Texture2D::from_image(&image);
+                texture.set_filter(FilterMode::Nearest);
+                self.texture = Some(texture);
+                texture
+            }
+        };
+        draw_texture_ex(texture, 0., 0., WHITE, DrawTextureParams {
+            dest_size: Some(vec2(screen_width(), screen_height())),
+            ..Default::default()
+        });
+    }
+}
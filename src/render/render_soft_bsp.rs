@@ -0,0 +1,442 @@
+use crate::level::{ChildNode, LineFlags, Sector, SectorE, SectorIndex, Sides, BOXBOTTOM, BOXLEFT, BOXRIGHT, BOXTOP};
+use crate::level::level_texture::{GameTexture, TextureID};
+
+use super::render_soft::{Frame, SoftwareRenderer};
+
+//the walls are clipped this close in front of the view
+const NEAR_Z: f64 = 1.;
+
+//the sides of a drawseg that clip the sprites
+pub(super) const SIL_BOTTOM: u8 = 1;
+pub(super) const SIL_TOP: u8 = 2;
+
+//the middle texture of a two sided line, drawn with the sprites, a column is None after it is drawn
+pub(super) struct MaskedSeg {
+    pub texture: TextureID,
+    pub texture_mid: f64,
+    pub light_level: i32,
+    pub columns: Vec<Option<f64>>
+}
+
+//drawseg_t, the columns a wall has on the screen and how it clips the sprites behind it
+pub(super) struct DrawSeg {
+    pub x1: i32,
+    pub x2: i32,
+    pub scale1: f64,
+    pub scale_step: f64,
+    pub v1: (f64, f64),
+    pub v2: (f64, f64),
+    pub silhouette: u8,
+    pub bottom_height: f64, //the sprites under it are clipped by the bottom
+    pub top_height: f64, //the sprites above it are clipped by the top
+    pub sprite_top_clip: Vec<i32>,
+    pub sprite_bottom_clip: Vec<i32>,
+    pub masked: Option<MaskedSeg>
+}
+
+impl DrawSeg {
+    pub fn scale(&self, x: i32) -> f64 {
+        self.scale1 + self.scale_step * (x - self.x1) as f64
+    }
+
+    //R_PointOnSegSide, the front is on the right of the seg
+    pub fn point_in_front(&self, x: f64, y: f64) -> bool {
+        (y - self.v1.1) * (self.v2.0 - self.v1.0) + (self.v1.0 - x) * (self.v2.1 - self.v1.1) <= 0.
+    }
+}
+
+//the part of a seg in the view, u is the distance along the side over z so it goes straight across the screen
+struct WallSeg {
+    line: usize,
+    side: usize,
+    front: usize,
+    back: SectorIndex,
+    v1: (f64, f64),
+    v2: (f64, f64),
+    sx1: f64,
+    sx2: f64,
+    inv_z1: f64,
+    inv_z2: f64,
+    u1: f64,
+    u2: f64
+}
+
+//the floor and ceiling of the subsector that is drawn, the walls add their columns
+struct Planes {
+    floor: Option<usize>,
+    ceiling: Option<usize>
+}
+
+fn plane_heights(sector: &Sector) -> (f64, f64) {
+    (sector.floorplane.z_at(&sector.center_spot), sector.ceilingplane.z_at(&sector.center_spot))
+}
+
+fn texture_height(texture: Option<&GameTexture>) -> f64 {
+    texture.map_or(0., |texture| texture.height as f64)
+}
+
+impl SoftwareRenderer {
+    //R_RenderBSPNode, the side of the node the view is on comes first and the other side only when its box can be seen
+    pub(super) fn render_bsp_node(&mut self, frame: &Frame, child: ChildNode) {
+        if Self::is_full(self) {return}
+        if child.node < 0 {
+            if child.subsector >= 0 {Self::render_subsector(self, frame, child.subsector as usize)}
+            return
+        }
+        let level = frame.level;
+        let side = level.point_on_node_side(self.view.x, self.view.y, child.node);
+        let children = level.nodes[child.node as usize].borrow().children;
+        Self::render_bsp_node(self, frame, children[side]);
+        if Self::check_bbox(self, &level.node_bbox(child.node, side ^ 1)) {
+            Self::render_bsp_node(self, frame, children[side ^ 1]);
+        }
+    }
+
+    /*
+     * The part of the line from a to b in front of the view and inside its 90 degrees, as fractions of the line.
+     * The points are in the view, x is to the right and z in front of it.
+     */
+    fn clip_to_view(a: (f64, f64), b: (f64, f64)) -> Option<(f64, f64)> {
+        let (mut t1, mut t2) = (0f64, 1f64);
+        for (x, z, d) in [(0., 1., -NEAR_Z), (-1., 1., 0.), (1., 1., 0.)] {
+            let from = a.0 * x + a.1 * z + d;
+            let to = b.0 * x + b.1 * z + d;
+            if from < 0. && to < 0. {return None}
+            if from < 0. {t1 = t1.max(from / (from - to))}
+            else if to < 0. {t2 = t2.min(from / (from - to))}
+        }
+        if t1 < t2 {Some((t1, t2))} else {None}
+    }
+
+    fn lerp(a: (f64, f64), b: (f64, f64), t: f64) -> (f64, f64) {
+        (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+    }
+
+    //R_CheckBBox, the box can be seen when one of the columns it covers isn't closed by a solid wall
+    fn check_bbox(&self, bbox: &[f64;4]) -> bool {
+        let inside = |x: f64, y: f64| (bbox[BOXLEFT]..=bbox[BOXRIGHT]).contains(&x) && (bbox[BOXBOTTOM]..=bbox[BOXTOP]).contains(&y);
+        if inside(self.view.x, self.view.y) {return true}
+
+        let corners = [(bbox[BOXLEFT], bbox[BOXTOP]), (bbox[BOXRIGHT], bbox[BOXTOP]), (bbox[BOXRIGHT], bbox[BOXBOTTOM]), (bbox[BOXLEFT], bbox[BOXBOTTOM])];
+        let mut range = (f64::MAX, f64::MIN);
+        for i in 0..4 {
+            let a = Self::to_view(self, corners[i].0, corners[i].1);
+            let b = Self::to_view(self, corners[(i + 1) % 4].0, corners[(i + 1) % 4].1);
+            if let Some((t1, t2)) = Self::clip_to_view(a, b) {
+                for t in [t1, t2] {
+                    let x = Self::screen_x(self, Self::lerp(a, b, t));
+                    range = (range.0.min(x), range.1.max(x));
+                }
+            }
+        }
+        //the corners of the view are in the box when it goes past the sides of the screen
+        let (sin, cos) = self.view.angle.sin_cos();
+        for (side, x) in [(-1., 0.), (1., self.width as f64)] {
+            let (lateral, forward) = (side * NEAR_Z, NEAR_Z);
+            if inside(self.view.x + cos * forward + sin * lateral, self.view.y + sin * forward - cos * lateral) {
+                range = (range.0.min(x), range.1.max(x));
+            }
+        }
+        if range.0 > range.1 {return false}
+        let x1 = (range.0.floor() as i32).max(0);
+        let x2 = (range.1.floor() as i32).min(self.width as i32 - 1);
+        x1 <= x2 && !Self::visible_ranges(self, x1, x2).is_empty()
+    }
+
+    //the columns from first to last that aren't closed by a solid wall yet
+    fn visible_ranges(&self, first: i32, last: i32) -> Vec<(i32, i32)> {
+        let mut ranges = vec![];
+        let mut start = first;
+        for &(low, high) in &self.solid_segs {
+            if high < start {continue}
+            if low > last {break}
+            if low > start {ranges.push((start, low - 1))}
+            start = start.max(high + 1);
+            if start > last {break}
+        }
+        if start <= last {ranges.push((start, last))}
+        ranges
+    }
+
+    //R_ClipSolidWallSegment, the columns are closed and the ranges next to them are joined
+    fn add_solid(&mut self, first: i32, last: i32) {
+        let (mut low, mut high) = (first, last);
+        self.solid_segs.retain(|&(a, b)| {
+            let touches = b + 1 >= low && a <= high + 1;
+            if touches {
+                low = low.min(a);
+                high = high.max(b);
+            }
+            !touches
+        });
+        let at = self.solid_segs.partition_point(|&(a, _)| a < low);
+        self.solid_segs.insert(at, (low, high));
+    }
+
+    fn is_full(&self) -> bool {
+        self.solid_segs.len() == 1 && self.solid_segs[0].0 <= 0 && self.solid_segs[0].1 >= self.width as i32 - 1
+    }
+
+    //R_Subsector, the floor and ceiling are found before the segs are drawn, the sprites of the sector are added once
+    fn render_subsector(&mut self, frame: &Frame, index: usize) {
+        let level = frame.level;
+        let subsector = level.subsectors[index].borrow();
+        let sector_index = subsector.sector as usize;
+        let mut planes = {
+            let sector = level.sectors[sector_index].borrow();
+            let (floor, ceiling) = plane_heights(&sector);
+            let light_level = sector.light_level as i32;
+            let floor_texture = sector.get_texture(SectorE::Floor as usize);
+            let ceiling_texture = sector.get_texture(SectorE::Ceiling as usize);
+            let floor_sky = floor_texture == level.sky_flat_num;
+            let ceiling_sky = ceiling_texture == level.sky_flat_num;
+            Planes {
+                floor: (floor < self.view.z).then(|| Self::find_plane(self, floor, floor_texture, light_level, floor_sky)),
+                ceiling: (ceiling > self.view.z || ceiling_sky).then(|| Self::find_plane(self, ceiling, ceiling_texture, light_level, ceiling_sky))
+            }
+        };
+        if !self.sectors_done[sector_index] {
+            self.sectors_done[sector_index] = true;
+            Self::add_sprites(self, frame, sector_index);
+        }
+        let first = subsector.first_line as usize;
+        for seg in first..first + subsector.line_count as usize {
+            Self::add_line(self, frame, seg, sector_index, &mut planes);
+        }
+    }
+
+    /*
+     * R_AddLine, the seg is clipped to the view and only drawn when its front is seen.
+     * A wall that closes the view goes in the solid segs, a window or a change of the flats only draws.
+     */
+    fn add_line(&mut self, frame: &Frame, seg_index: usize, front_index: usize, planes: &mut Planes) {
+        let level = frame.level;
+        let seg = level.segs[seg_index].borrow();
+        //the minisegs of the gl nodes have no wall
+        if seg.linedef < 0 || seg.sidedef < 0 {return}
+        let v1 = level.vertexes[seg.v1 as usize].borrow().f_pos();
+        let v2 = level.vertexes[seg.v2 as usize].borrow().f_pos();
+        let (a, b) = (Self::to_view(self, v1.x, v1.y), Self::to_view(self, v2.x, v2.y));
+        let (t1, t2) = match Self::clip_to_view(a, b) {
+            Some(clipped) => clipped,
+            None => return
+        };
+        let (p1, p2) = (Self::lerp(a, b, t1), Self::lerp(a, b, t2));
+        let (sx1, sx2) = (Self::screen_x(self, p1), Self::screen_x(self, p2));
+        //the back of the seg is seen
+        if sx1 >= sx2 {return}
+        let x1 = ((sx1 - 0.5).ceil() as i32).max(0);
+        let x2 = ((sx2 - 0.5).ceil() as i32 - 1).min(self.width as i32 - 1);
+        if x1 > x2 {return}
+
+        let line = level.lines[seg.linedef as usize].borrow();
+        let start = if line.sidedef[1] == seg.sidedef {line.v2.f_pos()} else {line.v1.f_pos()};
+        let offset = ((v1.x - start.x).powi(2) + (v1.y - start.y).powi(2)).sqrt();
+        let length = ((v2.x - v1.x).powi(2) + (v2.y - v1.y).powi(2)).sqrt();
+        let wall = WallSeg {
+            line: seg.linedef as usize, side: seg.sidedef as usize, front: front_index, back: seg.back_sector,
+            v1: (v1.x, v1.y), v2: (v2.x, v2.y), sx1, sx2, inv_z1: 1. / p1.1, inv_z2: 1. / p2.1,
+            u1: (offset + length * t1) / p1.1, u2: (offset + length * t2) / p2.1
+        };
+
+        let solid = if wall.back < 0 {true}
+            else {
+                let front = level.sectors[front_index].borrow();
+                let back = level.sectors[wall.back as usize].borrow();
+                let (front_floor, front_ceiling) = plane_heights(&front);
+                let (back_floor, back_ceiling) = plane_heights(&back);
+                //a closed door
+                if back_ceiling <= front_floor || back_floor >= front_ceiling {true}
+                //the same sector on both sides with nothing in the middle isn't seen
+                else if back_ceiling == front_ceiling && back_floor == front_floor
+                    && back.get_texture(SectorE::Floor as usize) == front.get_texture(SectorE::Floor as usize)
+                    && back.get_texture(SectorE::Ceiling as usize) == front.get_texture(SectorE::Ceiling as usize)
+                    && back.light_level == front.light_level
+                    && !level.sides[wall.side].borrow().get_texture(Sides::Mid.bits() as usize).is_valid() {return}
+                else {false}
+            };
+        for (start, stop) in Self::visible_ranges(self, x1, x2) {
+            Self::store_wall_range(self, frame, &wall, start, stop, planes);
+        }
+        if solid {Self::add_solid(self, x1, x2)}
+    }
+
+    /*
+     * R_StoreWallRange and R_RenderSegLoop, the columns from start to stop of the wall.
+     * The textures are pegged like in doom and the floors and ceilings are marked where the wall leaves them open.
+     * The drawseg keeps the clipping for the sprites and the columns of the masked middle.
+     */
+    fn store_wall_range(&mut self, frame: &Frame, wall: &WallSeg, start: i32, stop: i32, planes: &mut Planes) {
+        let level = frame.level;
+        let line = level.lines[wall.line].borrow();
+        let side = level.sides[wall.side].borrow();
+        let front = level.sectors[wall.front].borrow();
+        let (front_floor, front_ceiling) = plane_heights(&front);
+        let front_ceiling_sky = front.get_texture(SectorE::Ceiling as usize) == level.sky_flat_num;
+        let view_z = self.view.z;
+        let (top, mid, bottom) = (Sides::Top.bits() as usize, Sides::Mid.bits() as usize, Sides::Bottom.bits() as usize);
+        let texture = |part: usize| Some(side.get_texture(part)).filter(|id| id.is_valid())
+            .and_then(|id| frame.tex_man.get_game_texture(id, true)).filter(|texture| texture.is_valid());
+        let peg_top = line.flags & LineFlags::DontPegTop.bits() != 0;
+        let peg_bottom = line.flags & LineFlags::DontPegBottom.bits() != 0;
+
+        let count = (stop - start + 1) as usize;
+        let screen_x = |x: i32| ((x as f64 + 0.5 - wall.sx1) / (wall.sx2 - wall.sx1)).clamp(0., 1.);
+        let inv_z = |x: i32| wall.inv_z1 + (wall.inv_z2 - wall.inv_z1) * screen_x(x);
+        let scale1 = self.projection * inv_z(start);
+        let scale_step = if stop > start {(self.projection * inv_z(stop) - scale1) / (stop - start) as f64} else {0.};
+        let mut ds = DrawSeg {
+            x1: start, x2: stop, scale1, scale_step, v1: wall.v1, v2: wall.v2, silhouette: 0,
+            bottom_height: f64::MIN, top_height: f64::MAX, sprite_top_clip: vec![], sprite_bottom_clip: vec![], masked: None
+        };
+
+        let mut world_top = front_ceiling - view_z;
+        let world_bottom = front_floor - view_z;
+        let back_heights = (wall.back >= 0).then(|| plane_heights(&level.sectors[wall.back as usize].borrow()));
+        let (world_high, world_low) = back_heights.map_or((world_top, world_bottom), |(floor, ceiling)| (ceiling - view_z, floor - view_z));
+        //the fake contrast makes the walls along x darker and along y brighter
+        let light_level = front.light_level as i32 + if wall.v1.1 == wall.v2.1 {-16} else if wall.v1.0 == wall.v2.0 {16} else {0};
+        let (mut mid_texture, mut top_texture, mut bottom_texture) = (None, None, None);
+        let (mut mid_texture_mid, mut top_texture_mid, mut bottom_texture_mid) = (0., 0., 0.);
+        let (mut mark_floor, mut mark_ceiling) = (true, true);
+        if wall.back < 0 {
+            mid_texture = texture(mid);
+            mid_texture_mid = if peg_bottom {front_floor + texture_height(mid_texture) - view_z} else {world_top};
+            mid_texture_mid += side.get_texture_y_offset(mid);
+            //a solid wall hides all the sprites behind it
+            ds.silhouette = SIL_TOP | SIL_BOTTOM;
+            ds.sprite_top_clip = vec![self.height as i32; count];
+            ds.sprite_bottom_clip = vec![-1; count];
+            ds.bottom_height = f64::MAX;
+            ds.top_height = f64::MIN;
+        }
+        else {
+            let back = level.sectors[wall.back as usize].borrow();
+            let (back_floor, back_ceiling) = plane_heights(&back);
+            if front_floor > back_floor {
+                ds.silhouette |= SIL_BOTTOM;
+                ds.bottom_height = front_floor;
+            }
+            else if back_floor > view_z {
+                ds.silhouette |= SIL_BOTTOM;
+                ds.bottom_height = f64::MAX;
+            }
+            if front_ceiling < back_ceiling {
+                ds.silhouette |= SIL_TOP;
+                ds.top_height = front_ceiling;
+            }
+            else if back_ceiling < view_z {
+                ds.silhouette |= SIL_TOP;
+                ds.top_height = f64::MIN;
+            }
+            if back_ceiling <= front_floor {
+                ds.silhouette |= SIL_BOTTOM;
+                ds.bottom_height = f64::MAX;
+            }
+            if back_floor >= front_ceiling {
+                ds.silhouette |= SIL_TOP;
+                ds.top_height = f64::MIN;
+            }
+
+            //the sky hack, a change of height between two skies isn't a wall
+            if front_ceiling_sky && back.get_texture(SectorE::Ceiling as usize) == level.sky_flat_num {world_top = world_high}
+
+            mark_floor = world_low != world_bottom || back.get_texture(SectorE::Floor as usize) != front.get_texture(SectorE::Floor as usize)
+                || back.light_level != front.light_level;
+            mark_ceiling = world_high != world_top || back.get_texture(SectorE::Ceiling as usize) != front.get_texture(SectorE::Ceiling as usize)
+                || back.light_level != front.light_level;
+            //a closed door
+            if back_ceiling <= front_floor || back_floor >= front_ceiling {
+                mark_floor = true;
+                mark_ceiling = true;
+            }
+
+            if world_high < world_top {
+                top_texture = texture(top);
+                top_texture_mid = if peg_top {world_top} else {back_ceiling + texture_height(top_texture) - view_z};
+                top_texture_mid += side.get_texture_y_offset(top);
+            }
+            if world_low > world_bottom {
+                bottom_texture = texture(bottom);
+                bottom_texture_mid = if peg_bottom {world_top} else {world_low};
+                bottom_texture_mid += side.get_texture_y_offset(bottom);
+            }
+            if let Some(masked) = texture(mid) {
+                //the middle hangs from the lower ceiling or stands on the higher floor
+                let texture_mid = if peg_bottom {front_floor.max(back_floor) + masked.height as f64 - view_z}
+                    else {front_ceiling.min(back_ceiling) - view_z};
+                ds.masked = Some(MaskedSeg { texture: side.get_texture(mid), texture_mid: texture_mid + side.get_texture_y_offset(mid), light_level, columns: vec![None; count] });
+            }
+        }
+
+        //the floor under the view and the ceiling above it can't be seen, a sky can
+        if front_floor >= view_z {mark_floor = false}
+        if front_ceiling <= view_z && !front_ceiling_sky {mark_ceiling = false}
+        if let (true, Some(plane)) = (mark_ceiling, planes.ceiling) {planes.ceiling = Some(Self::check_plane(self, plane, start, stop))}
+        else {mark_ceiling = false}
+        if let (true, Some(plane)) = (mark_floor, planes.floor) {planes.floor = Some(Self::check_plane(self, plane, start, stop))}
+        else {mark_floor = false}
+
+
+        for x in start..=stop {
+            let inv_z = inv_z(x);
+            let scale = self.projection * inv_z;
+            let u = (wall.u1 + (wall.u2 - wall.u1) * screen_x(x)) / inv_z;
+            let colormap = Self::scale_light(self, light_level, scale);
+            let i = x as usize;
+
+            let y_top = ((self.center_y - world_top * scale - 0.5).ceil() as i32).max(self.ceiling_clip[i] + 1);
+            if mark_ceiling {
+                let (top, bottom) = (self.ceiling_clip[i] + 1, (y_top - 1).min(self.floor_clip[i] - 1));
+                if top <= bottom {Self::set_plane_column(self, planes.ceiling.unwrap(), x, top, bottom)}
+            }
+            let y_bottom = ((self.center_y - world_bottom * scale - 0.5).ceil() as i32 - 1).min(self.floor_clip[i] - 1);
+            if mark_floor {
+                let (top, bottom) = ((y_bottom + 1).max(self.ceiling_clip[i] + 1), self.floor_clip[i] - 1);
+                if top <= bottom {Self::set_plane_column(self, planes.floor.unwrap(), x, top, bottom)}
+            }
+
+            if wall.back < 0 {
+                if let Some(texture) = mid_texture {
+                    Self::draw_column(self, texture, x, u + side.get_texture_x_offset(mid), mid_texture_mid, y_top, y_bottom, scale, colormap, false);
+                }
+                self.ceiling_clip[i] = self.height as i32;
+                self.floor_clip[i] = -1;
+                continue
+            }
+
+            if let Some(texture) = top_texture {
+                let y_mid = ((self.center_y - world_high * scale - 0.5).ceil() as i32 - 1).min(self.floor_clip[i] - 1);
+                if y_mid >= y_top {
+                    Self::draw_column(self, texture, x, u + side.get_texture_x_offset(top), top_texture_mid, y_top, y_mid, scale, colormap, false);
+                    self.ceiling_clip[i] = y_mid;
+                }
+                else {self.ceiling_clip[i] = y_top - 1}
+            }
+            else if mark_ceiling {self.ceiling_clip[i] = y_top - 1}
+
+            if let Some(texture) = bottom_texture {
+                let y_mid = ((self.center_y - world_low * scale - 0.5).ceil() as i32).max(self.ceiling_clip[i] + 1);
+                if y_mid <= y_bottom {
+                    Self::draw_column(self, texture, x, u + side.get_texture_x_offset(bottom), bottom_texture_mid, y_mid, y_bottom, scale, colormap, false);
+                    self.floor_clip[i] = y_mid;
+                }
+                else {self.floor_clip[i] = y_bottom + 1}
+            }
+            else if mark_floor {self.floor_clip[i] = y_bottom + 1}
+
+            if let Some(masked) = ds.masked.as_mut() {masked.columns[(x - start) as usize] = Some(u + side.get_texture_x_offset(mid))}
+        }
+
+        //the clipping is kept for the sprites, the masked middle is clipped by it on both sides
+        if ds.masked.is_some() {
+            if ds.silhouette & SIL_TOP == 0 {ds.top_height = f64::MIN}
+            if ds.silhouette & SIL_BOTTOM == 0 {ds.bottom_height = f64::MAX}
+            ds.silhouette |= SIL_TOP | SIL_BOTTOM;
+        }
+        let columns = start as usize..=stop as usize;
+        if ds.silhouette & SIL_TOP != 0 && ds.sprite_top_clip.is_empty() {ds.sprite_top_clip = self.ceiling_clip[columns.clone()].to_vec()}
+        if ds.silhouette & SIL_BOTTOM != 0 && ds.sprite_bottom_clip.is_empty() {ds.sprite_bottom_clip = self.floor_clip[columns].to_vec()}
+        self.draw_segs.push(ds);
+    }
+}
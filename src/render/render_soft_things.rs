@@ -0,0 +1,250 @@
+use crate::parser::WADData;
+use crate::parser::parse_graphics::{WADSprite, WADSpritePost};
+use crate::level::{Actor, LevelLocals, MobjFlags, FF_FRAMEMASK, FF_FULLBRIGHT, NUMPSPRITES, S_NULL, STATES};
+
+use super::SpriteFrames;
+use super::render_soft::{Frame, SoftwareRenderer, BASE_HEIGHT, BASE_WIDTH};
+use super::render_soft_bsp::{DrawSeg, SIL_BOTTOM, SIL_TOP};
+
+//the sprites closer than this aren't drawn
+const MIN_Z: f64 = 4.;
+//the colormap the fuzz of the spectres darkens with
+const FUZZ_COLORMAP: usize = 6;
+//the fuzz takes the pixel above or below
+const FUZZ_OFFSETS: [i32; 50] = [
+    1, -1, 1, -1, 1, 1, -1, 1, 1, -1, 1, 1, 1, -1, 1, 1, 1, -1, -1, -1, -1, 1, -1, -1, 1,
+    1, 1, 1, -1, 1, -1, 1, 1, -1, -1, 1, 1, -1, -1, -1, -1, 1, 1, 1, 1, -1, 1, 1, -1, 1
+];
+
+//vissprite_t, a sprite on the screen, the colormap is None for the fuzz
+pub(super) struct VisSprite {
+    x1: i32,
+    x2: i32,
+    gx: f64,
+    gy: f64,
+    gz: f64,
+    gzt: f64,
+    scale: f64,
+    texture_mid: f64,
+    start_frac: f64,
+    x_iscale: f64,
+    lump: usize,
+    colormap: Option<usize>
+}
+
+//the posts of each column of the sprite
+fn sprite_columns(sprite: &WADSprite) -> Vec<Vec<&WADSpritePost>> {
+    let mut columns = vec![vec![]; sprite.width as usize];
+    for post in &sprite.posts {
+        if let Some(column) = columns.get_mut(post.col as usize) {column.push(post)}
+    }
+    columns
+}
+
+impl SoftwareRenderer {
+    //R_AddSprites, the actors of a sector are added when the first of its subsectors is drawn
+    pub(super) fn add_sprites(&mut self, frame: &Frame, sector_index: usize) {
+        let sector = frame.level.sectors[sector_index].borrow();
+        let light_level = sector.light_level as i32;
+        for index in &sector.thing_list {
+            if *index == self.view.actor {continue}
+            if let Some(actor) = frame.level.actor(*index) {Self::project_sprite(self, frame, actor, light_level)}
+        }
+    }
+
+    /*
+     * R_ProjectSprite, the rotation of the sprite is from the angle the actor is seen at.
+     * The offsets of the lump put it on the position of the actor, a mirrored lump goes through its columns backwards.
+     */
+    fn project_sprite(&mut self, frame: &Frame, actor: &Actor, light_level: i32) {
+        let x = actor.prev.x + (actor.pos.x - actor.prev.x) * frame.frac;
+        let y = actor.prev.y + (actor.pos.y - actor.prev.y) * frame.frac;
+        let z = actor.prev.z + (actor.pos.z - actor.prev.z) * frame.frac;
+        let (tx, tz) = Self::to_view(self, x, y);
+        //behind the view or too far to the side
+        if tz < MIN_Z || tx.abs() > tz * 4. {return}
+        let x_scale = self.projection / tz;
+
+        let view_angle = (y - self.view.y).atan2(x - self.view.x).to_degrees();
+        let rotation = ((view_angle - actor.angle.degrees() + 202.5).rem_euclid(360.) / 45.) as usize & 7;
+        let (lump, flip) = match frame.frames.get(actor.sprite, actor.frame & FF_FRAMEMASK, rotation) {
+            Some(found) => found,
+            None => return
+        };
+        let sprite = &frame.wad.sprites[lump].1;
+        let width = sprite.width as f64;
+        let left = tx - if flip {width - sprite.left_offset as f64} else {sprite.left_offset as f64};
+        let x1 = (self.center_x + left * x_scale).floor() as i32;
+        let x2 = (self.center_x + (left + width) * x_scale).floor() as i32 - 1;
+        if x1 >= self.width as i32 || x2 < 0 || x1 > x2 {return}
+
+        let colormap = if actor.flags.contains(MobjFlags::Shadow) {None}
+            else if actor.frame & FF_FULLBRIGHT != 0 {Some(0)}
+            else {Some(Self::scale_light(self, light_level, x_scale))};
+        let gzt = z + sprite.top_offset as f64;
+        let vis_sprite = Self::vis_sprite(self, x1, x2, x_scale, gzt - self.view.z, width, flip, lump, colormap);
+        self.vis_sprites.push(VisSprite { gx: x, gy: y, gz: z, gzt, ..vis_sprite });
+    }
+
+    //the columns of the sprite on the screen, the ones to the left of it are skipped
+    #[allow(clippy::too_many_arguments)]
+    fn vis_sprite(&self, x1: i32, x2: i32, scale: f64, texture_mid: f64, width: f64, flip: bool, lump: usize, colormap: Option<usize>) -> VisSprite {
+        let (start, x_iscale) = if flip {(width - 0.001, -1. / scale)} else {(0., 1. / scale)};
+        let clipped = x1.max(0);
+        VisSprite {
+            x1: clipped, x2: x2.min(self.width as i32 - 1), gx: 0., gy: 0., gz: 0., gzt: 0., scale, texture_mid,
+            start_frac: start + x_iscale * (clipped - x1) as f64, x_iscale, lump, colormap
+        }
+    }
+
+    //R_DrawMasked, the sprites from far to close and then the masked middles no sprite was in front of
+    pub(super) fn draw_masked(&mut self, frame: &Frame) {
+        let mut sprites = std::mem::take(&mut self.vis_sprites);
+        let mut draw_segs = std::mem::take(&mut self.draw_segs);
+        sprites.sort_by(|a, b| a.scale.total_cmp(&b.scale));
+        for sprite in &sprites {
+            Self::draw_sprite(self, frame, sprite, &mut draw_segs);
+        }
+        for ds in draw_segs.iter_mut().rev().filter(|ds| ds.masked.is_some()) {
+            let (x1, x2) = (ds.x1, ds.x2);
+            Self::render_masked_seg_range(self, frame, ds, x1, x2);
+        }
+        self.vis_sprites = sprites;
+        self.draw_segs = draw_segs;
+    }
+
+    /*
+     * R_DrawSprite, the sprite is clipped by the walls in front of it.
+     * The masked middles behind it are drawn first, the last drawseg in front of a column has the clipping of all of them.
+     */
+    fn draw_sprite(&mut self, frame: &Frame, sprite: &VisSprite, draw_segs: &mut [DrawSeg]) {
+        let count = (sprite.x2 - sprite.x1 + 1) as usize;
+        let (mut clip_top, mut clip_bottom) = (vec![-2; count], vec![-2; count]);
+        for ds in draw_segs.iter_mut().rev() {
+            if ds.x1 > sprite.x2 || ds.x2 < sprite.x1 || (ds.silhouette == 0 && ds.masked.is_none()) {continue}
+            let (x1, x2) = (ds.x1.max(sprite.x1), ds.x2.min(sprite.x2));
+            let (low_scale, scale) = if ds.scale1 > ds.scale(ds.x2) {(ds.scale(ds.x2), ds.scale1)} else {(ds.scale1, ds.scale(ds.x2))};
+            //the wall is behind the sprite
+            if scale < sprite.scale || (low_scale < sprite.scale && ds.point_in_front(sprite.gx, sprite.gy)) {
+                if ds.masked.is_some() {Self::render_masked_seg_range(self, frame, ds, x1, x2)}
+                continue
+            }
+
+            let mut silhouette = ds.silhouette;
+            if sprite.gz >= ds.bottom_height {silhouette &= !SIL_BOTTOM}
+            if sprite.gzt <= ds.top_height {silhouette &= !SIL_TOP}
+            for x in x1..=x2 {
+                let (i, j) = ((x - sprite.x1) as usize, (x - ds.x1) as usize);
+                if silhouette & SIL_BOTTOM != 0 && clip_bottom[i] == -2 {clip_bottom[i] = ds.sprite_bottom_clip[j]}
+                if silhouette & SIL_TOP != 0 && clip_top[i] == -2 {clip_top[i] = ds.sprite_top_clip[j]}
+            }
+        }
+        for clip in clip_bottom.iter_mut().filter(|clip| **clip == -2) {*clip = self.height as i32}
+        for clip in clip_top.iter_mut().filter(|clip| **clip == -2) {*clip = -1}
+        Self::draw_vis_sprite(self, frame.wad, sprite, &clip_top, &clip_bottom);
+    }
+
+    //R_DrawVisSprite, the clips are the rows above and under the sprite that it can't be drawn on
+    fn draw_vis_sprite(&mut self, wad: &WADData, sprite: &VisSprite, clip_top: &[i32], clip_bottom: &[i32]) {
+        let patch = &wad.sprites[sprite.lump].1;
+        let columns = sprite_columns(patch);
+        let mut frac = sprite.start_frac;
+        for x in sprite.x1..=sprite.x2 {
+            let column = frac.floor() as i64;
+            frac += sprite.x_iscale;
+            if column < 0 || column >= columns.len() as i64 {continue}
+            let i = (x - sprite.x1) as usize;
+            Self::draw_masked_column(self, x, &columns[column as usize], sprite.texture_mid, sprite.scale, clip_top[i], clip_bottom[i], sprite.colormap);
+        }
+    }
+
+    //R_DrawMaskedColumn, the posts of a column of a sprite between the clips
+    #[allow(clippy::too_many_arguments)]
+    fn draw_masked_column(&mut self, x: i32, posts: &[&WADSpritePost], texture_mid: f64, scale: f64, clip_top: i32, clip_bottom: i32, colormap: Option<usize>) {
+        let top_screen = self.center_y - texture_mid * scale;
+        for post in posts {
+            let top = top_screen + post.row as f64 * scale;
+            let bottom = top + post.pixels.len() as f64 * scale;
+            let y1 = ((top - 0.5).ceil() as i32).max(clip_top + 1).max(0);
+            let y2 = ((bottom - 0.5).ceil() as i32 - 1).min(clip_bottom - 1).min(self.height as i32 - 1);
+            for y in y1..=y2 {
+                match colormap {
+                    Some(colormap) => {
+                        let row = (((y as f64 + 0.5 - top) / scale) as usize).min(post.pixels.len() - 1);
+                        Self::put(self, x, y, colormap, post.pixels[row]);
+                    }
+                    None => Self::fuzz(self, x, y)
+                }
+            }
+        }
+    }
+
+    //R_DrawFuzzColumn, a pixel of a spectre is the darker pixel above or under it
+    fn fuzz(&mut self, x: i32, y: i32) {
+        let from = (y + FUZZ_OFFSETS[self.fuzz_pos]).clamp(0, self.height as i32 - 1);
+        self.fuzz_pos = (self.fuzz_pos + 1) % FUZZ_OFFSETS.len();
+        Self::remap(self, x, y, from, FUZZ_COLORMAP);
+    }
+
+    /*
+     * R_RenderMaskedSegRange, the middle texture of a two sided line isn't repeated up and down.
+     * It is clipped by what was in front of the wall when it was drawn, a column is only drawn once.
+     */
+    fn render_masked_seg_range(&mut self, frame: &Frame, ds: &mut DrawSeg, x1: i32, x2: i32) {
+        let masked = match ds.masked.as_mut() {
+            Some(masked) => masked,
+            None => return
+        };
+        let texture = match frame.tex_man.get_game_texture(masked.texture, true).filter(|texture| texture.is_valid()) {
+            Some(texture) => texture,
+            None => return
+        };
+        for x in x1..=x2 {
+            let j = (x - ds.x1) as usize;
+            let column = match masked.columns[j].take() {
+                Some(column) => column,
+                None => continue
+            };
+            let scale = ds.scale1 + ds.scale_step * j as f64;
+            let colormap = Self::scale_light(self, masked.light_level, scale);
+            Self::draw_column(self, texture, x, column, masked.texture_mid, ds.sprite_top_clip[j] + 1, ds.sprite_bottom_clip[j] - 1, scale, colormap, true);
+        }
+    }
+
+    /*
+     * R_DrawPlayerSprites, the weapon and its flash over the view, as big as on the screen of 320x200.
+     * They have the light of the sector of the player, the fuzz when it is a spectre.
+     */
+    pub fn draw_player_sprites(&mut self, level: &LevelLocals, wad: &WADData, frames: &SpriteFrames) {
+        let player = &level.players[0];
+        let actor = match level.actor(player.mo) {
+            Some(actor) => actor,
+            None => return
+        };
+        let light_level = level.sectors[actor.sector as usize].borrow().light_level as i32;
+        let scale = self.width as f64 / BASE_WIDTH;
+        for position in 0..NUMPSPRITES {
+            let psp = &player.psprites[position];
+            if psp.state == S_NULL {continue}
+            let state = &STATES[psp.state];
+            let (lump, flip) = match frames.get(state.sprite, state.frame_index(), 0) {
+                Some(found) => found,
+                None => continue
+            };
+            let sprite = &wad.sprites[lump].1;
+            let width = sprite.width as f64;
+            let left = psp.sx - BASE_WIDTH / 2. - if flip {width - sprite.left_offset as f64} else {sprite.left_offset as f64};
+            let x1 = (self.center_x + left * scale).floor() as i32;
+            let x2 = (self.center_x + (left + width) * scale).floor() as i32 - 1;
+            if x1 >= self.width as i32 || x2 < 0 || x1 > x2 {continue}
+
+            let colormap = if actor.flags.contains(MobjFlags::Shadow) {None}
+                else if state.frame & FF_FULLBRIGHT != 0 {Some(0)}
+                else {Some(Self::scale_light(self, light_level, f64::MAX))};
+            let texture_mid = BASE_HEIGHT / 2. - (psp.sy - sprite.top_offset as f64);
+            let vis_sprite = Self::vis_sprite(self, x1, x2, scale, texture_mid, width, flip, lump, colormap);
+            let count = (vis_sprite.x2 - vis_sprite.x1 + 1) as usize;
+            Self::draw_vis_sprite(self, wad, &vis_sprite, &vec![-1; count], &vec![self.height as i32; count]);
+        }
+    }
+}
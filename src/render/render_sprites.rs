@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use macroquad::prelude::*;
+use macroquad::models::Vertex;
+
+use crate::parser::WADData;
+use crate::level::{Actor, LevelLocals, MeshChunk, MobjFlags, SpriteNum, FF_FRAMEMASK, FF_FULLBRIGHT, SPRITE_NAMES};
+use crate::player::Player;
+
+use super::{LevelShaders, chunk_in_front, set_view_camera, sprite_texture};
+
+//how much of a spectre is seen, the fuzz of doom is drawn translucent
+const SHADOW_ALPHA: f32 = 0.3;
+
+//the lump in the sprites of the wad and if it is mirrored for each rotation of a frame
+type FrameRotations = [Option<(usize, bool)>; 8];
+
+/*
+ * The lumps of the frames of the sprites (R_InitSpriteDefs).
+ * A lump is named with the sprite, the frame and the rotation, rotation 0 is used for all of them.
+ * A lump with a second frame and rotation is that rotation mirrored.
+ */
+#[derive(Default)]
+pub struct SpriteFrames {
+    frames: HashMap<(String, i32), FrameRotations>
+}
+
+impl SpriteFrames {
+    pub fn new(wad: &WADData) -> SpriteFrames {
+        let mut sprite_frames = SpriteFrames::default();
+        for (lump, (name, _)) in wad.sprites.iter().enumerate() {
+            let bytes = name.as_bytes();
+            if (bytes.len() != 6 && bytes.len() != 8) || !SPRITE_NAMES.contains(&&name[..4]) {continue}
+            Self::add_rotation(&mut sprite_frames, &name[..4], bytes[4], bytes[5], lump, false);
+            if bytes.len() == 8 {Self::add_rotation(&mut sprite_frames, &name[..4], bytes[6], bytes[7], lump, true)}
+        }
+        sprite_frames
+    }
+
+    fn add_rotation(&mut self, sprite: &str, frame: u8, rotation: u8, lump: usize, flip: bool) {
+        if frame < b'A' || !(b'0'..=b'8').contains(&rotation) {return}
+        let rotations = self.frames.entry((sprite.to_string(), (frame - b'A') as i32)).or_insert([None; 8]);
+        if rotation == b'0' {rotations.fill(Some((lump, flip)))}
+        else {rotations[(rotation - b'1') as usize] = Some((lump, flip))}
+    }
+
+    pub fn get(&self, sprite: SpriteNum, frame: i32, rotation: usize) -> Option<(usize, bool)> {
+        self.frames.get(&(SPRITE_NAMES[sprite].to_string(), frame))?[rotation]
+    }
+}
+
+//a sprite that is seen (vissprite_t), the distance is for sorting the translucent ones with the translucent lines
+struct VisSprite {
+    mesh: Mesh,
+    dist: f32,
+    fade: Color,
+    alpha: f32
+}
+
+/*
+ * R_AddSprites and R_DrawMasked, the actors of the sectors are drawn as sprites standing up and facing the camera.
+ * The solid sprites are drawn first, their holes are cut out so the masked walls and the other sprites are seen through them.
+ * The translucent sprites and lines are drawn after them together from the back to the front.
+ */
+#[allow(clippy::too_many_arguments)]
+pub fn draw_sprites(wad: &WADData, level: &LevelLocals, frames: &SpriteFrames, shaders: &LevelShaders, textures: &mut HashMap<String, Texture2D>, translucent_chunks: &[MeshChunk], player: &Player, frac: f32) {
+    let position = set_view_camera(player, frac);
+    let view_actor = level.players[0].mo;
+
+    let mut sprites = vec![];
+    for sector in &level.sectors {
+        let sector = sector.borrow();
+        for index in &sector.thing_list {
+            if *index == view_actor {continue}
+            let actor = match level.actor(*index) {
+                Some(actor) => actor,
+                None => continue
+            };
+            if let Some(sprite) = vis_sprite(wad, level, frames, textures, actor, player, position, frac) {sprites.push(sprite)}
+        }
+    }
+
+    let light_mode = level.light_mode.resolve() as i32 as f32;
+    shaders.level.set_uniform("LightMode", light_mode);
+    gl_use_material(shaders.level);
+    for sprite in sprites.iter().filter(|sprite| sprite.alpha >= 1.) {
+        shaders.level.set_uniform("FadeColor", (sprite.fade.r, sprite.fade.g, sprite.fade.b));
+        draw_mesh(&sprite.mesh);
+    }
+
+    //the mesh, the distance to the camera, the fade color and the alpha
+    let mut translucent: Vec<(&Mesh, f32, Color, f32)> = sprites.iter().filter(|sprite| sprite.alpha < 1.)
+        .map(|sprite| (&sprite.mesh, sprite.dist, sprite.fade, sprite.alpha)).collect();
+    for chunk in translucent_chunks.iter().filter(|chunk| chunk_in_front(chunk, position, player.front)) {
+        let dist = ((chunk.min + chunk.max) / 2. - position).length();
+        translucent.push((&chunk.mesh, dist, chunk.fade, chunk.alpha));
+    }
+    translucent.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    shaders.translucent.set_uniform("LightMode", light_mode);
+    gl_use_material(shaders.translucent);
+    for (mesh, _, fade, alpha) in translucent {
+        shaders.translucent.set_uniform("FadeColor", (fade.r, fade.g, fade.b));
+        shaders.translucent.set_uniform("Alpha", alpha);
+        draw_mesh(mesh);
+    }
+    gl_use_default_material();
+    set_default_camera();
+}
+
+/*
+ * R_ProjectSprite, the rotation of the sprite is from the angle the actor is seen at.
+ * The offsets of the lump put it on the position of the actor, a mirrored lump has them on the other side.
+ */
+#[allow(clippy::too_many_arguments)]
+fn vis_sprite(wad: &WADData, level: &LevelLocals, frames: &SpriteFrames, textures: &mut HashMap<String, Texture2D>, actor: &Actor, player: &Player, position: Vec3, frac: f32) -> Option<VisSprite> {
+    let prev = vec3(actor.prev.x as f32, actor.prev.y as f32, actor.prev.z as f32);
+    let pos = prev.lerp(vec3(actor.pos.x as f32, actor.pos.y as f32, actor.pos.z as f32), frac);
+    let to_actor = pos - position;
+
+    let view_angle = to_actor.y.atan2(to_actor.x).to_degrees() as f64;
+    let rotation = ((view_angle - actor.angle.degrees() + 202.5).rem_euclid(360.) / 45.) as usize & 7;
+    let (lump, flip) = frames.get(actor.sprite, actor.frame & FF_FRAMEMASK, rotation)?;
+    let (name, sprite) = &wad.sprites[lump];
+    //the sprites behind the camera are left out, a wide one can still be seen next to it
+    if to_actor.dot(player.front) < -(sprite.width as f32) {return None}
+    let texture = *textures.entry(name.clone()).or_insert_with(|| sprite_texture(sprite, &wad.palletes[0]));
+
+    let right = vec3(player.front.y, -player.front.x, 0.).normalize_or_zero();
+    let left_offset = if flip {sprite.width as i32 - sprite.left_offset} else {sprite.left_offset};
+    let left = pos - right * left_offset as f32;
+    let width = right * sprite.width as f32;
+    let top = pos.z + sprite.top_offset as f32;
+    let bottom = top - sprite.height as f32;
+    let (u1, u2) = if flip {(1., 0.)} else {(0., 1.)};
+
+    //the full bright frames have all the light and none of the color of the sector
+    let sector = level.sectors[actor.sector as usize].borrow();
+    let color = if actor.frame & FF_FULLBRIGHT != 0 {WHITE}
+        else {
+            let light = &sector.color_map.light_color;
+            Color::from_rgba(light.r(), light.g(), light.b(), sector.light_level.clamp(0, 255) as u8)
+        };
+    let fade = &sector.color_map.fade_color;
+
+    let vertex = |corner: Vec3, z: f32, u: f32, v: f32| Vertex { position: vec3(corner.x, corner.y, z), uv: vec2(u, v), color };
+    let mesh = Mesh {
+        vertices: vec![vertex(left, bottom, u1, 1.), vertex(left + width, bottom, u2, 1.), vertex(left + width, top, u2, 0.), vertex(left, top, u1, 0.)],
+        indices: vec![0, 1, 2, 0, 2, 3],
+        texture: Some(texture)
+    };
+    let alpha = if actor.flags.contains(MobjFlags::Shadow) {SHADOW_ALPHA} else {1.};
+    Some(VisSprite { mesh, dist: to_actor.length(), fade: Color::from_rgba(fade.r(), fade.g(), fade.b(), 255), alpha })
+}
@@ -14,7 +14,7 @@ pub struct Vector3<T> {
     pub z: T
 }
 
-#[derive(Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Default, PartialEq, PartialOrd)]
 pub struct Angle<T> {
     degrees: T
 }
@@ -28,6 +28,10 @@ impl Angle<f64> {
         Angle { degrees: radians * 180.0 / pi64 }
     }
 
+    pub fn degrees(&self) -> f64 {
+        self.degrees
+    }
+
     pub fn from_bam_u(f: u32) -> Angle<f64> {
         Angle { degrees: f as f64 * (90. / 0x40000000 as f64) }
     }
@@ -57,8 +61,9 @@ impl Angle<f64> {
         Angle { degrees: (90. / 0x40000000 as f64) * (self.bams() as i32) as f64 }
     }
 
-    fn bams(&self) -> u32 {
-        ((((0x40000000 as f64 / 90.) * self.degrees).floor() + 0.5) as i32) as u32
+    //the binary angle of doom, the angles past 180 degrees wrap around
+    pub fn bams(&self) -> u32 {
+        ((((0x40000000 as f64 / 90.) * self.degrees).floor() + 0.5) as i64) as u32
     }
 
     pub fn abs_angle(angle_1: &Angle<f64>, angle_2: &Angle<f64>) -> Angle<f64> {